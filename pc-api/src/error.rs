@@ -1,10 +1,13 @@
 //! Error handling for Personal Controller API
 
 use axum::{
-    http::StatusCode,
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
+use pc_core::{Locale, Violations};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -24,7 +27,10 @@ pub enum AppError {
     DatabaseError(String),
     NotFound(String),
     ValidationError(String),
+    /// Entity rules violated; every violation goes into `details`
+    Invalid(Violations),
     BadRequest(String),
+    #[allow(dead_code)] // no auth layer yet
     Unauthorized(String),
     InternalError(String),
     LlmError(String),
@@ -36,6 +42,7 @@ impl fmt::Display for AppError {
             AppError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            AppError::Invalid(violations) => write!(f, "Validation error: {}", violations),
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
             AppError::InternalError(msg) => write!(f, "Internal error: {}", msg),
@@ -46,32 +53,90 @@ impl fmt::Display for AppError {
 
 impl std::error::Error for AppError {}
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
+/// Violations behind an error response, kept so [`localize_errors`] can
+/// render them again in the client's language
+#[derive(Clone)]
+struct InvalidDetails(Violations);
+
+impl AppError {
+    fn response_in(self, locale: Locale) -> Response {
         let (status, error_type) = match &self {
             AppError::DatabaseError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
             AppError::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
-            AppError::ValidationError(_) => (StatusCode::BAD_REQUEST, "validation_error"),
+            AppError::ValidationError(_) | AppError::Invalid(_) => (StatusCode::BAD_REQUEST, "validation_error"),
             AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, "bad_request"),
             AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "unauthorized"),
             AppError::InternalError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
             AppError::LlmError(_) => (StatusCode::SERVICE_UNAVAILABLE, "llm_error"),
         };
 
+        tracing::error!("API Error: {}", self);
+
+        let (message, details, violations) = match self {
+            AppError::Invalid(violations) => {
+                let localized = Violations::from(violations.localized(locale));
+                (
+                    format!("Validation error: {}", localized),
+                    serde_json::to_value(&localized).ok(),
+                    Some(violations),
+                )
+            }
+            other => (other.to_string(), None, None),
+        };
         let error_response = ErrorResponse {
             error: error_type.to_string(),
-            message: self.to_string(),
+            message,
             status: status.as_u16(),
-            details: None,
+            details,
         };
 
-        tracing::error!("API Error: {}", self);
+        let mut response = (status, Json(error_response)).into_response();
+        if let Some(violations) = violations {
+            response.extensions_mut().insert(InvalidDetails(violations));
+        }
+        response
+    }
+}
 
-        (status, Json(error_response)).into_response()
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        self.response_in(Locale::default())
+    }
+}
+
+/// Renders validation errors in the language asked by `Accept-Language`
+pub async fn localize_errors(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Locale::from_accept_language)
+        .unwrap_or_default();
+
+    let response = next.run(request).await;
+    match response.extensions().get::<InvalidDetails>() {
+        Some(InvalidDetails(violations)) if locale != Locale::default() => {
+            AppError::Invalid(violations.clone()).response_in(locale)
+        }
+        _ => response,
     }
 }
 
 // Conversions from common error types
+impl From<pc_core::Error> for AppError {
+    fn from(err: pc_core::Error) -> Self {
+        match err {
+            pc_core::Error::NotFound(msg) => AppError::NotFound(msg),
+            pc_core::Error::Validation(msg) => AppError::ValidationError(msg),
+            pc_core::Error::Invalid(violations) => AppError::Invalid(violations),
+            pc_core::Error::Database(msg) => AppError::DatabaseError(msg),
+            pc_core::Error::Llm(msg) => AppError::LlmError(msg),
+            pc_core::Error::Import(msg) | pc_core::Error::Cancelled(msg) => AppError::BadRequest(msg),
+            pc_core::Error::Internal(msg) => AppError::InternalError(msg),
+        }
+    }
+}
+
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
         AppError::InternalError(err.to_string())
@@ -115,4 +180,21 @@ mod tests {
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("validation_error"));
     }
+
+    #[tokio::test]
+    async fn test_invalid_details_per_violation() {
+        let mut violations = Violations::new();
+        violations.required("numero", "");
+        violations.non_negative("peso", -1.0);
+
+        let response = AppError::Invalid(violations).response_in(Locale::En);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: ErrorResponse = serde_json::from_slice(&body).unwrap();
+
+        let details = body.details.unwrap();
+        assert_eq!(details[0]["field"], "numero");
+        assert_eq!(details[0]["code"], "required");
+        assert_eq!(details[1]["message"], "peso cannot be negative");
+    }
 }
@@ -1,12 +1,24 @@
+//! CRUD, chat and statistics handlers still backed by placeholders
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use pc_models::{FreightOrder, Route, Timesheet};
+use serde::Deserialize;
+
+use crate::{AppState, ListQuery, PaginatedResponse, Pagination};
+
 // ===== Freight Orders API =====
 
-async fn list_freight_orders(
+pub(crate) async fn list_freight_orders(
     State(_state): State<AppState>,
     Query(query): Query<ListQuery>,
 ) -> impl IntoResponse {
     tracing::debug!("Listing freight orders: page={}, per_page={}", query.page, query.per_page);
 
-    Json(PaginatedResponse {
+    Json(PaginatedResponse::<FreightOrder> {
         data: vec![],
         pagination: Pagination {
             page: query.page,
@@ -17,7 +29,7 @@ async fn list_freight_orders(
     })
 }
 
-async fn create_freight_order(
+pub(crate) async fn create_freight_order(
     State(_state): State<AppState>,
     Json(payload): Json<serde_json::Value>,
 ) -> impl IntoResponse {
@@ -29,7 +41,7 @@ async fn create_freight_order(
     })))
 }
 
-async fn get_freight_order(
+pub(crate) async fn get_freight_order(
     State(_state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
@@ -42,7 +54,7 @@ async fn get_freight_order(
     }))
 }
 
-async fn update_freight_order(
+pub(crate) async fn update_freight_order(
     State(_state): State<AppState>,
     Path(id): Path<String>,
     Json(payload): Json<serde_json::Value>,
@@ -55,7 +67,7 @@ async fn update_freight_order(
     }))
 }
 
-async fn delete_freight_order(
+pub(crate) async fn delete_freight_order(
     State(_state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
@@ -66,13 +78,13 @@ async fn delete_freight_order(
 
 // ===== Timesheets API =====
 
-async fn list_timesheets(
+pub(crate) async fn list_timesheets(
     State(_state): State<AppState>,
     Query(query): Query<ListQuery>,
 ) -> impl IntoResponse {
     tracing::debug!("Listing timesheets: page={}, per_page={}", query.page, query.per_page);
 
-    Json(PaginatedResponse {
+    Json(PaginatedResponse::<Timesheet> {
         data: vec![],
         pagination: Pagination {
             page: query.page,
@@ -83,7 +95,7 @@ async fn list_timesheets(
     })
 }
 
-async fn create_timesheet(
+pub(crate) async fn create_timesheet(
     State(_state): State<AppState>,
     Json(payload): Json<serde_json::Value>,
 ) -> impl IntoResponse {
@@ -95,7 +107,7 @@ async fn create_timesheet(
     })))
 }
 
-async fn get_timesheet(
+pub(crate) async fn get_timesheet(
     State(_state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
@@ -108,7 +120,7 @@ async fn get_timesheet(
     }))
 }
 
-async fn update_timesheet(
+pub(crate) async fn update_timesheet(
     State(_state): State<AppState>,
     Path(id): Path<String>,
     Json(payload): Json<serde_json::Value>,
@@ -121,7 +133,7 @@ async fn update_timesheet(
     }))
 }
 
-async fn delete_timesheet(
+pub(crate) async fn delete_timesheet(
     State(_state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
@@ -132,13 +144,13 @@ async fn delete_timesheet(
 
 // ===== Routes API =====
 
-async fn list_routes(
+pub(crate) async fn list_routes(
     State(_state): State<AppState>,
     Query(query): Query<ListQuery>,
 ) -> impl IntoResponse {
     tracing::debug!("Listing routes: page={}, per_page={}", query.page, query.per_page);
 
-    Json(PaginatedResponse {
+    Json(PaginatedResponse::<Route> {
         data: vec![],
         pagination: Pagination {
             page: query.page,
@@ -149,7 +161,7 @@ async fn list_routes(
     })
 }
 
-async fn create_route(
+pub(crate) async fn create_route(
     State(_state): State<AppState>,
     Json(payload): Json<serde_json::Value>,
 ) -> impl IntoResponse {
@@ -161,7 +173,7 @@ async fn create_route(
     })))
 }
 
-async fn get_route(
+pub(crate) async fn get_route(
     State(_state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
@@ -174,7 +186,7 @@ async fn get_route(
     }))
 }
 
-async fn update_route(
+pub(crate) async fn update_route(
     State(_state): State<AppState>,
     Path(id): Path<String>,
     Json(payload): Json<serde_json::Value>,
@@ -187,7 +199,7 @@ async fn update_route(
     }))
 }
 
-async fn delete_route(
+pub(crate) async fn delete_route(
     State(_state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
@@ -199,11 +211,11 @@ async fn delete_route(
 // ===== Chat API =====
 
 #[derive(Deserialize)]
-struct ChatRequest {
+pub(crate) struct ChatRequest {
     query: String,
 }
 
-async fn chat(
+pub(crate) async fn chat(
     State(_state): State<AppState>,
     Json(payload): Json<ChatRequest>,
 ) -> impl IntoResponse {
@@ -218,7 +230,7 @@ async fn chat(
     }))
 }
 
-async fn chat_history(State(_state): State<AppState>) -> impl IntoResponse {
+pub(crate) async fn chat_history(State(_state): State<AppState>) -> impl IntoResponse {
     tracing::debug!("Getting chat history");
 
     Json(serde_json::json!({
@@ -227,7 +239,7 @@ async fn chat_history(State(_state): State<AppState>) -> impl IntoResponse {
     }))
 }
 
-async fn clear_chat(State(_state): State<AppState>) -> impl IntoResponse {
+pub(crate) async fn clear_chat(State(_state): State<AppState>) -> impl IntoResponse {
     tracing::debug!("Clearing chat history");
 
     Json(serde_json::json!({
@@ -237,19 +249,7 @@ async fn clear_chat(State(_state): State<AppState>) -> impl IntoResponse {
 
 // ===== Statistics API =====
 
-async fn stats(State(_state): State<AppState>) -> impl IntoResponse {
-    tracing::debug!("Getting general stats");
-
-    Json(serde_json::json!({
-        "companies": 0,
-        "freight_orders": 0,
-        "timesheets": 0,
-        "routes": 0,
-        "total_revenue": 0.0
-    }))
-}
-
-async fn company_stats(State(_state): State<AppState>) -> impl IntoResponse {
+pub(crate) async fn company_stats(State(_state): State<AppState>) -> impl IntoResponse {
     tracing::debug!("Getting company stats");
 
     Json(serde_json::json!({
@@ -262,7 +262,7 @@ async fn company_stats(State(_state): State<AppState>) -> impl IntoResponse {
     }))
 }
 
-async fn freight_stats(State(_state): State<AppState>) -> impl IntoResponse {
+pub(crate) async fn freight_stats(State(_state): State<AppState>) -> impl IntoResponse {
     tracing::debug!("Getting freight stats");
 
     Json(serde_json::json!({
@@ -277,7 +277,7 @@ async fn freight_stats(State(_state): State<AppState>) -> impl IntoResponse {
     }))
 }
 
-async fn timesheet_stats(State(_state): State<AppState>) -> impl IntoResponse {
+pub(crate) async fn timesheet_stats(State(_state): State<AppState>) -> impl IntoResponse {
     tracing::debug!("Getting timesheet stats");
 
     Json(serde_json::json!({
@@ -1,10 +1,11 @@
 //! Personal Controller API Server
 
 use axum::{
-    extract::{Path, Query, State},
-    http::{StatusCode, HeaderMap},
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, StatusCode, HeaderMap},
     response::{IntoResponse, Json},
-    routing::{get, post, put, delete},
+    routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
@@ -12,9 +13,21 @@ use std::sync::Arc;
 use tower_http::cors::{CorsLayer, Any};
 use tower_http::trace::TraceLayer;
 
+mod error;
+mod handlers;
+
+use error::{ApiResult, AppError};
+use handlers::*;
+use pc_db::GenericRepository;
+use pc_importers::{
+    lineage, registry, ExportFormat, Exporter, ImportOptions, ImporterRegistry, JobTracker, LineageReport, RecordFilter,
+};
+use pc_models::{ImportJob, ImportedFile};
+
 #[derive(Clone)]
 struct AppState {
-    // db: Arc<pc_db::PersonalControllerDb>,
+    db: Arc<pc_db::PersonalControllerDb>,
+    importers: Arc<ImporterRegistry>,
     // llm: Arc<pc_llm::PersonalControllerLlm>,
 }
 
@@ -32,8 +45,15 @@ async fn main() -> anyhow::Result<()> {
     // let mut llm = pc_llm::PersonalControllerLlm::new(pc_llm::LlmConfig::default())?;
     // llm.initialize().await?;
 
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "file://.personal-controller".to_string());
+    let db = Arc::new(pc_db::PersonalControllerDb::connect(&url).await?);
+
+    let importers = Arc::new(ImporterRegistry::default());
+    spawn_folder_watchers(&db, &importers);
+
     let state = AppState {
-        // db: Arc::new(db),
+        db,
+        importers,
         // llm: Arc::new(llm),
     };
 
@@ -58,6 +78,21 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/v1/routes", get(list_routes).post(create_route))
         .route("/api/v1/routes/:id", get(get_route).put(update_route).delete(delete_route))
 
+        // Import jobs API
+        .route(
+            "/api/v1/imports",
+            get(list_imports).post(upload_import).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/api/v1/imports/:id", get(get_import))
+        .route("/api/v1/imports/:id/cancel", post(cancel_import))
+        .route("/api/v1/importers", get(list_importers))
+
+        // Lineage API
+        .route("/api/v1/lineage/:entity/:id", get(get_lineage))
+
+        // Export API
+        .route("/api/v1/export/:collection", get(export_collection))
+
         // LLM Chat API
         .route("/api/v1/chat", post(chat))
         .route("/api/v1/chat/history", get(chat_history))
@@ -70,6 +105,7 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/v1/stats/timesheets", get(timesheet_stats))
 
         // Middleware
+        .layer(axum::middleware::from_fn(error::localize_errors))
         .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
         .layer(TraceLayer::new_for_http())
         .with_state(state);
@@ -84,6 +120,20 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Starts one folder watcher per directory listed in `PC_WATCH_DIRS` (comma-separated)
+fn spawn_folder_watchers(db: &Arc<pc_db::PersonalControllerDb>, importers: &Arc<ImporterRegistry>) {
+    let Some(dirs) = std::env::var("PC_WATCH_DIRS").ok().filter(|d| !d.trim().is_empty()) else {
+        return;
+    };
+
+    for dir in dirs.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        tracing::info!("👀 Watching {} for imports", dir);
+        let mut watcher = pc_importers::FolderWatcher::new(dir).with_registry(Arc::clone(importers));
+        let db = Arc::clone(db);
+        tokio::spawn(async move { watcher.run(&db).await });
+    }
+}
+
 // ===== Root & Health =====
 
 async fn root() -> impl IntoResponse {
@@ -97,6 +147,10 @@ async fn root() -> impl IntoResponse {
             "freight_orders": "/api/v1/freight-orders",
             "timesheets": "/api/v1/timesheets",
             "routes": "/api/v1/routes",
+            "imports": "/api/v1/imports",
+            "importers": "/api/v1/importers",
+            "lineage": "/api/v1/lineage/:entity/:id",
+            "export": "/api/v1/export/:collection",
             "chat": "/api/v1/chat",
             "stats": "/api/v1/stats"
         }
@@ -144,7 +198,7 @@ async fn list_companies(
     tracing::debug!("Listing companies: page={}, per_page={}", query.page, query.per_page);
 
     // TODO: Query database
-    Json(PaginatedResponse {
+    Json(PaginatedResponse::<pc_models::Company> {
         data: vec![],
         pagination: Pagination {
             page: query.page,
@@ -203,73 +257,309 @@ async fn delete_company(
     (StatusCode::NO_CONTENT, ())
 }
 
-async fn get_company(
-    State(_state): State<AppState>,
-    Path(_id): Path<String>,
-) -> impl IntoResponse {
-    Json(serde_json::json!({
-        "id": "123",
-        "nome": "Avila Transportes"
+// ===== Import jobs API =====
+
+#[derive(Deserialize)]
+struct ImportsQuery {
+    #[serde(default = "default_page")]
+    page: usize,
+    #[serde(default = "default_per_page")]
+    per_page: usize,
+    /// pending, processing, completed, failed or cancelled
+    status: Option<String>,
+}
+
+async fn list_imports(
+    State(state): State<AppState>,
+    Query(query): Query<ImportsQuery>,
+) -> ApiResult<Json<PaginatedResponse<ImportJob>>> {
+    tracing::debug!("Listing imports: page={}, per_page={}, status={:?}", query.page, query.per_page, query.status);
+
+    let status = query
+        .status
+        .as_deref()
+        .map(str::parse::<pc_core::Status>)
+        .transpose()?;
+    let jobs: Vec<ImportJob> = state
+        .db
+        .import_jobs
+        .recent(usize::MAX)
+        .await?
+        .into_iter()
+        .filter(|j| status.is_none_or(|s| j.status == s))
+        .collect();
+
+    let per_page = query.per_page.max(1);
+    let total = jobs.len();
+    let data = jobs
+        .into_iter()
+        .skip(query.page.saturating_sub(1) * per_page)
+        .take(per_page)
+        .collect();
+
+    Ok(Json(PaginatedResponse {
+        data,
+        pagination: Pagination {
+            page: query.page,
+            per_page,
+            total,
+            total_pages: total.div_ceil(per_page),
+        },
     }))
 }
 
-async fn list_freight_orders(
-    State(_state): State<AppState>,
-    Query(_query): Query<ListQuery>,
-) -> impl IntoResponse {
-    Json(serde_json::json!({
-        "data": [],
-        "pagination": {
-            "page": 1,
-            "per_page": 10,
-            "total": 0
+async fn get_import(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> ApiResult<Json<ImportJob>> {
+    tracing::debug!("Getting import job: id={}", id);
+
+    state
+        .db
+        .import_jobs
+        .find_by_id(&id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Import job {}", id)))
+}
+
+/// Largest file accepted by the upload endpoint
+const MAX_UPLOAD_BYTES: usize = 256 * 1024 * 1024;
+
+#[derive(Deserialize)]
+struct UploadQuery {
+    /// Original file name; used for detection and to skip files already imported
+    filename: String,
+    /// Importer name (see /api/v1/importers), or auto to detect from the content
+    #[serde(default = "default_import_type")]
+    r#type: String,
+}
+
+fn default_import_type() -> String { "auto".to_string() }
+
+/// Uploaded files are kept, one directory per upload, under `PC_UPLOAD_DIR`
+fn upload_dir() -> std::path::PathBuf {
+    std::env::var("PC_UPLOAD_DIR")
+        .unwrap_or_else(|_| ".personal-controller/uploads".to_string())
+        .into()
+}
+
+/// Imports the request body as a file, through the importer registry
+async fn upload_import(
+    State(state): State<AppState>,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> ApiResult<(StatusCode, Json<ImportJob>)> {
+    tracing::debug!("Uploading import: filename={}, type={}, {} bytes", query.filename, query.r#type, body.len());
+
+    let filename = std::path::Path::new(&query.filename)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.starts_with('.'))
+        .map(String::from)
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid filename: {}", query.filename)))?;
+    if body.is_empty() {
+        return Err(AppError::BadRequest("Empty upload".to_string()));
+    }
+
+    let sha256 = pc_core::hash::sha256_hex(&body);
+    if state.db.imported_files.is_already_imported(&filename, &sha256).await? {
+        return Err(AppError::BadRequest(format!("{} was already imported with the same content", filename)));
+    }
+
+    let dir = upload_dir().join(uuid::Uuid::new_v4().to_string());
+    let file = dir.join(&filename);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| AppError::InternalError(format!("Cannot create {}: {}", dir.display(), e)))?;
+    tokio::fs::write(&file, &body)
+        .await
+        .map_err(|e| AppError::InternalError(format!("Cannot write {}: {}", file.display(), e)))?;
+
+    // Detection reads the file, so it runs after the write; nothing is kept when it fails
+    let importer = match state.importers.resolve(&query.r#type, &file) {
+        Ok(resolved) => resolved.importer,
+        Err(e) => {
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+            return Err(e.into());
         }
-    }))
+    };
+
+    let mut job = JobTracker::start(&state.db.import_jobs, &file, importer.name(), None).await?;
+    let result =
+        registry::import_file(importer.as_ref(), &state.db, &file, &ImportOptions::default(), &mut job, |_| {}).await;
+    let job = job.finish(&result).await?;
+
+    match result {
+        Ok(Some(report)) => {
+            state
+                .db
+                .imported_files
+                .record(ImportedFile::new(filename, sha256, importer.name().to_string(), report.total()))
+                .await?;
+            Ok((StatusCode::CREATED, Json(job)))
+        }
+        Ok(None) | Err(pc_core::Error::Cancelled(_)) => Ok((StatusCode::OK, Json(job))),
+        Err(e) => Err(e.into()),
+    }
 }
 
-async fn create_freight_order(
-    State(_state): State<AppState>,
-    Json(_payload): Json<serde_json::Value>,
-) -> impl IntoResponse {
-    (StatusCode::CREATED, Json(serde_json::json!({
-        "id": "456",
-        "message": "Freight order created"
-    })))
+#[derive(Serialize)]
+struct ImporterInfo {
+    name: &'static str,
+    description: &'static str,
+    extensions: &'static [&'static str],
+    /// Collection the importer writes to, if fixed
+    entity: Option<&'static str>,
 }
 
-async fn get_freight_order(
-    State(_state): State<AppState>,
-    Path(_id): Path<String>,
-) -> impl IntoResponse {
-    Json(serde_json::json!({
-        "id": "456",
-        "numero": "288415"
-    }))
+async fn list_importers(State(state): State<AppState>) -> Json<Vec<ImporterInfo>> {
+    Json(
+        state
+            .importers
+            .iter()
+            .map(|i| ImporterInfo {
+                name: i.name(),
+                description: i.description(),
+                extensions: i.extensions(),
+                entity: i.entity(),
+            })
+            .collect(),
+    )
 }
 
+/// Pending jobs are cancelled at once; running ones stop after the current batch
+async fn cancel_import(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> ApiResult<(StatusCode, Json<ImportJob>)> {
+    tracing::debug!("Cancelling import job: id={}", id);
+
+    let job = state
+        .db
+        .import_jobs
+        .request_cancel(&id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Import job {}", id)))?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+// ===== Lineage =====
+
+/// Where a record came from, with the original row of the imported file
+///
+/// `id` is the record UUID or its natural key (e.g. the freight order number).
+async fn get_lineage(
+    State(state): State<AppState>,
+    Path((entity, id)): Path<(String, String)>,
+) -> ApiResult<Json<LineageReport>> {
+    tracing::debug!("Getting lineage: entity={}, id={}", entity, id);
+
+    Ok(Json(lineage::lookup(&state.db, &entity, &id).await?))
+}
+
+// ===== Export =====
+
+/// Bytes per chunk sent to the client while the export is written
+const EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+
 #[derive(Deserialize)]
-struct ChatRequest {
-    query: String,
+struct ExportQuery {
+    /// csv, csv-excel, xlsx, json or jsonl
+    #[serde(default = "default_export_format")]
+    format: String,
+    /// Comma-separated columns, in output order
+    columns: Option<String>,
+    /// `field=value` or `field~value`, separated by commas
+    filter: Option<String>,
 }
 
-async fn chat(
-    State(_state): State<AppState>,
-    Json(payload): Json<ChatRequest>,
-) -> impl IntoResponse {
-    Json(serde_json::json!({
-        "response": format!("Resposta da LLM para: {}", payload.query),
-        "sources": [],
-        "confidence": 0.85
-    }))
+fn default_export_format() -> String { "csv".to_string() }
+
+/// Streams a collection as a file download
+async fn export_collection(
+    State(state): State<AppState>,
+    Path(collection): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> ApiResult<impl IntoResponse> {
+    tracing::debug!("Exporting {}: format={}", collection, query.format);
+
+    let format: ExportFormat = query.format.parse()?;
+    let columns: Vec<String> = query
+        .columns
+        .iter()
+        .flat_map(|c| c.split(','))
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(String::from)
+        .collect();
+    let filters = query
+        .filter
+        .iter()
+        .flat_map(|f| f.split(','))
+        .filter(|f| !f.trim().is_empty())
+        .map(str::parse::<RecordFilter>)
+        .collect::<pc_core::Result<Vec<_>>>()?;
+
+    let records = state.db.collection_json(&collection).await?;
+    let exporter = Exporter::new(format)
+        .with_columns(columns)
+        .with_filters(filters)
+        .with_sheet_name(&collection);
+    if let Some(first) = records.first() {
+        exporter.check_columns(first)?;
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::channel(8);
+    let name = collection.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut writer = ChannelWriter { tx, buffer: Vec::with_capacity(EXPORT_CHUNK_SIZE) };
+        match exporter.export(records, &mut writer) {
+            Ok(count) => tracing::info!("📤 Exported {} records from {}", count, name),
+            Err(e) => {
+                tracing::error!("Export of {} failed: {}", name, e);
+                let _ = writer.tx.blocking_send(Err(std::io::Error::other(e.to_string())));
+            }
+        }
+    });
+
+    let disposition = format!("attachment; filename=\"{}.{}\"", collection, format.extension());
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)),
+    ))
 }
 
-async fn stats(State(_state): State<AppState>) -> impl IntoResponse {
-    Json(serde_json::json!({
-        "companies": 0,
-        "freight_orders": 0,
-        "timesheets": 0,
-        "contacts": 0,
-        "routes": 0,
-        "manifests": 0
-    }))
+/// Sends what the exporter writes to the response body in chunks
+struct ChannelWriter {
+    tx: tokio::sync::mpsc::Sender<std::io::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+}
+
+impl std::io::Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= EXPORT_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(EXPORT_CHUNK_SIZE));
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "client disconnected"))
+    }
+}
+
+/// Record counts and freight totals (BRL, non-cancelled orders)
+async fn stats(State(state): State<AppState>) -> ApiResult<Json<pc_db::DatabaseStats>> {
+    Ok(Json(state.db.stats().await?))
 }
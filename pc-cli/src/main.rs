@@ -1,8 +1,48 @@
 //! Personal Controller CLI
 
 use clap::{Parser, Subcommand};
-use pc_importers::{freight_importer::FreightOrderImporter, Importer};
-use std::path::PathBuf;
+use pc_importers::{
+    csv_source::{CsvOptions, TextEncoding},
+    geo::Municipalities,
+    photo_importer::PhotoImporter,
+    registry,
+    streaming::DEFAULT_BATCH_SIZE,
+    jobs,
+    lineage,
+    JobTracker,
+    LineageReport,
+    LineageStamp,
+    ImportOptions,
+    ImporterRegistry,
+    SelectedImporter,
+    BulkImport,
+    BulkSummary,
+    DryRunReport,
+    FolderWatcher,
+    PhotoLinker,
+    ImportOutput,
+    ArchiveContents,
+    CompanyResolver,
+    ProfileReport,
+    Profiler,
+    ExportFormat,
+    Exporter,
+    RecordFilter,
+    BankStatementImporter,
+    PaymentMatcher,
+    ReconciliationSummary,
+    Importer,
+    cnab::{apply_retorno, Cedente, CnabLayout, RemessaWriter, RetornoParser, RetornoSummary, Titulo},
+};
+use pc_db::GenericRepository;
+use pc_core::{Money, Status};
+use pc_models::{Boleto, BoletoStatus, CompanyReview, ImportJob, ImportedFile, LinkStatus, PaymentMethod, PaymentTransaction, PhotoLink};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_DATABASE_URL: &str = "file://.personal-controller";
 
 #[derive(Parser)]
 #[command(name = "pc")]
@@ -16,15 +56,152 @@ struct Cli {
 enum Commands {
     /// Importa dados de arquivos CSV
     Import {
-        /// Tipo de dados (freight, timesheet, route, auto)
+        /// Importador (auto detecta pelo conteúdo; veja `pc importers`)
         #[arg(short, long, default_value = "auto")]
         r#type: String,
         
-        /// Arquivo ou diretório para importar
+        /// Arquivo para importar
+        #[arg(short, long, required_unless_present = "recursive")]
+        file: Option<PathBuf>,
+
+        /// Importa todos os arquivos do diretório (e subdiretórios) em paralelo
+        #[arg(short = 'R', long, value_name = "DIR", conflicts_with = "file")]
+        recursive: Option<PathBuf>,
+
+        /// Threads de leitura na importação recursiva (padrão: uma por núcleo)
         #[arg(short, long)]
-        file: PathBuf,
+        jobs: Option<usize>,
+
+        /// Checkpoint da importação recursiva, para retomar execuções interrompidas
+        #[arg(long, default_value = ".personal-controller/bulk-import.checkpoint.json")]
+        checkpoint: PathBuf,
+
+        /// Encoding do CSV (utf-8, utf-16le, utf-16be, windows-1252, iso-8859-1); detectado se omitido
+        #[arg(long)]
+        encoding: Option<String>,
+
+        /// Delimitador do CSV (";", ",", "tab", "|"); detectado se omitido
+        #[arg(long)]
+        delimiter: Option<String>,
+
+        /// Registros gravados por lote no banco
+        #[arg(long, default_value_t = DEFAULT_BATCH_SIZE)]
+        batch_size: usize,
+
+        /// Lista de municípios (um por linha, ex.: "Franca/SP" ou "Franca;SP") para padronizar as cidades das rotas
+        #[arg(long, value_name = "FILE")]
+        municipalities: Option<PathBuf>,
+
+        /// Mostra o que seria importado (novos, alterados, rejeitados) sem gravar nada
+        #[arg(long, conflicts_with = "recursive")]
+        dry_run: bool,
+
+        /// Formato da simulação (table, json)
+        #[arg(long, default_value = "table", requires = "dry_run")]
+        format: String,
     },
     
+    /// Monitora pastas e importa automaticamente os arquivos que chegam
+    Watch {
+        /// Pastas monitoradas
+        #[arg(required = true)]
+        dirs: Vec<PathBuf>,
+
+        /// Intervalo entre verificações, em segundos
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+
+        /// Segundos sem alteração para considerar o arquivo completo
+        #[arg(long, default_value_t = 10)]
+        stable: u64,
+    },
+
+    /// Analisa um arquivo (preenchimento, tipos, valores inválidos) e dá uma nota de qualidade
+    Profile {
+        /// Arquivo CSV
+        file: PathBuf,
+
+        /// Encoding do CSV; detectado se omitido
+        #[arg(long)]
+        encoding: Option<String>,
+
+        /// Delimitador do CSV; detectado se omitido
+        #[arg(long)]
+        delimiter: Option<String>,
+
+        /// Formato da saída (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Exporta uma collection para CSV, XLSX, JSON ou JSONL
+    Export {
+        /// Collection (freight_orders, companies, timesheets, routes, ...)
+        collection: String,
+
+        /// Formato (csv, csv-excel, xlsx, json, jsonl); pela extensão do arquivo se omitido
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Arquivo de saída; stdout se omitido
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Colunas, na ordem desejada (ex. numero,data_emissao,valor_frete)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+
+        /// Filtro campo=valor ou campo~valor (contém); pode repetir
+        #[arg(long)]
+        filter: Vec<String>,
+    },
+
+    /// Fotos de comprovantes e seus vínculos com ordens de frete
+    Photos {
+        #[command(subcommand)]
+        action: PhotosAction,
+    },
+
+    /// Empresas citadas nas ordens de frete (resolução e revisão)
+    Companies {
+        #[command(subcommand)]
+        action: CompaniesAction,
+    },
+
+    /// Extratos bancários e conciliação dos pagamentos com as ordens de frete
+    Payments {
+        #[command(subcommand)]
+        action: PaymentsAction,
+    },
+
+    /// Boletos de cobrança: remessa CNAB para o banco e leitura do retorno
+    Boletos {
+        #[command(subcommand)]
+        action: BoletosAction,
+    },
+
+    /// Lista os importadores disponíveis
+    Importers,
+
+    /// Mostra de onde veio um registro importado: arquivo, linha e a linha original
+    Lineage {
+        /// Entidade (freight_orders, timesheets, routes, documents, payments)
+        entity: String,
+
+        /// UUID ou chave natural do registro (ex.: número da ordem de frete)
+        id: String,
+
+        /// Formato da saída (table, json)
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+
+    /// Importações executadas (jobs): andamento, erros e cancelamento
+    Imports {
+        #[command(subcommand)]
+        action: ImportsAction,
+    },
+
     /// Chat com a Personal-Controller-LLM
     Chat {
         /// Query para a LLM
@@ -38,18 +215,252 @@ enum Commands {
     Init,
 }
 
+#[derive(Subcommand)]
+enum PhotosAction {
+    /// Lê as fotos da pasta (EXIF) e sugere ordens de frete para cada uma
+    Suggest {
+        /// Pasta com as fotos
+        dir: PathBuf,
+    },
+
+    /// Lista as sugestões aguardando revisão
+    Pending,
+
+    /// Aceita uma sugestão de vínculo
+    Accept {
+        /// ID da sugestão
+        id: uuid::Uuid,
+    },
+
+    /// Rejeita uma sugestão de vínculo
+    Reject {
+        /// ID da sugestão
+        id: uuid::Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+enum CompaniesAction {
+    /// Casa pagador, remetente e destinatário das ordens com o cadastro de empresas
+    Resolve,
+
+    /// Lista os nomes ambíguos aguardando revisão
+    Pending,
+
+    /// Confirma a empresa de um nome ambíguo
+    Accept {
+        /// ID da revisão
+        id: uuid::Uuid,
+
+        /// ID da empresa correta
+        company: uuid::Uuid,
+    },
+
+    /// Indica que nenhum candidato serve (uma empresa nova será cadastrada)
+    Reject {
+        /// ID da revisão
+        id: uuid::Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+enum PaymentsAction {
+    /// Importa um extrato (OFX ou CSV) e concilia os créditos
+    Import {
+        /// Arquivo do extrato
+        file: PathBuf,
+
+        /// Conta do extrato (padrão: a do OFX, ou "extrato")
+        #[arg(long)]
+        account: Option<String>,
+
+        /// Encoding do CSV; detectado se omitido
+        #[arg(long)]
+        encoding: Option<String>,
+
+        /// Delimitador do CSV; detectado se omitido
+        #[arg(long)]
+        delimiter: Option<String>,
+    },
+
+    /// Concilia de novo os lançamentos em aberto (após importar ordens novas)
+    Reconcile,
+
+    /// Lista os pagamentos parciais ou ambíguos aguardando revisão
+    Pending,
+
+    /// Concilia um lançamento com uma das candidatas
+    Accept {
+        /// ID do lançamento
+        id: uuid::Uuid,
+
+        /// ID da ordem de frete ou nota fiscal
+        entity: uuid::Uuid,
+    },
+
+    /// Indica que nenhuma candidata serve
+    Reject {
+        /// ID do lançamento
+        id: uuid::Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+enum BoletosAction {
+    /// Gera a remessa com os boletos das ordens de frete em aberto
+    Remessa {
+        /// Configuração do beneficiário (JSON com banco, agência, conta, convênio...)
+        #[arg(long)]
+        cedente: PathBuf,
+
+        /// Layout do arquivo (240 ou 400)
+        #[arg(long, default_value = "240")]
+        layout: String,
+
+        /// Dias entre a emissão e o vencimento
+        #[arg(long, default_value_t = 30)]
+        vencimento_dias: u64,
+
+        /// Números das ordens, separados por vírgula (padrão: as com forma de pagamento boleto)
+        #[arg(long)]
+        orders: Option<String>,
+
+        /// Arquivo de saída (padrão: REM<banco><sequencial>.txt)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Lê um arquivo de retorno do banco e atualiza a situação dos boletos
+    Retorno {
+        /// Arquivo de retorno (CNAB 240 ou 400)
+        file: PathBuf,
+    },
+
+    /// Lista os boletos
+    List {
+        /// Só boletos nesta situação (emitido, registrado, rejeitado, pago, baixado)
+        #[arg(long)]
+        status: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportsAction {
+    /// Lista as importações, da mais recente para a mais antiga
+    List {
+        /// Quantidade máxima de jobs
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+
+        /// Só jobs neste status (pending, processing, completed, failed, cancelled)
+        #[arg(long)]
+        status: Option<String>,
+    },
+
+    /// Mostra um job com o relatório de erros
+    Show {
+        /// ID do job
+        id: uuid::Uuid,
+    },
+
+    /// Cancela uma importação pendente ou em andamento
+    Cancel {
+        /// ID do job
+        id: uuid::Uuid,
+    },
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Setup logging
+    // Logs no stderr, para não misturar com saídas em JSON
     tracing_subscriber::fmt()
         .with_env_filter("pc=debug,pc_core=debug,pc_models=debug")
+        .with_writer(std::io::stderr)
         .init();
     
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Import { r#type, file } => {
-            import_command(r#type, file).await?;
+        Commands::Import {
+            r#type,
+            file,
+            recursive,
+            jobs,
+            checkpoint,
+            encoding,
+            delimiter,
+            batch_size,
+            municipalities,
+            dry_run,
+            format,
+        } => {
+            let csv_options = CsvOptions {
+                encoding: encoding.as_deref().map(str::parse::<TextEncoding>).transpose()?,
+                delimiter: delimiter.as_deref().map(CsvOptions::parse_delimiter).transpose()?,
+                quote: None,
+            };
+            let municipalities = match municipalities {
+                Some(path) => Some(Arc::new(Municipalities::new().load(&path)?)),
+                None => None,
+            };
+            let options = ImportOptions {
+                csv: csv_options,
+                batch_size,
+                municipalities,
+            };
+            match (recursive, file) {
+                (Some(dir), _) => bulk_import_command(dir, jobs, checkpoint, options).await?,
+                (None, Some(file)) if dry_run => dry_run_command(r#type, file, options, &format).await?,
+                (None, Some(file)) => import_command(r#type, file, options).await?,
+                (None, None) => unreachable!("clap exige --file ou --recursive"),
+            }
+        }
+        Commands::Watch { dirs, interval, stable } => {
+            watch_command(dirs, interval, stable).await?;
+        }
+        Commands::Profile {
+            file,
+            encoding,
+            delimiter,
+            format,
+        } => {
+            let csv_options = CsvOptions {
+                encoding: encoding.as_deref().map(str::parse::<TextEncoding>).transpose()?,
+                delimiter: delimiter.as_deref().map(CsvOptions::parse_delimiter).transpose()?,
+                quote: None,
+            };
+            profile_command(file, csv_options, &format)?;
+        }
+        Commands::Export {
+            collection,
+            format,
+            output,
+            columns,
+            filter,
+        } => {
+            export_command(collection, format, output, columns, filter).await?;
+        }
+        Commands::Photos { action } => {
+            photos_command(action).await?;
+        }
+        Commands::Companies { action } => {
+            companies_command(action).await?;
+        }
+        Commands::Payments { action } => {
+            payments_command(action).await?;
+        }
+        Commands::Boletos { action } => {
+            boletos_command(action).await?;
+        }
+        Commands::Importers => {
+            importers_command();
+        }
+        Commands::Lineage { entity, id, format } => {
+            lineage_command(&entity, &id, &format).await?;
+        }
+        Commands::Imports { action } => {
+            imports_command(action).await?;
         }
         Commands::Chat { query } => {
             chat_command(query).await?;
@@ -65,38 +476,987 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn import_command(import_type: String, file: PathBuf) -> anyhow::Result<()> {
+async fn import_command(
+    import_type: String,
+    file: PathBuf,
+    options: ImportOptions,
+) -> anyhow::Result<()> {
     println!("🚀 Importando dados do tipo: {}", import_type);
     println!("📁 Arquivo: {:?}", file);
-    
-    match import_type.as_str() {
-        "freight" | "auto" => {
-            let importer = FreightOrderImporter::new();
-            let orders = importer.import_file(&file)?;
-            println!("✅ Importadas {} ordens de frete", orders.len());
-            
-            // TODO: Salvar no banco
-            // let db = pc_db::PersonalControllerDb::connect("http://localhost:8000").await?;
-            // for order in orders {
-            //     db.freight_orders.save(&order).await?;
-            // }
+
+    let registry = ImporterRegistry::default();
+    let importer = select_importer(&registry, &import_type, &file)?.importer;
+
+    let db = connect_db().await?;
+    let fonte_arquivo = file
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string();
+    // A cópia para o blob store dá o hash usado em todo o resto da importação
+    let sha256 = db.blobs.put_file(&file)?;
+
+    if db.imported_files.is_already_imported(&fonte_arquivo, &sha256).await? {
+        println!("⏭️  Arquivo já importado com o mesmo conteúdo (sha256 {}), nada a fazer", &sha256[..12]);
+        return Ok(());
+    }
+
+    let mut job = JobTracker::start(&db.import_jobs, &file, importer.name(), jobs::current_user()).await?;
+    println!("🆔 Job {} (para cancelar: pc imports cancel {})", job.id(), job.id());
+
+    let result = registry::import_stored_file(importer.as_ref(), &db, &file, &sha256, &options, &mut job, |output| {
+        println!("✅ Lidos {} {}", output.len(), output.entity_name());
+        if let ImportOutput::Archive(contents) = output {
+            print_archive_entries(contents);
         }
-        "timesheet" => {
-            println!("⏰ Importando timesheets...");
-            // TODO: Implementar
+    })
+    .await;
+    let job = job.finish(&result).await?;
+    let report = match result {
+        Err(pc_core::Error::Cancelled(_)) => {
+            println!("🛑 Importação cancelada após {} registros", job.registros_lidos);
+            return Ok(());
         }
-        "route" => {
-            println!("🗺️ Importando rotas...");
-            // TODO: Implementar
+        other => other?,
+    };
+
+    if let Some(report) = report {
+        if let Some(line) = report.resumed_from {
+            println!("↩️  Retomado a partir da linha {}", line);
         }
-        _ => {
-            println!("❌ Tipo desconhecido: {}", import_type);
+        println!("🆕 Novos: {}", report.new);
+        println!("🔄 Atualizados: {}", report.updated);
+        println!("➖ Inalterados: {}", report.unchanged);
+        if job.rejeitados > 0 {
+            println!("🚫 Rejeitados: {} (detalhes em pc imports show {})", job.rejeitados, job.id);
         }
+
+        db.imported_files
+            .record(ImportedFile::new(fonte_arquivo, sha256, importer.name().to_string(), report.total()))
+            .await?;
     }
     
     Ok(())
 }
 
+/// Um arquivo extraído por linha, com o e-mail de origem
+fn print_archive_entries(contents: &ArchiveContents) {
+    for entry in &contents.entries {
+        let origem = &entry.origem;
+        let email = match (&origem.remetente, &origem.assunto) {
+            (Some(remetente), Some(assunto)) => format!("  ✉️  {} - {}", remetente, assunto),
+            (Some(remetente), None) => format!("  ✉️  {}", remetente),
+            _ => String::new(),
+        };
+        match &entry.output {
+            Ok(output) => println!("   📎 {} → {} {}{}", origem.caminho, output.len(), output.entity_name(), email),
+            Err(e) => println!("   ⚠️  {}: {}{}", origem.caminho, e, email),
+        }
+    }
+}
+
+/// Escolhe o importador pelo nome informado ou pelo conteúdo (`auto`)
+fn select_importer(registry: &ImporterRegistry, import_type: &str, file: &Path) -> anyhow::Result<SelectedImporter> {
+    let selected = registry.resolve(import_type, file)?;
+    if let Some(detection) = &selected.detection {
+        eprintln!(
+            "🔎 Detectado: {} (confiança {:.0}%) - {}",
+            selected.importer.name(),
+            detection.confidence * 100.0,
+            detection.reason
+        );
+    }
+    Ok(selected)
+}
+
+fn importers_command() {
+    println!("{:<10} {:<18} {:<24} Descrição", "Nome", "Grava em", "Extensões");
+    for importer in ImporterRegistry::default().iter() {
+        println!(
+            "{:<10} {:<18} {:<24} {}",
+            importer.name(),
+            importer.entity().unwrap_or("-"),
+            importer.extensions().join(", "),
+            importer.description()
+        );
+    }
+}
+
+async fn lineage_command(entity: &str, id: &str, format: &str) -> anyhow::Result<()> {
+    let db = connect_db().await?;
+    let report = lineage::lookup(&db, entity, id).await?;
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        "table" => print_lineage(&report),
+        other => anyhow::bail!("Formato desconhecido: {} (use table ou json)", other),
+    }
+    Ok(())
+}
+
+fn print_lineage(report: &LineageReport) {
+    let linhagem = &report.linhagem;
+    println!("🧬 {} {} ({})", report.entidade, report.chave, report.id);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("Arquivo:     {}", linhagem.caminho);
+    println!("SHA-256:     {}", linhagem.arquivo_sha256);
+    if let Some(planilha) = &linhagem.planilha {
+        println!("Planilha:    {}", planilha);
+    }
+    if let Some(linha) = linhagem.linha {
+        println!("Linha:       {}", linha);
+    }
+    if let Some(coluna) = &linhagem.coluna {
+        println!("Coluna:      {}", coluna);
+    }
+    println!("Importador:  {} {}", linhagem.importador, linhagem.versao_importador);
+    if let Some(job_id) = linhagem.job_id {
+        println!("Job:         {}", job_id);
+    }
+    println!("Importado:   {}", linhagem.importado_em.format("%d/%m/%Y %H:%M:%S"));
+
+    if !report.arquivo_disponivel {
+        println!("\n⚠️  Cópia do arquivo não encontrada no blob store");
+        return;
+    }
+    if let Some(linha) = &report.linha_original {
+        println!("\nLinha original:");
+        println!("  {}", linha);
+    }
+    if !report.campos.is_empty() {
+        println!();
+        let largura = report.campos.iter().map(|c| c.coluna.chars().count()).max().unwrap_or(0);
+        for campo in &report.campos {
+            println!("  {:<largura$}  {}", campo.coluna, campo.valor, largura = largura);
+        }
+    }
+}
+
+async fn dry_run_command(
+    import_type: String,
+    file: PathBuf,
+    options: ImportOptions,
+    format: &str,
+) -> anyhow::Result<()> {
+    let registry = ImporterRegistry::default();
+    let importer = select_importer(&registry, &import_type, &file)?.importer;
+
+    let db = connect_db().await?;
+    let report = importer.dry_run(&db, &file, &options).await?;
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        "table" => print_dry_run(&file, &report),
+        other => anyhow::bail!("Formato desconhecido: {} (use table ou json)", other),
+    }
+    Ok(())
+}
+
+fn print_dry_run(file: &Path, report: &DryRunReport) {
+    println!("🔍 Simulação de {:?} - nada foi gravado", file);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+    let Some(collection) = &report.collection else {
+        println!("📄 {} registros lidos; este tipo de arquivo ainda não é gravado no banco", report.records);
+        return;
+    };
+
+    println!("🗂️  Destino: {}", collection);
+    println!("🆕 Novos: {}", report.new.len());
+    println!("🔄 Alterados: {}", report.changed.len());
+    println!("➖ Inalterados: {}", report.unchanged.len());
+    println!("🚫 Rejeitados: {}", report.rejected.len());
+
+    if !report.new.is_empty() {
+        println!();
+        println!("Novos:");
+        for key in &report.new {
+            println!("  {}", key);
+        }
+    }
+
+    if !report.changed.is_empty() {
+        println!();
+        println!("{:<24} {:<22} {:<30} Depois", "Chave", "Campo", "Antes");
+        for changed in &report.changed {
+            for (i, change) in changed.changes.iter().enumerate() {
+                let key = if i == 0 { changed.key.as_str() } else { "" };
+                println!(
+                    "{:<24} {:<22} {:<30} {}",
+                    key,
+                    change.field,
+                    cell(&change.before),
+                    cell(&change.after)
+                );
+            }
+        }
+    }
+
+    if !report.rejected.is_empty() {
+        println!();
+        println!("{:>7}  Motivo", "Linha");
+        for rejected in &report.rejected {
+            let line = if rejected.line == 0 { "-".to_string() } else { rejected.line.to_string() };
+            println!("{:>7}  {}", line, rejected.reason);
+        }
+    }
+}
+
+/// Valor de um campo em uma célula de até 30 caracteres
+fn cell(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::Null => "-".to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.chars().count() > 30 {
+        format!("{}…", text.chars().take(29).collect::<String>())
+    } else {
+        text
+    }
+}
+
+async fn bulk_import_command(
+    dir: PathBuf,
+    jobs: Option<usize>,
+    checkpoint: PathBuf,
+    options: ImportOptions,
+) -> anyhow::Result<()> {
+    println!("🚀 Importação recursiva de {:?}", dir);
+
+    let mut bulk = BulkImport::new([&dir])
+        .with_csv_options(options.csv)
+        .with_checkpoint(checkpoint)
+        .with_user(jobs::current_user());
+    if let Some(jobs) = jobs {
+        bulk = bulk.with_threads(jobs);
+    }
+    if let Some(municipalities) = options.municipalities {
+        bulk = bulk.with_municipalities(municipalities);
+    }
+
+    let db = connect_db().await?;
+    let result = bulk
+        .run(&db, |progress| {
+            if progress.processed == 1 {
+                eprintln!("🆔 Job {} (para cancelar: pc imports cancel {})", progress.job_id, progress.job_id);
+            }
+            let name = progress
+                .current
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            eprint!(
+                "\r\x1b[2K⏳ [{}/{}] {} registros - {}",
+                progress.processed, progress.total, progress.records, name
+            );
+            let _ = std::io::stderr().flush();
+        })
+        .await;
+    eprintln!();
+    let summary = match result {
+        Err(pc_core::Error::Cancelled(_)) => {
+            println!("🛑 Importação cancelada; rode de novo para continuar do checkpoint");
+            return Ok(());
+        }
+        other => other?,
+    };
+
+    print_bulk_summary(&summary);
+    Ok(())
+}
+
+fn print_bulk_summary(summary: &BulkSummary) {
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("📁 Arquivos encontrados: {}", summary.files_found);
+    println!("✅ Importados: {}", summary.files_imported);
+    println!("⏭️  Já importados: {}", summary.files_skipped);
+    println!("🔗 Cópias de outro arquivo: {}", summary.files_duplicated);
+    println!("❔ Sem importador: {}", summary.files_unsupported);
+    println!("❌ Falhas: {}", summary.files_failed);
+
+    if !summary.by_kind.is_empty() {
+        println!();
+        println!("{:<12} {:>8} {:>10} {:>8} {:>11} {:>11}", "Tipo", "Arquivos", "Registros", "Novos", "Atualizados", "Inalterados");
+        for (kind, totals) in &summary.by_kind {
+            println!(
+                "{:<12} {:>8} {:>10} {:>8} {:>11} {:>11}",
+                kind, totals.files, totals.records, totals.new, totals.updated, totals.unchanged
+            );
+        }
+    }
+
+    if !summary.by_source.is_empty() {
+        println!();
+        println!("{:<40} {:>8} {:>10} {:>7}", "Origem", "Arquivos", "Registros", "Falhas");
+        for (source, totals) in &summary.by_source {
+            println!("{:<40} {:>8} {:>10} {:>7}", source, totals.files, totals.records, totals.failed);
+        }
+    }
+
+    for (path, error) in &summary.errors {
+        println!("⚠️  {}: {}", path.display(), error);
+    }
+    println!("⏱️  Tempo total: {:.1}s", summary.elapsed.as_secs_f64());
+}
+
+async fn watch_command(dirs: Vec<PathBuf>, interval: u64, stable: u64) -> anyhow::Result<()> {
+    let db = Arc::new(connect_db().await?);
+    let mut watchers = tokio::task::JoinSet::new();
+
+    for dir in dirs {
+        anyhow::ensure!(dir.is_dir(), "{:?} não é uma pasta", dir);
+        println!("👀 Monitorando {:?} (processados em {:?})", dir, dir.join(pc_importers::watch::PROCESSED_DIR));
+
+        let mut watcher = FolderWatcher::new(dir)
+            .with_poll_interval(Duration::from_secs(interval))
+            .with_stable_for(Duration::from_secs(stable));
+        let db = Arc::clone(&db);
+        watchers.spawn(async move { watcher.run(&db).await });
+    }
+
+    println!("Ctrl+C para encerrar");
+    tokio::signal::ctrl_c().await?;
+    watchers.shutdown().await;
+    println!("👋 Monitoramento encerrado");
+    Ok(())
+}
+
+fn profile_command(file: PathBuf, csv_options: CsvOptions, format: &str) -> anyhow::Result<()> {
+    let report = Profiler::new().with_csv_options(csv_options).profile(&file)?;
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        "table" => print_profile(&report),
+        other => anyhow::bail!("Formato desconhecido: {} (use table ou json)", other),
+    }
+    Ok(())
+}
+
+async fn export_command(
+    collection: String,
+    format: Option<String>,
+    output: Option<PathBuf>,
+    columns: Vec<String>,
+    filters: Vec<String>,
+) -> anyhow::Result<()> {
+    let format = match (format, &output) {
+        (Some(format), _) => format.parse()?,
+        (None, Some(path)) => ExportFormat::from_path(path)
+            .ok_or_else(|| anyhow::anyhow!("Informe --format: extensão de {:?} não reconhecida", path))?,
+        (None, None) => ExportFormat::Csv,
+    };
+    let filters = filters
+        .iter()
+        .map(|f| f.parse::<RecordFilter>())
+        .collect::<pc_core::Result<Vec<_>>>()?;
+
+    let db = connect_db().await?;
+    let records = db.collection_json(&collection).await?;
+    let exporter = Exporter::new(format)
+        .with_columns(columns)
+        .with_filters(filters)
+        .with_sheet_name(&collection);
+
+    // Mensagens no stderr: o stdout pode ser a própria exportação
+    match output {
+        Some(path) => {
+            let file = std::fs::File::create(&path)?;
+            let count = exporter.export(records, std::io::BufWriter::new(file))?;
+            eprintln!("✅ {} registros de {} exportados para {:?}", count, collection, path);
+        }
+        None => {
+            let count = exporter.export(records, std::io::stdout().lock())?;
+            eprintln!("✅ {} registros de {} exportados", count, collection);
+        }
+    }
+    Ok(())
+}
+
+fn print_profile(report: &ProfileReport) {
+    println!("📊 Perfil de {:?}", report.file);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!(
+        "Tipo: {}  Encoding: {}  Delimitador: {:?}  Linhas: {}",
+        report.kind, report.encoding, report.delimiter, report.rows
+    );
+    println!();
+
+    println!(
+        "{:<28} {:<9} {:>6} {:>9} {:>9}  Detalhes",
+        "Coluna", "Tipo", "Preen.", "Distintos", "Inválidos"
+    );
+    for column in &report.columns {
+        let mut details = Vec::new();
+        if let Some((min, max)) = column.date_range {
+            details.push(format!("{} a {}", min.format("%d/%m/%Y"), max.format("%d/%m/%Y")));
+        }
+        if let Some(numbers) = &column.numbers {
+            details.push(format!("{} a {} (média {:.2})", numbers.min, numbers.max, numbers.mean));
+            if numbers.zeros > 0 {
+                details.push(format!("{} zerados", numbers.zeros));
+            }
+            if numbers.negatives > 0 {
+                details.push(format!("{} negativos", numbers.negatives));
+            }
+            if numbers.outliers > 0 {
+                details.push(format!("{} fora da curva", numbers.outliers));
+            }
+        }
+        if !column.invalid_examples.is_empty() {
+            details.push(format!("ex.: {}", column.invalid_examples.join(", ")));
+        }
+        if details.is_empty() {
+            let top: Vec<String> = column
+                .top_values
+                .iter()
+                .take(3)
+                .map(|v| format!("{} ({})", v.value, v.count))
+                .collect();
+            details.push(top.join(", "));
+        }
+
+        println!(
+            "{:<28} {:<9} {:>5.0}% {:>9} {:>9}  {}",
+            column.name.chars().take(28).collect::<String>(),
+            column.kind.as_str(),
+            column.fill_rate * 100.0,
+            column.distinct,
+            column.invalid,
+            details.join("; ")
+        );
+    }
+
+    if let Some(records) = report.records {
+        println!();
+        println!("📄 Registros lidos pelo importador: {}", records);
+        for issue in &report.record_issues {
+            let lines: Vec<String> = issue.lines.iter().map(u64::to_string).collect();
+            println!("  ⚠️  {} ({}x, linhas {})", issue.issue, issue.count, lines.join(", "));
+        }
+    }
+
+    println!();
+    println!("⭐ Nota de qualidade: {:.1}/100", report.score);
+}
+
+async fn photos_command(action: PhotosAction) -> anyhow::Result<()> {
+    let db = connect_db().await?;
+
+    match action {
+        PhotosAction::Suggest { dir } => {
+            let photos = PhotoImporter::new().import_directory(&dir)?;
+            let with_exif = photos.iter().filter(|p| p.exif.is_some()).count();
+            println!("📷 {} fotos ({} com EXIF)", photos.len(), with_exif);
+
+            let orders = db.freight_orders.find_all().await?;
+            let links: Vec<PhotoLink> = PhotoLinker::new()
+                .suggest_all(&photos, &orders)
+                .into_iter()
+                .map(|s| s.into_link())
+                .collect();
+            let report = db.photo_links.upsert_many(links).await?;
+            println!("🔗 {} sugestões ({} novas)", report.total(), report.new.len());
+
+            print_photo_links(&pending_links(&db).await?);
+        }
+        PhotosAction::Pending => print_photo_links(&pending_links(&db).await?),
+        PhotosAction::Accept { id } | PhotosAction::Reject { id } => {
+            let accepted = matches!(action, PhotosAction::Accept { .. });
+            let mut link = db
+                .photo_links
+                .find_by_id(&id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Sugestão {} não encontrada", id))?;
+            link.review(accepted);
+            db.photo_links.update(&link).await?;
+            println!("✅ {} -> ordem {}: {}", link.foto_caminho, link.numero_ordem, link.status);
+        }
+    }
+
+    Ok(())
+}
+
+async fn pending_links(db: &pc_db::PersonalControllerDb) -> anyhow::Result<Vec<PhotoLink>> {
+    let mut links: Vec<PhotoLink> = db
+        .photo_links
+        .find_all()
+        .await?
+        .into_iter()
+        .filter(|l| l.status == LinkStatus::Suggested)
+        .collect();
+    links.sort_by(|a, b| a.foto_caminho.cmp(&b.foto_caminho).then(b.pontuacao.total_cmp(&a.pontuacao)));
+    Ok(links)
+}
+
+fn print_photo_links(links: &[PhotoLink]) {
+    if links.is_empty() {
+        println!("Nenhuma sugestão pendente");
+        return;
+    }
+
+    println!("{:<36} {:<30} {:<10} {:>5}  Motivos", "ID", "Foto", "Ordem", "Conf.");
+    for link in links {
+        let foto = Path::new(&link.foto_caminho)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        println!(
+            "{:<36} {:<30} {:<10} {:>4.0}%  {}",
+            link.id,
+            foto,
+            link.numero_ordem,
+            link.pontuacao * 100.0,
+            link.motivos.join("; ")
+        );
+    }
+}
+
+async fn companies_command(action: CompaniesAction) -> anyhow::Result<()> {
+    let db = connect_db().await?;
+
+    match action {
+        CompaniesAction::Resolve => {
+            let mut orders = db.freight_orders.find_all().await?;
+            let mut resolver = CompanyResolver::new(db.companies.find_all().await?)
+                .with_reviews(db.company_reviews.find_all().await?);
+            let resolution = resolver.resolve(&mut orders);
+
+            db.companies.upsert_many(resolution.companies).await?;
+            let report = db.freight_orders.upsert_many(orders).await?;
+            db.company_reviews.upsert_many(resolution.reviews.clone()).await?;
+
+            println!("🏢 Citações reconhecidas: {}, empresas novas: {}", resolution.matched, resolution.created);
+            println!("🔄 Ordens atualizadas: {}", report.updated.len());
+            println!("❓ Aguardando revisão: {}", resolution.queued);
+            if resolution.queued > 0 {
+                println!();
+                print_company_reviews(&resolution.reviews);
+            }
+        }
+        CompaniesAction::Pending => {
+            let mut reviews: Vec<CompanyReview> = db
+                .company_reviews
+                .find_all()
+                .await?
+                .into_iter()
+                .filter(|r| r.is_pending())
+                .collect();
+            reviews.sort_by(|a, b| a.chave.cmp(&b.chave));
+            print_company_reviews(&reviews);
+        }
+        CompaniesAction::Accept { id, company } => {
+            let mut review = find_company_review(&db, &id).await?;
+            let company = db
+                .companies
+                .find_by_id(&company)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Empresa {} não encontrada", company))?;
+            review.accept(company.id);
+            db.company_reviews.update(&review).await?;
+            println!("✅ \"{}\" -> {} ({}). Rode `pc companies resolve` para atualizar as ordens", review.nome, company.nome, company.cidade);
+        }
+        CompaniesAction::Reject { id } => {
+            let mut review = find_company_review(&db, &id).await?;
+            review.reject();
+            db.company_reviews.update(&review).await?;
+            println!("❌ \"{}\" será cadastrada como empresa nova na próxima resolução", review.nome);
+        }
+    }
+
+    Ok(())
+}
+
+async fn payments_command(action: PaymentsAction) -> anyhow::Result<()> {
+    let db = connect_db().await?;
+
+    match action {
+        PaymentsAction::Import {
+            file,
+            account,
+            encoding,
+            delimiter,
+        } => {
+            let mut importer = BankStatementImporter::new().with_csv_options(CsvOptions {
+                encoding: encoding.as_deref().map(str::parse::<TextEncoding>).transpose()?,
+                delimiter: delimiter.as_deref().map(CsvOptions::parse_delimiter).transpose()?,
+                quote: None,
+            });
+            if let Some(account) = account {
+                importer = importer.with_account(account);
+            }
+            let mut transactions = importer.import_file(&file)?;
+            println!("🏦 {} lançamentos em {:?}", transactions.len(), file);
+
+            let sha256 = db.blobs.put_file(&file)?;
+            LineageStamp::for_file(&file, sha256, "bank-statement", lineage::IMPORTER_VERSION)
+                .apply_all(&mut transactions);
+
+            let summary = payment_matcher(&db).await?.reconcile(&mut transactions);
+            let report = db.payments.upsert_many(transactions).await?;
+            println!("➕ Novos: {}  🔄 Atualizados: {}  ⏭️  Inalterados: {}", report.new.len(), report.updated.len(), report.unchanged.len());
+            print_reconciliation(&summary);
+        }
+        PaymentsAction::Reconcile => {
+            let mut transactions: Vec<PaymentTransaction> = db
+                .payments
+                .find_all()
+                .await?
+                .into_iter()
+                .filter(|t| t.is_credit() && !t.is_reviewed() && t.status != pc_models::MatchStatus::Matched)
+                .collect();
+            let summary = payment_matcher(&db).await?.reconcile(&mut transactions);
+            db.payments.upsert_many(transactions).await?;
+            print_reconciliation(&summary);
+        }
+        PaymentsAction::Pending => {
+            let mut pending: Vec<PaymentTransaction> = db
+                .payments
+                .find_all()
+                .await?
+                .into_iter()
+                .filter(|t| t.is_pending())
+                .collect();
+            pending.sort_by(|a, b| a.data.cmp(&b.data).then_with(|| a.fitid.cmp(&b.fitid)));
+            print_pending_payments(&pending);
+        }
+        PaymentsAction::Accept { id, entity } => {
+            let mut transaction = find_payment(&db, &id).await?;
+            if !transaction.accept(&entity) {
+                anyhow::bail!("{} não é candidata do lançamento {}", entity, id);
+            }
+            db.payments.update(&transaction).await?;
+            let matched = transaction.conciliado_com.as_ref().map(|m| m.numero.as_str()).unwrap_or_default();
+            println!("✅ {} de {} conciliado com {}", transaction.valor, transaction.data.format("%d/%m/%Y"), matched);
+        }
+        PaymentsAction::Reject { id } => {
+            let mut transaction = find_payment(&db, &id).await?;
+            transaction.reject();
+            db.payments.update(&transaction).await?;
+            println!("❌ Lançamento {} fica sem conciliação", transaction.id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Ordens, notas e conciliações já gravadas
+async fn payment_matcher(db: &pc_db::PersonalControllerDb) -> anyhow::Result<PaymentMatcher> {
+    let orders = db.freight_orders.find_all().await?;
+    let invoices = db.invoices.find_all().await?;
+    Ok(PaymentMatcher::new(&orders, &invoices).with_transactions(&db.payments.find_all().await?))
+}
+
+async fn find_payment(db: &pc_db::PersonalControllerDb, id: &uuid::Uuid) -> anyhow::Result<PaymentTransaction> {
+    db.payments
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Lançamento {} não encontrado", id))
+}
+
+fn print_reconciliation(summary: &ReconciliationSummary) {
+    println!("💰 Conciliados: {}", summary.matched);
+    println!("❓ Aguardando revisão: {}", summary.review);
+    println!("🚫 Sem correspondência: {}", summary.unmatched);
+    if summary.skipped > 0 {
+        println!("⏭️  Débitos ou já revisados: {}", summary.skipped);
+    }
+}
+
+fn print_pending_payments(transactions: &[PaymentTransaction]) {
+    if transactions.is_empty() {
+        println!("Nenhum pagamento aguardando revisão");
+        return;
+    }
+
+    for transaction in transactions {
+        println!(
+            "{}  {}  {}  {}",
+            transaction.id,
+            transaction.data.format("%d/%m/%Y"),
+            transaction.valor,
+            transaction.descricao
+        );
+        for candidato in &transaction.candidatos {
+            println!(
+                "    {:>4.0}%  {}  {} {} ({})  {}",
+                candidato.pontuacao * 100.0,
+                candidato.entidade_id,
+                candidato.entidade,
+                candidato.numero,
+                candidato.valor,
+                candidato.motivos.join("; ")
+            );
+        }
+    }
+}
+
+async fn boletos_command(action: BoletosAction) -> anyhow::Result<()> {
+    let db = connect_db().await?;
+
+    match action {
+        BoletosAction::Remessa {
+            cedente,
+            layout,
+            vencimento_dias,
+            orders,
+            output,
+        } => {
+            let cedente = Cedente::load(&cedente)?;
+            let layout: CnabLayout = layout.parse()?;
+            let numeros: Option<Vec<String>> =
+                orders.map(|o| o.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect());
+
+            let boletos = db.boletos.find_all().await?;
+            let com_boleto: std::collections::HashSet<uuid::Uuid> =
+                boletos.iter().filter(|b| b.is_active()).map(|b| b.freight_order_id).collect();
+
+            let emissao = chrono::Local::now().date_naive();
+            let vencimento = emissao + chrono::Days::new(vencimento_dias);
+            let mut nosso_numero = db.boletos.next_nosso_numero(&cedente.banco_codigo).await?;
+            let mut titulos = Vec::new();
+            for order in db.freight_orders.find_all().await? {
+                let selecionada = match &numeros {
+                    Some(numeros) => numeros.contains(&order.numero),
+                    None => order.forma_pagamento.as_deref().is_some_and(|f| PaymentMethod::Boleto.matches(f)),
+                };
+                if !selecionada
+                    || matches!(order.status, Status::Cancelled | Status::Failed)
+                    || !order.valor_frete.is_positive()
+                    || com_boleto.contains(&order.id)
+                {
+                    continue;
+                }
+                let Some(pagador) = order.pagador_id else {
+                    println!("⚠️  Ordem {}: pagador sem cadastro (rode pc companies resolve)", order.numero);
+                    continue;
+                };
+                let Some(pagador) = db.companies.find_by_id(&pagador).await? else {
+                    println!("⚠️  Ordem {}: empresa pagadora {} não encontrada", order.numero, pagador);
+                    continue;
+                };
+                match Titulo::from_order(&order, &pagador, nosso_numero, emissao, vencimento) {
+                    Ok(titulo) => {
+                        titulos.push(titulo);
+                        nosso_numero += 1;
+                    }
+                    Err(e) => println!("⚠️  Ordem {}: {}", order.numero, e),
+                }
+            }
+
+            if titulos.is_empty() {
+                println!("Nenhuma ordem em aberto para cobrar por boleto");
+                return Ok(());
+            }
+
+            let sequencial = db.boletos.next_remessa(&cedente.banco_codigo).await?;
+            let content = RemessaWriter::new(layout).write(&cedente, &titulos, sequencial, chrono::Local::now().naive_local())?;
+            let output = output.unwrap_or_else(|| PathBuf::from(format!("REM{}{:06}.txt", cedente.banco_codigo, sequencial)));
+            std::fs::write(&output, content)?;
+
+            let total: Money = titulos.iter().map(|t| t.valor).sum();
+            let boletos: Vec<Boleto> = titulos.iter().map(|t| t.to_boleto(&cedente, sequencial)).collect();
+            db.boletos.upsert_many(boletos).await?;
+            println!(
+                "🧾 Remessa {} (CNAB {}): {} boletos, {}, em {:?}",
+                sequencial,
+                layout.as_str(),
+                titulos.len(),
+                total,
+                output
+            );
+        }
+        BoletosAction::Retorno { file } => {
+            let retorno = RetornoParser::new().parse_file(&file)?;
+            println!(
+                "🏦 Retorno CNAB {} do banco {}: {} ocorrências",
+                retorno.layout.as_str(),
+                retorno.banco,
+                retorno.titulos.len()
+            );
+
+            let arquivo = file.file_name().map(|n| n.to_string_lossy().to_string());
+            let (updated, summary) = apply_retorno(db.boletos.find_all().await?, &retorno, arquivo.as_deref());
+            db.boletos.upsert_many(updated).await?;
+            print_retorno(&summary);
+        }
+        BoletosAction::List { status } => {
+            let status = status.as_deref().map(str::parse::<BoletoStatus>).transpose()?;
+            let mut boletos: Vec<Boleto> = db
+                .boletos
+                .find_all()
+                .await?
+                .into_iter()
+                .filter(|b| status.is_none_or(|s| b.situacao == s))
+                .collect();
+            boletos.sort_by(|a, b| a.vencimento.cmp(&b.vencimento).then_with(|| a.numero_ordem.cmp(&b.numero_ordem)));
+            if boletos.is_empty() {
+                println!("Nenhum boleto");
+            }
+            for boleto in boletos {
+                println!(
+                    "{:>3} {:>10}  ordem {:<10} venc. {}  {:>13}  {}",
+                    boleto.banco,
+                    boleto.nosso_numero,
+                    boleto.numero_ordem,
+                    boleto.vencimento.format("%d/%m/%Y"),
+                    boleto.valor,
+                    boleto.situacao
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_retorno(summary: &RetornoSummary) {
+    println!("✅ Registrados: {}", summary.registrados);
+    println!("💰 Pagos: {}", summary.pagos);
+    println!("❌ Rejeitados: {}", summary.rejeitados);
+    println!("📤 Baixados: {}", summary.baixados);
+    if summary.outros > 0 {
+        println!("ℹ️  Outras ocorrências: {}", summary.outros);
+    }
+    if !summary.nao_encontrados.is_empty() {
+        println!("⚠️  Nossos números sem boleto: {}", summary.nao_encontrados.join(", "));
+    }
+}
+
+async fn find_company_review(db: &pc_db::PersonalControllerDb, id: &uuid::Uuid) -> anyhow::Result<CompanyReview> {
+    db.company_reviews
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Revisão {} não encontrada", id))
+}
+
+fn print_company_reviews(reviews: &[CompanyReview]) {
+    if reviews.is_empty() {
+        println!("Nenhuma revisão pendente");
+        return;
+    }
+
+    for review in reviews {
+        println!("{}  \"{}\" ({}) em {} ordem(ns)", review.id, review.nome, review.cidade, review.ordens.len());
+        for candidato in &review.candidatos {
+            println!(
+                "    {:>4.0}%  {}  {} ({})",
+                candidato.pontuacao * 100.0,
+                candidato.empresa_id,
+                candidato.nome,
+                candidato.cidade
+            );
+        }
+    }
+}
+
+async fn imports_command(action: ImportsAction) -> anyhow::Result<()> {
+    let db = connect_db().await?;
+
+    match action {
+        ImportsAction::List { limit, status } => {
+            let status = status.as_deref().map(str::parse::<Status>).transpose()?;
+            let jobs: Vec<ImportJob> = db
+                .import_jobs
+                .recent(usize::MAX)
+                .await?
+                .into_iter()
+                .filter(|j| status.is_none_or(|s| j.status == s))
+                .take(limit)
+                .collect();
+            print_import_jobs(&jobs);
+        }
+        ImportsAction::Show { id } => {
+            let job = db
+                .import_jobs
+                .find_by_id(&id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Job {} não encontrado", id))?;
+            print_import_job(&job);
+        }
+        ImportsAction::Cancel { id } => {
+            let job = db
+                .import_jobs
+                .request_cancel(&id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Job {} não encontrado", id))?;
+            match job.status {
+                Status::Cancelled => println!("🛑 Job {} cancelado", job.id),
+                _ => println!("🛑 Cancelamento pedido; {} para após o lote atual", job.arquivo),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_import_jobs(jobs: &[ImportJob]) {
+    if jobs.is_empty() {
+        println!("Nenhuma importação registrada");
+        return;
+    }
+
+    println!(
+        "{:<36}  {:<16} {:<12} {:<10} {:>9} {:>9} {:>8}  Arquivo",
+        "Job", "Início", "Status", "Importador", "Registros", "Rejeitad.", "Duração"
+    );
+    for job in jobs {
+        let duration = job
+            .duration()
+            .map(|d| format!("{:.1}s", d.num_milliseconds() as f64 / 1000.0))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<36}  {:<16} {:<12} {:<10} {:>9} {:>9} {:>8}  {}",
+            job.id,
+            job.criado_em.with_timezone(&chrono::Local).format("%d/%m/%Y %H:%M"),
+            job.status.to_string(),
+            job.importador,
+            job.registros_lidos,
+            job.rejeitados,
+            duration,
+            job.arquivo
+        );
+    }
+}
+
+fn print_import_job(job: &ImportJob) {
+    println!("📋 Job {}", job.id);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("📁 Arquivo: {}", job.arquivo);
+    println!("🔧 Importador: {}", job.importador);
+    println!("👤 Iniciado por: {}", job.iniciado_por.as_deref().unwrap_or("-"));
+    println!("📌 Status: {}{}", job.status, if job.cancelamento_solicitado && !job.is_finished() { " (cancelamento pedido)" } else { "" });
+    println!("🕐 Criado em: {}", job.criado_em.with_timezone(&chrono::Local).format("%d/%m/%Y %H:%M:%S"));
+    if let Some(concluido_em) = job.concluido_em {
+        println!("🏁 Concluído em: {}", concluido_em.with_timezone(&chrono::Local).format("%d/%m/%Y %H:%M:%S"));
+    }
+    if let Some(duration) = job.duration() {
+        println!("⏱️  Duração: {:.1}s", duration.num_milliseconds() as f64 / 1000.0);
+    }
+    println!("🆕 Novos: {}", job.novos);
+    println!("🔄 Atualizados: {}", job.atualizados);
+    println!("➖ Inalterados: {}", job.inalterados);
+    println!("🚫 Rejeitados: {}", job.rejeitados);
+
+    if !job.erros.is_empty() {
+        println!();
+        println!("{:>7}  Erro", "Linha");
+        for erro in &job.erros {
+            let linha = erro.linha.map(|l| l.to_string()).unwrap_or_else(|| "-".to_string());
+            println!("{:>7}  {}", linha, erro.mensagem);
+        }
+        if job.erros.len() < job.rejeitados {
+            println!("   ... e mais {} linhas rejeitadas", job.rejeitados - job.erros.len());
+        }
+    }
+}
+
+/// Conecta ao banco configurado em `DATABASE_URL` (padrão: diretório local)
+async fn connect_db() -> anyhow::Result<pc_db::PersonalControllerDb> {
+    let url = std::env::var("DATABASE_URL").unwrap_or_else(|_| DEFAULT_DATABASE_URL.to_string());
+    Ok(pc_db::PersonalControllerDb::connect(&url).await?)
+}
+
 async fn chat_command(query: String) -> anyhow::Result<()> {
     println!("💬 Personal-Controller-LLM");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -118,16 +1478,17 @@ async fn stats_command() -> anyhow::Result<()> {
     println!("📊 Estatísticas do Personal Controller");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     
-    // TODO: Conectar ao banco e buscar stats
-    // let db = pc_db::PersonalControllerDb::connect("http://localhost:8000").await?;
-    // let stats = db.stats().await?;
-    
-    println!("🏢 Empresas: 0");
-    println!("📦 Ordens de Frete: 0");
-    println!("⏰ Registros de Ponto: 0");
-    println!("👥 Contatos: 0");
-    println!("🗺️ Rotas: 0");
-    println!("📄 Manifestos: 0");
+    let db = connect_db().await?;
+    let stats = db.stats().await?;
+    
+    println!("🏢 Empresas: {}", stats.companies);
+    println!("📦 Ordens de Frete: {}", stats.freight_orders);
+    println!("⏰ Registros de Ponto: {}", stats.timesheets);
+    println!("👥 Contatos: {}", stats.contacts);
+    println!("🗺️ Rotas: {}", stats.routes);
+    println!("📄 Manifestos: {}", stats.manifests);
+    println!("💰 Frete: {}", stats.valor_frete_total);
+    println!("🧾 Valor das notas: {}", stats.valor_notas_total);
     
     Ok(())
 }
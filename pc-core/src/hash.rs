@@ -0,0 +1,54 @@
+//! Hashes de conteúdo usados para identificar arquivos e documentos
+
+use crate::Result;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// SHA-256 em hexadecimal minúsculo
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// SHA-256 do conteúdo de um arquivo, lido em blocos
+pub fn sha256_file<P: AsRef<Path>>(path: P) -> Result<String> {
+    let mut file = std::fs::File::open(path.as_ref())
+        .map_err(|e| crate::Error::Import(format!("Erro ao abrir arquivo: {}", e)))?;
+
+    sha256_copy(&mut file, &mut std::io::sink())
+        .map_err(|e| crate::Error::Import(format!("Erro ao ler arquivo: {}", e)))
+}
+
+/// Copia `reader` para `writer` em blocos, calculando o SHA-256 no caminho
+pub fn sha256_copy<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+    }
+    writer.flush()?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let mut copy = Vec::new();
+        assert_eq!(sha256_copy(&mut &b"abc"[..], &mut copy).unwrap(), sha256_hex(b"abc"));
+        assert_eq!(copy, b"abc");
+    }
+}
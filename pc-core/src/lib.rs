@@ -6,6 +6,13 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use uuid::Uuid;
 
+pub mod hash;
+pub mod money;
+pub mod validation;
+
+pub use money::{Money, Rounding};
+pub use validation::{Locale, Violation, Violations};
+
 /// Result type padrão do Personal Controller
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -21,6 +28,10 @@ pub enum Error {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// Entidade com uma ou mais regras violadas
+    #[error("Validation error: {0}")]
+    Invalid(Violations),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
@@ -29,6 +40,9 @@ pub enum Error {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
 }
 
 /// Trait para entidades que podem ser armazenadas no banco
@@ -39,8 +53,28 @@ pub trait Entity: Send + Sync {
     /// Retorna o tipo da entidade
     fn entity_type(&self) -> &str;
 
-    /// Valida a entidade
-    fn validate(&self) -> Result<()>;
+    /// Confere as regras da entidade, acumulando todas as violações
+    fn check(&self, violations: &mut Violations);
+
+    /// Valida a entidade; o erro ([`Error::Invalid`]) traz todas as violações
+    fn validate(&self) -> Result<()> {
+        let mut violations = Violations::new();
+        self.check(&mut violations);
+        violations.into_result()
+    }
+}
+
+/// Trait para entidades identificadas por uma chave de negócio
+///
+/// Usada para fazer upsert na reimportação de arquivos: um registro com a
+/// mesma chave natural substitui o existente em vez de criar um duplicado.
+pub trait NaturalKey: Entity {
+    /// Chave de negócio (ex.: número da ordem, chave do CT-e)
+    fn natural_key(&self) -> String;
+
+    /// Copia do registro existente o que não vem do arquivo (ID, vínculos
+    /// já resolvidos e auditoria), para que só o conteúdo seja comparado
+    fn adopt_identity(&mut self, existing: &Self);
 }
 
 /// Trait para entidades que podem ser importadas de CSV
@@ -83,6 +117,22 @@ impl fmt::Display for Status {
     }
 }
 
+impl std::str::FromStr for Status {
+    type Err = Error;
+
+    /// Aceita o nome em inglês ou em português, sem diferenciar maiúsculas
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "pending" | "pendente" => Ok(Status::Pending),
+            "processing" | "processando" => Ok(Status::Processing),
+            "completed" | "concluido" | "concluído" => Ok(Status::Completed),
+            "failed" | "falhou" => Ok(Status::Failed),
+            "cancelled" | "cancelado" => Ok(Status::Cancelled),
+            other => Err(Error::Validation(format!("Status desconhecido: {}", other))),
+        }
+    }
+}
+
 /// Metadados de auditoria
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditMetadata {
@@ -118,7 +168,7 @@ impl Pagination {
     }
 
     pub fn total_pages(&self) -> usize {
-        (self.total + self.per_page - 1) / self.per_page
+        self.total.div_ceil(self.per_page)
     }
 
     pub fn offset(&self) -> usize {
@@ -148,5 +198,7 @@ mod tests {
     fn test_status_display() {
         assert_eq!(Status::Pending.to_string(), "Pendente");
         assert_eq!(Status::Completed.to_string(), "Concluído");
+        assert_eq!("cancelado".parse::<Status>().unwrap(), Status::Cancelled);
+        assert_eq!("Processing".parse::<Status>().unwrap(), Status::Processing);
     }
 }
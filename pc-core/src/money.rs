@@ -0,0 +1,407 @@
+//! Valores monetários em reais
+//!
+//! [`Money`] guarda centavos em um `i64`: somar milhares de fretes não
+//! acumula o erro de arredondamento do ponto flutuante. Frações de centavo
+//! (percentuais, rateios, textos com mais de duas casas) passam sempre por
+//! uma regra de [`Rounding`] explícita.
+
+use crate::{Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Valor em reais (BRL), em centavos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+/// Regra para frações de centavo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Metade para longe do zero (0,005 vira 0,01), o arredondamento comercial
+    #[default]
+    HalfUp,
+    /// Metade para o par (ABNT NBR 5891): sem viés em somas longas
+    HalfEven,
+    /// Descarta a fração
+    Down,
+}
+
+impl Rounding {
+    /// `num / den` arredondado; `den` é positivo
+    fn divide(self, num: i128, den: i128) -> i128 {
+        let (quotient, remainder) = (num / den, num % den);
+        if remainder == 0 {
+            return quotient;
+        }
+        let twice = 2 * remainder.abs();
+        let away = match self {
+            Rounding::HalfUp => twice >= den,
+            Rounding::HalfEven => twice > den || (twice == den && quotient % 2 != 0),
+            Rounding::Down => false,
+        };
+        if away {
+            quotient + num.signum()
+        } else {
+            quotient
+        }
+    }
+}
+
+/// Escala dos fatores em ponto flutuante (6 casas), para multiplicar em inteiros
+const FACTOR_SCALE: i128 = 1_000_000;
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    pub const fn from_reais(reais: i64) -> Self {
+        Money(reais * 100)
+    }
+
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+
+    /// Converte de ponto flutuante pela representação decimal mais curta
+    /// (1.005 vira R$ 1,01, e não R$ 1,00); `None` para NaN e infinito
+    pub fn from_f64(value: f64) -> Option<Self> {
+        value.is_finite().then(|| Self::from_decimal(&value.to_string(), Rounding::HalfUp)).flatten()
+    }
+
+    /// Para cálculos que aceitam a aproximação (médias, gráficos)
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn abs(self) -> Self {
+        Money(self.0.abs())
+    }
+
+    /// Multiplica por um fator (0.02 para 2%), arredondando ao centavo
+    pub fn scale(self, factor: f64, rounding: Rounding) -> Self {
+        let factor = (factor * FACTOR_SCALE as f64).round() as i128;
+        Money(rounding.divide(self.0 as i128 * factor, FACTOR_SCALE) as i64)
+    }
+
+    /// Percentual do valor (2.0 para 2%)
+    pub fn percent(self, rate: f64, rounding: Rounding) -> Self {
+        self.scale(rate / 100.0, rounding)
+    }
+
+    /// Divide por um inteiro positivo, arredondando ao centavo
+    pub fn divide(self, divisor: i64, rounding: Rounding) -> Self {
+        assert!(divisor > 0, "divisor deve ser positivo");
+        Money(rounding.divide(self.0 as i128, divisor as i128) as i64)
+    }
+
+    /// Rateio em `parts` parcelas que somam exatamente o valor; os centavos
+    /// que sobram vão para as primeiras parcelas
+    pub fn split(self, parts: usize) -> Vec<Money> {
+        if parts == 0 {
+            return Vec::new();
+        }
+        let (base, rest) = (self.0 / parts as i64, self.0 % parts as i64);
+        (0..parts as i64)
+            .map(|i| Money(base + if i < rest.abs() { rest.signum() } else { 0 }))
+            .collect()
+    }
+
+    /// Lê um valor digitado em pt-BR: "R$ 1.234,56", "1234,5", "-35,90",
+    /// "35,90-" e "(35,90)". Sem vírgula, o ponto é decimal ("1234.56"),
+    /// exceto quando separa milhares ("1.234", "1.234.567"). Mais de duas
+    /// casas decimais são arredondadas por `HalfUp`.
+    pub fn parse(value: &str) -> Result<Self> {
+        Self::parse_with(value, Rounding::HalfUp)
+    }
+
+    pub fn parse_with(value: &str, rounding: Rounding) -> Result<Self> {
+        let invalid = || Error::Validation(format!("Valor monetário inválido: '{}'", value));
+
+        let mut text: String = value
+            .replace("R$", "")
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let mut negative = false;
+        if let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            text = inner.to_string();
+            negative = true;
+        }
+        if let Some(rest) = text.strip_prefix('-').or_else(|| text.strip_suffix('-')) {
+            // Um sinal só: "(-5)" é recusado
+            if negative {
+                return Err(invalid());
+            }
+            text = rest.to_string();
+            negative = true;
+        } else if let Some(rest) = text.strip_prefix('+') {
+            text = rest.to_string();
+        }
+        if text.contains(['-', '+']) {
+            return Err(invalid());
+        }
+
+        // Milhar: primeiro grupo de 1 a 3 dígitos sem zero à esquerda e os
+        // demais com 3 ("0.125" é decimal)
+        let mut groups = text.split('.');
+        let first = groups.next().unwrap_or_default();
+        let thousands_only = !text.contains(',')
+            && text.contains('.')
+            && (1..=3).contains(&first.len())
+            && !first.starts_with('0')
+            && groups.all(|group| group.len() == 3);
+        let decimal = if text.contains(',') || thousands_only {
+            text.replace('.', "").replace(',', ".")
+        } else {
+            text
+        };
+
+        let money = Self::from_decimal(&decimal, rounding).ok_or_else(invalid)?;
+        Ok(if negative { -money } else { money })
+    }
+
+    /// Decimal com ponto e sem separador de milhares, como em OFX e JSON
+    /// ("1234.5", "-0.500")
+    pub fn parse_decimal(value: &str) -> Result<Self> {
+        Self::from_decimal(value.trim(), Rounding::HalfUp)
+            .ok_or_else(|| Error::Validation(format!("Valor monetário inválido: '{}'", value)))
+    }
+
+    fn from_decimal(value: &str, rounding: Rounding) -> Option<Self> {
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if int.is_empty() && frac.is_empty() {
+            return None;
+        }
+        if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) || frac.len() > 18 {
+            return None;
+        }
+
+        let int: i128 = if int.is_empty() { 0 } else { int.parse().ok()? };
+        let scale = 10i128.pow(frac.len() as u32);
+        let frac: i128 = if frac.is_empty() { 0 } else { frac.parse().ok()? };
+        let mut cents = rounding.divide((int * scale + frac) * 100, scale);
+        if negative {
+            cents = -cents;
+        }
+        i64::try_from(cents).ok().map(Money)
+    }
+
+    /// "1.234,56", sem o símbolo
+    pub fn format_plain(self) -> String {
+        let reais = (self.0 / 100).unsigned_abs().to_string();
+        let mut grouped = String::with_capacity(reais.len() + reais.len() / 3);
+        for (i, c) in reais.chars().enumerate() {
+            if i > 0 && (reais.len() - i).is_multiple_of(3) {
+                grouped.push('.');
+            }
+            grouped.push(c);
+        }
+        let sign = if self.0 < 0 { "-" } else { "" };
+        format!("{}{},{:02}", sign, grouped, (self.0 % 100).unsigned_abs())
+    }
+
+    /// "1234.56": para arquivos e chaves que esperam ponto decimal
+    pub fn to_decimal_string(self) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        format!("{}{}.{:02}", sign, (self.0 / 100).unsigned_abs(), (self.0 % 100).unsigned_abs())
+    }
+}
+
+/// "R$ 1.234,56" e "-R$ 35,90"; respeita largura e alinhamento
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plain = self.abs().format_plain();
+        let text = if self.is_negative() { format!("-R$ {}", plain) } else { format!("R$ {}", plain) };
+        f.pad(&text)
+    }
+}
+
+impl FromStr for Money {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Money::parse(s)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, times: i64) -> Money {
+        Money(self.0 * times)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+/// Número em reais com duas casas (`1234.56`), como os valores já gravados
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+/// Aceita número em reais ou texto ("1.234,56", "R$ 10,00", "1234.56")
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct MoneyVisitor;
+
+        impl de::Visitor<'_> for MoneyVisitor {
+            type Value = Money;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("um valor em reais")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Money, E> {
+                v.checked_mul(100).map(Money).ok_or_else(|| E::custom("valor fora do limite"))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Money, E> {
+                i64::try_from(v).map_err(E::custom).and_then(|v| self.visit_i64(v))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Money, E> {
+                Money::from_f64(v).ok_or_else(|| E::custom(format!("valor inválido: {}", v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Money, E> {
+                Money::parse(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format_pt_br() {
+        assert_eq!(Money::parse("R$ 1.234,56").unwrap(), Money::from_cents(123_456));
+        assert_eq!(Money::parse("1234,5").unwrap(), Money::from_cents(123_450));
+        assert_eq!(Money::parse("1.234").unwrap(), Money::from_reais(1234));
+        assert_eq!(Money::parse("1.234.567").unwrap(), Money::from_reais(1_234_567));
+        assert_eq!(Money::parse("1234.56").unwrap(), Money::from_cents(123_456));
+        assert_eq!(Money::parse_decimal("-1.500").unwrap(), Money::from_cents(-150));
+        assert_eq!(Money::parse("-35,90").unwrap(), Money::from_cents(-3590));
+        assert_eq!(Money::parse("35,90-").unwrap(), Money::from_cents(-3590));
+        assert_eq!(Money::parse("(R$ 35,90)").unwrap(), Money::from_cents(-3590));
+        assert_eq!(Money::parse("90").unwrap(), Money::from_reais(90));
+        assert!(Money::parse("").is_err());
+        assert!(Money::parse("R$ abc").is_err());
+        assert!(Money::parse("1,2,3").is_err());
+        assert_eq!(Money::parse("0.125").unwrap(), Money::from_cents(13));
+        assert_eq!(Money::parse("1234.567").unwrap(), Money::from_cents(123_457));
+        assert!(Money::parse("--5").is_err());
+        assert!(Money::parse("(-5)").is_err());
+
+        assert_eq!(Money::from_cents(123_456_789).to_string(), "R$ 1.234.567,89");
+        assert_eq!(Money::from_cents(-3590).to_string(), "-R$ 35,90");
+        assert_eq!(Money::from_cents(5).format_plain(), "0,05");
+        assert_eq!(Money::from_cents(-125_050).to_decimal_string(), "-1250.50");
+        assert_eq!(format!("{:>12}", Money::from_reais(10)), "    R$ 10,00");
+    }
+
+    #[test]
+    fn test_arithmetic_and_rounding() {
+        // 0,1 + 0,2 em ponto flutuante não dá 0,3
+        let total: Money = std::iter::repeat_n(Money::parse("0,10").unwrap(), 3).sum();
+        assert_eq!(total, Money::parse("0,30").unwrap());
+        assert_eq!(Money::from_reais(10) - Money::from_cents(1), Money::from_cents(999));
+
+        assert_eq!(Money::parse_with("0,125", Rounding::HalfUp).unwrap(), Money::from_cents(13));
+        assert_eq!(Money::parse_with("0,125", Rounding::HalfEven).unwrap(), Money::from_cents(12));
+        assert_eq!(Money::parse_with("0,135", Rounding::HalfEven).unwrap(), Money::from_cents(14));
+        assert_eq!(Money::parse_with("-0,125", Rounding::HalfUp).unwrap(), Money::from_cents(-13));
+        assert_eq!(Money::parse_with("0,129", Rounding::Down).unwrap(), Money::from_cents(12));
+
+        assert_eq!(Money::from_reais(1250).percent(2.0, Rounding::HalfUp), Money::from_reais(25));
+        assert_eq!(Money::from_cents(1001).divide(2, Rounding::HalfEven), Money::from_cents(500));
+        assert_eq!(
+            Money::from_reais(100).split(3),
+            [Money::from_cents(3334), Money::from_cents(3333), Money::from_cents(3333)]
+        );
+        assert_eq!(Money::from_f64(1.005), Some(Money::from_cents(101)));
+        assert_eq!(Money::from_f64(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_serde_as_reais() {
+        let valor = Money::from_cents(123_456);
+        assert_eq!(serde_json::to_string(&valor).unwrap(), "1234.56");
+        assert_eq!(serde_json::from_str::<Money>("1234.56").unwrap(), valor);
+        assert_eq!(serde_json::from_str::<Money>("50").unwrap(), Money::from_reais(50));
+        assert_eq!(serde_json::from_str::<Money>("\"R$ 1.234,56\"").unwrap(), valor);
+        assert!(serde_json::from_str::<Money>("\"abc\"").is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//! Validação das entidades
+//!
+//! As regras de cada entidade acumulam as violações em [`Violations`] em vez
+//! de parar na primeira: importadores, repositórios e API mostram todas de
+//! uma vez, cada uma com o caminho do campo, um código estável e a mensagem
+//! no idioma pedido.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Idioma das mensagens de validação
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    PtBr,
+    En,
+}
+
+impl Locale {
+    /// Primeiro idioma suportado de um cabeçalho `Accept-Language`
+    pub fn from_accept_language(header: &str) -> Self {
+        header
+            .split(',')
+            .filter_map(|tag| tag.split(';').next())
+            .map(|tag| tag.trim().to_ascii_lowercase())
+            .find_map(|tag| match tag.split('-').next() {
+                Some("pt") => Some(Locale::PtBr),
+                Some("en") => Some(Locale::En),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Códigos de violação usados pelos modelos
+pub mod codes {
+    pub const REQUIRED: &str = "required";
+    pub const NEGATIVE: &str = "negative";
+    pub const NOT_POSITIVE: &str = "not_positive";
+    pub const ZERO: &str = "zero";
+    pub const OUT_OF_RANGE: &str = "out_of_range";
+    pub const BEFORE: &str = "before";
+    pub const EXCEEDS: &str = "exceeds";
+    pub const LENGTH: &str = "length";
+    pub const CHECK_DIGIT: &str = "check_digit";
+    pub const MISMATCH: &str = "mismatch";
+    pub const INVALID_FORMAT: &str = "invalid_format";
+    pub const INVALID_CNPJ: &str = "invalid_cnpj";
+    pub const INVALID_CPF: &str = "invalid_cpf";
+    pub const INVALID_IE: &str = "invalid_ie";
+    pub const INVALID_UF: &str = "invalid_uf";
+    pub const INVALID_EMAIL: &str = "invalid_email";
+    pub const INVALID_PHONE: &str = "invalid_phone";
+    pub const INVALID_CEP: &str = "invalid_cep";
+}
+
+/// Mensagem de cada código; `{field}` e os parâmetros são substituídos
+fn template(code: &str, locale: Locale) -> &'static str {
+    use codes::*;
+    match (code, locale) {
+        (REQUIRED, Locale::PtBr) => "{field} é obrigatório",
+        (REQUIRED, Locale::En) => "{field} is required",
+        (NEGATIVE, Locale::PtBr) => "{field} não pode ser negativo",
+        (NEGATIVE, Locale::En) => "{field} cannot be negative",
+        (NOT_POSITIVE, Locale::PtBr) => "{field} deve ser maior que zero",
+        (NOT_POSITIVE, Locale::En) => "{field} must be greater than zero",
+        (ZERO, Locale::PtBr) => "{field} não pode ser zero",
+        (ZERO, Locale::En) => "{field} cannot be zero",
+        (OUT_OF_RANGE, Locale::PtBr) => "{field} deve estar entre {min} e {max}",
+        (OUT_OF_RANGE, Locale::En) => "{field} must be between {min} and {max}",
+        (BEFORE, Locale::PtBr) => "{field} não pode ser anterior a {other}",
+        (BEFORE, Locale::En) => "{field} cannot be before {other}",
+        (EXCEEDS, Locale::PtBr) => "{field} não pode ser maior que {other}",
+        (EXCEEDS, Locale::En) => "{field} cannot be greater than {other}",
+        (LENGTH, Locale::PtBr) => "{field} deve ter {length} dígitos",
+        (LENGTH, Locale::En) => "{field} must have {length} digits",
+        (CHECK_DIGIT, Locale::PtBr) => "{field}: dígito verificador não confere",
+        (CHECK_DIGIT, Locale::En) => "{field}: check digit does not match",
+        (MISMATCH, Locale::PtBr) => "{field} não confere com {other}",
+        (MISMATCH, Locale::En) => "{field} does not match {other}",
+        (INVALID_FORMAT, Locale::PtBr) => "{field}: formato inválido",
+        (INVALID_FORMAT, Locale::En) => "{field}: invalid format",
+        (INVALID_CNPJ, Locale::PtBr) => "{field}: CNPJ inválido",
+        (INVALID_CNPJ, Locale::En) => "{field}: invalid CNPJ",
+        (INVALID_CPF, Locale::PtBr) => "{field}: CPF inválido",
+        (INVALID_CPF, Locale::En) => "{field}: invalid CPF",
+        (INVALID_IE, Locale::PtBr) => "{field}: inscrição estadual inválida para {uf}",
+        (INVALID_IE, Locale::En) => "{field}: invalid state registration for {uf}",
+        (INVALID_UF, Locale::PtBr) => "{field}: UF desconhecida",
+        (INVALID_UF, Locale::En) => "{field}: unknown state",
+        (INVALID_EMAIL, Locale::PtBr) => "{field}: e-mail inválido",
+        (INVALID_EMAIL, Locale::En) => "{field}: invalid e-mail",
+        (INVALID_PHONE, Locale::PtBr) => "{field}: telefone deve ter 10 ou 11 dígitos",
+        (INVALID_PHONE, Locale::En) => "{field}: phone must have 10 or 11 digits",
+        (INVALID_CEP, Locale::PtBr) => "{field}: CEP deve ter 8 dígitos",
+        (INVALID_CEP, Locale::En) => "{field}: postal code must have 8 digits",
+        (_, Locale::PtBr) => "{field} inválido",
+        (_, Locale::En) => "{field} is invalid",
+    }
+}
+
+/// Regra violada por um campo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+    /// Caminho do campo ("peso", "notas_fiscais[2]", "ctes[0].chave_acesso")
+    pub field: String,
+    /// Código estável da regra (ver [`codes`])
+    pub code: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(field: impl Into<String>, code: impl Into<String>) -> Self {
+        let mut violation = Self {
+            field: field.into(),
+            code: code.into(),
+            params: BTreeMap::new(),
+            message: String::new(),
+        };
+        violation.message = violation.message_in(Locale::default());
+        violation
+    }
+
+    pub fn with_param(mut self, name: &str, value: impl fmt::Display) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self.message = self.message_in(Locale::default());
+        self
+    }
+
+    /// Mensagem no idioma pedido
+    pub fn message_in(&self, locale: Locale) -> String {
+        let mut message = template(&self.code, locale).replace("{field}", &self.field);
+        for (name, value) in &self.params {
+            message = message.replace(&format!("{{{}}}", name), value);
+        }
+        message
+    }
+
+    /// Cópia com a mensagem no idioma pedido
+    pub fn localized(&self, locale: Locale) -> Self {
+        Self {
+            message: self.message_in(locale),
+            ..self.clone()
+        }
+    }
+}
+
+/// Violações encontradas na validação de uma ou mais entidades
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Violations(Vec<Violation>);
+
+impl Violations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, violation: Violation) {
+        self.0.push(violation);
+    }
+
+    /// Registra a violação de `code` em `field`
+    pub fn add(&mut self, field: impl Into<String>, code: &str) {
+        self.push(Violation::new(field, code));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Violation> {
+        self.0.iter()
+    }
+
+    /// Violações de uma entidade aninhada, com `prefix` no caminho dos campos
+    pub fn nested(&mut self, prefix: &str, inner: Violations) {
+        for mut violation in inner.0 {
+            violation.field = format!("{}.{}", prefix, violation.field);
+            violation.message = violation.message_in(Locale::default());
+            self.0.push(violation);
+        }
+    }
+
+    /// Todas as violações com a mensagem no idioma pedido
+    pub fn localized(&self, locale: Locale) -> Vec<Violation> {
+        self.0.iter().map(|v| v.localized(locale)).collect()
+    }
+
+    /// `Ok` sem violações; senão [`crate::Error::Invalid`] com todas elas
+    pub fn into_result(self) -> crate::Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::Error::Invalid(self))
+        }
+    }
+
+    // Regras comuns
+
+    /// Texto obrigatório (não vazio nem só espaços)
+    pub fn required(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.add(field, codes::REQUIRED);
+        }
+    }
+
+    pub fn non_negative<T: PartialOrd + Default>(&mut self, field: &str, value: T) {
+        if value < T::default() {
+            self.add(field, codes::NEGATIVE);
+        }
+    }
+
+    pub fn positive<T: PartialOrd + Default>(&mut self, field: &str, value: T) {
+        if value <= T::default() {
+            self.add(field, codes::NOT_POSITIVE);
+        }
+    }
+
+    pub fn in_range<T: PartialOrd + fmt::Display>(&mut self, field: &str, value: T, min: T, max: T) {
+        if value < min || value > max {
+            self.push(Violation::new(field, codes::OUT_OF_RANGE).with_param("min", min).with_param("max", max));
+        }
+    }
+
+    /// `value` (quando informado) não pode ser anterior a `other`
+    pub fn not_before<T: PartialOrd>(&mut self, field: &str, value: Option<T>, other_field: &str, other: T) {
+        if value.is_some_and(|value| value < other) {
+            self.push(Violation::new(field, codes::BEFORE).with_param("other", other_field));
+        }
+    }
+
+    /// `value` não pode passar de `other`
+    pub fn not_greater<T: PartialOrd>(&mut self, field: &str, value: T, other_field: &str, other: T) {
+        if value > other {
+            self.push(Violation::new(field, codes::EXCEEDS).with_param("other", other_field));
+        }
+    }
+
+    /// Exatamente `length` dígitos; devolve se a regra foi atendida
+    pub fn digits(&mut self, field: &str, value: &str, length: usize) -> bool {
+        let ok = value.len() == length && value.bytes().all(|b| b.is_ascii_digit());
+        if !ok {
+            self.push(Violation::new(field, codes::LENGTH).with_param("length", length));
+        }
+        ok
+    }
+}
+
+impl fmt::Display for Violations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.0.iter().map(|v| v.message.as_str()).collect();
+        f.write_str(&messages.join("; "))
+    }
+}
+
+impl From<Vec<Violation>> for Violations {
+    fn from(violations: Vec<Violation>) -> Self {
+        Self(violations)
+    }
+}
+
+impl IntoIterator for Violations {
+    type Item = Violation;
+    type IntoIter = std::vec::IntoIter<Violation>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Violations {
+    type Item = &'a Violation;
+    type IntoIter = std::slice::Iter<'a, Violation>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_all_violations() {
+        let mut violations = Violations::new();
+        violations.required("numero", " ");
+        violations.non_negative("peso", -1.0);
+        violations.not_before("data_entrega", Some(1), "data_emissao", 2);
+        violations.digits("chave_acesso", "123", 44);
+
+        let codes: Vec<&str> = violations.iter().map(|v| v.code.as_str()).collect();
+        assert_eq!(codes, ["required", "negative", "before", "length"]);
+        assert_eq!(violations.iter().nth(2).unwrap().message, "data_entrega não pode ser anterior a data_emissao");
+        assert_eq!(
+            violations.localized(Locale::En)[3].message,
+            "chave_acesso must have 44 digits"
+        );
+        assert!(matches!(violations.into_result(), Err(crate::Error::Invalid(v)) if v.len() == 4));
+    }
+
+    #[test]
+    fn test_nested_paths_and_locale() {
+        let mut inner = Violations::new();
+        inner.required("numero", "");
+        let mut violations = Violations::new();
+        violations.nested("ctes[1]", inner);
+        assert_eq!(violations.iter().next().unwrap().field, "ctes[1].numero");
+        assert_eq!(violations.to_string(), "ctes[1].numero é obrigatório");
+
+        assert_eq!(Locale::from_accept_language("en-US,en;q=0.9,pt-BR;q=0.8"), Locale::En);
+        assert_eq!(Locale::from_accept_language("fr, pt-BR"), Locale::PtBr);
+        assert_eq!(Locale::from_accept_language(""), Locale::PtBr);
+    }
+}
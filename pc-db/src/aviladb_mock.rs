@@ -1,10 +1,7 @@
 // Mock implementation of AvilaDB for development
 // TODO: Replace with real aviladb when ../../arxis/aviladb is available
 
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use async_trait::async_trait;
-use uuid::Uuid;
 use anyhow::{Result, Error};
 
 /// Mock vector index
@@ -24,6 +21,10 @@ impl VectorIndex {
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub async fn insert(&mut self, id: impl Into<String>, vector: Vec<f32>) -> Result<()> {
         if vector.len() != self.dimension {
             return Err(Error::msg(format!(
@@ -67,6 +68,10 @@ impl DocumentStore {
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub async fn insert(&mut self, id: impl Into<String>, doc: serde_json::Value) -> Result<()> {
         self.documents.insert(id.into(), doc);
         Ok(())
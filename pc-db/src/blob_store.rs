@@ -0,0 +1,183 @@
+//! Armazenamento de arquivos endereçado por conteúdo
+//!
+//! Cada arquivo é gravado uma única vez em `<dir>/<aa>/<sha256>`, onde `aa`
+//! são os dois primeiros caracteres do hash. Cópias do mesmo arquivo não
+//! ocupam espaço extra, e o hash confirma a integridade na leitura.
+
+use pc_core::hash::{sha256_copy, sha256_hex};
+use pc_core::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+pub struct BlobStore {
+    /// Diretório dos blobs quando conectado via `file://`
+    root: Option<PathBuf>,
+    // Sem diretório, os blobs ficam em memória
+    memory: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl BlobStore {
+    pub fn in_memory() -> Self {
+        Self {
+            root: None,
+            memory: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Abre (ou cria) o blob store em `dir`
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| pc_core::Error::Database(format!("Erro ao criar {:?}: {}", dir, e)))?;
+
+        Ok(Self {
+            root: Some(dir.to_path_buf()),
+            memory: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Grava o conteúdo e retorna o sha256 que o identifica
+    pub fn put(&self, content: &[u8]) -> Result<String> {
+        let sha256 = sha256_hex(content);
+
+        let Some(path) = self.path(&sha256) else {
+            self.memory
+                .write()
+                .map_err(|_| pc_core::Error::Database("Lock envenenado no blob store".into()))?
+                .entry(sha256.clone())
+                .or_insert_with(|| content.to_vec());
+            return Ok(sha256);
+        };
+
+        if !path.exists() {
+            let dir = path.parent().expect("blob dentro do diretório do store");
+            let tmp = path.with_extension("tmp");
+            std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(&tmp, content))
+                .and_then(|_| std::fs::rename(&tmp, &path))
+                .map_err(|e| pc_core::Error::Database(format!("Erro ao gravar blob {}: {}", sha256, e)))?;
+        }
+        Ok(sha256)
+    }
+
+    /// Grava o conteúdo de um arquivo e retorna o sha256
+    ///
+    /// O arquivo é copiado em blocos para um temporário enquanto o hash é
+    /// calculado, e o temporário é renomeado para o hash no fim: arquivos
+    /// grandes não passam inteiros pela memória nem são lidos duas vezes.
+    pub fn put_file(&self, path: &Path) -> Result<String> {
+        let read_error = |e: std::io::Error| pc_core::Error::Database(format!("Erro ao ler {:?}: {}", path, e));
+        let mut file = std::fs::File::open(path).map_err(read_error)?;
+
+        let Some(root) = &self.root else {
+            let mut content = Vec::new();
+            sha256_copy(&mut file, &mut content).map_err(read_error)?;
+            return self.put(&content);
+        };
+
+        let tmp = root.join(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let copied = std::fs::File::create(&tmp)
+            .map(std::io::BufWriter::new)
+            .and_then(|mut writer| sha256_copy(&mut file, &mut writer));
+        let sha256 = match copied {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp);
+                return Err(read_error(e));
+            }
+        };
+
+        let blob = self.path(&sha256).expect("store em disco");
+        let stored = if blob.exists() {
+            std::fs::remove_file(&tmp)
+        } else {
+            std::fs::create_dir_all(blob.parent().expect("blob dentro do diretório do store"))
+                .and_then(|_| std::fs::rename(&tmp, &blob))
+        };
+        stored.map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            pc_core::Error::Database(format!("Erro ao gravar blob {}: {}", sha256, e))
+        })?;
+        Ok(sha256)
+    }
+
+    /// Lê um blob, conferindo o hash
+    pub fn get(&self, sha256: &str) -> Result<Option<Vec<u8>>> {
+        if !is_sha256(sha256) {
+            return Ok(None);
+        }
+        let content = match self.path(sha256) {
+            Some(path) if path.exists() => std::fs::read(&path)
+                .map_err(|e| pc_core::Error::Database(format!("Erro ao ler blob {}: {}", sha256, e)))?,
+            Some(_) => return Ok(None),
+            None => match self
+                .memory
+                .read()
+                .map_err(|_| pc_core::Error::Database("Lock envenenado no blob store".into()))?
+                .get(sha256)
+            {
+                Some(content) => content.clone(),
+                None => return Ok(None),
+            },
+        };
+
+        if sha256_hex(&content) != sha256 {
+            return Err(pc_core::Error::Database(format!("Blob {} corrompido", sha256)));
+        }
+        Ok(Some(content))
+    }
+
+    pub fn contains(&self, sha256: &str) -> bool {
+        if !is_sha256(sha256) {
+            return false;
+        }
+        match self.path(sha256) {
+            Some(path) => path.exists(),
+            None => self.memory.read().is_ok_and(|m| m.contains_key(sha256)),
+        }
+    }
+
+    /// Caminho do blob em disco (`None` no store em memória)
+    pub fn path(&self, sha256: &str) -> Option<PathBuf> {
+        let root = self.root.as_ref()?;
+        Some(root.join(sha256.get(..2).unwrap_or("00")).join(sha256))
+    }
+}
+
+/// Impede que hashes vindos de fora (API, CLI) saiam do diretório do store
+fn is_sha256(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_is_content_addressed() {
+        let dir = std::env::temp_dir().join(format!("pc-blobs-{}", uuid::Uuid::new_v4()));
+        let store = BlobStore::open(&dir).unwrap();
+
+        let sha256 = store.put(b"conteudo do CT-e").unwrap();
+        assert_eq!(store.put(b"conteudo do CT-e").unwrap(), sha256);
+        assert!(store.path(&sha256).unwrap().starts_with(dir.join(&sha256[..2])));
+        assert_eq!(store.get(&sha256).unwrap().unwrap(), b"conteudo do CT-e");
+
+        // Arquivo alterado em disco não passa na conferência do hash
+        std::fs::write(store.path(&sha256).unwrap(), b"outro conteudo").unwrap();
+        assert!(store.get(&sha256).is_err());
+
+        assert!(store.get(&"0".repeat(64)).unwrap().is_none());
+        assert!(store.get("../../etc/passwd").unwrap().is_none());
+
+        // Arquivo copiado em blocos: mesmo hash do conteúdo, sem temporários
+        let file = dir.join("cte.xml");
+        std::fs::write(&file, b"<cteProc/>").unwrap();
+        let sha256 = store.put_file(&file).unwrap();
+        assert_eq!(sha256, sha256_hex(b"<cteProc/>"));
+        assert_eq!(store.get(&sha256).unwrap().unwrap(), b"<cteProc/>");
+        assert_eq!(store.put_file(&file).unwrap(), sha256);
+        assert!(!std::fs::read_dir(&dir).unwrap().any(|e| e.unwrap().path().extension().is_some_and(|x| x == "tmp")));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
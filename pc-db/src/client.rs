@@ -1,8 +1,32 @@
 //! Personal Controller Database Client
 
-use crate::repository::Repository;
-use pc_core::Result;
-use pc_models::{Company, FreightOrder, Timesheet, Contact, Route, Manifest};
+use crate::blob_store::BlobStore;
+use crate::repository::{Repository, UpsertReport};
+use crate::GenericRepository;
+use pc_core::{Money, Result, Status};
+use pc_models::{Boleto, Company, CompanyReview, Cte, Document, FreightOrder, ImportCheckpoint, ImportJob, ImportedFile, Invoice, PaymentTransaction, PhotoLink, Timesheet, Contact, Route, Manifest};
+use serde::Serialize;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Collections que podem ser consultadas pelo nome (exportação, API)
+pub const COLLECTIONS: &[&str] = &[
+    "companies",
+    "freight_orders",
+    "timesheets",
+    "contacts",
+    "routes",
+    "manifests",
+    "ctes",
+    "invoices",
+    "payments",
+    "boletos",
+    "imported_files",
+    "photo_links",
+    "documents",
+    "company_reviews",
+    "import_jobs",
+];
 
 /// Cliente principal do Personal Controller Database
 pub struct PersonalControllerDb {
@@ -16,16 +40,39 @@ pub struct PersonalControllerDb {
     pub contacts: Repository<Contact>,
     pub routes: Repository<Route>,
     pub manifests: Repository<Manifest>,
+    pub ctes: Repository<Cte>,
+    pub invoices: Repository<Invoice>,
+    /// Lançamentos dos extratos bancários e sua conciliação
+    pub payments: Repository<PaymentTransaction>,
+    /// Títulos enviados ao banco nas remessas CNAB
+    pub boletos: Repository<Boleto>,
+    pub imported_files: Repository<ImportedFile>,
+    pub import_checkpoints: Repository<ImportCheckpoint>,
+    pub photo_links: Repository<PhotoLink>,
+    pub documents: Repository<Document>,
+    pub company_reviews: Repository<CompanyReview>,
+    pub import_jobs: Repository<ImportJob>,
+
+    /// Conteúdo dos documentos, por sha256
+    pub blobs: BlobStore,
 }
 
 impl PersonalControllerDb {
     /// Cria uma nova instância conectada ao AvilaDB
+    ///
+    /// URLs `file://<dir>` gravam as collections como JSON em `<dir>`;
+    /// as demais mantêm os dados apenas em memória.
     pub async fn connect(url: &str) -> Result<Self> {
         tracing::info!("Conectando ao AvilaDB em: {}", url);
         
         // TODO: Conectar ao AvilaDB real
         // let client = aviladb::Client::connect(url).await?;
         
+        if let Some(dir) = url.strip_prefix("file://") {
+            return Self::open_dir(Path::new(dir));
+        }
+
+        tracing::warn!("AvilaDB indisponível: dados mantidos apenas em memória");
         Ok(Self {
             companies: Repository::new("companies"),
             freight_orders: Repository::new("freight_orders"),
@@ -33,9 +80,123 @@ impl PersonalControllerDb {
             contacts: Repository::new("contacts"),
             routes: Repository::new("routes"),
             manifests: Repository::new("manifests"),
+            ctes: Repository::new("ctes"),
+            invoices: Repository::new("invoices"),
+            payments: Repository::new("payments"),
+            boletos: Repository::new("boletos"),
+            imported_files: Repository::new("imported_files"),
+            import_checkpoints: Repository::new("import_checkpoints"),
+            photo_links: Repository::new("photo_links"),
+            documents: Repository::new("documents"),
+            company_reviews: Repository::new("company_reviews"),
+            import_jobs: Repository::new("import_jobs"),
+            blobs: BlobStore::in_memory(),
+        })
+    }
+
+    fn open_dir(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| pc_core::Error::Database(format!("Erro ao criar {:?}: {}", dir, e)))?;
+
+        Ok(Self {
+            companies: Repository::open("companies", dir)?,
+            freight_orders: Repository::open("freight_orders", dir)?,
+            timesheets: Repository::open("timesheets", dir)?,
+            contacts: Repository::open("contacts", dir)?,
+            routes: Repository::open("routes", dir)?,
+            manifests: Repository::open("manifests", dir)?,
+            ctes: Repository::open("ctes", dir)?,
+            invoices: Repository::open("invoices", dir)?,
+            payments: Repository::open("payments", dir)?,
+            boletos: Repository::open("boletos", dir)?,
+            imported_files: Repository::open("imported_files", dir)?,
+            import_checkpoints: Repository::open("import_checkpoints", dir)?,
+            photo_links: Repository::open("photo_links", dir)?,
+            documents: Repository::open("documents", dir)?,
+            company_reviews: Repository::open("company_reviews", dir)?,
+            import_jobs: Repository::open("import_jobs", dir)?,
+            blobs: BlobStore::open(&dir.join("blobs"))?,
         })
     }
     
+    /// Guarda os arquivos no blob store e registra os documentos
+    ///
+    /// O conteúdo é lido de `caminho_origem`; o mesmo conteúdo vindo de
+    /// outro caminho vira um novo local do documento já existente.
+    pub async fn store_documents(&self, documents: Vec<Document>) -> Result<UpsertReport> {
+        let mut stored = Vec::with_capacity(documents.len());
+        for mut document in documents {
+            let path = Path::new(&document.caminho_origem);
+            let sha256 = self.blobs.put_file(path)?;
+            if !document.sha256.is_empty() && document.sha256 != sha256 {
+                return Err(pc_core::Error::Database(format!(
+                    "Arquivo {:?} mudou durante a importação",
+                    path
+                )));
+            }
+            document.sha256 = sha256;
+            stored.push(document);
+        }
+        self.documents.upsert_many(stored).await
+    }
+
+    /// Junta o journal de cada collection ao seu JSON
+    pub fn compact(&self) -> Result<()> {
+        self.companies.compact()?;
+        self.freight_orders.compact()?;
+        self.timesheets.compact()?;
+        self.contacts.compact()?;
+        self.routes.compact()?;
+        self.manifests.compact()?;
+        self.ctes.compact()?;
+        self.invoices.compact()?;
+        self.payments.compact()?;
+        self.boletos.compact()?;
+        self.imported_files.compact()?;
+        self.import_checkpoints.compact()?;
+        self.photo_links.compact()?;
+        self.documents.compact()?;
+        self.company_reviews.compact()?;
+        self.import_jobs.compact()
+    }
+
+    /// Documentos vinculados a uma entidade
+    pub async fn documents_linked_to(&self, entidade: &str, entidade_id: &Uuid) -> Result<Vec<Document>> {
+        Ok(self
+            .documents
+            .find_all()
+            .await?
+            .into_iter()
+            .filter(|d| d.is_linked_to(entidade, entidade_id))
+            .collect())
+    }
+
+    /// Registros de uma collection, pelo nome, como JSON
+    pub async fn collection_json(&self, name: &str) -> Result<Vec<serde_json::Value>> {
+        match name {
+            "companies" => to_json(self.companies.find_all().await?),
+            "freight_orders" => to_json(self.freight_orders.find_all().await?),
+            "timesheets" => to_json(self.timesheets.find_all().await?),
+            "contacts" => to_json(self.contacts.find_all().await?),
+            "routes" => to_json(self.routes.find_all().await?),
+            "manifests" => to_json(self.manifests.find_all().await?),
+            "ctes" => to_json(self.ctes.find_all().await?),
+            "invoices" => to_json(self.invoices.find_all().await?),
+            "payments" => to_json(self.payments.find_all().await?),
+            "boletos" => to_json(self.boletos.find_all().await?),
+            "imported_files" => to_json(self.imported_files.find_all().await?),
+            "photo_links" => to_json(self.photo_links.find_all().await?),
+            "documents" => to_json(self.documents.find_all().await?),
+            "company_reviews" => to_json(self.company_reviews.find_all().await?),
+            "import_jobs" => to_json(self.import_jobs.find_all().await?),
+            other => Err(pc_core::Error::NotFound(format!(
+                "Collection desconhecida: {} (disponíveis: {})",
+                other,
+                COLLECTIONS.join(", ")
+            ))),
+        }
+    }
+
     /// Cria as collections se não existirem
     pub async fn initialize(&self) -> Result<()> {
         tracing::info!("Inicializando collections do Personal Controller");
@@ -50,18 +211,35 @@ impl PersonalControllerDb {
     
     /// Retorna estatísticas do banco
     pub async fn stats(&self) -> Result<DatabaseStats> {
+        // Canceladas não entram no faturamento
+        let orders: Vec<FreightOrder> = self
+            .freight_orders
+            .find_all()
+            .await?
+            .into_iter()
+            .filter(|o| o.status != Status::Cancelled)
+            .collect();
         Ok(DatabaseStats {
-            companies: 0,
-            freight_orders: 0,
-            timesheets: 0,
-            contacts: 0,
-            routes: 0,
-            manifests: 0,
+            valor_frete_total: orders.iter().map(|o| o.valor_frete).sum(),
+            valor_notas_total: orders.iter().map(|o| o.valor_notas).sum(),
+            companies: self.companies.count().await?,
+            freight_orders: self.freight_orders.count().await?,
+            timesheets: self.timesheets.count().await?,
+            contacts: self.contacts.count().await?,
+            routes: self.routes.count().await?,
+            manifests: self.manifests.count().await?,
         })
     }
 }
 
-#[derive(Debug, Clone)]
+fn to_json<T: Serialize>(records: Vec<T>) -> Result<Vec<serde_json::Value>> {
+    records
+        .into_iter()
+        .map(|r| serde_json::to_value(r).map_err(|e| pc_core::Error::Database(format!("Erro ao serializar registro: {}", e))))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct DatabaseStats {
     pub companies: usize,
     pub freight_orders: usize,
@@ -69,4 +247,7 @@ pub struct DatabaseStats {
     pub contacts: usize,
     pub routes: usize,
     pub manifests: usize,
+    /// Soma do frete das ordens não canceladas
+    pub valor_frete_total: Money,
+    pub valor_notas_total: Money,
 }
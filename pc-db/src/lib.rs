@@ -17,9 +17,11 @@ pub use aviladb::AvilaDB;
 
 pub mod repository;
 pub mod client;
+pub mod blob_store;
 
-pub use repository::Repository;
-pub use client::PersonalControllerDb;
+pub use repository::{ChangedEntity, FieldChange, InvalidEntity, Repository, UpsertDiff, UpsertReport};
+pub use client::{DatabaseStats, PersonalControllerDb, COLLECTIONS};
+pub use blob_store::BlobStore;
 
 /// Trait para repositórios genéricos
 #[async_trait]
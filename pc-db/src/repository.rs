@@ -1,63 +1,601 @@
 //! Repository implementation using AvilaDB
 
 use async_trait::async_trait;
-use pc_core::{Entity, Result};
-use serde::{de::DeserializeOwned, Serialize};
+use pc_core::{Entity, NaturalKey, Result, Violation, Violations};
+use pc_models::{Boleto, ImportJob, ImportedFile};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use uuid::Uuid;
 
+/// Entradas no journal abaixo das quais não vale compactar
+const MIN_COMPACT_ENTRIES: usize = 1024;
+
 pub struct Repository<T> {
     collection_name: String,
-    _phantom: std::marker::PhantomData<T>,
+    // TODO: Trocar pelo AvilaDB; por enquanto os documentos ficam em memória
+    documents: RwLock<Documents<T>>,
+    /// Arquivo JSON da collection quando conectado via `file://`
+    storage: Option<PathBuf>,
+}
+
+/// Documentos em memória e o índice por chave natural
+struct Documents<T> {
+    by_id: BTreeMap<Uuid, T>,
+    /// Chave natural → ID; montado no primeiro upsert e mantido a cada lote.
+    /// `save`/`update`/`delete` não conhecem a chave e o descartam.
+    by_key: Option<HashMap<String, Uuid>>,
+    /// Entradas gravadas no journal desde a última compactação
+    journaled: usize,
+}
+
+impl<T> Deref for Documents<T> {
+    type Target = BTreeMap<Uuid, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.by_id
+    }
+}
+
+/// Linha do journal `<collection>.journal.jsonl`
+///
+/// Gravações acrescentam só as entidades alteradas; `open` reaplica o
+/// journal sobre o JSON da collection, e `compact` junta os dois.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JournalEntry<T> {
+    Put(T),
+    Delete(Uuid),
+}
+
+/// Resultado do upsert de um lote, por chave natural
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpsertReport {
+    pub new: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+impl UpsertReport {
+    pub fn total(&self) -> usize {
+        self.new.len() + self.updated.len() + self.unchanged.len()
+    }
+}
+
+/// Campo que mudaria em um upsert
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// Entidade existente que seria atualizada, com os campos alterados
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedEntity {
+    pub key: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Entidade que falharia na validação
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidEntity {
+    pub key: String,
+    pub error: String,
+    pub violations: Vec<Violation>,
+}
+
+/// O que `upsert_many` faria, sem gravar nada
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpsertDiff {
+    pub new: Vec<String>,
+    pub changed: Vec<ChangedEntity>,
+    pub unchanged: Vec<String>,
+    pub invalid: Vec<InvalidEntity>,
 }
 
 impl<T> Repository<T>
 where
-    T: Entity + Serialize + DeserializeOwned + Send + Sync,
+    T: Entity + Clone + Serialize + DeserializeOwned + Send + Sync,
 {
     pub fn new(collection_name: impl Into<String>) -> Self {
         Self {
             collection_name: collection_name.into(),
-            _phantom: std::marker::PhantomData,
+            documents: RwLock::new(Documents {
+                by_id: BTreeMap::new(),
+                by_key: None,
+                journaled: 0,
+            }),
+            storage: None,
+        }
+    }
+
+    /// Abre uma collection persistida em `<dir>/<collection>.json`
+    pub fn open(collection_name: impl Into<String>, dir: &Path) -> Result<Self> {
+        let collection_name = collection_name.into();
+        let path = dir.join(format!("{}.json", collection_name));
+
+        let (by_id, journaled) = load(&collection_name, &path)?;
+
+        Ok(Self {
+            collection_name,
+            documents: RwLock::new(Documents {
+                by_id,
+                by_key: None,
+                journaled,
+            }),
+            storage: Some(path),
+        })
+    }
+
+    pub fn collection_name(&self) -> &str {
+        &self.collection_name
+    }
+
+    pub async fn count(&self) -> Result<usize> {
+        Ok(self.read()?.len())
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, Documents<T>>> {
+        self.documents
+            .read()
+            .map_err(|_| pc_core::Error::Database(format!("Lock envenenado em {}", self.collection_name)))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, Documents<T>>> {
+        self.documents
+            .write()
+            .map_err(|_| pc_core::Error::Database(format!("Lock envenenado em {}", self.collection_name)))
+    }
+
+    /// Acrescenta ao journal as entidades gravadas (`written`, já em
+    /// `documents`) e removidas, compactando quando o journal passou do
+    /// tamanho da collection
+    fn persist(&self, documents: &mut Documents<T>, written: &[Uuid], deleted: &[Uuid]) -> Result<()> {
+        let Some(path) = &self.storage else {
+            return Ok(());
+        };
+        if written.is_empty() && deleted.is_empty() {
+            return Ok(());
+        }
+
+        let entries = written
+            .iter()
+            .filter_map(|id| documents.by_id.get(id).map(JournalEntry::Put))
+            .chain(deleted.iter().copied().map(JournalEntry::Delete));
+        let mut content = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut content, &entry)
+                .map_err(|e| pc_core::Error::Database(format!("Erro ao serializar {}: {}", self.collection_name, e)))?;
+            content.push(b'\n');
+        }
+
+        let journal = journal_path(path);
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&journal)
+            .and_then(|mut file| file.write_all(&content))
+            .map_err(|e| pc_core::Error::Database(format!("Erro ao gravar {:?}: {}", journal, e)))?;
+        documents.journaled += written.len() + deleted.len();
+
+        if documents.journaled > documents.by_id.len().max(MIN_COMPACT_ENTRIES) {
+            self.write_snapshot(documents)?;
         }
+        Ok(())
+    }
+
+    /// Reescreve o JSON da collection com o journal aplicado e o apaga
+    ///
+    /// Chamado ao fim das importações em lote; as gravações avulsas
+    /// compactam sozinhas quando o journal cresce demais.
+    pub fn compact(&self) -> Result<()> {
+        let mut documents = self.write()?;
+        if documents.journaled > 0 {
+            self.write_snapshot(&mut documents)?;
+        }
+        Ok(())
+    }
+
+    /// Grava a collection inteira (arquivo temporário + rename)
+    fn write_snapshot(&self, documents: &mut Documents<T>) -> Result<()> {
+        let Some(path) = &self.storage else {
+            return Ok(());
+        };
+
+        let entities: Vec<&T> = documents.by_id.values().collect();
+        let content = serde_json::to_vec_pretty(&entities)
+            .map_err(|e| pc_core::Error::Database(format!("Erro ao serializar {}: {}", self.collection_name, e)))?;
+
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| pc_core::Error::Database(format!("Erro ao gravar {:?}: {}", path, e)))?;
+
+        // O JSON já contém tudo; se o processo cair antes daqui, o journal
+        // é reaplicado sobre ele sem efeito
+        let journal = journal_path(path);
+        match std::fs::remove_file(&journal) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(pc_core::Error::Database(format!("Erro ao remover {:?}: {}", journal, e))),
+        }
+        documents.journaled = 0;
+        Ok(())
     }
 }
 
+fn journal_path(path: &Path) -> PathBuf {
+    path.with_extension("journal.jsonl")
+}
+
+/// Lê o JSON da collection e reaplica o journal; devolve também quantas
+/// entradas o journal tinha
+///
+/// Uma última linha incompleta (processo interrompido no meio da gravação)
+/// é descartada.
+fn load<T>(collection_name: &str, path: &Path) -> Result<(BTreeMap<Uuid, T>, usize)>
+where
+    T: Entity + DeserializeOwned,
+{
+    let mut documents = BTreeMap::new();
+    if path.exists() {
+        let content = std::fs::read(path)
+            .map_err(|e| pc_core::Error::Database(format!("Erro ao ler {:?}: {}", path, e)))?;
+        let entities: Vec<T> = serde_json::from_slice(&content)
+            .map_err(|e| pc_core::Error::Database(format!("Collection {} corrompida: {}", collection_name, e)))?;
+        documents.extend(entities.into_iter().map(|e| (*e.id(), e)));
+    }
+
+    let journal = journal_path(path);
+    if !journal.exists() {
+        return Ok((documents, 0));
+    }
+    let read_error = |e: std::io::Error| pc_core::Error::Database(format!("Erro ao ler {:?}: {}", journal, e));
+    let mut reader = std::io::BufReader::new(std::fs::File::open(&journal).map_err(read_error)?);
+
+    let mut line = Vec::new();
+    let mut valid_len = 0u64;
+    let mut entries = 0;
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line).map_err(read_error)?;
+        if read == 0 {
+            break;
+        }
+        match serde_json::from_slice::<JournalEntry<T>>(&line) {
+            Ok(JournalEntry::Put(entity)) => {
+                documents.insert(*entity.id(), entity);
+            }
+            Ok(JournalEntry::Delete(id)) => {
+                documents.remove(&id);
+            }
+            Err(e) if !line.ends_with(b"\n") => {
+                // Corta a linha para que a próxima gravação não a continue
+                tracing::warn!("Descartando última linha incompleta do journal de {}: {}", collection_name, e);
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .open(&journal)
+                    .and_then(|file| file.set_len(valid_len))
+                    .map_err(|e| pc_core::Error::Database(format!("Erro ao gravar {:?}: {}", journal, e)))?;
+                break;
+            }
+            Err(e) => {
+                return Err(pc_core::Error::Database(format!(
+                    "Journal de {} corrompido na linha {}: {}",
+                    collection_name,
+                    entries + 1,
+                    e
+                )))
+            }
+        }
+        valid_len += read as u64;
+        entries += 1;
+    }
+    Ok((documents, entries))
+}
+
+impl<T> Repository<T>
+where
+    T: NaturalKey + Clone + Serialize + DeserializeOwned + Send + Sync,
+{
+    /// Busca pela chave de negócio
+    pub async fn find_by_natural_key(&self, key: &str) -> Result<Option<T>> {
+        let documents = self.read()?;
+        if let Some(by_key) = &documents.by_key {
+            return Ok(by_key.get(key).and_then(|id| documents.by_id.get(id)).cloned());
+        }
+        Ok(documents.values().find(|e| e.natural_key() == key).cloned())
+    }
+
+    /// Insere ou atualiza cada entidade pela chave natural
+    ///
+    /// Entidades já existentes mantêm o ID e a auditoria originais e só são
+    /// regravadas quando o conteúdo mudou. O lote inteiro é validado antes
+    /// de gravar: se alguma entidade é inválida nada é gravado, e o erro traz
+    /// as violações de todas, com o campo prefixado pelo tipo e pela chave.
+    pub async fn upsert_many(&self, entities: Vec<T>) -> Result<UpsertReport> {
+        let mut violations = Violations::new();
+        for entity in &entities {
+            let mut own = Violations::new();
+            entity.check(&mut own);
+            violations.nested(&format!("{}[{}]", entity.entity_type(), entity.natural_key()), own);
+        }
+        violations.into_result()?;
+
+        let mut report = UpsertReport::default();
+        let mut guard = self.write()?;
+        let documents = &mut *guard;
+
+        let by_key = documents
+            .by_key
+            .get_or_insert_with(|| documents.by_id.values().map(|e| (e.natural_key(), *e.id())).collect());
+        let mut written = Vec::new();
+
+        for mut entity in entities {
+            let key = entity.natural_key();
+
+            match by_key.get(&key).and_then(|id| documents.by_id.get(id)) {
+                Some(existing) => {
+                    entity.adopt_identity(existing);
+                    if content_of(existing)? == content_of(&entity)? {
+                        report.unchanged.push(key);
+                    } else {
+                        written.push(*entity.id());
+                        documents.by_id.insert(*entity.id(), entity);
+                        report.updated.push(key);
+                    }
+                }
+                None => {
+                    by_key.insert(key.clone(), *entity.id());
+                    written.push(*entity.id());
+                    documents.by_id.insert(*entity.id(), entity);
+                    report.new.push(key);
+                }
+            }
+        }
+
+        // Chaves repetidas no lote gravam a mesma entidade mais de uma vez
+        written.sort();
+        written.dedup();
+        self.persist(documents, &written, &[])?;
+
+        tracing::debug!(
+            "upsert em {}: {} novos, {} atualizados, {} inalterados",
+            self.collection_name,
+            report.new.len(),
+            report.updated.len(),
+            report.unchanged.len()
+        );
+        Ok(report)
+    }
+}
+
+impl<T> Repository<T>
+where
+    T: NaturalKey + Clone + Serialize + DeserializeOwned + Send + Sync,
+{
+    /// Calcula o resultado de `upsert_many` campo a campo, sem gravar
+    ///
+    /// Entidades inválidas são listadas em vez de abortar, e chaves repetidas
+    /// no lote são comparadas com a versão anterior do próprio lote.
+    pub async fn diff_many(&self, entities: Vec<T>) -> Result<UpsertDiff> {
+        let mut diff = UpsertDiff::default();
+        let documents = self.read()?;
+
+        let existing: HashMap<String, &T> = documents.values().map(|e| (e.natural_key(), e)).collect();
+        let mut pending: HashMap<String, T> = HashMap::new();
+
+        for mut entity in entities {
+            let key = entity.natural_key();
+            let mut violations = Violations::new();
+            entity.check(&mut violations);
+            if !violations.is_empty() {
+                diff.invalid.push(InvalidEntity {
+                    key,
+                    error: violations.to_string(),
+                    violations: violations.into_iter().collect(),
+                });
+                continue;
+            }
+
+            match pending.get(&key).or(existing.get(&key).copied()) {
+                None => diff.new.push(key.clone()),
+                Some(current) => {
+                    entity.adopt_identity(current);
+                    let changes = field_changes(&content_of(current)?, &content_of(&entity)?);
+                    if changes.is_empty() {
+                        diff.unchanged.push(key.clone());
+                    } else {
+                        diff.changed.push(ChangedEntity { key: key.clone(), changes });
+                    }
+                }
+            }
+            pending.insert(key, entity);
+        }
+
+        Ok(diff)
+    }
+}
+
+impl Repository<ImportedFile> {
+    /// Verifica se o arquivo já foi importado com exatamente este conteúdo
+    pub async fn is_already_imported(&self, fonte_arquivo: &str, sha256: &str) -> Result<bool> {
+        Ok(self
+            .find_by_natural_key(fonte_arquivo)
+            .await?
+            .is_some_and(|f| f.sha256 == sha256))
+    }
+
+    /// Registra (ou atualiza) o hash do arquivo importado
+    pub async fn record(&self, file: ImportedFile) -> Result<()> {
+        self.upsert_many(vec![file]).await.map(|_| ())
+    }
+}
+
+impl Repository<ImportJob> {
+    /// Relê o job do disco, onde outro processo (`pc imports cancel`) pode
+    /// ter pedido o cancelamento, e atualiza a cópia em memória
+    pub async fn refresh(&self, id: &Uuid) -> Result<Option<ImportJob>> {
+        let Some(path) = &self.storage else {
+            return Ok(self.read()?.get(id).cloned());
+        };
+
+        let (mut jobs, _) = load::<ImportJob>(&self.collection_name, path)?;
+
+        let mut documents = self.write()?;
+        let Some(stored) = jobs.remove(id) else {
+            return Ok(documents.get(id).cloned());
+        };
+        if let Some(job) = documents.by_id.get_mut(id) {
+            job.cancelamento_solicitado |= stored.cancelamento_solicitado;
+            return Ok(Some(job.clone()));
+        }
+        documents.by_key = None;
+        documents.by_id.insert(*id, stored.clone());
+        Ok(Some(stored))
+    }
+
+    /// Pede o cancelamento do job; `None` se não existir
+    pub async fn request_cancel(&self, id: &Uuid) -> Result<Option<ImportJob>> {
+        let mut documents = self.write()?;
+        let Some(job) = documents.by_id.get_mut(id) else {
+            return Ok(None);
+        };
+        if !job.request_cancel() {
+            return Err(pc_core::Error::Validation(format!(
+                "Job {} já terminou ({})",
+                id, job.status
+            )));
+        }
+        let job = job.clone();
+        self.persist(&mut documents, &[*id], &[])?;
+        Ok(Some(job))
+    }
+
+    /// Jobs do mais recente para o mais antigo
+    pub async fn recent(&self, limit: usize) -> Result<Vec<ImportJob>> {
+        let mut jobs: Vec<ImportJob> = self.read()?.values().cloned().collect();
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.criado_em));
+        jobs.truncate(limit);
+        Ok(jobs)
+    }
+}
+
+impl Repository<Boleto> {
+    /// Próximo nosso número livre no banco
+    pub async fn next_nosso_numero(&self, banco: &str) -> Result<u64> {
+        Ok(self
+            .read()?
+            .values()
+            .filter(|b| b.banco == banco)
+            .filter_map(|b| b.nosso_numero.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1)
+    }
+
+    /// Próximo número sequencial de remessa para o banco
+    pub async fn next_remessa(&self, banco: &str) -> Result<u32> {
+        Ok(self
+            .read()?
+            .values()
+            .filter(|b| b.banco == banco)
+            .map(|b| b.remessa)
+            .max()
+            .unwrap_or(0)
+            + 1)
+    }
+}
+
+/// Conteúdo comparável da entidade (sem os metadados de auditoria)
+///
+/// A linhagem também fica de fora: reimportar os mesmos dados de outro
+/// arquivo não é uma alteração, e o registro mantém a origem anterior.
+fn content_of<T: Serialize>(entity: &T) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(entity)
+        .map_err(|e| pc_core::Error::Internal(format!("Erro ao serializar entidade: {}", e)))?;
+    if let Some(object) = value.as_object_mut() {
+        object.remove("metadata");
+        object.remove("linhagem");
+    }
+    Ok(value)
+}
+
+/// Campos de primeiro nível com valores diferentes
+fn field_changes(before: &serde_json::Value, after: &serde_json::Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let old = before.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            let new = after.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            (old != new).then(|| FieldChange {
+                field: field.clone(),
+                before: old,
+                after: new,
+            })
+        })
+        .collect()
+}
+
 #[async_trait]
 impl<T> super::GenericRepository<T> for Repository<T>
 where
-    T: Entity + Serialize + DeserializeOwned + Send + Sync + 'static,
+    T: Entity + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
 {
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<T>> {
-        // TODO: Implementar com AvilaDB
         tracing::debug!("find_by_id: {} in {}", id, self.collection_name);
-        Ok(None)
+        Ok(self.read()?.get(id).cloned())
     }
-    
+
     async fn find_all(&self) -> Result<Vec<T>> {
         tracing::debug!("find_all in {}", self.collection_name);
-        Ok(Vec::new())
+        Ok(self.read()?.values().cloned().collect())
     }
-    
+
     async fn save(&self, entity: &T) -> Result<()> {
         entity.validate()?;
         tracing::debug!("save: {} in {}", entity.id(), self.collection_name);
-        // TODO: Implementar com AvilaDB
-        Ok(())
+        let mut documents = self.write()?;
+        documents.by_key = None;
+        documents.by_id.insert(*entity.id(), entity.clone());
+        self.persist(&mut documents, &[*entity.id()], &[])
     }
-    
+
     async fn update(&self, entity: &T) -> Result<()> {
         entity.validate()?;
         tracing::debug!("update: {} in {}", entity.id(), self.collection_name);
-        // TODO: Implementar com AvilaDB
-        Ok(())
+        let mut documents = self.write()?;
+        if !documents.contains_key(entity.id()) {
+            return Err(pc_core::Error::NotFound(format!("{} {}", entity.entity_type(), entity.id())));
+        }
+        documents.by_key = None;
+        documents.by_id.insert(*entity.id(), entity.clone());
+        self.persist(&mut documents, &[*entity.id()], &[])
     }
-    
+
     async fn delete(&self, id: &Uuid) -> Result<()> {
         tracing::debug!("delete: {} from {}", id, self.collection_name);
-        // TODO: Implementar com AvilaDB
+        let mut documents = self.write()?;
+        if documents.by_id.remove(id).is_some() {
+            documents.by_key = None;
+            self.persist(&mut documents, &[], &[*id])?;
+        }
         Ok(())
     }
-    
+
     async fn find_paginated(&self, page: usize, per_page: usize) -> Result<Vec<T>> {
         tracing::debug!(
             "find_paginated: page={}, per_page={} in {}",
@@ -65,9 +603,15 @@ where
             per_page,
             self.collection_name
         );
-        Ok(Vec::new())
+        Ok(self
+            .read()?
+            .values()
+            .skip(page.saturating_sub(1) * per_page)
+            .take(per_page)
+            .cloned()
+            .collect())
     }
-    
+
     async fn find_by_field(&self, field: &str, value: &str) -> Result<Vec<T>> {
         tracing::debug!(
             "find_by_field: {}={} in {}",
@@ -75,6 +619,168 @@ where
             value,
             self.collection_name
         );
-        Ok(Vec::new())
+
+        let mut found = Vec::new();
+        for entity in self.read()?.values() {
+            let json = serde_json::to_value(entity)
+                .map_err(|e| pc_core::Error::Internal(format!("Erro ao serializar entidade: {}", e)))?;
+            let matches = match json.get(field) {
+                Some(serde_json::Value::String(s)) => s == value,
+                Some(other) => serde_json::from_str::<serde_json::Value>(value).is_ok_and(|v| &v == other),
+                None => false,
+            };
+            if matches {
+                found.push(entity.clone());
+            }
+        }
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GenericRepository;
+    use chrono::NaiveDate;
+    use pc_core::Money;
+    use pc_models::FreightOrder;
+
+    fn order(numero: &str, valor_frete: i64) -> FreightOrder {
+        let mut order = FreightOrder::new(
+            numero.into(),
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            "FENIOR".into(),
+            "FENIOR".into(),
+            "Ribeirão Preto".into(),
+            "Comercial Franca".into(),
+            "Franca".into(),
+        );
+        order.valor_frete = Money::from_reais(valor_frete);
+        order
+    }
+
+    #[tokio::test]
+    async fn test_upsert_by_natural_key() {
+        let repo = Repository::<FreightOrder>::new("freight_orders");
+
+        let first = repo.upsert_many(vec![order("1", 100), order("2", 50)]).await.unwrap();
+        assert_eq!(first.new, vec!["1", "2"]);
+
+        let original_id = repo.find_by_natural_key("1").await.unwrap().unwrap().id;
+
+        // Reimportação: pedidos recebem UUIDs novos, mas casam pelo número;
+        // vir de outra linha do arquivo não conta como alteração
+        let mut same = order("2", 50);
+        same.linhagem = Some(pc_models::Lineage::at_line(3));
+        let second = repo.upsert_many(vec![order("1", 120), same]).await.unwrap();
+        assert!(second.new.is_empty());
+        assert_eq!(second.updated, vec!["1"]);
+        assert_eq!(second.unchanged, vec!["2"]);
+
+        assert_eq!(repo.count().await.unwrap(), 2);
+        let updated = repo.find_by_id(&original_id).await.unwrap().unwrap();
+        assert_eq!(updated.valor_frete, Money::from_reais(120));
+    }
+
+    #[tokio::test]
+    async fn test_diff_many_without_writing() {
+        let repo = Repository::<FreightOrder>::new("freight_orders");
+        repo.upsert_many(vec![order("1", 100), order("2", 50)]).await.unwrap();
+
+        let diff = repo
+            .diff_many(vec![order("1", 120), order("2", 50), order("3", 10), order("3", 15)])
+            .await
+            .unwrap();
+
+        assert_eq!(diff.new, vec!["3"]);
+        assert_eq!(diff.unchanged, vec!["2"]);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].key, "1");
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![FieldChange {
+                field: "valor_frete".into(),
+                before: serde_json::json!(100.0),
+                after: serde_json::json!(120.0),
+            }]
+        );
+        // Nada foi gravado
+        assert_eq!(repo.find_by_natural_key("1").await.unwrap().unwrap().valor_frete, Money::from_reais(100));
+        assert_eq!(repo.count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_many_rejects_whole_batch() {
+        let repo = Repository::<FreightOrder>::new("freight_orders");
+        let mut late = order("2", 50);
+        late.data_entrega = NaiveDate::from_ymd_opt(2025, 3, 30);
+
+        let Err(pc_core::Error::Invalid(violations)) =
+            repo.upsert_many(vec![order("1", 100), late, order("3", -1)]).await
+        else {
+            panic!("esperava violações");
+        };
+        let fields: Vec<&str> = violations.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["freight_order[2].data_entrega", "freight_order[3].valor_frete"]);
+        assert_eq!(repo.count().await.unwrap(), 0);
+
+        let diff = repo.diff_many(vec![order("3", -1)]).await.unwrap();
+        assert_eq!(diff.invalid[0].violations[0].code, "negative");
+    }
+
+    #[tokio::test]
+    async fn test_file_storage_and_imported_files() {
+        let dir = std::env::temp_dir().join(format!("pc-db-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let repo = Repository::<ImportedFile>::open("imported_files", &dir).unwrap();
+        let sha = "a".repeat(64);
+        repo.record(ImportedFile::new("Planilha.csv".into(), sha.clone(), "freight".into(), 10))
+            .await
+            .unwrap();
+
+        let reopened = Repository::<ImportedFile>::open("imported_files", &dir).unwrap();
+        assert!(reopened.is_already_imported("Planilha.csv", &sha).await.unwrap());
+        assert!(!reopened.is_already_imported("Planilha.csv", &"b".repeat(64)).await.unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_batches_go_to_journal_until_compacted() {
+        let dir = std::env::temp_dir().join(format!("pc-db-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let snapshot = dir.join("freight_orders.json");
+        let journal = dir.join("freight_orders.journal.jsonl");
+
+        let repo = Repository::<FreightOrder>::open("freight_orders", &dir).unwrap();
+        repo.upsert_many(vec![order("1", 100), order("2", 50)]).await.unwrap();
+        repo.upsert_many(vec![order("1", 120), order("2", 50)]).await.unwrap();
+        let id = repo.find_by_natural_key("2").await.unwrap().unwrap().id;
+        repo.delete(&id).await.unwrap();
+
+        // Só as gravações foram acrescentadas; o JSON da collection não existe ainda
+        assert!(!snapshot.exists());
+        assert_eq!(std::fs::read_to_string(&journal).unwrap().lines().count(), 4);
+
+        // Linha cortada no fim (processo interrompido) é descartada
+        let mut file = std::fs::OpenOptions::new().append(true).open(&journal).unwrap();
+        file.write_all(b"{\"put\":{\"id\"").unwrap();
+
+        let reopened = Repository::<FreightOrder>::open("freight_orders", &dir).unwrap();
+        assert_eq!(reopened.count().await.unwrap(), 1);
+        let first = reopened.find_by_natural_key("1").await.unwrap().unwrap();
+        assert_eq!(first.valor_frete, Money::from_reais(120));
+        reopened.upsert_many(vec![order("3", 10)]).await.unwrap();
+
+        let again = Repository::<FreightOrder>::open("freight_orders", &dir).unwrap();
+        assert_eq!(again.count().await.unwrap(), 2);
+        again.compact().unwrap();
+        assert!(snapshot.exists());
+        assert!(!journal.exists());
+        let compacted = Repository::<FreightOrder>::open("freight_orders", &dir).unwrap();
+        assert_eq!(compacted.find_all().await.unwrap().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
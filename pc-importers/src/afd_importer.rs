@@ -0,0 +1,462 @@
+//! AFD (Arquivo Fonte de Dados) importer
+//!
+//! Lê os arquivos exportados pelos relógios de ponto (REP) e agrupa as
+//! marcações em registros de ponto por funcionário e dia.
+//!
+//! Suporta o leiaute da Portaria 671/2021 (marcações por CPF com data/hora
+//! ISO 8601) e o leiaute antigo da Portaria 1510/2009 (marcações por PIS).
+
+use pc_core::Result;
+use pc_models::{Lineage, Timesheet};
+use crate::streaming::{RejectedRow, RowStream, Streamed, StreamedRow, StreamingImporter};
+use crate::text;
+use crate::Importer;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use encoding_rs::WINDOWS_1252;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+/// Cabeçalho do AFD (registro tipo 1)
+#[derive(Debug, Clone)]
+pub struct AfdHeader {
+    pub empregador_documento: String,
+    pub razao_social: String,
+    pub numero_rep: String,
+}
+
+/// Marcação de ponto (registros tipo 3 e 7)
+#[derive(Debug, Clone)]
+pub struct AfdPunch {
+    pub nsr: u64,
+    pub line_number: usize,
+    /// CPF (Portaria 671) ou PIS (Portaria 1510), somente dígitos
+    pub empregado: String,
+    pub momento: NaiveDateTime,
+}
+
+/// Problemas encontrados durante a leitura do AFD
+#[derive(Debug, Clone, PartialEq)]
+pub enum AfdError {
+    /// Marcação sem par (entrada sem saída) no dia
+    OrphanPunch { nsr: u64, line_number: usize, empregado: String, momento: NaiveDateTime },
+    /// Salto na numeração sequencial dos registros
+    NsrGap { line_number: usize, esperado: u64, encontrado: u64 },
+    /// Registro que não pôde ser interpretado
+    InvalidRecord { line_number: usize, message: String },
+}
+
+impl fmt::Display for AfdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AfdError::OrphanPunch { nsr, line_number, empregado, momento } => write!(
+                f,
+                "Linha {}: marcação sem par: NSR {} empregado {} em {}",
+                line_number,
+                nsr,
+                empregado,
+                momento.format("%d/%m/%Y %H:%M")
+            ),
+            AfdError::NsrGap { line_number, esperado, encontrado } => write!(
+                f,
+                "Linha {}: NSR fora de sequência (esperado {}, encontrado {})",
+                line_number, esperado, encontrado
+            ),
+            AfdError::InvalidRecord { line_number, message } => {
+                write!(f, "Linha {}: {}", line_number, message)
+            }
+        }
+    }
+}
+
+impl AfdError {
+    /// Linha do arquivo onde está o problema
+    pub fn line_number(&self) -> usize {
+        match self {
+            AfdError::OrphanPunch { line_number, .. }
+            | AfdError::NsrGap { line_number, .. }
+            | AfdError::InvalidRecord { line_number, .. } => *line_number,
+        }
+    }
+}
+
+/// Resultado da leitura de um AFD
+#[derive(Debug, Clone)]
+pub struct AfdImport {
+    pub header: Option<AfdHeader>,
+    pub punches: Vec<AfdPunch>,
+    pub timesheets: Vec<Timesheet>,
+    pub errors: Vec<AfdError>,
+}
+
+pub struct AfdImporter;
+
+impl AfdImporter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Lê o conteúdo de um AFD, agrupa as marcações e reporta inconsistências
+    pub fn parse(&self, content: &str, fonte_arquivo: Option<String>) -> AfdImport {
+        let mut header = None;
+        let mut punches = Vec::new();
+        let mut nomes: HashMap<String, String> = HashMap::new();
+        let mut errors = Vec::new();
+        let mut last_nsr: Option<u64> = None;
+
+        for (idx, line) in content.lines().enumerate() {
+            let line_number = idx + 1;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (Some(nsr_str), Some(tipo)) = (line.get(0..9), line.get(9..10)) else {
+                errors.push(AfdError::InvalidRecord { line_number, message: "Registro muito curto".into() });
+                continue;
+            };
+
+            // Cabeçalho (000000000) e trailer (999999999) não entram na sequência
+            if nsr_str == "000000000" || nsr_str == "999999999" {
+                if tipo == "1" {
+                    header = Some(parse_header(line));
+                }
+                continue;
+            }
+
+            let Ok(nsr) = nsr_str.parse::<u64>() else {
+                errors.push(AfdError::InvalidRecord { line_number, message: format!("NSR inválido: {}", nsr_str) });
+                continue;
+            };
+
+            if let Some(last) = last_nsr {
+                if nsr != last + 1 {
+                    errors.push(AfdError::NsrGap { line_number, esperado: last + 1, encontrado: nsr });
+                }
+            }
+            last_nsr = Some(nsr);
+
+            match tipo {
+                "3" | "7" => match parse_punch(line, nsr, line_number) {
+                    Some(punch) => punches.push(punch),
+                    None => errors.push(AfdError::InvalidRecord {
+                        line_number,
+                        message: "Marcação de ponto inválida".into(),
+                    }),
+                },
+                "5" => {
+                    if let Some((empregado, nome)) = parse_employee(line) {
+                        nomes.insert(empregado, nome);
+                    }
+                }
+                // 2 = empresa, 4 = ajuste de relógio, 6 = eventos sensíveis
+                _ => {}
+            }
+        }
+
+        let timesheets = self.pair_punches(&punches, &nomes, fonte_arquivo, &mut errors);
+
+        AfdImport { header, punches, timesheets, errors }
+    }
+
+    /// Agrupa as marcações por empregado e dia, formando pares entrada/saída
+    ///
+    /// Dias com mais de duas marcações (intervalo de almoço) geram um único
+    /// registro com a primeira entrada e a última saída; o total de minutos
+    /// soma apenas os intervalos trabalhados.
+    fn pair_punches(
+        &self,
+        punches: &[AfdPunch],
+        nomes: &HashMap<String, String>,
+        fonte_arquivo: Option<String>,
+        errors: &mut Vec<AfdError>,
+    ) -> Vec<Timesheet> {
+        let mut by_day: BTreeMap<(String, NaiveDate), Vec<&AfdPunch>> = BTreeMap::new();
+        for punch in punches {
+            by_day
+                .entry((punch.empregado.clone(), punch.momento.date()))
+                .or_default()
+                .push(punch);
+        }
+
+        let mut timesheets = Vec::new();
+
+        for ((empregado, data), mut day) in by_day {
+            day.sort_by_key(|p| p.momento);
+
+            let pairs: Vec<(&AfdPunch, &AfdPunch)> = day.chunks_exact(2).map(|c| (c[0], c[1])).collect();
+            if day.len() % 2 == 1 {
+                let orphan = day[day.len() - 1];
+                errors.push(AfdError::OrphanPunch {
+                    nsr: orphan.nsr,
+                    line_number: orphan.line_number,
+                    empregado: empregado.clone(),
+                    momento: orphan.momento,
+                });
+            }
+
+            let (Some(first), Some(last)) = (pairs.first(), pairs.last()) else {
+                continue;
+            };
+
+            let funcionario = nomes.get(&empregado).cloned().unwrap_or_else(|| empregado.clone());
+            let mut timesheet = Timesheet::new(
+                funcionario,
+                format_mes(data),
+                data,
+                first.0.momento.time(),
+                last.1.momento.time(),
+            );
+            timesheet.linhagem = Some(Lineage::at_line(first.0.line_number as u64));
+
+            timesheet.total_minutos = pairs
+                .iter()
+                .map(|(entrada, saida)| (saida.momento - entrada.momento).num_minutes() as i32)
+                .sum();
+
+            let intervalos: Vec<String> = pairs
+                .iter()
+                .map(|(entrada, saida)| {
+                    format!("{}-{}", entrada.momento.format("%H:%M"), saida.momento.format("%H:%M"))
+                })
+                .collect();
+            timesheet.observacoes = Some(format!("CPF/PIS {}; marcações {}", empregado, intervalos.join(", ")));
+            timesheet.fonte_arquivo = fonte_arquivo.clone();
+
+            timesheets.push(timesheet);
+        }
+
+        timesheets
+    }
+}
+
+fn parse_header(line: &str) -> AfdHeader {
+    // Portaria 671: registro de 302 posições, razão social a partir da 40ª
+    // Portaria 1510: registro de 232 posições, razão social a partir da 38ª
+    let (razao_start, rep_start) = if line.len() >= 300 { (39, 189) } else { (37, 187) };
+
+    AfdHeader {
+        empregador_documento: field(line, 11, 14).to_string(),
+        razao_social: field(line, razao_start, 150).to_string(),
+        numero_rep: field(line, rep_start, 17).to_string(),
+    }
+}
+
+fn parse_punch(line: &str, nsr: u64, line_number: usize) -> Option<AfdPunch> {
+    let (momento, empregado) = if line.len() >= 46 {
+        // 671: data/hora ISO 8601 (24) + CPF (12)
+        let momento = NaiveDateTime::parse_from_str(field(line, 10, 16), "%Y-%m-%dT%H:%M").ok()?;
+        (momento, field(line, 34, 12))
+    } else {
+        // 1510: data DDMMAAAA + hora HHMM + PIS (12)
+        let data = NaiveDate::parse_from_str(field(line, 10, 8), "%d%m%Y").ok()?;
+        let hora = NaiveTime::parse_from_str(field(line, 18, 4), "%H%M").ok()?;
+        (data.and_time(hora), field(line, 22, 12))
+    };
+
+    Some(AfdPunch {
+        nsr,
+        line_number,
+        empregado: normalize_id(empregado),
+        momento,
+    })
+}
+
+fn parse_employee(line: &str) -> Option<(String, String)> {
+    let (empregado, nome) = if line.len() >= 100 {
+        // 671: data/hora (24) + operação (1) + CPF (12) + nome (52)
+        (field(line, 35, 12), field(line, 47, 52))
+    } else {
+        // 1510: data (8) + hora (4) + operação (1) + PIS (12) + nome (52)
+        (field(line, 23, 12), field(line, 35, 52))
+    };
+
+    if nome.is_empty() {
+        None
+    } else {
+        Some((normalize_id(empregado), nome.to_string()))
+    }
+}
+
+/// Campo a partir de um offset (base 0) com espaços removidos
+fn field(line: &str, start: usize, len: usize) -> &str {
+    let end = (start + len).min(line.len());
+    line.get(start..end).unwrap_or("").trim()
+}
+
+/// CPF e PIS têm 11 dígitos; o AFD os grava com 12 posições
+fn normalize_id(value: &str) -> String {
+    let digits = text::digits(value);
+    digits[digits.len().saturating_sub(11)..].to_string()
+}
+
+fn format_mes(data: NaiveDate) -> String {
+    const MESES: [&str; 12] = [
+        "Janeiro", "Fevereiro", "Março", "Abril", "Maio", "Junho",
+        "Julho", "Agosto", "Setembro", "Outubro", "Novembro", "Dezembro",
+    ];
+    format!("{}/{}", MESES[data.month0() as usize], data.year())
+}
+
+impl Default for AfdImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AfdImporter {
+    /// Lê e interpreta o arquivo inteiro (o pareamento precisa do dia completo)
+    pub fn parse_file(&self, path: &Path) -> Result<AfdImport> {
+        tracing::info!("Importando AFD de: {:?}", path);
+
+        let file_bytes = std::fs::read(path)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
+
+        let (content, _, _) = WINDOWS_1252.decode(&file_bytes);
+        let source_file = path.file_name()
+            .and_then(|n| n.to_str())
+            .map(|s| s.to_string());
+
+        let result = self.parse(&content, source_file);
+        tracing::info!(
+            "Lidos {} registros de ponto de {} marcações ({} inconsistências)",
+            result.timesheets.len(),
+            result.punches.len(),
+            result.errors.len()
+        );
+        Ok(result)
+    }
+}
+
+/// Registros e inconsistências em ordem de linha: marcações sem par e saltos
+/// de NSR chegam ao job como linhas rejeitadas
+impl StreamingImporter for AfdImporter {
+    type Output = Timesheet;
+
+    fn name(&self) -> &'static str {
+        "afd"
+    }
+
+    fn stream_rows<P: AsRef<Path>>(&self, path: P) -> Result<RowStream<'_, Self::Output>> {
+        let result = self.parse_file(path.as_ref())?;
+
+        let mut rows: Vec<StreamedRow<Timesheet>> = result
+            .timesheets
+            .into_iter()
+            .map(|timesheet| {
+                let line = timesheet.linhagem.as_ref().and_then(|l| l.linha).unwrap_or(0);
+                StreamedRow::Record(Streamed { line, record: timesheet })
+            })
+            .chain(result.errors.iter().map(|error| {
+                StreamedRow::Rejected(RejectedRow { line: error.line_number() as u64, reason: error.to_string() })
+            }))
+            .collect();
+        rows.sort_by_key(|row| match row {
+            StreamedRow::Record(streamed) => streamed.line,
+            StreamedRow::Rejected(rejected) => rejected.line,
+        });
+        Ok(Box::new(rows.into_iter().map(Ok)))
+    }
+}
+
+impl Importer for AfdImporter {
+    type Output = Timesheet;
+
+    /// Só os registros válidos; as inconsistências vão para o log (use
+    /// [`StreamingImporter::stream_rows`] para recebê-las)
+    fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
+        let result = self.parse_file(path.as_ref())?;
+        for error in &result.errors {
+            tracing::warn!("{}", error);
+        }
+        Ok(result.timesheets)
+    }
+
+    fn can_import<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
+            filename.to_uppercase().starts_with("AFD") && filename.to_lowercase().ends_with(".txt")
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_afd_671() {
+        let content = include_str!("../tests/fixtures/afd/AFD_671.txt");
+        let result = AfdImporter::new().parse(content, None);
+
+        let header = result.header.unwrap();
+        assert_eq!(header.empregador_documento, "12345678000195");
+        assert_eq!(header.razao_social, "AVILA TRANSPORTES LTDA");
+        assert_eq!(result.punches.len(), 9);
+
+        // Dia com intervalo de almoço: 08:00-12:00 e 13:00-17:30
+        let lindomar = result
+            .timesheets
+            .iter()
+            .find(|t| t.funcionario == "LINDOMAR DA SILVA" && t.data == NaiveDate::from_ymd_opt(2025, 4, 10).unwrap())
+            .unwrap();
+        assert_eq!(lindomar.entrada, NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+        assert_eq!(lindomar.saida, NaiveTime::from_hms_opt(17, 30, 0).unwrap());
+        assert_eq!(lindomar.total_minutos, 510);
+        assert_eq!(lindomar.mes, "Abril/2025");
+    }
+
+    #[test]
+    fn test_orphan_and_gap_errors() {
+        let content = include_str!("../tests/fixtures/afd/AFD_671.txt");
+        let result = AfdImporter::new().parse(content, None);
+
+        assert!(result.errors.iter().any(|e| matches!(
+            e,
+            AfdError::NsrGap { esperado: 9, encontrado: 11, .. }
+        )));
+        assert!(result.errors.iter().any(|e| matches!(
+            e,
+            AfdError::OrphanPunch { nsr: 12, .. }
+        )));
+        assert_eq!(result.errors.len(), 2);
+    }
+
+    #[test]
+    fn test_stream_rows_reports_inconsistencies() {
+        let path = std::env::temp_dir().join(format!("AFD-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, include_str!("../tests/fixtures/afd/AFD_671.txt")).unwrap();
+
+        let rows: Vec<StreamedRow<Timesheet>> =
+            AfdImporter::new().stream_rows(&path).unwrap().map(|r| r.unwrap()).collect();
+        let rejected: Vec<&RejectedRow> = rows
+            .iter()
+            .filter_map(|row| match row {
+                StreamedRow::Rejected(rejected) => Some(rejected),
+                StreamedRow::Record(_) => None,
+            })
+            .collect();
+        assert_eq!(rejected.len(), 2);
+        assert!(rejected.iter().any(|r| r.reason.contains("NSR fora de sequência")));
+        assert!(rejected.iter().any(|r| r.reason.contains("marcação sem par")));
+        assert!(rows.len() > rejected.len());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_afd_1510() {
+        let content = include_str!("../tests/fixtures/afd/AFD_1510.txt");
+        let result = AfdImporter::new().parse(content, None);
+
+        assert!(result.errors.is_empty());
+        assert_eq!(result.timesheets.len(), 2);
+
+        let dia = &result.timesheets[0];
+        assert_eq!(dia.funcionario, "JOAO PEREIRA");
+        assert_eq!(dia.data, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+        assert_eq!(dia.total_minutos, 480);
+    }
+}
@@ -0,0 +1,523 @@
+//! Arquivos compactados (ZIP) e caixas de e-mail (mbox/.eml)
+//!
+//! XMLs e PDFs de NF-e e CT-e chegam como anexos de e-mail e em lotes
+//! mensais zipados. O conteúdo é extraído para um diretório temporário,
+//! abrindo também ZIPs e e-mails aninhados, e cada arquivo passa pela
+//! detecção automática como se tivesse sido importado sozinho. A procedência
+//! (caminho dentro do contêiner e remetente, assunto e data do e-mail) fica
+//! registrada nos documentos e entidades gerados.
+
+use crate::csv_source::CsvOptions;
+use crate::detect::{self, AnyImporter, FileKind, ImportOutput};
+use crate::geo::Municipalities;
+use chrono::{DateTime, Utc};
+use mail_parser::{MessageParser, MimeHeaders};
+use pc_core::Result;
+use pc_db::{PersonalControllerDb, UpsertReport};
+use pc_models::{ImportedFile, Provenance};
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Níveis de contêineres aninhados abertos por padrão (ZIP dentro de e-mail...)
+pub const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// Limite do conteúdo extraído de um único arquivo, contra "zip bombs"
+const MAX_EXTRACTED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Arquivo extraído, ainda no diretório temporário
+#[derive(Debug, Clone)]
+pub struct ExtractedFile {
+    pub path: PathBuf,
+    pub origem: Provenance,
+}
+
+/// Diretório de extração, removido quando o conteúdo deixa de ser usado
+#[derive(Debug)]
+struct StagingDir(PathBuf);
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            tracing::warn!("Não foi possível remover {:?}: {}", self.0, e);
+        }
+    }
+}
+
+/// Um arquivo extraído e o resultado da sua importação
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: PathBuf,
+    pub origem: Provenance,
+    pub kind: FileKind,
+    pub sha256: String,
+    /// Registros lidos, ou o motivo de o arquivo não ter sido importado
+    pub output: std::result::Result<ImportOutput, String>,
+}
+
+/// Conteúdo importado de um ZIP ou caixa de e-mail
+#[derive(Debug, Clone)]
+pub struct ArchiveContents {
+    pub entries: Vec<ArchiveEntry>,
+    /// Mantém os arquivos extraídos até a gravação
+    staging: Arc<StagingDir>,
+}
+
+impl ArchiveContents {
+    /// Registros lidos em todos os arquivos extraídos
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter_map(|e| e.output.as_ref().ok()).map(ImportOutput::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn staging_dir(&self) -> &Path {
+        &self.staging.0
+    }
+}
+
+/// Importador de ZIPs e e-mails: extrai e repassa cada arquivo ao importador detectado
+pub struct ArchiveImporter {
+    max_depth: usize,
+    csv_options: CsvOptions,
+    municipalities: Option<Arc<Municipalities>>,
+    staging_root: PathBuf,
+}
+
+impl ArchiveImporter {
+    pub fn new() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            csv_options: CsvOptions::default(),
+            municipalities: None,
+            staging_root: std::env::temp_dir(),
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Ajustes de leitura repassados às planilhas extraídas
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
+
+    /// Lista de municípios repassada às planilhas de rotas extraídas
+    pub fn with_municipalities(mut self, municipalities: Arc<Municipalities>) -> Self {
+        self.municipalities = Some(municipalities);
+        self
+    }
+
+    /// Onde criar o diretório de extração (padrão: diretório temporário do sistema)
+    pub fn with_staging_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.staging_root = dir.into();
+        self
+    }
+
+    /// Extrai o arquivo e importa cada arquivo de dentro dele
+    pub fn import<P: AsRef<Path>>(&self, path: P) -> Result<ArchiveContents> {
+        let path = path.as_ref();
+        let content = std::fs::read(path)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler {:?}: {}", path, e)))?;
+
+        let staging = StagingDir(self.staging_root.join(format!("pc-archive-{}", uuid::Uuid::new_v4())));
+        std::fs::create_dir_all(&staging.0)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao criar {:?}: {}", staging.0, e)))?;
+
+        let arquivo = path.to_string_lossy().to_string();
+        let mut extraction = Extraction {
+            staging: &staging.0,
+            max_depth: self.max_depth,
+            budget: MAX_EXTRACTED_BYTES,
+            files: Vec::new(),
+        };
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        extraction.expand(content, &name, &Provenance::new(arquivo.clone(), arquivo), 0)?;
+
+        tracing::info!("{} arquivos extraídos de {:?}", extraction.files.len(), path);
+        let entries = extraction.files.into_iter().map(|f| self.import_extracted(f)).collect();
+        Ok(ArchiveContents {
+            entries,
+            staging: Arc::new(staging),
+        })
+    }
+
+    /// Detecta e importa um arquivo extraído
+    fn import_extracted(&self, file: ExtractedFile) -> ArchiveEntry {
+        let detection = detect::detect_file(&file.path);
+        let kind = detection.as_ref().map(|d| d.kind).unwrap_or(FileKind::Unknown);
+        let sha256 = pc_core::hash::sha256_file(&file.path).unwrap_or_default();
+
+        let output = match (detection, kind) {
+            (Err(e), _) => Err(e.to_string()),
+            (Ok(_), FileKind::Archive | FileKind::Email) => Err(format!(
+                "Contêiner aninhado além de {} níveis, não aberto",
+                self.max_depth
+            )),
+            (Ok(detection), kind) => match AnyImporter::for_kind(kind) {
+                Some(importer) => {
+                    let importer = importer.with_csv_options(self.csv_options);
+                    let importer = match &self.municipalities {
+                        Some(list) => importer.with_municipalities(Arc::clone(list)),
+                        None => importer,
+                    };
+                    importer.run(&file.path).map_err(|e| e.to_string())
+                }
+                None => Err(format!(
+                    "Sem importador para arquivos do tipo {} ({})",
+                    kind.as_str(),
+                    detection.reason
+                )),
+            },
+        };
+
+        ArchiveEntry {
+            output: output.map(|o| o.with_origem(&file.origem)),
+            path: file.path,
+            origem: file.origem,
+            kind,
+            sha256,
+        }
+    }
+}
+
+impl Default for ArchiveImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Estado da extração recursiva de um arquivo
+struct Extraction<'a> {
+    staging: &'a Path,
+    max_depth: usize,
+    /// Bytes que ainda podem ser extraídos
+    budget: u64,
+    files: Vec<ExtractedFile>,
+}
+
+impl Extraction<'_> {
+    /// Abre contêineres (até a profundidade máxima) e grava os demais arquivos
+    fn expand(&mut self, content: Vec<u8>, name: &str, origem: &Provenance, depth: usize) -> Result<()> {
+        let head = &content[..content.len().min(8 * 1024)];
+        match detect::sniff(Path::new(name), head).kind {
+            FileKind::Archive if depth <= self.max_depth => self.expand_zip(content, origem, depth),
+            FileKind::Email if depth <= self.max_depth => self.expand_mail(&content, origem, depth),
+            _ => self.stage(&content, name, origem),
+        }
+    }
+
+    fn expand_zip(&mut self, content: Vec<u8>, origem: &Provenance, depth: usize) -> Result<()> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(content))
+            .map_err(|e| pc_core::Error::Import(format!("ZIP inválido em {}: {}", origem.caminho, e)))?;
+
+        for index in 0..archive.len() {
+            let mut entry = archive
+                .by_index(index)
+                .map_err(|e| pc_core::Error::Import(format!("Erro ao ler {}: {}", origem.caminho, e)))?;
+            if entry.is_dir() {
+                continue;
+            }
+            // Caminhos absolutos ou com ".." não são extraídos
+            let Some(inner) = entry.enclosed_name() else {
+                tracing::warn!("Entrada ignorada em {}: caminho inseguro {:?}", origem.caminho, entry.name());
+                continue;
+            };
+            let inner = inner.to_string_lossy().replace('\\', "/");
+            if inner.starts_with("__MACOSX/") || inner.rsplit('/').next().is_some_and(|n| n.starts_with("._")) {
+                continue;
+            }
+
+            let mut data = Vec::with_capacity(entry.size().min(self.budget) as usize);
+            (&mut entry)
+                .take(self.budget + 1)
+                .read_to_end(&mut data)
+                .map_err(|e| pc_core::Error::Import(format!("Erro ao extrair {}/{}: {}", origem.caminho, inner, e)))?;
+            self.spend(data.len(), &origem.caminho)?;
+
+            let name = inner.rsplit('/').next().unwrap_or(&inner).to_string();
+            let child = Provenance {
+                caminho: format!("{}/{}", origem.caminho, inner),
+                ..origem.clone()
+            };
+            self.expand(data, &name, &child, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Uma caixa mbox (mensagens separadas por linhas "From ") ou uma única mensagem
+    fn expand_mail(&mut self, content: &[u8], origem: &Provenance, depth: usize) -> Result<()> {
+        if !content.starts_with(b"From ") {
+            return self.expand_message(content, origem, depth);
+        }
+
+        for (index, message) in mail_parser::mailbox::mbox::MessageIterator::new(Cursor::new(content)).enumerate() {
+            let message = message
+                .map_err(|e| pc_core::Error::Import(format!("Erro ao ler a caixa {}: {}", origem.caminho, e)))?;
+            let child = Provenance {
+                caminho: format!("{}/{}", origem.caminho, index + 1),
+                ..origem.clone()
+            };
+            self.expand_message(message.contents(), &child, depth)?;
+        }
+        Ok(())
+    }
+
+    fn expand_message(&mut self, raw: &[u8], origem: &Provenance, depth: usize) -> Result<()> {
+        let message = MessageParser::default()
+            .parse(raw)
+            .ok_or_else(|| pc_core::Error::Import(format!("E-mail inválido em {}", origem.caminho)))?;
+
+        let remetente = message.from().and_then(|from| from.first()).map(|addr| {
+            match (addr.name(), addr.address()) {
+                (Some(name), Some(address)) => format!("{} <{}>", name, address),
+                (name, address) => address.or(name).unwrap_or_default().to_string(),
+            }
+        });
+        let origem = Provenance {
+            remetente,
+            assunto: message.subject().map(String::from),
+            data_email: message.date().and_then(|d| DateTime::<Utc>::from_timestamp(d.to_timestamp(), 0)),
+            message_id: message.message_id().map(String::from),
+            ..origem.clone()
+        };
+
+        for (index, part) in message.attachments().enumerate() {
+            let name = part
+                .attachment_name()
+                .map(String::from)
+                .unwrap_or_else(|| match part.is_message() {
+                    true => format!("mensagem-{}.eml", index + 1),
+                    false => format!("anexo-{}", index + 1),
+                });
+            let child = Provenance {
+                caminho: format!("{}/{}", origem.caminho, name),
+                ..origem.clone()
+            };
+            // Mensagem encaminhada como anexo: a procedência passa a ser a dela
+            if part.is_message() && depth < self.max_depth {
+                self.expand_message(part.contents(), &child, depth + 1)?;
+            } else {
+                self.expand(part.contents().to_vec(), &name, &child, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Grava o arquivo no diretório de extração
+    fn stage(&mut self, content: &[u8], name: &str, origem: &Provenance) -> Result<()> {
+        // Um subdiretório por arquivo: anexos homônimos não se sobrescrevem
+        let dir = self.staging.join(self.files.len().to_string());
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao criar {:?}: {}", dir, e)))?;
+
+        let file_name = Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| "anexo".to_string());
+        let path = dir.join(file_name);
+        std::fs::write(&path, content)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao gravar {:?}: {}", path, e)))?;
+
+        self.files.push(ExtractedFile {
+            path,
+            origem: origem.clone(),
+        });
+        Ok(())
+    }
+
+    fn spend(&mut self, bytes: usize, caminho: &str) -> Result<()> {
+        self.budget = self.budget.checked_sub(bytes as u64).ok_or_else(|| {
+            pc_core::Error::Import(format!(
+                "{} excede o limite de {} MB extraídos",
+                caminho,
+                MAX_EXTRACTED_BYTES / (1024 * 1024)
+            ))
+        })?;
+        Ok(())
+    }
+}
+
+/// Grava o que foi importado de cada arquivo extraído
+///
+/// Cada arquivo é registrado em `imported_files` pelo próprio nome e hash:
+/// o mesmo anexo recebido em outro e-mail ou lote não é importado de novo.
+pub async fn store(db: &PersonalControllerDb, contents: &ArchiveContents) -> Result<UpsertReport> {
+    let mut total = UpsertReport::default();
+
+    for entry in &contents.entries {
+        let Ok(output) = &entry.output else { continue };
+        let fonte_arquivo = entry.path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        if db.imported_files.is_already_imported(&fonte_arquivo, &entry.sha256).await? {
+            continue;
+        }
+
+        // Cópia de cada arquivo extraído, para a linhagem e para os documentos
+        let sha256 = db.blobs.put_file(&entry.path)?;
+        let records = output.len();
+        let report = match output.clone() {
+            ImportOutput::FreightOrders(orders) => db.freight_orders.upsert_many(orders).await?,
+            ImportOutput::Timesheets(timesheets) => db.timesheets.upsert_many(timesheets).await?,
+            ImportOutput::Routes(routes) => db.routes.upsert_many(routes).await?,
+            // Os documentos apontam para o caminho dentro do contêiner; o
+            // conteúdo vem do arquivo extraído
+            ImportOutput::Documents(mut documents) => {
+                documents.iter_mut().for_each(|d| d.sha256 = sha256.clone());
+                db.documents.upsert_many(documents).await?
+            }
+            _ => continue,
+        };
+
+        db.imported_files
+            .record(ImportedFile::new(fonte_arquivo, entry.sha256.clone(), entry.kind.as_str().to_string(), records))
+            .await?;
+        total.new.extend(report.new);
+        total.updated.extend(report.updated);
+        total.unchanged.extend(report.unchanged);
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_db::GenericRepository;
+    use std::io::Write;
+
+    const TIMESHEET: &str = "funcionario;mes;data;entrada;saida\nJoão;Abril;01/04/2025;08:00;17:00\n";
+    const NFE: &str = "<?xml version=\"1.0\"?><nfeProc xmlns=\"http://www.portalfiscal.inf.br/nfe\"><NFe/></nfeProc>";
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pc-archive-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn mbox() -> String {
+        let csv = "ZnVuY2lvbmFyaW87bWVzO2RhdGE7ZW50cmFkYTtzYWlkYQpKb8OjbztBYnJpbDswMS8wNC8yMDI1OzA4OjAwOzE3OjAwCg==";
+        [
+            "From faturamento@fenior.com.br Mon Apr 14 09:30:00 2025",
+            "From: Faturamento Fenior <faturamento@fenior.com.br>",
+            "To: fretes@avila.com.br",
+            "Subject: Ponto de abril",
+            "Date: Mon, 14 Apr 2025 09:30:00 -0300",
+            "Message-ID: <ponto-abril@fenior.com.br>",
+            "MIME-Version: 1.0",
+            "Content-Type: multipart/mixed; boundary=\"b1\"",
+            "",
+            "--b1",
+            "Content-Type: text/plain; charset=utf-8",
+            "",
+            "Segue o ponto.",
+            "--b1",
+            "Content-Type: text/csv; name=\"ponto.csv\"",
+            "Content-Disposition: attachment; filename=\"ponto.csv\"",
+            "Content-Transfer-Encoding: base64",
+            "",
+            csv,
+            "--b1--",
+            "",
+            "From nfe@sefaz.example Tue Apr 15 10:00:00 2025",
+            "From: nfe@sefaz.example",
+            "Subject: NF-e 4512",
+            "Date: Tue, 15 Apr 2025 10:00:00 -0300",
+            "Message-ID: <nfe-4512@sefaz.example>",
+            "MIME-Version: 1.0",
+            "Content-Type: multipart/mixed; boundary=\"b2\"",
+            "",
+            "--b2",
+            "Content-Type: application/xml; name=\"nfe-4512.xml\"",
+            "Content-Disposition: attachment; filename=\"nfe-4512.xml\"",
+            "",
+            NFE,
+            "--b2--",
+            "",
+        ]
+        .join("\n")
+    }
+
+    #[test]
+    fn test_nested_zip_is_routed_with_provenance() {
+        let inner = zip(&[("nfe.xml", NFE.as_bytes()), ("leia-me.txt", b"lote de abril")]);
+        let outer = zip(&[("ponto/abril.csv", TIMESHEET.as_bytes()), ("notas.zip", &inner), ("__MACOSX/._x", b"")]);
+        let path = temp_file("lote-abril.zip", &outer);
+
+        let contents = ArchiveImporter::new().import(&path).unwrap();
+        let staging = contents.staging_dir().to_path_buf();
+        let caminhos: Vec<_> = contents.entries.iter().map(|e| e.origem.caminho.clone()).collect();
+        let base = path.to_string_lossy();
+        assert_eq!(
+            caminhos,
+            [
+                format!("{}/ponto/abril.csv", base),
+                format!("{}/notas.zip/nfe.xml", base),
+                format!("{}/notas.zip/leia-me.txt", base),
+            ]
+        );
+
+        let kinds: Vec<_> = contents.entries.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, [FileKind::TimesheetCsv, FileKind::NfeXml, FileKind::Unknown]);
+        assert!(contents.entries[2].output.is_err());
+        assert_eq!(contents.len(), 2);
+
+        let Ok(ImportOutput::Timesheets(timesheets)) = &contents.entries[0].output else {
+            panic!("esperava registros de ponto");
+        };
+        assert_eq!(timesheets[0].origem.as_ref().unwrap().caminho, caminhos[0]);
+        let Ok(ImportOutput::Documents(documents)) = &contents.entries[1].output else {
+            panic!("esperava documento");
+        };
+        assert_eq!(documents[0].tipo, "NF-e");
+        assert_eq!(documents[0].caminho_origem, caminhos[1]);
+
+        drop(contents);
+        assert!(!staging.exists());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mbox_attachments_record_sender_and_subject() {
+        let inner = zip(&[("caixa.mbox", mbox().as_bytes())]);
+        let path = temp_file("backup.zip", &inner);
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+
+        let contents = ArchiveImporter::new().import(&path).unwrap();
+        assert_eq!(contents.entries.len(), 2);
+        let ponto = &contents.entries[0].origem;
+        assert!(ponto.caminho.ends_with("backup.zip/caixa.mbox/1/ponto.csv"));
+        assert_eq!(ponto.remetente.as_deref(), Some("Faturamento Fenior <faturamento@fenior.com.br>"));
+        assert_eq!(ponto.assunto.as_deref(), Some("Ponto de abril"));
+        assert_eq!(ponto.data_email.unwrap().to_rfc3339(), "2025-04-14T12:30:00+00:00");
+        assert_eq!(contents.entries[1].origem.message_id.as_deref(), Some("nfe-4512@sefaz.example"));
+
+        let report = store(&db, &contents).await.unwrap();
+        assert_eq!(report.new.len(), 2);
+        let timesheets = db.timesheets.find_all().await.unwrap();
+        assert_eq!(timesheets[0].origem.as_ref().unwrap().assunto.as_deref(), Some("Ponto de abril"));
+        let documents = db.documents.find_all().await.unwrap();
+        assert_eq!(documents[0].origens[0].remetente.as_deref(), Some("nfe@sefaz.example"));
+
+        // O mesmo anexo em outro lote não é importado de novo
+        let again = store(&db, &ArchiveImporter::new().import(&path).unwrap()).await.unwrap();
+        assert_eq!(again.total(), 0);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,373 @@
+//! Extratos bancários (OFX e CSV)
+//!
+//! Os clientes pagam o frete por transferência, boleto e PIX; cada lançamento
+//! do extrato vira uma [`PaymentTransaction`] para a conciliação. O OFX é
+//! lido tanto no formato SGML (1.x, o que os bancos brasileiros exportam)
+//! quanto no XML (2.x). Extratos em CSV são lidos pelos nomes das colunas.
+
+use crate::csv_source::{decode, detect_encoding, CsvOptions, CsvSource};
+use crate::text::{digits, normalize_text};
+use crate::Importer;
+use chrono::NaiveDate;
+use pc_core::{Money, Result};
+use pc_models::{Lineage, PaymentTransaction};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Conta usada quando o extrato não informa
+pub const CONTA_NAO_INFORMADA: &str = "extrato";
+
+const DATE_COLUMNS: &[&str] = &["DATA", "DATA LANCAMENTO", "DATA MOVIMENTO", "DT LANCAMENTO", "DATA DO LANCAMENTO"];
+const DESCRIPTION_COLUMNS: &[&str] = &["HISTORICO", "DESCRICAO", "LANCAMENTO", "MEMO", "DETALHES"];
+const AMOUNT_COLUMNS: &[&str] = &["VALOR", "VALOR R", "VALOR RS", "MONTANTE"];
+const CREDIT_COLUMNS: &[&str] = &["CREDITO", "CREDITO R", "ENTRADA"];
+const DEBIT_COLUMNS: &[&str] = &["DEBITO", "DEBITO R", "SAIDA"];
+const DOCUMENT_COLUMNS: &[&str] = &["DOCUMENTO", "DOC", "NR DOCUMENTO", "N DOCUMENTO", "NUMERO DOCUMENTO"];
+const PAYER_COLUMNS: &[&str] = &["NOME", "FAVORECIDO", "PAGADOR", "REMETENTE", "ORIGEM"];
+const PAYER_DOCUMENT_COLUMNS: &[&str] = &["CPF CNPJ", "CNPJ", "CPF", "DOCUMENTO PAGADOR"];
+
+/// Importa lançamentos de extratos OFX ou CSV
+pub struct BankStatementImporter {
+    csv_options: CsvOptions,
+    account: Option<String>,
+    document_regex: Regex,
+}
+
+impl BankStatementImporter {
+    pub fn new() -> Self {
+        Self {
+            csv_options: CsvOptions::default(),
+            account: None,
+            // CNPJ ou CPF, com ou sem pontuação
+            document_regex: Regex::new(r"\b(\d{2}\.?\d{3}\.?\d{3}/?\d{4}-?\d{2}|\d{3}\.?\d{3}\.?\d{3}-?\d{2})\b").unwrap(),
+        }
+    }
+
+    /// Força encoding, delimitador ou aspas do CSV em vez de detectá-los
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
+
+    /// Conta do extrato; no OFX, substitui a informada no arquivo
+    pub fn with_account(mut self, account: impl Into<String>) -> Self {
+        self.account = Some(account.into());
+        self
+    }
+
+    /// Lê um extrato OFX já carregado
+    pub fn parse_ofx(&self, bytes: &[u8]) -> Result<Vec<PaymentTransaction>> {
+        let (content, _) = decode(bytes, detect_encoding(bytes));
+        if !content.to_uppercase().contains("<OFX>") {
+            return Err(pc_core::Error::Import("Arquivo não é um extrato OFX".into()));
+        }
+
+        let mut account: HashMap<&'static str, String> = HashMap::new();
+        let mut current: Option<HashMap<String, String>> = None;
+        let mut entries = Vec::new();
+
+        // SGML não fecha os elementos simples: o valor vai até a próxima tag
+        for chunk in content.split('<').skip(1) {
+            let (tag, value) = chunk.split_once('>').unwrap_or((chunk, ""));
+            let tag = tag.trim().to_uppercase();
+            let value = value.trim();
+
+            match tag.as_str() {
+                "STMTTRN" => current = Some(HashMap::new()),
+                "/STMTTRN" => entries.extend(current.take()),
+                "BANKID" | "BRANCHID" | "ACCTID" if !value.is_empty() => {
+                    let key = match tag.as_str() {
+                        "BANKID" => "BANKID",
+                        "BRANCHID" => "BRANCHID",
+                        _ => "ACCTID",
+                    };
+                    account.entry(key).or_insert_with(|| value.to_string());
+                }
+                _ if !tag.starts_with('/') && !value.is_empty() => {
+                    if let Some(entry) = current.as_mut() {
+                        entry.insert(tag, unescape(value));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let conta = self.account.clone().unwrap_or_else(|| {
+            let parts: Vec<&str> = ["BANKID", "BRANCHID", "ACCTID"]
+                .iter()
+                .filter_map(|k| account.get(k).map(String::as_str))
+                .collect();
+            if parts.is_empty() {
+                CONTA_NAO_INFORMADA.to_string()
+            } else {
+                parts.join("/")
+            }
+        });
+
+        let mut transactions = Vec::with_capacity(entries.len());
+        for (idx, entry) in entries.iter().enumerate() {
+            let data = entry.get("DTPOSTED").and_then(|d| parse_ofx_date(d));
+            let valor = entry.get("TRNAMT").and_then(|v| parse_ofx_amount(v));
+            let (Some(data), Some(valor)) = (data, valor) else {
+                tracing::warn!("Lançamento {} do OFX sem data ou valor válidos ignorado", idx + 1);
+                continue;
+            };
+            if valor.is_zero() {
+                continue;
+            }
+
+            let nome = entry.get("NAME").cloned();
+            let memo = entry.get("MEMO").cloned();
+            let descricao = match (&nome, &memo) {
+                (Some(nome), Some(memo)) if !memo.contains(nome.as_str()) => format!("{} - {}", nome, memo),
+                (_, Some(memo)) => memo.clone(),
+                (Some(nome), None) => nome.clone(),
+                (None, None) => entry.get("TRNTYPE").cloned().unwrap_or_default(),
+            };
+            let fitid = match entry.get("FITID") {
+                Some(fitid) => fitid.clone(),
+                None => derived_fitid(data, valor, &descricao, idx),
+            };
+
+            let mut transaction = PaymentTransaction::new(conta.clone(), fitid, data, valor, descricao);
+            // Em PIX e TED o banco costuma pôr o pagador no NAME
+            transaction.pagador_nome = nome.filter(|_| valor.is_positive());
+            transaction.pagador_documento = self.payer_document(&transaction.descricao);
+            transactions.push(transaction);
+        }
+
+        Ok(transactions)
+    }
+
+    /// Lê um extrato CSV já carregado
+    pub fn parse_csv(&self, source: &CsvSource) -> Result<Vec<PaymentTransaction>> {
+        let mut reader = source.reader();
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler cabeçalho do extrato: {}", e)))?
+            .iter()
+            .map(header_key)
+            .collect();
+        let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+
+        let date = column(DATE_COLUMNS);
+        let description = column(DESCRIPTION_COLUMNS);
+        let amount = column(AMOUNT_COLUMNS);
+        let credit = column(CREDIT_COLUMNS);
+        let debit = column(DEBIT_COLUMNS);
+        let (Some(date), Some(description)) = (date, description) else {
+            return Err(pc_core::Error::Import(
+                "Extrato CSV precisa das colunas de data e histórico".into(),
+            ));
+        };
+        if amount.is_none() && credit.is_none() && debit.is_none() {
+            return Err(pc_core::Error::Import("Extrato CSV sem coluna de valor".into()));
+        }
+        let document = column(DOCUMENT_COLUMNS);
+        let payer = column(PAYER_COLUMNS);
+        let payer_document = column(PAYER_DOCUMENT_COLUMNS);
+        let conta = self.account.clone().unwrap_or_else(|| CONTA_NAO_INFORMADA.to_string());
+
+        let mut transactions = Vec::new();
+        for (idx, record) in reader.records().enumerate() {
+            let line = idx + 2;
+            let record = record.map_err(|e| pc_core::Error::Import(format!("Linha {} do extrato: {}", line, e)))?;
+            let field = |col: Option<usize>| col.and_then(|c| record.get(c)).map(str::trim).filter(|v| !v.is_empty());
+
+            let descricao = field(Some(description)).unwrap_or_default().to_string();
+            // Linhas de saldo não são lançamentos
+            if normalize_text(&descricao).starts_with("SALDO") {
+                continue;
+            }
+            let Some(data) = field(Some(date)).and_then(parse_date) else {
+                tracing::warn!("Linha {} do extrato sem data válida ignorada", line);
+                continue;
+            };
+            let valor = match field(amount).and_then(parse_amount) {
+                Some(valor) => Some(valor),
+                None => match (field(credit).and_then(parse_amount), field(debit).and_then(parse_amount)) {
+                    (Some(c), _) if !c.is_zero() => Some(c.abs()),
+                    (_, Some(d)) if !d.is_zero() => Some(-d.abs()),
+                    _ => None,
+                },
+            };
+            let Some(valor) = valor.filter(|v| !v.is_zero()) else {
+                tracing::warn!("Linha {} do extrato sem valor ignorada", line);
+                continue;
+            };
+
+            let key = format!("{}{}", field(document).unwrap_or_default(), descricao);
+            let fitid = derived_fitid(data, valor, &key, idx);
+            let mut transaction = PaymentTransaction::new(conta.clone(), fitid, data, valor, descricao);
+            transaction.pagador_nome = field(payer).map(String::from);
+            transaction.linhagem = Some(Lineage::at_line(line as u64));
+            transaction.pagador_documento = field(payer_document)
+                .map(digits)
+                .filter(|d| d.len() == 11 || d.len() == 14)
+                .or_else(|| self.payer_document(&transaction.descricao));
+            transactions.push(transaction);
+        }
+
+        Ok(transactions)
+    }
+
+    /// CPF ou CNPJ citado no histórico, só dígitos
+    fn payer_document(&self, descricao: &str) -> Option<String> {
+        self.document_regex.find(descricao).map(|m| digits(m.as_str()))
+    }
+}
+
+impl Default for BankStatementImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Importer for BankStatementImporter {
+    type Output = PaymentTransaction;
+
+    fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
+        let path = path.as_ref();
+        tracing::info!("Importando extrato de: {:?}", path);
+
+        let bytes = std::fs::read(path)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
+        let is_ofx = has_extension(path, "ofx") || bytes.windows(5).any(|w| w.eq_ignore_ascii_case(b"<OFX>"));
+        let mut transactions = if is_ofx {
+            self.parse_ofx(&bytes)?
+        } else {
+            self.parse_csv(&CsvSource::from_bytes(&bytes, &self.csv_options))?
+        };
+
+        let fonte = path.file_name().map(|n| n.to_string_lossy().into_owned());
+        for transaction in &mut transactions {
+            transaction.fonte_arquivo = fonte.clone();
+        }
+
+        tracing::info!("Importados {} lançamentos", transactions.len());
+        Ok(transactions)
+    }
+
+    fn can_import<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        has_extension(path, "ofx") || (has_extension(path, "csv") && name.contains("extrato"))
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// "Data Lançamento" e "DATA_LANCAMENTO" viram "DATA LANCAMENTO"
+fn header_key(header: &str) -> String {
+    normalize_text(header)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// "20250403120000[-3:BRT]" -> 2025-04-03
+fn parse_ofx_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    ["%d/%m/%Y", "%Y-%m-%d", "%d-%m-%Y", "%d/%m/%y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// "1.234,56", "-1234.56", "R$ 10,00", "150,00 C" e "80,00 D"
+fn parse_amount(value: &str) -> Option<Money> {
+    let value = value.trim();
+    let (value, debit) = match value.to_uppercase() {
+        v if v.ends_with(" D") => (&value[..value.len() - 1], true),
+        v if v.ends_with(" C") => (&value[..value.len() - 1], false),
+        _ => (value, false),
+    };
+    let valor = Money::parse(value).ok()?;
+    Some(if debit { -valor } else { valor })
+}
+
+/// TRNAMT do OFX: ponto decimal pela especificação, mas há bancos que usam vírgula
+fn parse_ofx_amount(value: &str) -> Option<Money> {
+    if value.contains(',') {
+        parse_amount(value)
+    } else {
+        Money::parse_decimal(value).ok()
+    }
+}
+
+/// Identificador estável para lançamentos sem FITID
+fn derived_fitid(data: NaiveDate, valor: Money, descricao: &str, ordinal: usize) -> String {
+    let key = format!("{}|{}|{}|{}", data, valor.to_decimal_string(), descricao.trim(), ordinal);
+    pc_core::hash::sha256_hex(key.as_bytes())[..20].to_string()
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_models::PaymentMethod;
+
+    const OFX_SGML: &str = "OFXHEADER:100\nDATA:OFXSGML\nCHARSET:1252\n\n<OFX>\n<BANKMSGSRSV1><STMTTRNRS><STMTRS>\n\
+        <BANKACCTFROM>\n<BANKID>0341\n<BRANCHID>1234\n<ACCTID>56789-0\n</BANKACCTFROM>\n<BANKTRANLIST>\n\
+        <STMTTRN>\n<TRNTYPE>CREDIT\n<DTPOSTED>20250403120000[-3:BRT]\n<TRNAMT>1250.50\n<FITID>20250403001\n\
+        <MEMO>PIX RECEBIDO COMERCIAL FRANCA 12.345.678/0001-90\n</STMTTRN>\n\
+        <STMTTRN>\n<TRNTYPE>DEBIT\n<DTPOSTED>20250404\n<TRNAMT>-35,90\n<MEMO>TARIFA PACOTE\n</STMTTRN>\n\
+        </BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1>\n</OFX>\n";
+
+    #[test]
+    fn test_parse_ofx_sgml() {
+        let transactions = BankStatementImporter::new().parse_ofx(OFX_SGML.as_bytes()).unwrap();
+        assert_eq!(transactions.len(), 2);
+
+        let pix = &transactions[0];
+        assert_eq!(pix.conta, "0341/1234/56789-0");
+        assert_eq!(pix.fitid, "20250403001");
+        assert_eq!(pix.data, NaiveDate::from_ymd_opt(2025, 4, 3).unwrap());
+        assert_eq!(pix.valor, Money::from_cents(125_050));
+        assert_eq!(pix.forma, PaymentMethod::Pix);
+        assert_eq!(pix.pagador_documento.as_deref(), Some("12345678000190"));
+
+        // Sem FITID: identificador derivado, o mesmo a cada leitura
+        let tarifa = &transactions[1];
+        assert_eq!(tarifa.valor, Money::from_cents(-3590));
+        assert!(!tarifa.is_credit());
+        let again = BankStatementImporter::new().parse_ofx(OFX_SGML.as_bytes()).unwrap();
+        assert_eq!(again[1].fitid, tarifa.fitid);
+    }
+
+    #[test]
+    fn test_parse_csv_with_credit_and_debit_columns() {
+        let csv = "Data;Histórico;Documento;Crédito (R$);Débito (R$)\n\
+                   01/04/2025;SALDO ANTERIOR;;;\n\
+                   03/04/2025;TED RECEBIDA FENIOR LTDA;000123;1.250,50;\n\
+                   04/04/2025;TARIFA;;;35,90\n";
+        let source = CsvSource::from_bytes(csv.as_bytes(), &CsvOptions::default());
+        let transactions = BankStatementImporter::new()
+            .with_account("itau-corrente")
+            .parse_csv(&source)
+            .unwrap();
+
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].conta, "itau-corrente");
+        assert_eq!(transactions[0].valor, Money::from_cents(125_050));
+        assert_eq!(transactions[0].forma, PaymentMethod::Transferencia);
+        assert_eq!(transactions[1].valor, Money::from_cents(-3590));
+    }
+}
@@ -0,0 +1,861 @@
+//! Importação em massa de diretórios
+//!
+//! Percorre uma ou mais raízes, detecta o tipo de cada arquivo e importa
+//! tudo o que tiver importador. A leitura dos arquivos roda em paralelo
+//! (rayon); a gravação no banco é feita por uma única tarefa assíncrona.
+//! Um checkpoint em disco guarda os arquivos concluídos para que uma
+//! execução interrompida continue de onde parou.
+//!
+//! Planilhas de fretes e de ponto, NOTFIS e AFD não são lidas pelos
+//! workers: a tarefa de gravação as importa em lotes
+//! ([`crate::streaming`]), com checkpoint por arquivo. A execução toda é
+//! um job (`bulk`), e o cancelamento pedido é atendido entre um arquivo e
+//! outro.
+//!
+//! Planilhas são gravadas assim que lidas. Fotos e PDFs esperam o fim da
+//! leitura e passam, todos juntos, pelo agrupamento de [`crate::dedup`]: as
+//! cópias espalhadas pelas raízes viram um documento com vários locais.
+//! Planilhas com o mesmo conteúdo e o mesmo nome de arquivo são puladas;
+//! documentos sempre passam pelo upsert, que soma o caminho novo aos locais
+//! do documento gravado numa execução anterior.
+
+use crate::csv_source::CsvOptions;
+use crate::dedup::{self, DuplicateGroup};
+use crate::detect::{FileKind, ImportOutput};
+use crate::geo::Municipalities;
+use crate::jobs::JobTracker;
+use crate::lineage::LineageStamp;
+use crate::pdf_importer::{PdfImporter, PdfMetadata};
+use crate::photo_importer::{PhotoImporter, PhotoMetadata};
+use crate::registry::{self, FileSample, ImportOptions, ImporterRegistry, RegisteredImporter};
+use pc_core::Result;
+use pc_db::PersonalControllerDb;
+use pc_models::ImportedFile;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// Profundidade máxima padrão da varredura
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+/// A cada quantos arquivos concluídos o checkpoint é regravado
+const CHECKPOINT_EVERY: usize = 25;
+
+/// Tamanho e data de modificação, usados para saber se o arquivo mudou
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: u64,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Result<Self> {
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler metadados de {:?}: {}", path, e)))?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Ok(Self {
+            size: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// Arquivos já concluídos em execuções anteriores
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkCheckpoint {
+    pub files: BTreeMap<PathBuf, FileStamp>,
+}
+
+impl BulkCheckpoint {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read(path)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler checkpoint: {}", e)))?;
+        serde_json::from_slice(&content)
+            .map_err(|e| pc_core::Error::Import(format!("Checkpoint inválido em {:?}: {}", path, e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| pc_core::Error::Import(format!("Erro ao criar {:?}: {}", parent, e)))?;
+        }
+        let content = serde_json::to_vec_pretty(self)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao serializar checkpoint: {}", e)))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao gravar checkpoint: {}", e)))
+    }
+
+    pub fn is_done(&self, path: &Path, stamp: &FileStamp) -> bool {
+        self.files.get(path) == Some(stamp)
+    }
+}
+
+/// Progresso enviado a cada arquivo processado
+#[derive(Debug, Clone)]
+pub struct BulkProgress {
+    /// Job da execução, para `pc imports cancel`
+    pub job_id: Uuid,
+    pub processed: usize,
+    pub total: usize,
+    pub records: usize,
+    pub current: PathBuf,
+}
+
+/// Totais por tipo de arquivo
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KindSummary {
+    pub files: usize,
+    pub records: usize,
+    pub new: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+/// Totais por raiz de origem
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceSummary {
+    pub files: usize,
+    pub records: usize,
+    pub failed: usize,
+}
+
+/// Resumo final da importação em massa
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BulkSummary {
+    pub job_id: Uuid,
+    pub files_found: usize,
+    pub files_imported: usize,
+    /// Concluídos em execução anterior ou com o mesmo conteúdo já importado
+    pub files_skipped: usize,
+    pub files_unsupported: usize,
+    pub files_failed: usize,
+    /// Fotos e PDFs unidos a uma cópia encontrada em outro caminho
+    pub files_duplicated: usize,
+    pub by_kind: BTreeMap<String, KindSummary>,
+    pub by_source: BTreeMap<String, SourceSummary>,
+    pub errors: Vec<(PathBuf, String)>,
+    pub elapsed: Duration,
+}
+
+impl BulkSummary {
+    fn fail(&mut self, root: &Path, path: PathBuf, error: String) {
+        self.by_source.entry(root.display().to_string()).or_default().failed += 1;
+        self.files_failed += 1;
+        self.errors.push((path, error));
+    }
+}
+
+/// Importação em massa de uma ou mais raízes
+pub struct BulkImport {
+    roots: Vec<PathBuf>,
+    max_depth: usize,
+    threads: Option<usize>,
+    csv_options: CsvOptions,
+    municipalities: Option<Arc<Municipalities>>,
+    checkpoint: Option<PathBuf>,
+    /// Registrado como `iniciado_por` nos jobs de cada arquivo
+    user: Option<String>,
+    registry: Arc<ImporterRegistry>,
+}
+
+/// Resultado da leitura de um arquivo por um worker
+enum FileOutcome {
+    Skipped,
+    Unsupported(FileKind),
+    Parsed {
+        importer: Arc<dyn RegisteredImporter>,
+        sha256: String,
+        output: ImportOutput,
+    },
+    /// Gravado em lotes pela tarefa de gravação, sem leitura prévia
+    Stream(Arc<dyn RegisteredImporter>),
+    Failed(String),
+}
+
+/// Arquivo lido, aguardando gravação
+#[derive(Clone)]
+struct ParsedFile {
+    path: PathBuf,
+    root: usize,
+    stamp: Option<FileStamp>,
+    importer: Arc<dyn RegisteredImporter>,
+    /// `None` nos arquivos gravados em lotes: o hash sai da cópia para o blob store
+    sha256: Option<String>,
+}
+
+struct FileResult {
+    path: PathBuf,
+    root: usize,
+    stamp: Option<FileStamp>,
+    outcome: FileOutcome,
+}
+
+impl BulkImport {
+    pub fn new<I, P>(roots: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self {
+            roots: roots.into_iter().map(Into::into).collect(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            threads: None,
+            csv_options: CsvOptions::default(),
+            municipalities: None,
+            checkpoint: None,
+            user: None,
+            registry: Arc::new(ImporterRegistry::default()),
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Número de threads de leitura (padrão: uma por núcleo)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
+
+    /// Municípios para padronizar as cidades das planilhas de rotas
+    pub fn with_municipalities(mut self, municipalities: Arc<Municipalities>) -> Self {
+        self.municipalities = Some(municipalities);
+        self
+    }
+
+    /// Arquivo de checkpoint para retomar execuções interrompidas
+    pub fn with_checkpoint<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    pub fn with_user(mut self, user: Option<String>) -> Self {
+        self.user = user;
+        self
+    }
+
+    /// Importadores disponíveis (padrão: os embutidos)
+    pub fn with_registry(mut self, registry: Arc<ImporterRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    fn options(&self) -> ImportOptions {
+        ImportOptions {
+            csv: self.csv_options,
+            municipalities: self.municipalities.clone(),
+            ..ImportOptions::default()
+        }
+    }
+
+    /// Lista os arquivos das raízes (ignorando diretórios ocultos), com o
+    /// índice da raiz de cada um
+    pub fn scan(&self) -> Vec<(usize, PathBuf)> {
+        let mut files = Vec::new();
+        for (idx, root) in self.roots.iter().enumerate() {
+            if !root.exists() {
+                tracing::warn!("Raiz {:?} não encontrada, ignorando", root);
+                continue;
+            }
+
+            let entries = walkdir::WalkDir::new(root)
+                .max_depth(self.max_depth)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file());
+
+            files.extend(entries.map(|e| (idx, e.into_path())));
+        }
+        files
+    }
+
+    /// Executa a importação, chamando `on_progress` a cada arquivo
+    pub async fn run<F>(&self, db: &PersonalControllerDb, mut on_progress: F) -> Result<BulkSummary>
+    where
+        F: FnMut(&BulkProgress),
+    {
+        let started = Instant::now();
+        let files = self.scan();
+        let mut checkpoint = match &self.checkpoint {
+            Some(path) => BulkCheckpoint::load(path)?,
+            None => BulkCheckpoint::default(),
+        };
+
+        let roots = self.roots.iter().map(|r| r.display().to_string()).collect::<Vec<_>>().join(", ");
+        let mut run_job = JobTracker::start(&db.import_jobs, Path::new(&roots), "bulk", self.user.clone()).await?;
+        let mut summary = BulkSummary {
+            job_id: run_job.id(),
+            files_found: files.len(),
+            ..BulkSummary::default()
+        };
+        tracing::info!("Importação em massa: {} arquivos em {} raízes", files.len(), self.roots.len());
+
+        let pool = {
+            let mut builder = rayon::ThreadPoolBuilder::new();
+            if let Some(threads) = self.threads {
+                builder = builder.num_threads(threads);
+            }
+            builder
+                .build()
+                .map_err(|e| pc_core::Error::Internal(format!("Erro ao criar pool de threads: {}", e)))?
+        };
+
+        // Canal limitado: no máximo alguns arquivos lidos aguardando gravação
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<FileResult>(pool.current_num_threads() * 2);
+        let done = Arc::new(checkpoint.clone());
+        let options = self.options();
+        let registry = Arc::clone(&self.registry);
+        let total = files.len();
+
+        let workers = std::thread::spawn(move || {
+            pool.install(|| {
+                files.into_par_iter().for_each_with(tx, |tx, (root, path)| {
+                    // Receptor encerrado: a importação foi abortada ou cancelada
+                    if tx.is_closed() {
+                        return;
+                    }
+                    let result = read_file(root, path, &done, &registry, &options);
+                    let _ = tx.blocking_send(result);
+                });
+            });
+        });
+
+        let mut processed = 0;
+        let mut records = 0;
+        let mut since_save = 0;
+        let mut photos: Vec<(ParsedFile, PhotoMetadata)> = Vec::new();
+        let mut pdfs: Vec<(ParsedFile, PdfMetadata)> = Vec::new();
+        let mut cancelled = None;
+        while let Some(result) = rx.recv().await {
+            if let Err(e) = run_job.check_cancelled().await {
+                cancelled = Some(e);
+                break;
+            }
+            processed += 1;
+            let source = self.roots[result.root].display().to_string();
+            summary.by_source.entry(source).or_default().files += 1;
+
+            match result.outcome {
+                FileOutcome::Skipped => summary.files_skipped += 1,
+                FileOutcome::Unsupported(kind) => {
+                    tracing::debug!("Sem importador para {:?} ({})", result.path, kind.as_str());
+                    summary.files_unsupported += 1;
+                }
+                FileOutcome::Failed(error) => {
+                    tracing::warn!("Falha ao importar {:?}: {}", result.path, error);
+                    self.record_failure(db, &result.path, &error).await?;
+                    summary.fail(&self.roots[result.root], result.path.clone(), error);
+                }
+                FileOutcome::Parsed { importer, sha256, output } => {
+                    let file = ParsedFile {
+                        path: result.path.clone(),
+                        root: result.root,
+                        stamp: result.stamp,
+                        importer,
+                        sha256: Some(sha256),
+                    };
+                    match output {
+                        // Fotos e PDFs esperam o fim da leitura para o agrupamento de duplicados
+                        ImportOutput::Photos(items) => photos.extend(items.into_iter().map(|p| (file.clone(), p))),
+                        ImportOutput::Pdfs(items) => pdfs.extend(items.into_iter().map(|p| (file.clone(), p))),
+                        output => {
+                            if let Some(persisted) = self.persist_file(db, &file, Some(output), &mut summary, &mut checkpoint).await? {
+                                records += persisted;
+                                since_save += 1;
+                            }
+                        }
+                    }
+                }
+                FileOutcome::Stream(importer) => {
+                    let file = ParsedFile {
+                        path: result.path.clone(),
+                        root: result.root,
+                        stamp: result.stamp,
+                        importer,
+                        sha256: None,
+                    };
+                    if let Some(persisted) = self.persist_file(db, &file, None, &mut summary, &mut checkpoint).await? {
+                        records += persisted;
+                        since_save += 1;
+                    }
+                }
+            }
+
+            if since_save >= CHECKPOINT_EVERY {
+                if let Some(path) = &self.checkpoint {
+                    checkpoint.save(path)?;
+                }
+                since_save = 0;
+            }
+
+            on_progress(&BulkProgress { job_id: run_job.id(), processed, total, records, current: result.path });
+        }
+
+        // Sem receptor, os workers descartam os arquivos que faltam
+        drop(rx);
+        workers
+            .join()
+            .map_err(|_| pc_core::Error::Internal("Worker de importação em pânico".into()))?;
+
+        if let Some(cancelled) = cancelled {
+            if let Some(path) = &self.checkpoint {
+                checkpoint.save(path)?;
+            }
+            let result = Err(cancelled);
+            run_job.finish(&result).await?;
+            return result;
+        }
+
+        // Cópias da mesma foto ou PDF em raízes diferentes viram um documento só
+        self.persist_grouped(db, photos, &mut summary, &mut checkpoint).await?;
+        self.persist_grouped(db, pdfs, &mut summary, &mut checkpoint).await?;
+
+        if let Some(path) = &self.checkpoint {
+            checkpoint.save(path)?;
+        }
+
+        summary.elapsed = started.elapsed();
+        let totals = summary.by_kind.values();
+        run_job
+            .record_batch(
+                totals.clone().map(|k| k.new).sum(),
+                totals.clone().map(|k| k.updated).sum(),
+                totals.map(|k| k.unchanged).sum(),
+            )
+            .await?;
+        tracing::info!(
+            "Importação em massa concluída: {} importados, {} pulados, {} falhas em {:?}",
+            summary.files_imported,
+            summary.files_skipped,
+            summary.files_failed,
+            summary.elapsed
+        );
+        let result = Ok(summary);
+        run_job.finish(&result).await?;
+        db.compact()?;
+        result
+    }
+
+    /// Grava um arquivo, acompanhado por um job
+    ///
+    /// `output` é a saída lida pelos workers; sem ela o arquivo é importado
+    /// em lotes. Planilhas com o mesmo conteúdo já importado não geram job.
+    async fn persist_tracked(
+        &self,
+        db: &PersonalControllerDb,
+        file: &ParsedFile,
+        output: Option<ImportOutput>,
+    ) -> Result<Option<KindSummary>> {
+        let (path, importer) = (&file.path, file.importer.as_ref());
+        let sha256 = match &file.sha256 {
+            Some(sha256) => sha256.clone(),
+            None => db.blobs.put_file(path)?,
+        };
+        let fonte_arquivo = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if !output.as_ref().is_some_and(is_document) && db.imported_files.is_already_imported(fonte_arquivo, &sha256).await? {
+            return Ok(None);
+        }
+
+        let mut job = JobTracker::start(&db.import_jobs, path, importer.name(), self.user.clone()).await?;
+        let result = match output {
+            Some(output) => {
+                let result = persist(db, path, importer, sha256, output, Some(job.id())).await;
+                if let Ok(Some(summary)) = &result {
+                    job.record_batch(summary.new, summary.updated, summary.unchanged).await?;
+                }
+                result
+            }
+            None => self.stream(db, file, sha256, &mut job).await,
+        };
+        job.finish(&result).await?;
+        result
+    }
+
+    /// Importa em lotes um arquivo que os workers não leram
+    ///
+    /// O checkpoint por arquivo ([`crate::streaming`]) permite retomar uma
+    /// planilha grande da linha em que parou.
+    async fn stream(
+        &self,
+        db: &PersonalControllerDb,
+        file: &ParsedFile,
+        sha256: String,
+        job: &mut JobTracker<'_>,
+    ) -> Result<Option<KindSummary>> {
+        let importer = file.importer.as_ref();
+        let report = registry::import_stored_file(importer, db, &file.path, &sha256, &self.options(), job, |_| {})
+            .await?
+            .unwrap_or_default();
+
+        let fonte_arquivo = file.path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        db.imported_files
+            .record(ImportedFile::new(fonte_arquivo, sha256, importer.name().to_string(), report.total()))
+            .await?;
+        Ok(Some(KindSummary {
+            files: 1,
+            records: report.total(),
+            new: report.new,
+            updated: report.updated,
+            unchanged: report.unchanged,
+        }))
+    }
+
+    /// Grava um arquivo lido e soma o resultado ao resumo
+    ///
+    /// Retorna os registros gravados; `None` se o arquivo foi pulado ou
+    /// falhou (a falha vai para o resumo, sem interromper a importação).
+    async fn persist_file(
+        &self,
+        db: &PersonalControllerDb,
+        file: &ParsedFile,
+        output: Option<ImportOutput>,
+        summary: &mut BulkSummary,
+        checkpoint: &mut BulkCheckpoint,
+    ) -> Result<Option<usize>> {
+        let persisted = self.persist_tracked(db, file, output).await;
+        let kind_summary = match persisted {
+            Ok(Some(kind_summary)) => kind_summary,
+            Ok(None) => {
+                summary.files_skipped += 1;
+                return Ok(None);
+            }
+            Err(e) => {
+                summary.fail(&self.roots[file.root], file.path.clone(), e.to_string());
+                return Ok(None);
+            }
+        };
+
+        summary.files_imported += 1;
+        summary.by_source.entry(self.roots[file.root].display().to_string()).or_default().records += kind_summary.records;
+        let entry = summary.by_kind.entry(file.importer.name().to_string()).or_default();
+        entry.files += 1;
+        entry.records += kind_summary.records;
+        entry.new += kind_summary.new;
+        entry.updated += kind_summary.updated;
+        entry.unchanged += kind_summary.unchanged;
+
+        if let Some(stamp) = file.stamp {
+            checkpoint.files.insert(file.path.clone(), stamp);
+        }
+        Ok(Some(kind_summary.records))
+    }
+
+    /// Grava as fotos ou PDFs de todas as raízes, um por grupo de duplicados
+    ///
+    /// O canônico de cada grupo leva os caminhos das cópias em `locations`;
+    /// as cópias só entram no checkpoint.
+    async fn persist_grouped<T: HeldDocument>(
+        &self,
+        db: &PersonalControllerDb,
+        held: Vec<(ParsedFile, T)>,
+        summary: &mut BulkSummary,
+        checkpoint: &mut BulkCheckpoint,
+    ) -> Result<()> {
+        let (files, items): (Vec<ParsedFile>, Vec<T>) = held.into_iter().unzip();
+        let groups = T::duplicate_groups(&items);
+        for group in &groups {
+            for &copy in group.members.iter().filter(|&&i| i != group.canonical) {
+                tracing::debug!("{:?} é cópia de {:?}", files[copy].path, files[group.canonical].path);
+                summary.files_duplicated += 1;
+                if let Some(stamp) = files[copy].stamp {
+                    checkpoint.files.insert(files[copy].path.clone(), stamp);
+                }
+            }
+        }
+
+        let held = files.into_iter().zip(items).collect();
+        for (file, item) in dedup::merge_duplicates(held, &groups, |(_, item)| item.locations()) {
+            self.persist_file(db, &file, Some(T::output(vec![item])), summary, checkpoint).await?;
+        }
+        Ok(())
+    }
+
+    /// Registra como job falho um arquivo que não pôde ser lido
+    async fn record_failure(&self, db: &PersonalControllerDb, path: &Path, error: &str) -> Result<()> {
+        let job = JobTracker::start(&db.import_jobs, path, "auto", self.user.clone()).await?;
+        let failed: Result<()> = Err(pc_core::Error::Import(error.to_string()));
+        job.finish(&failed).await.map(|_| ())
+    }
+}
+
+/// Detecta e lê um arquivo (executa nos workers)
+fn read_file(
+    root: usize,
+    path: PathBuf,
+    done: &BulkCheckpoint,
+    registry: &ImporterRegistry,
+    options: &ImportOptions,
+) -> FileResult {
+    let stamp = FileStamp::of(&path).ok();
+    let outcome = (|| {
+        if stamp.is_some_and(|s| done.is_done(&path, &s)) {
+            return FileOutcome::Skipped;
+        }
+
+        let sample = match FileSample::read(&path) {
+            Ok(sample) => sample,
+            Err(e) => return FileOutcome::Failed(e.to_string()),
+        };
+        let Some(selected) = registry.find(&sample) else {
+            return FileOutcome::Unsupported(sample.detection.kind);
+        };
+        if selected.importer.streams() {
+            return FileOutcome::Stream(selected.importer);
+        }
+
+        let sha256 = match pc_core::hash::sha256_file(&path) {
+            Ok(sha256) => sha256,
+            Err(e) => return FileOutcome::Failed(e.to_string()),
+        };
+
+        match selected.importer.read(&path, options) {
+            Ok(output) => FileOutcome::Parsed {
+                importer: selected.importer,
+                sha256,
+                output,
+            },
+            Err(e) => FileOutcome::Failed(e.to_string()),
+        }
+    })();
+
+    FileResult {
+        path,
+        root,
+        stamp,
+        outcome,
+    }
+}
+
+/// Grava o resultado de um arquivo; `None` se a planilha, com o mesmo
+/// conteúdo, já foi importada
+///
+/// Os registros recebem a linhagem do arquivo, copiado para o blob store.
+async fn persist(
+    db: &PersonalControllerDb,
+    path: &Path,
+    importer: &dyn RegisteredImporter,
+    sha256: String,
+    output: ImportOutput,
+    job_id: Option<Uuid>,
+) -> Result<Option<KindSummary>> {
+    let fonte_arquivo = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    if !is_document(&output) && db.imported_files.is_already_imported(&fonte_arquivo, &sha256).await? {
+        return Ok(None);
+    }
+
+    db.blobs.put_file(path)?;
+    let stamp = LineageStamp::for_file(path, sha256.clone(), importer.name(), importer.version()).with_job(job_id);
+    let records = output.len();
+    let report = importer.persist(db, output.with_lineage(&stamp)).await?;
+
+    let mut summary = KindSummary {
+        files: 1,
+        records,
+        ..KindSummary::default()
+    };
+    if let Some(report) = report {
+        summary.new = report.new.len();
+        summary.updated = report.updated.len();
+        summary.unchanged = report.unchanged.len();
+        db.imported_files
+            .record(ImportedFile::new(fonte_arquivo, sha256, importer.name().to_string(), records))
+            .await?;
+    }
+
+    Ok(Some(summary))
+}
+
+/// Fotos e PDFs guardados até o agrupamento de duplicados
+trait HeldDocument: Sized {
+    fn duplicate_groups(items: &[Self]) -> Vec<DuplicateGroup>;
+
+    fn locations(&mut self) -> &mut Vec<PathBuf>;
+
+    fn output(items: Vec<Self>) -> ImportOutput;
+}
+
+impl HeldDocument for PhotoMetadata {
+    fn duplicate_groups(items: &[Self]) -> Vec<DuplicateGroup> {
+        PhotoImporter::new().duplicate_groups(items)
+    }
+
+    fn locations(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.locations
+    }
+
+    fn output(items: Vec<Self>) -> ImportOutput {
+        ImportOutput::Photos(items)
+    }
+}
+
+impl HeldDocument for PdfMetadata {
+    fn duplicate_groups(items: &[Self]) -> Vec<DuplicateGroup> {
+        PdfImporter::new().duplicate_groups(items)
+    }
+
+    fn locations(&mut self) -> &mut Vec<PathBuf> {
+        &mut self.locations
+    }
+
+    fn output(items: Vec<Self>) -> ImportOutput {
+        ImportOutput::Pdfs(items)
+    }
+}
+
+/// Fotos, PDFs e XMLs sempre passam pelo upsert: a mesma cópia em outro
+/// caminho (outro disco, outra pasta) vira mais um local do documento
+fn is_document(output: &ImportOutput) -> bool {
+    matches!(output, ImportOutput::Photos(_) | ImportOutput::Pdfs(_) | ImportOutput::Documents(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_db::GenericRepository;
+
+    fn sample_tree() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("pc-bulk-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("ponto/2025")).unwrap();
+        std::fs::create_dir_all(root.join(".cache")).unwrap();
+
+        std::fs::write(
+            root.join("ponto/2025/abril.csv"),
+            "funcionario;mes;data;entrada;saida\nJoão;Abril;01/04/2025;08:00;17:00\nJoão;Abril;02/04/2025;08:00;17:00\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("ponto/maio.csv"),
+            "funcionario,mes,data,entrada,saida\nMaria,Maio,02/05/2025,09:00,18:00\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("leia-me.txt"), "anotações").unwrap();
+        std::fs::write(root.join(".cache/ignorado.csv"), "funcionario;mes;data;entrada;saida\n").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_scan_skips_hidden_directories() {
+        let root = sample_tree();
+        let files = BulkImport::new([&root]).scan();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|(_, p)| !p.to_string_lossy().contains(".cache")));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_resumes_from_checkpoint() {
+        let root = sample_tree();
+        let checkpoint = root.join(".cache/checkpoint.json");
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+
+        let mut calls = 0;
+        let summary = BulkImport::new([&root])
+            .with_threads(2)
+            .with_checkpoint(&checkpoint)
+            .run(&db, |_| calls += 1)
+            .await
+            .unwrap();
+
+        assert_eq!(calls, 3);
+        assert_eq!(summary.files_imported, 2);
+        assert_eq!(summary.files_unsupported, 1);
+        assert_eq!(summary.by_kind["timesheet"].records, 3);
+        assert_eq!(db.timesheets.count().await.unwrap(), 3);
+        // Um job por arquivo e um da execução, com os totais
+        assert_eq!(db.import_jobs.count().await.unwrap(), 3);
+        let run_job = db.import_jobs.find_by_id(&summary.job_id).await.unwrap().unwrap();
+        assert_eq!(run_job.importador, "bulk");
+        assert_eq!(run_job.status, pc_core::Status::Completed);
+        assert_eq!(run_job.novos, 3);
+
+        let again = BulkImport::new([&root])
+            .with_checkpoint(&checkpoint)
+            .run(&db, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(again.files_skipped, 2);
+        assert_eq!(again.files_imported, 0);
+        assert_eq!(db.import_jobs.count().await.unwrap(), 4);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_same_document_in_two_roots_keeps_both_locations() {
+        let base = std::env::temp_dir().join(format!("pc-bulk-{}", uuid::Uuid::new_v4()));
+        let xml = "<?xml version=\"1.0\"?><cteProc xmlns=\"http://www.portalfiscal.inf.br/cte\"><CTe/></cteProc>";
+        let (hd, pendrive) = (base.join("hd"), base.join("pendrive/backup"));
+        for root in [&hd, &pendrive] {
+            std::fs::create_dir_all(root).unwrap();
+            std::fs::write(root.join("cte-4512.xml"), xml).unwrap();
+        }
+
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+        BulkImport::new([&hd, &pendrive]).run(&db, |_| {}).await.unwrap();
+
+        let documents = db.documents.find_all().await.unwrap();
+        assert_eq!(documents.len(), 1);
+        let locais = &documents[0].locais;
+        assert_eq!(locais.len(), 2);
+        assert!(locais.iter().any(|l| l.starts_with(&*hd.to_string_lossy())));
+        assert!(locais.iter().any(|l| l.starts_with(&*pendrive.to_string_lossy())));
+
+        std::fs::remove_dir_all(base).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_photo_copies_across_roots_become_one_document() {
+        let base = std::env::temp_dir().join(format!("pc-bulk-{}", uuid::Uuid::new_v4()));
+        let (celular, whatsapp) = (base.join("celular"), base.join("whatsapp/imagens"));
+        std::fs::create_dir_all(&celular).unwrap();
+        std::fs::create_dir_all(&whatsapp).unwrap();
+
+        let canhoto = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 128]));
+        canhoto.save(celular.join("canhoto.png")).unwrap();
+        // Reenviada pelo WhatsApp: reduzida, com outro nome e outro sha256
+        image::imageops::resize(&canhoto, 32, 24, image::imageops::FilterType::Triangle)
+            .save(whatsapp.join("IMG-20250401-WA0003.png"))
+            .unwrap();
+
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+        let summary = BulkImport::new([&celular, &whatsapp]).run(&db, |_| {}).await.unwrap();
+        assert_eq!(summary.files_imported, 1);
+        assert_eq!(summary.files_duplicated, 1);
+
+        let documents = db.documents.find_all().await.unwrap();
+        assert_eq!(documents.len(), 1);
+        assert!(documents[0].caminho_origem.ends_with("canhoto.png"));
+        assert_eq!(documents[0].locais.len(), 2);
+
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
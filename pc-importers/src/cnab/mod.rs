@@ -0,0 +1,349 @@
+//! CNAB (cobrança bancária em arquivos de posição fixa)
+//!
+//! Os boletos de frete são registrados no banco por arquivos de remessa e
+//! o banco devolve a situação de cada título em arquivos de retorno:
+//!
+//! - CNAB 240 (FEBRABAN): lote de cobrança com segmentos P e Q na remessa
+//!   e T e U no retorno
+//! - CNAB 400: registro de transação tipo 1, no layout do Bradesco (adotado
+//!   por vários bancos)
+//!
+//! Os títulos saem das ordens de frete em aberto, com o sacado tirado do
+//! cadastro da empresa pagadora. As posições seguem os manuais (começando
+//! em 1), como nos layouts EDI.
+
+pub mod remessa;
+pub mod retorno;
+
+pub use remessa::RemessaWriter;
+pub use retorno::{Retorno, RetornoParser, TituloRetorno};
+
+use crate::text::digits;
+use chrono::NaiveDate;
+use pc_core::{Money, Result, Rounding};
+use pc_models::{Boleto, BoletoOcorrencia, Company, FreightOrder};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Layout do arquivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CnabLayout {
+    Cnab240,
+    Cnab400,
+}
+
+impl CnabLayout {
+    pub fn width(&self) -> usize {
+        match self {
+            CnabLayout::Cnab240 => 240,
+            CnabLayout::Cnab400 => 400,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CnabLayout::Cnab240 => "240",
+            CnabLayout::Cnab400 => "400",
+        }
+    }
+}
+
+impl std::str::FromStr for CnabLayout {
+    type Err = pc_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().trim_start_matches("cnab") {
+            "240" => Ok(CnabLayout::Cnab240),
+            "400" => Ok(CnabLayout::Cnab400),
+            other => Err(pc_core::Error::Validation(format!("Layout CNAB desconhecido: {} (use 240 ou 400)", other))),
+        }
+    }
+}
+
+/// Beneficiário da cobrança: a transportadora e sua conta no banco
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cedente {
+    pub banco_codigo: String,
+    pub banco_nome: String,
+    pub agencia: String,
+    pub agencia_digito: String,
+    pub conta: String,
+    pub conta_digito: String,
+    /// Convênio (CNAB 240) ou código da empresa (CNAB 400) fornecido pelo banco
+    pub convenio: String,
+    pub carteira: String,
+    pub cnpj: String,
+    pub razao_social: String,
+    /// Juros de mora ao mês, em %, sobre o valor do título
+    #[serde(default)]
+    pub juros_mes: f64,
+    /// Multa por atraso, em %
+    #[serde(default)]
+    pub multa: f64,
+}
+
+impl Cedente {
+    /// Lê a configuração do beneficiário de um arquivo JSON
+    pub fn load(path: &std::path::Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler {:?}: {}", path, e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| pc_core::Error::Validation(format!("Configuração do cedente inválida em {:?}: {}", path, e)))
+    }
+}
+
+/// Pagador do boleto, como o banco exige
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sacado {
+    pub nome: String,
+    /// CPF ou CNPJ, só dígitos
+    pub documento: String,
+    pub endereco: String,
+    pub bairro: String,
+    pub cep: String,
+    pub cidade: String,
+    pub uf: String,
+}
+
+impl Sacado {
+    /// Sacado a partir do cadastro; o banco recusa títulos sem documento ou endereço
+    pub fn from_company(company: &Company) -> Result<Self> {
+        let documento = company
+            .cnpj
+            .as_deref()
+            .or(company.cpf.as_deref())
+            .map(digits)
+            .filter(|d| d.len() == 14 || d.len() == 11)
+            .ok_or_else(|| pc_core::Error::Validation(format!("Empresa {} sem CNPJ ou CPF", company.nome)))?;
+        let cep = company
+            .cep
+            .as_deref()
+            .map(digits)
+            .filter(|c| c.len() == 8)
+            .ok_or_else(|| pc_core::Error::Validation(format!("Empresa {} sem CEP válido", company.nome)))?;
+        let endereco = company
+            .endereco
+            .as_deref()
+            .filter(|e| !e.trim().is_empty())
+            .ok_or_else(|| pc_core::Error::Validation(format!("Empresa {} sem endereço", company.nome)))?;
+
+        let endereco = [Some(endereco), company.numero.as_deref(), company.complemento.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(Self {
+            nome: company.nome.clone(),
+            documento,
+            endereco,
+            bairro: company.bairro.clone().unwrap_or_default(),
+            cep,
+            cidade: company.cidade.clone(),
+            uf: company.estado.clone(),
+        })
+    }
+
+    /// 1 para CPF, 2 para CNPJ
+    pub fn tipo_inscricao(&self) -> i64 {
+        if self.documento.len() == 11 { 1 } else { 2 }
+    }
+}
+
+/// Título a registrar no banco
+#[derive(Debug, Clone)]
+pub struct Titulo {
+    pub freight_order_id: Uuid,
+    pub pagador_id: Uuid,
+    pub nosso_numero: u64,
+    /// Número do documento (seu número): o número da ordem de frete
+    pub numero_documento: String,
+    pub emissao: NaiveDate,
+    pub vencimento: NaiveDate,
+    pub valor: Money,
+    pub sacado: Sacado,
+}
+
+impl Titulo {
+    /// Título do frete da ordem, cobrado da empresa pagadora
+    pub fn from_order(
+        order: &FreightOrder,
+        pagador: &Company,
+        nosso_numero: u64,
+        emissao: NaiveDate,
+        vencimento: NaiveDate,
+    ) -> Result<Self> {
+        if !order.valor_frete.is_positive() {
+            return Err(pc_core::Error::Validation(format!("Ordem {} sem valor de frete", order.numero)));
+        }
+        if vencimento < emissao {
+            return Err(pc_core::Error::Validation(format!(
+                "Ordem {}: vencimento anterior à emissão",
+                order.numero
+            )));
+        }
+
+        Ok(Self {
+            freight_order_id: order.id,
+            pagador_id: pagador.id,
+            nosso_numero,
+            numero_documento: order.numero.clone(),
+            emissao,
+            vencimento,
+            valor: order.valor_frete,
+            sacado: Sacado::from_company(pagador)?,
+        })
+    }
+
+    /// Boleto a gravar depois de gerada a remessa
+    pub fn to_boleto(&self, cedente: &Cedente, remessa: u32) -> Boleto {
+        Boleto::new(
+            self.freight_order_id,
+            self.numero_documento.clone(),
+            self.pagador_id,
+            cedente.banco_codigo.clone(),
+            self.nosso_numero,
+            remessa,
+            self.emissao,
+            self.vencimento,
+            self.valor,
+        )
+    }
+
+    /// Juros de mora por dia de atraso, em reais
+    fn juros_dia(&self, cedente: &Cedente) -> Money {
+        self.valor.percent(cedente.juros_mes / 30.0, Rounding::HalfUp)
+    }
+}
+
+/// Resultado da aplicação de um retorno
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RetornoSummary {
+    pub registrados: usize,
+    pub pagos: usize,
+    pub rejeitados: usize,
+    pub baixados: usize,
+    pub outros: usize,
+    /// Nossos números do retorno sem boleto correspondente
+    pub nao_encontrados: Vec<String>,
+}
+
+/// Atualiza a situação dos boletos com as ocorrências do retorno
+///
+/// Retorna os boletos alterados, para gravar.
+pub fn apply_retorno(boletos: Vec<Boleto>, retorno: &Retorno, arquivo: Option<&str>) -> (Vec<Boleto>, RetornoSummary) {
+    use pc_models::BoletoStatus;
+
+    let mut summary = RetornoSummary::default();
+    let mut by_number: std::collections::HashMap<String, Boleto> = boletos
+        .into_iter()
+        .filter(|b| b.banco == retorno.banco)
+        .map(|b| (b.nosso_numero.clone(), b))
+        .collect();
+    let mut changed: Vec<String> = Vec::new();
+
+    for titulo in &retorno.titulos {
+        let Some(boleto) = by_number.get_mut(&titulo.nosso_numero) else {
+            if !summary.nao_encontrados.contains(&titulo.nosso_numero) {
+                summary.nao_encontrados.push(titulo.nosso_numero.clone());
+            }
+            continue;
+        };
+
+        match titulo.situacao {
+            Some(BoletoStatus::Registrado) => summary.registrados += 1,
+            Some(BoletoStatus::Pago) => summary.pagos += 1,
+            Some(BoletoStatus::Rejeitado) => summary.rejeitados += 1,
+            Some(BoletoStatus::Baixado) => summary.baixados += 1,
+            Some(BoletoStatus::Emitido) | None => summary.outros += 1,
+        }
+        if titulo.situacao == Some(BoletoStatus::Pago) {
+            boleto.valor_pago = Some(titulo.valor_pago);
+            boleto.data_pagamento = titulo.data_ocorrencia;
+            boleto.data_credito = titulo.data_credito;
+        }
+        boleto.apply(
+            BoletoOcorrencia {
+                codigo: titulo.ocorrencia.clone(),
+                descricao: titulo.descricao().to_string(),
+                motivos: titulo.motivos.clone(),
+                data: titulo.data_ocorrencia,
+                arquivo: arquivo.map(String::from),
+            },
+            titulo.situacao,
+        );
+        if !changed.contains(&titulo.nosso_numero) {
+            changed.push(titulo.nosso_numero.clone());
+        }
+    }
+
+    let updated = changed.iter().filter_map(|n| by_number.remove(n)).collect();
+    (updated, summary)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pc_models::CompanyType;
+
+    /// Beneficiário, pagadores e ordens usados nos testes de remessa e retorno
+    pub(crate) fn sample() -> (Cedente, Vec<Titulo>) {
+        let cedente = Cedente {
+            banco_codigo: "237".into(),
+            banco_nome: "Bradesco".into(),
+            agencia: "1234".into(),
+            agencia_digito: "5".into(),
+            conta: "67890".into(),
+            conta_digito: "1".into(),
+            convenio: "4567890".into(),
+            carteira: "09".into(),
+            cnpj: "12.345.678/0001-95".into(),
+            razao_social: "Ávila Transportes Ltda".into(),
+            juros_mes: 3.0,
+            multa: 2.0,
+        };
+
+        let mut pagador = Company::new("Comercial Franca Ltda".into(), "Franca".into(), "SP".into(), CompanyType::Cliente);
+        pagador.cnpj = Some("98.765.432/0001-10".into());
+        pagador.endereco = Some("Rua Voluntários da Franca".into());
+        pagador.numero = Some("1500".into());
+        pagador.bairro = Some("Centro".into());
+        pagador.cep = Some("14400-490".into());
+
+        let emissao = NaiveDate::from_ymd_opt(2025, 4, 10).unwrap();
+        let vencimento = NaiveDate::from_ymd_opt(2025, 5, 10).unwrap();
+        let titulos = [("288415", 125_050), ("288416", 9210)]
+            .iter()
+            .enumerate()
+            .map(|(i, (numero, frete))| {
+                let mut order = FreightOrder::new(
+                    numero.to_string(),
+                    emissao,
+                    pagador.nome.clone(),
+                    "FENIOR".into(),
+                    "Ribeirão Preto".into(),
+                    pagador.nome.clone(),
+                    "Franca".into(),
+                );
+                order.valor_frete = Money::from_cents(*frete);
+                Titulo::from_order(&order, &pagador, 101 + i as u64, emissao, vencimento).unwrap()
+            })
+            .collect();
+
+        (cedente, titulos)
+    }
+
+    #[test]
+    fn test_sacado_requires_document_and_address() {
+        let (_, titulos) = sample();
+        assert_eq!(titulos[0].sacado.documento, "98765432000110");
+        assert_eq!(titulos[0].sacado.endereco, "Rua Voluntários da Franca 1500");
+        assert_eq!(titulos[0].sacado.tipo_inscricao(), 2);
+
+        let sem_cnpj = Company::new("Fulano".into(), "Franca".into(), "SP".into(), CompanyType::Cliente);
+        assert!(matches!(Sacado::from_company(&sem_cnpj), Err(pc_core::Error::Validation(_))));
+    }
+}
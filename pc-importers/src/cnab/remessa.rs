@@ -0,0 +1,506 @@
+//! Arquivo de remessa (registro de títulos no banco)
+//!
+//! CNAB 240: header de arquivo (0), header de lote (1), segmentos P, Q e,
+//! com multa, R (3) para cada título, trailer de lote (5) e de arquivo (9).
+//!
+//! CNAB 400: header (0), um registro de transação (1) por título e
+//! trailer (9), com número sequencial nas posições 395 a 400.
+
+use super::{CnabLayout, Cedente, Titulo};
+use crate::edi::{join_records, RecordWriter};
+use crate::text;
+use chrono::{Days, NaiveDate, NaiveDateTime};
+use pc_core::{Money, Result};
+
+/// Versão do layout FEBRABAN informada no header de arquivo
+const VERSAO_ARQUIVO_240: &str = "103";
+/// Versão do layout do lote de cobrança
+const VERSAO_LOTE_240: &str = "060";
+/// Espécie "duplicata de serviço" (DS), usada para cobrança de frete
+const ESPECIE_240: &str = "04";
+const ESPECIE_400: &str = "12";
+/// Dias após o vencimento para o banco baixar o título não pago
+const PRAZO_BAIXA: i64 = 60;
+
+/// Gerador de arquivos de remessa
+pub struct RemessaWriter {
+    layout: CnabLayout,
+}
+
+impl RemessaWriter {
+    pub fn new(layout: CnabLayout) -> Self {
+        Self { layout }
+    }
+
+    /// Monta a remessa `sequencial` com os títulos
+    pub fn write(
+        &self,
+        cedente: &Cedente,
+        titulos: &[Titulo],
+        sequencial: u32,
+        gerado_em: NaiveDateTime,
+    ) -> Result<String> {
+        validate_cedente(cedente)?;
+        if titulos.is_empty() {
+            return Err(pc_core::Error::Validation("Remessa sem títulos".into()));
+        }
+
+        Ok(match self.layout {
+            CnabLayout::Cnab240 => write_240(cedente, titulos, sequencial, gerado_em),
+            CnabLayout::Cnab400 => write_400(cedente, titulos, sequencial, gerado_em),
+        })
+    }
+}
+
+fn validate_cedente(cedente: &Cedente) -> Result<()> {
+    let digits = |value: &str| value.chars().filter(|c| c.is_ascii_digit()).count();
+    let problem = if digits(&cedente.banco_codigo) != 3 {
+        Some("código do banco deve ter 3 dígitos")
+    } else if digits(&cedente.cnpj) != 14 {
+        Some("CNPJ inválido")
+    } else if digits(&cedente.agencia) == 0 || digits(&cedente.conta) == 0 {
+        Some("agência e conta são obrigatórias")
+    } else if cedente.convenio.trim().is_empty() {
+        Some("convênio (código da empresa no banco) é obrigatório")
+    } else {
+        None
+    };
+    match problem {
+        Some(problem) => Err(pc_core::Error::Validation(format!("Cedente: {}", problem))),
+        None => Ok(()),
+    }
+}
+
+fn write_240(cedente: &Cedente, titulos: &[Titulo], sequencial: u32, gerado_em: NaiveDateTime) -> String {
+    let width = CnabLayout::Cnab240.width();
+    let banco = || RecordWriter::new("", width).digits(&cedente.banco_codigo, 3);
+    let conta = |record: RecordWriter, agencia_len: usize| {
+        record
+            .digits(&cedente.agencia, agencia_len)
+            .alpha(&cedente.agencia_digito, 1)
+            .digits(&cedente.conta, 12)
+            .alpha(&cedente.conta_digito, 1)
+            .raw("", 1)
+    };
+
+    let header_arquivo = banco()
+        .num(0, 4)
+        .raw("0", 1)
+        .raw("", 9)
+        .num(2, 1)
+        .digits(&cedente.cnpj, 14)
+        .alpha(&cedente.convenio, 20);
+    let header_arquivo = conta(header_arquivo, 5)
+        .alpha(&cedente.razao_social, 30)
+        .alpha(&cedente.banco_nome, 30)
+        .raw("", 10)
+        .num(1, 1)
+        .date(Some(gerado_em.date()))
+        .raw(&gerado_em.format("%H%M%S").to_string(), 6)
+        .num(sequencial as i64, 6)
+        .raw(VERSAO_ARQUIVO_240, 3)
+        .num(1600, 5)
+        .finish();
+
+    let header_lote = banco()
+        .num(1, 4)
+        .raw("1", 1)
+        .raw("R", 1)
+        .raw("01", 2)
+        .raw("", 2)
+        .raw(VERSAO_LOTE_240, 3)
+        .raw("", 1)
+        .num(2, 1)
+        .digits(&cedente.cnpj, 15)
+        .alpha(&cedente.convenio, 20);
+    let header_lote = conta(header_lote, 5)
+        .alpha(&cedente.razao_social, 30)
+        .raw("", 40)
+        .raw("", 40)
+        .num(sequencial as i64, 8)
+        .date(Some(gerado_em.date()))
+        .num(0, 8)
+        .finish();
+
+    let mut records = vec![header_arquivo, header_lote];
+    let mut lote = 0;
+    for titulo in titulos {
+        let juros_dia = titulo.juros_dia(cedente);
+        let inicio_encargos = titulo.vencimento.checked_add_days(Days::new(1));
+
+        lote += 1;
+        let segmento_p = banco().num(1, 4).raw("3", 1).num(lote, 5).raw("P", 1).raw("", 1).raw("01", 2);
+        records.push(
+            conta(segmento_p, 5)
+                .digits(&titulo.nosso_numero.to_string(), 20)
+                // Cobrança simples, com registro, tradicional, boleto e distribuição pelo beneficiário
+                .raw("11122", 5)
+                .alpha(&titulo.numero_documento, 15)
+                .date(Some(titulo.vencimento))
+                .money(titulo.valor, 15)
+                .num(0, 5)
+                .raw("", 1)
+                .raw(ESPECIE_240, 2)
+                .raw("N", 1)
+                .date(Some(titulo.emissao))
+                // 1 = valor por dia, 3 = isento
+                .num(if juros_dia.is_positive() { 1 } else { 3 }, 1)
+                .date(inicio_encargos.filter(|_| juros_dia.is_positive()))
+                .money(juros_dia, 15)
+                .num(0, 1)
+                .date(None)
+                .money(Money::ZERO, 15)
+                .money(Money::ZERO, 15)
+                .money(Money::ZERO, 15)
+                .alpha(&titulo.numero_documento, 25)
+                // 3 = não protestar; 1 = baixar após o prazo
+                .raw("3", 1)
+                .num(0, 2)
+                .raw("1", 1)
+                .num(PRAZO_BAIXA, 3)
+                .raw("09", 2)
+                .num(0, 10)
+                .finish(),
+        );
+
+        lote += 1;
+        let sacado = &titulo.sacado;
+        let (cep, sufixo) = sacado.cep.split_at(5);
+        records.push(
+            banco()
+                .num(1, 4)
+                .raw("3", 1)
+                .num(lote, 5)
+                .raw("Q", 1)
+                .raw("", 1)
+                .raw("01", 2)
+                .num(sacado.tipo_inscricao(), 1)
+                .digits(&sacado.documento, 15)
+                .alpha(&sacado.nome, 40)
+                .alpha(&sacado.endereco, 40)
+                .alpha(&sacado.bairro, 15)
+                .digits(cep, 5)
+                .digits(sufixo, 3)
+                .alpha(&sacado.cidade, 15)
+                .alpha(&sacado.uf, 2)
+                .num(0, 1)
+                .num(0, 15)
+                .raw("", 40)
+                .num(0, 3)
+                .raw("", 20)
+                .finish(),
+        );
+
+        if cedente.multa > 0.0 {
+            lote += 1;
+            records.push(
+                banco()
+                    .num(1, 4)
+                    .raw("3", 1)
+                    .num(lote, 5)
+                    .raw("R", 1)
+                    .raw("", 1)
+                    .raw("01", 2)
+                    .num(0, 24)
+                    .num(0, 24)
+                    // 2 = percentual
+                    .raw("2", 1)
+                    .date(inicio_encargos)
+                    .decimal(cedente.multa, 15, 2)
+                    .raw("", 10)
+                    .raw("", 40)
+                    .raw("", 40)
+                    .raw("", 20)
+                    .num(0, 8)
+                    .num(0, 3)
+                    .num(0, 5)
+                    .raw("", 1)
+                    .num(0, 12)
+                    .raw("", 2)
+                    .num(0, 1)
+                    .finish(),
+            );
+        }
+    }
+
+    let total: Money = titulos.iter().map(|t| t.valor).sum();
+    records.push(
+        banco()
+            .num(1, 4)
+            .raw("5", 1)
+            .raw("", 9)
+            .num(lote + 2, 6)
+            .num(titulos.len() as i64, 6)
+            .money(total, 17)
+            .num(0, 69)
+            .finish(),
+    );
+
+    let registros = records.len() as i64 + 1;
+    records.push(banco().raw("9999", 4).raw("9", 1).raw("", 9).num(1, 6).num(registros, 6).num(0, 6).finish());
+
+    join_records(records)
+}
+
+fn write_400(cedente: &Cedente, titulos: &[Titulo], sequencial: u32, gerado_em: NaiveDateTime) -> String {
+    let width = CnabLayout::Cnab400.width();
+
+    let mut records = vec![RecordWriter::new("01REMESSA01", width)
+        .alpha("COBRANCA", 15)
+        .digits(&cedente.convenio, 20)
+        .alpha(&cedente.razao_social, 30)
+        .digits(&cedente.banco_codigo, 3)
+        .alpha(&cedente.banco_nome, 15)
+        .raw(&short_date(gerado_em.date()), 6)
+        .raw("", 8)
+        .raw("MX", 2)
+        .num(sequencial as i64, 7)
+        .raw("", 277)
+        .num(1, 6)
+        .finish()];
+
+    for titulo in titulos {
+        let sacado = &titulo.sacado;
+        let seq = records.len() as i64 + 1;
+        records.push(
+            RecordWriter::new("1", width)
+                .num(0, 5)
+                .num(0, 1)
+                .num(0, 5)
+                .num(0, 7)
+                .num(0, 1)
+                // Identificação da empresa: zero, carteira, agência, conta e dígito
+                .raw("0", 1)
+                .digits(&cedente.carteira, 3)
+                .digits(&cedente.agencia, 5)
+                .digits(&cedente.conta, 7)
+                .alpha(&cedente.conta_digito, 1)
+                .alpha(&titulo.numero_documento, 25)
+                .num(0, 3)
+                .num(if cedente.multa > 0.0 { 2 } else { 0 }, 1)
+                .decimal(cedente.multa, 4, 2)
+                .num(titulo.nosso_numero as i64, 11)
+                .raw(&dv_nosso_numero(&cedente.carteira, titulo.nosso_numero), 1)
+                .money(Money::ZERO, 10)
+                // Boleto emitido pelo beneficiário, sem débito automático
+                .raw("2N", 2)
+                .raw("", 10)
+                .raw(" 2", 2)
+                .raw("", 2)
+                .raw("01", 2)
+                .alpha(&titulo.numero_documento, 10)
+                .raw(&short_date(titulo.vencimento), 6)
+                .money(titulo.valor, 13)
+                .num(0, 3)
+                .num(0, 5)
+                .raw(ESPECIE_400, 2)
+                .raw("N", 1)
+                .raw(&short_date(titulo.emissao), 6)
+                .raw("0000", 4)
+                .money(titulo.juros_dia(cedente), 13)
+                .num(0, 6)
+                .money(Money::ZERO, 13)
+                .money(Money::ZERO, 13)
+                .money(Money::ZERO, 13)
+                .num(sacado.tipo_inscricao(), 2)
+                .digits(&sacado.documento, 14)
+                .alpha(&sacado.nome, 40)
+                .alpha(&sacado.endereco, 40)
+                .raw("", 12)
+                .digits(&sacado.cep, 8)
+                .raw("", 60)
+                .num(seq, 6)
+                .finish(),
+        );
+    }
+
+    let seq = records.len() as i64 + 1;
+    records.push(RecordWriter::new("9", width).raw("", 393).num(seq, 6).finish());
+
+    join_records(records)
+}
+
+/// DDMMAA, usado nas datas do CNAB 400
+fn short_date(date: NaiveDate) -> String {
+    date.format("%d%m%y").to_string()
+}
+
+/// Dígito do nosso número: módulo 11 (pesos 2 a 7) sobre carteira e nosso número
+///
+/// Resto 0 dá "0" e resto 1 dá "P", como no manual do Bradesco.
+pub fn dv_nosso_numero(carteira: &str, nosso_numero: u64) -> String {
+    let carteira = text::digits(carteira);
+    let carteira = format!("{:0>2}", carteira);
+    let base = format!("{}{:011}", &carteira[carteira.len() - 2..], nosso_numero);
+
+    let sum: u32 = base
+        .bytes()
+        .rev()
+        .zip((2..=7).cycle())
+        .map(|(digit, weight)| (digit - b'0') as u32 * weight)
+        .sum();
+    match sum % 11 {
+        0 => "0".to_string(),
+        1 => "P".to_string(),
+        rest => (11 - rest).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnab::tests::sample;
+
+    fn gerado_em() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 4, 10).unwrap().and_hms_opt(14, 30, 0).unwrap()
+    }
+
+    /// Confere campos pelas posições do layout do banco (de/até, inclusivas
+    /// e a partir de 1), recortando a linha sem passar pelo leitor de registros
+    fn assert_positions(line: &str, campos: &[(usize, usize, &str)]) {
+        for &(de, ate, esperado) in campos {
+            assert_eq!(&line[de - 1..ate], esperado, "registro {}, posições {:03}-{:03}", &line[..3], de, ate);
+        }
+    }
+
+    #[test]
+    fn test_dv_nosso_numero() {
+        // Exemplo do manual do Bradesco: carteira 19, nosso número 00000000002 -> 8
+        assert_eq!(dv_nosso_numero("19", 2), "8");
+        assert_eq!(dv_nosso_numero("09", 101), "8");
+        assert_eq!(dv_nosso_numero("09", 100), "P");
+    }
+
+    #[test]
+    fn test_write_remessa_240() {
+        let (cedente, titulos) = sample();
+        let content = RemessaWriter::new(CnabLayout::Cnab240)
+            .write(&cedente, &titulos, 7, gerado_em())
+            .unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == 240));
+        assert_eq!(lines.len(), 10);
+
+        // Posições do layout FEBRABAN 240 (v10), sem passar pelo leitor de registros
+        assert_positions(lines[0], &[
+            (1, 3, "237"),
+            (4, 7, "0000"),
+            (8, 8, "0"),
+            (18, 18, "2"),
+            (19, 32, "12345678000195"),
+            (33, 52, &format!("{:<20}", "4567890")),
+            (53, 57, "01234"),
+            (58, 58, "5"),
+            (59, 70, "000000067890"),
+            (71, 71, "1"),
+            (73, 102, &format!("{:<30}", "AVILA TRANSPORTES LTDA")),
+            (103, 132, &format!("{:<30}", "BRADESCO")),
+            (143, 143, "1"),
+            (144, 151, "10042025"),
+            (152, 157, "143000"),
+            (158, 163, "000007"),
+            (164, 166, "103"),
+        ]);
+        assert_positions(lines[1], &[(8, 8, "1"), (9, 9, "R"), (10, 11, "01"), (14, 16, "060"), (184, 191, "00000007")]);
+
+        // Segmento P: identificação e valores do título
+        assert_positions(lines[2], &[
+            (1, 7, "2370001"),
+            (8, 8, "3"),
+            (9, 13, "00001"),
+            (14, 14, "P"),
+            (16, 17, "01"),
+            (18, 22, "01234"),
+            (24, 35, "000000067890"),
+            (38, 57, "00000000000000000101"),
+            (58, 58, "1"),
+            (63, 77, &format!("{:<15}", "288415")),
+            (78, 85, "10052025"),
+            (86, 100, "000000000125050"),
+            (107, 108, "04"),
+            (109, 109, "N"),
+            (110, 117, "10042025"),
+            (118, 118, "1"),
+            (119, 126, "11052025"),
+            // 3% ao mês sobre R$ 1.250,50: R$ 1,25 por dia
+            (127, 141, "000000000000125"),
+            (196, 220, &format!("{:<25}", "288415")),
+            (221, 221, "3"),
+            (224, 227, "1060"),
+            (228, 229, "09"),
+        ]);
+
+        // Segmento Q: pagador
+        assert_positions(lines[3], &[
+            (9, 13, "00002"),
+            (14, 14, "Q"),
+            (18, 18, "2"),
+            (19, 33, "098765432000110"),
+            (34, 73, &format!("{:<40}", "COMERCIAL FRANCA LTDA")),
+            (74, 113, &format!("{:<40}", "RUA VOLUNTARIOS DA FRANCA 1500")),
+            (114, 128, &format!("{:<15}", "CENTRO")),
+            (129, 136, "14400490"),
+            (137, 151, &format!("{:<15}", "FRANCA")),
+            (152, 153, "SP"),
+        ]);
+
+        // Segmento R: multa de 2% a partir do dia seguinte ao vencimento
+        assert_positions(lines[4], &[(14, 14, "R"), (66, 66, "2"), (67, 74, "11052025"), (75, 89, "000000000000200")]);
+
+        let segmentos: String = lines[2..8].iter().map(|l| &l[13..14]).collect();
+        assert_eq!(segmentos, "PQRPQR");
+        assert_positions(lines[8], &[(8, 8, "5"), (18, 23, "000008"), (24, 29, "000002"), (30, 46, "00000000000134260")]);
+        assert_positions(lines[9], &[(4, 8, "99999"), (18, 23, "000001"), (24, 29, "000010")]);
+    }
+
+    #[test]
+    fn test_write_remessa_400() {
+        let (cedente, titulos) = sample();
+        let content = RemessaWriter::new(CnabLayout::Cnab400)
+            .write(&cedente, &titulos, 7, gerado_em())
+            .unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == 400));
+        assert_eq!(lines.len(), 4);
+
+        // Posições do layout CNAB 400 do Bradesco
+        assert_positions(lines[0], &[
+            (1, 26, "01REMESSA01COBRANCA       "),
+            (27, 46, "00000000000004567890"),
+            (47, 76, &format!("{:<30}", "AVILA TRANSPORTES LTDA")),
+            (77, 79, "237"),
+            (80, 94, &format!("{:<15}", "BRADESCO")),
+            (95, 100, "100425"),
+            (109, 110, "MX"),
+            (111, 117, "0000007"),
+            (395, 400, "000001"),
+        ]);
+        assert_positions(lines[1], &[
+            (1, 1, "1"),
+            (21, 37, "00090123400678901"),
+            (38, 62, &format!("{:<25}", "288415")),
+            (66, 70, "20200"),
+            (71, 82, "000000001018"),
+            (93, 94, "2N"),
+            (109, 110, "01"),
+            (111, 120, &format!("{:<10}", "288415")),
+            (121, 126, "100525"),
+            (127, 139, "0000000125050"),
+            (148, 150, "12N"),
+            (151, 156, "100425"),
+            (161, 173, "0000000000125"),
+            (219, 220, "02"),
+            (221, 234, "98765432000110"),
+            (235, 274, &format!("{:<40}", "COMERCIAL FRANCA LTDA")),
+            (275, 314, &format!("{:<40}", "RUA VOLUNTARIOS DA FRANCA 1500")),
+            (327, 334, "14400490"),
+            (395, 400, "000002"),
+        ]);
+        assert_positions(lines[2], &[(71, 82, "000000001026"), (395, 400, "000003")]);
+        assert_positions(lines[3], &[(1, 1, "9"), (395, 400, "000004")]);
+
+        let mut sem_cnpj = cedente.clone();
+        sem_cnpj.cnpj.clear();
+        assert!(RemessaWriter::new(CnabLayout::Cnab400).write(&sem_cnpj, &titulos, 7, gerado_em()).is_err());
+    }
+}
@@ -0,0 +1,413 @@
+//! Resolução de empresas citadas nas ordens de frete
+//!
+//! As planilhas trazem pagador, remetente e destinatário só pelo nome (às
+//! vezes com cidade, telefone ou CNPJ), escritos de formas diferentes a cada
+//! exportação. Cada nome é casado com uma [`Company`] por CNPJ, nome
+//! normalizado ou similaridade; sem candidato, uma empresa nova é cadastrada
+//! com ID estável, e casos ambíguos viram [`CompanyReview`]s para o operador.
+
+use crate::text::{digits, normalize_text};
+use crate::geo::{normalize_city, split_uf};
+use pc_models::brdoc::cnpj;
+use pc_models::{Company, CompanyCandidate, CompanyReview, CompanyType, FreightOrder, LinkStatus};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Cidade gravada quando o nome só aparece como pagador
+pub const CIDADE_NAO_INFORMADA: &str = "Não informada";
+
+/// Sufixos societários ignorados na comparação
+const LEGAL_SUFFIXES: &[&str] = &["LTDA", "ME", "EPP", "EIRELI", "SA", "S A", "CIA", "MEI", "SS"];
+
+const BONUS_CIDADE: f64 = 0.05;
+const PENALIDADE_CIDADE: f64 = 0.15;
+const BONUS_TELEFONE: f64 = 0.15;
+
+/// Papel da empresa na ordem de frete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Pagador,
+    Remetente,
+    Destinatario,
+}
+
+/// Empresa como aparece em uma ordem
+#[derive(Debug, Clone)]
+struct Mention {
+    nome: String,
+    cidade: Option<String>,
+    telefone: Option<String>,
+    cnpj: Option<String>,
+}
+
+/// Resultado de uma resolução
+#[derive(Debug, Default)]
+pub struct CompanyResolution {
+    /// Empresas novas ou completadas (telefone, CNPJ), para gravar
+    pub companies: Vec<Company>,
+    /// Casos ambíguos, para revisão
+    pub reviews: Vec<CompanyReview>,
+    pub matched: usize,
+    pub created: usize,
+    pub queued: usize,
+}
+
+/// Casa nomes de empresas com o cadastro
+pub struct CompanyResolver {
+    companies: Vec<Company>,
+    /// Nome normalizado de cada empresa, na mesma ordem
+    names: Vec<String>,
+    reviews: HashMap<String, CompanyReview>,
+    auto_threshold: f64,
+    review_threshold: f64,
+    default_uf: String,
+}
+
+impl CompanyResolver {
+    pub fn new(companies: Vec<Company>) -> Self {
+        let names = companies.iter().map(|c| normalize_company_name(&c.nome)).collect();
+        Self {
+            companies,
+            names,
+            reviews: HashMap::new(),
+            auto_threshold: 0.9,
+            review_threshold: 0.7,
+            // A operação é toda em SP; cidades com "/UF" usam a UF informada
+            default_uf: "SP".to_string(),
+        }
+    }
+
+    /// Revisões anteriores: decisões do operador são reaproveitadas
+    pub fn with_reviews(mut self, reviews: Vec<CompanyReview>) -> Self {
+        self.reviews = reviews.into_iter().map(|r| (r.chave.clone(), r)).collect();
+        self
+    }
+
+    /// Similaridade mínima para casar sem revisão (e para ir à revisão)
+    pub fn with_thresholds(mut self, auto: f64, review: f64) -> Self {
+        self.auto_threshold = auto;
+        self.review_threshold = review;
+        self
+    }
+
+    pub fn with_default_uf(mut self, uf: impl Into<String>) -> Self {
+        self.default_uf = uf.into();
+        self
+    }
+
+    /// Preenche `pagador_id`, `remetente_id` e `destinatario_id` das ordens
+    ///
+    /// IDs já preenchidos são mantidos. Nomes ambíguos ficam sem ID até a
+    /// revisão.
+    pub fn resolve(&mut self, orders: &mut [FreightOrder]) -> CompanyResolution {
+        let mut resolution = CompanyResolution::default();
+        let mut touched: Vec<usize> = Vec::new();
+
+        for order in orders.iter_mut() {
+            // Remetente e destinatário primeiro: trazem a cidade que o pagador não tem
+            if order.remetente_id.is_none() {
+                let mention = Mention {
+                    nome: order.remetente_nome.clone(),
+                    cidade: Some(order.remetente_cidade.clone()),
+                    telefone: None,
+                    cnpj: order.remetente_cnpj.clone(),
+                };
+                order.remetente_id = self.resolve_mention(&mention, Role::Remetente, &order.numero, &mut resolution, &mut touched);
+            }
+            if order.destinatario_id.is_none() {
+                let mention = Mention {
+                    nome: order.destinatario_nome.clone(),
+                    cidade: Some(order.destinatario_cidade.clone()),
+                    telefone: None,
+                    cnpj: order.destinatario_cnpj.clone(),
+                };
+                order.destinatario_id =
+                    self.resolve_mention(&mention, Role::Destinatario, &order.numero, &mut resolution, &mut touched);
+            }
+            if order.pagador_id.is_none() {
+                let mention = Mention {
+                    nome: order.pagador_nome.clone(),
+                    cidade: None,
+                    telefone: order.pagador_telefone.clone(),
+                    cnpj: order.pagador_cnpj.clone(),
+                };
+                order.pagador_id = self.resolve_mention(&mention, Role::Pagador, &order.numero, &mut resolution, &mut touched);
+            }
+        }
+
+        touched.sort_unstable();
+        touched.dedup();
+        resolution.companies = touched.into_iter().map(|i| self.companies[i].clone()).collect();
+
+        let mut reviews: Vec<CompanyReview> = self.reviews.values().filter(|r| r.is_pending()).cloned().collect();
+        reviews.sort_by(|a, b| a.chave.cmp(&b.chave));
+        resolution.queued = reviews.len();
+        resolution.reviews = reviews;
+        resolution
+    }
+
+    fn resolve_mention(
+        &mut self,
+        mention: &Mention,
+        role: Role,
+        numero: &str,
+        resolution: &mut CompanyResolution,
+        touched: &mut Vec<usize>,
+    ) -> Option<Uuid> {
+        let name = normalize_company_name(&mention.nome);
+        if name.is_empty() {
+            return None;
+        }
+        let city = mention.cidade.as_deref().map(|c| normalize_city(split_uf(c).0)).unwrap_or_default();
+        let key = format!("{}|{}", name, city);
+
+        let mut rejected = false;
+        if let Some(review) = self.reviews.get_mut(&key) {
+            match (review.status, review.empresa_id) {
+                (LinkStatus::Accepted, Some(id)) => {
+                    resolution.matched += 1;
+                    return Some(id);
+                }
+                (LinkStatus::Rejected, _) => rejected = true,
+                // Ainda aguardando o operador
+                _ => {
+                    if !review.ordens.iter().any(|o| o == numero) {
+                        review.ordens.push(numero.to_string());
+                    }
+                    return None;
+                }
+            }
+        }
+
+        if !rejected {
+            let candidates = self.candidates(&name, &city, mention);
+            let best = candidates.first().map(|&(_, score)| score).unwrap_or(0.0);
+            let runner_up = candidates.get(1).map(|&(_, score)| score).unwrap_or(0.0);
+
+            if best >= self.auto_threshold && best - runner_up >= 0.05 {
+                let index = candidates[0].0;
+                if self.enrich(index, mention) {
+                    touched.push(index);
+                }
+                resolution.matched += 1;
+                return Some(self.companies[index].id);
+            }
+
+            if best >= self.review_threshold {
+                let mut review = CompanyReview::new(
+                    key.clone(),
+                    mention.nome.trim().to_string(),
+                    mention.cidade.clone().unwrap_or_default(),
+                    candidates
+                        .iter()
+                        .take(3)
+                        .map(|&(i, score)| CompanyCandidate {
+                            empresa_id: self.companies[i].id,
+                            nome: self.companies[i].nome.clone(),
+                            cidade: self.companies[i].cidade.clone(),
+                            pontuacao: (score * 100.0).round() / 100.0,
+                        })
+                        .collect(),
+                );
+                review.telefone = mention.telefone.clone();
+                review.cnpj = mention.cnpj.clone();
+                review.ordens.push(numero.to_string());
+                tracing::info!("Empresa \"{}\" ambígua, enviada para revisão", mention.nome.trim());
+                self.reviews.insert(key, review);
+                return None;
+            }
+        }
+
+        let company = self.new_company(mention, role, &name, &city);
+        // Nome rejeitado na revisão que já ganhou empresa própria
+        if let Some(existing) = self.companies.iter().find(|c| c.id == company.id) {
+            resolution.matched += 1;
+            return Some(existing.id);
+        }
+        tracing::debug!("Nova empresa \"{}\" ({})", company.nome, company.id);
+        self.names.push(name);
+        self.companies.push(company);
+        touched.push(self.companies.len() - 1);
+        resolution.created += 1;
+        self.companies.last().map(|c| c.id)
+    }
+
+    /// Empresas candidatas, da maior para a menor pontuação
+    fn candidates(&self, name: &str, city: &str, mention: &Mention) -> Vec<(usize, f64)> {
+        let cnpj = mention.cnpj.as_deref().map(cnpj::normalize);
+        let phone = mention.telefone.as_deref().map(digits).filter(|p| p.len() >= 8);
+
+        let mut candidates: Vec<(usize, f64)> = self
+            .companies
+            .iter()
+            .enumerate()
+            .filter_map(|(i, company)| {
+                match (&cnpj, company.cnpj.as_deref().map(cnpj::normalize)) {
+                    (Some(a), Some(b)) if *a == b => return Some((i, 1.0)),
+                    // CNPJs diferentes: outra empresa (ou outra filial)
+                    (Some(_), Some(_)) => return None,
+                    _ => {}
+                }
+
+                let mut score = strsim::sorensen_dice(name, &self.names[i]);
+                if !city.is_empty() && company.cidade != CIDADE_NAO_INFORMADA {
+                    if normalize_city(&company.cidade) == city {
+                        score += BONUS_CIDADE;
+                    } else {
+                        score -= PENALIDADE_CIDADE;
+                    }
+                }
+                let same_phone = [&company.telefone, &company.celular]
+                    .into_iter()
+                    .flatten()
+                    .any(|p| phone.as_deref().is_some_and(|phone| digits(p).ends_with(phone)));
+                if same_phone {
+                    score += BONUS_TELEFONE;
+                }
+
+                let score = score.clamp(0.0, 1.0);
+                (score >= self.review_threshold).then_some((i, score))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        candidates
+    }
+
+    /// Completa telefone e CNPJ que a empresa ainda não tinha
+    fn enrich(&mut self, index: usize, mention: &Mention) -> bool {
+        let company = &mut self.companies[index];
+        let mut changed = false;
+        if company.cnpj.is_none() && mention.cnpj.is_some() {
+            company.cnpj = mention.cnpj.clone();
+            changed = true;
+        }
+        if company.telefone.is_none() && mention.telefone.is_some() {
+            company.telefone = mention.telefone.clone();
+            changed = true;
+        }
+        changed
+    }
+
+    fn new_company(&self, mention: &Mention, role: Role, name: &str, city: &str) -> Company {
+        let (cidade, uf) = match mention.cidade.as_deref() {
+            Some(cidade) if !cidade.trim().is_empty() => split_uf(cidade),
+            _ => (CIDADE_NAO_INFORMADA, None),
+        };
+        let tipo = match role {
+            Role::Pagador => CompanyType::Cliente,
+            Role::Remetente | Role::Destinatario => CompanyType::Outros,
+        };
+
+        let mut company = Company::new(
+            mention.nome.trim().to_string(),
+            cidade.to_string(),
+            uf.map(str::to_string).unwrap_or_else(|| self.default_uf.clone()),
+            tipo,
+        );
+        company.id = match mention.cnpj.as_deref().map(cnpj::normalize) {
+            Some(cnpj) => stable_id(&cnpj),
+            None => stable_id(&format!("{}|{}", name, city)),
+        };
+        company.cnpj = mention.cnpj.clone();
+        company.telefone = mention.telefone.clone();
+        company
+    }
+}
+
+/// "Comercial Franca Ltda. - ME" e "COMERCIAL  FRANCA" viram "COMERCIAL FRANCA"
+pub fn normalize_company_name(name: &str) -> String {
+    let mut normalized = normalize_text(name)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    while let Some(suffix) = LEGAL_SUFFIXES
+        .iter()
+        .find(|s| normalized.len() > s.len() && normalized.ends_with(&format!(" {}", s)))
+    {
+        normalized.truncate(normalized.len() - suffix.len() - 1);
+    }
+    normalized
+}
+
+/// ID derivado da chave, igual em qualquer banco e em qualquer execução
+fn stable_id(key: &str) -> Uuid {
+    let hash = pc_core::hash::sha256_hex(key.as_bytes());
+    let bytes = u128::from_str_radix(&hash[..32], 16).unwrap_or_default().to_be_bytes();
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn order(numero: &str, pagador: &str, remetente: &str, destinatario: &str, cidade: &str) -> FreightOrder {
+        FreightOrder::new(
+            numero.into(),
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            pagador.into(),
+            remetente.into(),
+            "Ribeirão Preto/SP".into(),
+            destinatario.into(),
+            cidade.into(),
+        )
+    }
+
+    #[test]
+    fn test_normalize_company_name() {
+        assert_eq!(normalize_company_name("Comercial Franca Ltda. - ME"), "COMERCIAL FRANCA");
+        assert_eq!(normalize_company_name("COMERCIAL  FRANCA"), "COMERCIAL FRANCA");
+        assert_eq!(normalize_company_name("Distribuidora São João S/A"), "DISTRIBUIDORA SAO JOAO");
+        assert_eq!(normalize_company_name("ME"), "ME");
+    }
+
+    #[test]
+    fn test_resolve_matches_creates_and_queues() {
+        let mut orders = vec![
+            order("1", "Fenior Ltda", "FENIOR LTDA", "Comercial Franca Ltda", "Franca/SP"),
+            order("2", "FENIOR", "Fenior", "COMERCIAL FRANCA", "Franca"),
+            order("3", "Fenior", "Fenior", "Comercial Franca Alimentos", "Franca"),
+        ];
+
+        let resolution = CompanyResolver::new(Vec::new()).resolve(&mut orders);
+        assert_eq!(resolution.created, 2);
+        assert_eq!(resolution.queued, 1);
+        assert_eq!(resolution.companies.len(), 2);
+
+        // Mesmo pagador e remetente nas três ordens, com o mesmo ID
+        assert!(orders.iter().all(|o| o.pagador_id == orders[0].remetente_id));
+        assert_eq!(orders[0].destinatario_id, orders[1].destinatario_id);
+        assert!(orders[2].destinatario_id.is_none());
+
+        let review = &resolution.reviews[0];
+        assert_eq!(review.ordens, vec!["3"]);
+        assert_eq!(review.candidatos[0].nome, "Comercial Franca Ltda");
+
+        // Outra execução, em outro banco, gera os mesmos IDs
+        let mut again = vec![order("1", "Fenior Ltda", "FENIOR LTDA", "Comercial Franca Ltda", "Franca/SP")];
+        CompanyResolver::new(Vec::new()).resolve(&mut again);
+        assert_eq!(again[0].destinatario_id, orders[0].destinatario_id);
+    }
+
+    #[test]
+    fn test_review_decision_is_reused() {
+        let mut orders = vec![
+            order("1", "Fenior", "Fenior", "Comercial Franca", "Franca"),
+            order("2", "Fenior", "Fenior", "Comercial Franca Alimentos", "Franca"),
+        ];
+        let mut resolver = CompanyResolver::new(Vec::new());
+        let mut review = resolver.resolve(&mut orders).reviews.remove(0);
+        let franca = orders[0].destinatario_id.unwrap();
+
+        review.accept(franca);
+        let mut resolver = CompanyResolver::new(resolver.companies).with_reviews(vec![review]);
+        let resolution = resolver.resolve(&mut orders);
+
+        assert_eq!(orders[1].destinatario_id, Some(franca));
+        assert!(resolution.reviews.is_empty());
+        assert_eq!(resolution.created, 0);
+    }
+}
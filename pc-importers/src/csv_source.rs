@@ -0,0 +1,413 @@
+//! Leitura de CSVs com detecção de encoding, delimitador e aspas
+//!
+//! Os CSVs dos sistemas antigos vêm em Windows-1252 separados por `;`;
+//! exportações mais novas vêm em UTF-8 (às vezes com BOM), UTF-16 ou
+//! separadas por vírgula/tabulação. Tudo pode ser forçado via [`CsvOptions`].
+
+use encoding_rs::{UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+use pc_core::Result;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Delimitadores considerados na detecção, em ordem de preferência
+const DELIMITERS: [u8; 4] = [b';', b',', b'\t', b'|'];
+
+/// Linhas analisadas para detectar delimitador e aspas
+const SNIFF_LINES: usize = 20;
+
+/// Bytes lidos do início do arquivo para detectar o formato na leitura incremental
+pub const HEAD_LEN: usize = 64 * 1024;
+
+/// Encoding de texto suportado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+    Iso8859_1,
+}
+
+impl TextEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16Be => "utf-16be",
+            TextEncoding::Windows1252 => "windows-1252",
+            TextEncoding::Iso8859_1 => "iso-8859-1",
+        }
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = pc_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Ok(TextEncoding::Utf8),
+            "utf-16" | "utf-16le" | "utf16" | "utf16le" => Ok(TextEncoding::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(TextEncoding::Utf16Be),
+            "windows-1252" | "cp1252" | "win1252" => Ok(TextEncoding::Windows1252),
+            "iso-8859-1" | "latin1" | "latin-1" => Ok(TextEncoding::Iso8859_1),
+            other => Err(pc_core::Error::Import(format!("Encoding desconhecido: {}", other))),
+        }
+    }
+}
+
+/// Ajustes manuais de leitura; campos `None` são detectados automaticamente
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvOptions {
+    pub encoding: Option<TextEncoding>,
+    pub delimiter: Option<u8>,
+    pub quote: Option<u8>,
+}
+
+impl CsvOptions {
+    /// Converte o delimitador informado pelo usuário (`;`, `,`, `tab`, `\t`, `|`)
+    pub fn parse_delimiter(value: &str) -> Result<u8> {
+        match value {
+            "tab" | "\\t" | "\t" => Ok(b'\t'),
+            v if v.len() == 1 && v.is_ascii() => Ok(v.as_bytes()[0]),
+            other => Err(pc_core::Error::Import(format!("Delimitador inválido: {}", other))),
+        }
+    }
+}
+
+/// Parâmetros de leitura resolvidos (informados ou detectados)
+#[derive(Debug, Clone, Copy)]
+pub struct CsvFormat {
+    pub encoding: TextEncoding,
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl CsvFormat {
+    /// Detecta o formato a partir do início do arquivo
+    pub fn sniff(head: &[u8], options: &CsvOptions) -> Self {
+        // O início lido pode cortar um caractere UTF-8 multibyte ao meio
+        let head = match std::str::from_utf8(head) {
+            Err(e) if e.error_len().is_none() => &head[..e.valid_up_to()],
+            _ => head,
+        };
+
+        let encoding = options.encoding.unwrap_or_else(|| detect_encoding(head));
+        let (text, _) = decode(head, encoding);
+        let delimiter = options.delimiter.unwrap_or_else(|| sniff_delimiter(&text));
+        let quote = options.quote.unwrap_or_else(|| sniff_quote(&text, delimiter));
+        tracing::debug!(
+            "CSV: encoding {}, delimitador {:?}, aspas {:?}",
+            encoding.as_str(),
+            delimiter as char,
+            quote as char
+        );
+
+        Self {
+            encoding,
+            delimiter,
+            quote,
+        }
+    }
+
+    /// Abre o arquivo para leitura incremental, detectando o formato pelos
+    /// primeiros [`HEAD_LEN`] bytes
+    pub fn open<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<(Self, csv::Reader<DecodedFile>)> {
+        let path = path.as_ref();
+        let mut head = Vec::with_capacity(HEAD_LEN);
+        open_file(path)?
+            .take(HEAD_LEN as u64)
+            .read_to_end(&mut head)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
+
+        let mut format = Self::sniff(&head, options);
+
+        // Um início em ASCII puro não garante UTF-8 no restante do arquivo
+        let has_bom = head.starts_with(&[0xEF, 0xBB, 0xBF]);
+        let may_change = options.encoding.is_none() && format.encoding == TextEncoding::Utf8 && !has_bom;
+        if may_change && head.len() == HEAD_LEN && !is_utf8(open_file(path)?)? {
+            tracing::debug!("Bytes não UTF-8 após o início do arquivo; usando Windows-1252");
+            format.encoding = TextEncoding::Windows1252;
+        }
+
+        let reader = format.reader(format.decoder(open_file(path)?));
+        Ok((format, reader))
+    }
+
+    /// Decodificador incremental para UTF-8 (o BOM, se houver, é descartado)
+    pub fn decoder<R: Read>(&self, inner: R) -> DecodeReaderBytes<R, Vec<u8>> {
+        let encoding = match self.encoding {
+            TextEncoding::Utf8 => UTF_8,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+            // ISO-8859-1 só é escolhido quando não há bytes 0x80..=0x9F, faixa
+            // em que ele difere do Windows-1252
+            TextEncoding::Windows1252 | TextEncoding::Iso8859_1 => WINDOWS_1252,
+        };
+        DecodeReaderBytesBuilder::new()
+            .encoding(Some(encoding))
+            .bom_override(true)
+            .build(inner)
+    }
+
+    /// Leitor CSV configurado com cabeçalho e registros de tamanho variável
+    pub fn reader<R: Read>(&self, inner: R) -> csv::Reader<R> {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(true)
+            .flexible(true)
+            .from_reader(inner)
+    }
+}
+
+/// Arquivo decodificado para UTF-8 sob demanda
+pub type DecodedFile = DecodeReaderBytes<File, Vec<u8>>;
+
+/// Conteúdo de um CSV já decodificado, com os parâmetros de leitura resolvidos
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    pub content: String,
+    pub encoding: TextEncoding,
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl CsvSource {
+    /// Lê e decodifica o arquivo inteiro
+    pub fn read<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref())
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
+        Ok(Self::from_bytes(&bytes, options))
+    }
+
+    pub fn from_bytes(bytes: &[u8], options: &CsvOptions) -> Self {
+        let format = CsvFormat::sniff(bytes, options);
+        let (content, had_errors) = decode(bytes, format.encoding);
+        if had_errors {
+            tracing::warn!("Erros de encoding detectados no arquivo ({})", format.encoding.as_str());
+        }
+
+        Self {
+            content,
+            encoding: format.encoding,
+            delimiter: format.delimiter,
+            quote: format.quote,
+        }
+    }
+
+    /// Leitor CSV sobre o conteúdo em memória
+    pub fn reader(&self) -> csv::Reader<&[u8]> {
+        CsvFormat {
+            encoding: self.encoding,
+            delimiter: self.delimiter,
+            quote: self.quote,
+        }
+        .reader(self.content.as_bytes())
+    }
+}
+
+/// Valida UTF-8 em blocos, sem carregar o arquivo inteiro
+fn is_utf8<R: Read>(mut inner: R) -> Result<bool> {
+    let mut buffer = vec![0u8; HEAD_LEN];
+    let mut pending = 0;
+    loop {
+        let read = inner
+            .read(&mut buffer[pending..])
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
+        let filled = pending + read;
+        if read == 0 {
+            return Ok(pending == 0);
+        }
+
+        match std::str::from_utf8(&buffer[..filled]) {
+            Ok(_) => pending = 0,
+            // Caractere multibyte cortado no fim do bloco: continua no próximo
+            Err(e) if e.error_len().is_none() => {
+                let valid = e.valid_up_to();
+                buffer.copy_within(valid..filled, 0);
+                pending = filled - valid;
+            }
+            Err(_) => return Ok(false),
+        }
+    }
+}
+
+fn open_file(path: &Path) -> Result<File> {
+    File::open(path).map_err(|e| pc_core::Error::Import(format!("Erro ao abrir arquivo: {}", e)))
+}
+
+/// Detecta o encoding pelo BOM, pela distribuição de bytes nulos e pela
+/// validade como UTF-8
+pub fn detect_encoding(bytes: &[u8]) -> TextEncoding {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return TextEncoding::Utf8;
+    }
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return TextEncoding::Utf16Le;
+    }
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        return TextEncoding::Utf16Be;
+    }
+
+    // UTF-16 sem BOM: texto latino tem um byte nulo a cada dois
+    let sample = &bytes[..bytes.len().min(4096)];
+    if sample.len() >= 4 {
+        let zeros_even = sample.iter().step_by(2).filter(|b| **b == 0).count();
+        let zeros_odd = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+        let half = sample.len() / 2;
+        if zeros_odd * 10 >= half * 7 && zeros_even * 10 < half {
+            return TextEncoding::Utf16Le;
+        }
+        if zeros_even * 10 >= half * 7 && zeros_odd * 10 < half {
+            return TextEncoding::Utf16Be;
+        }
+    }
+
+    if std::str::from_utf8(bytes).is_ok() {
+        return TextEncoding::Utf8;
+    }
+
+    // 0x80..=0x9F são caracteres imprimíveis só no Windows-1252 (€, “, ”, –...)
+    if bytes.iter().any(|b| (0x80..=0x9F).contains(b)) {
+        TextEncoding::Windows1252
+    } else {
+        TextEncoding::Iso8859_1
+    }
+}
+
+/// Decodifica o texto, removendo o BOM; retorna se houve bytes inválidos
+pub fn decode(bytes: &[u8], encoding: TextEncoding) -> (String, bool) {
+    match encoding {
+        TextEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF][..]).unwrap_or(bytes);
+            match String::from_utf8_lossy(bytes) {
+                std::borrow::Cow::Borrowed(s) => (s.to_string(), false),
+                std::borrow::Cow::Owned(s) => (s, true),
+            }
+        }
+        TextEncoding::Utf16Le => {
+            let bytes = bytes.strip_prefix(&[0xFF, 0xFE][..]).unwrap_or(bytes);
+            let (content, had_errors) = UTF_16LE.decode_without_bom_handling(bytes);
+            (content.into_owned(), had_errors)
+        }
+        TextEncoding::Utf16Be => {
+            let bytes = bytes.strip_prefix(&[0xFE, 0xFF][..]).unwrap_or(bytes);
+            let (content, had_errors) = UTF_16BE.decode_without_bom_handling(bytes);
+            (content.into_owned(), had_errors)
+        }
+        TextEncoding::Windows1252 => {
+            let (content, _, had_errors) = WINDOWS_1252.decode(bytes);
+            (content.into_owned(), had_errors)
+        }
+        // O encoding_rs trata ISO-8859-1 como Windows-1252; aqui cada byte é o code point
+        TextEncoding::Iso8859_1 => (bytes.iter().map(|b| *b as char).collect(), false),
+    }
+}
+
+/// Escolhe o delimitador que aparece com a contagem mais consistente entre
+/// as primeiras linhas (ignorando o que está entre aspas)
+pub fn sniff_delimiter(content: &str) -> u8 {
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+
+    let mut best = (b';', 0usize, 0usize);
+    for delimiter in DELIMITERS {
+        let counts: Vec<usize> = lines.iter().map(|l| count_unquoted(l, delimiter)).collect();
+        let Some(header) = counts.first().copied().filter(|c| *c > 0) else {
+            continue;
+        };
+
+        // Linhas cuja contagem bate com a do cabeçalho
+        let consistent = counts.iter().filter(|c| **c == header).count();
+        if (consistent, header) > (best.1, best.2) {
+            best = (delimiter, consistent, header);
+        }
+    }
+
+    best.0
+}
+
+/// Usa apóstrofo como aspas apenas quando ele delimita campos e `"` não aparece
+pub fn sniff_quote(content: &str, delimiter: u8) -> u8 {
+    let delimiter = delimiter as char;
+    let sample: Vec<&str> = content.lines().take(SNIFF_LINES).collect();
+
+    let quoted_fields = |quote: char| -> usize {
+        sample
+            .iter()
+            .flat_map(|l| l.split(delimiter))
+            .map(str::trim)
+            .filter(|f| f.len() >= 2 && f.starts_with(quote) && f.ends_with(quote))
+            .count()
+    };
+
+    if quoted_fields('"') == 0 && quoted_fields('\'') > 0 {
+        b'\''
+    } else {
+        b'"'
+    }
+}
+
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for b in line.bytes() {
+        if b == b'"' {
+            in_quotes = !in_quotes;
+        } else if b == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFNome;Cidade"), TextEncoding::Utf8);
+        assert_eq!(detect_encoding("Número;São Carlos".as_bytes()), TextEncoding::Utf8);
+        assert_eq!(detect_encoding(b"N\xfamero;S\xe3o Carlos"), TextEncoding::Iso8859_1);
+        assert_eq!(detect_encoding(b"Valor \x80 10;\x93teste\x94"), TextEncoding::Windows1252);
+
+        let utf16: Vec<u8> = "Nome;Cidade".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        assert_eq!(detect_encoding(&utf16), TextEncoding::Utf16Le);
+    }
+
+    #[test]
+    fn test_decode_strips_bom() {
+        let source = CsvSource::from_bytes(b"\xEF\xBB\xBFN\xC3\xBAmero,Cidade\n1,Franca", &CsvOptions::default());
+        assert_eq!(source.encoding, TextEncoding::Utf8);
+        assert!(source.content.starts_with("Número"));
+        assert_eq!(source.delimiter, b',');
+
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("Cidade\tUF\nSão Paulo\tSP".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        let source = CsvSource::from_bytes(&bytes, &CsvOptions::default());
+        assert_eq!(source.content, "Cidade\tUF\nSão Paulo\tSP");
+        assert_eq!(source.delimiter, b'\t');
+    }
+
+    #[test]
+    fn test_sniff_delimiter_and_quote() {
+        // Vírgula decimal dentro de aspas não pode ganhar do separador real
+        let content = "Número,Valor,Cidade\n1,\"1.234,56\",Franca\n2,\"90,00\",Matão";
+        assert_eq!(sniff_delimiter(content), b',');
+        assert_eq!(sniff_delimiter("a;b;c\n1;2,5;3\n4;5,1;6"), b';');
+        assert_eq!(sniff_quote("'a';'b'\n'1';'2'", b';'), b'\'');
+
+        let options = CsvOptions {
+            delimiter: Some(b'|'),
+            ..CsvOptions::default()
+        };
+        assert_eq!(CsvSource::from_bytes(b"a;b|c", &options).delimiter, b'|');
+    }
+}
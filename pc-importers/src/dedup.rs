@@ -0,0 +1,320 @@
+//! Detecção de fotos e documentos duplicados
+//!
+//! Os backups guardam várias cópias dos mesmos arquivos, às vezes
+//! recomprimidas ou redimensionadas. Arquivos são agrupados quando têm o
+//! mesmo sha256 (cópia exata), hashes perceptuais próximos (imagens) ou o
+//! mesmo texto normalizado (PDFs). De cada grupo fica um documento
+//! canônico, e todos os caminhos viram locais desse documento.
+//!
+//! O agrupamento vale para os arquivos lidos juntos: um diretório de fotos
+//! ou de PDFs, ou todas as raízes de uma importação em massa
+//! ([`crate::bulk`]).
+
+use crate::text::normalize_text;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Distância de Hamming máxima entre hashes perceptuais de imagens iguais
+pub const PERCEPTUAL_THRESHOLD: u32 = 6;
+
+/// Como dois arquivos foram considerados iguais
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DuplicateKind {
+    /// Mesmo conteúdo (sha256)
+    Exact,
+    /// Imagens visualmente iguais
+    Perceptual,
+    /// PDFs com o mesmo texto
+    Text,
+}
+
+/// Impressões digitais de um arquivo
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    pub sha256: String,
+    pub perceptual_hash: Option<u64>,
+    pub text_hash: Option<String>,
+}
+
+/// Grupo de arquivos duplicados, por índice na lista original
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// Índice do arquivo mantido
+    pub canonical: usize,
+    /// Todos os índices do grupo, inclusive o canônico
+    pub members: Vec<usize>,
+    /// Critério mais fraco que uniu o grupo
+    pub kind: DuplicateKind,
+}
+
+/// Agrupa os arquivos duplicados; `rank` escolhe o canônico (maior vence)
+///
+/// Só retorna grupos com mais de um arquivo. Cópias exatas e textos iguais
+/// são agrupados por tabela hash; a distância de Hamming só é calculada
+/// entre hashes perceptuais que caem numa mesma faixa de bits.
+pub fn group_duplicates<R, K>(fingerprints: &[Fingerprint], rank: R) -> Vec<DuplicateGroup>
+where
+    R: Fn(usize) -> K,
+    K: Ord,
+{
+    let n = fingerprints.len();
+    let mut sets = DisjointSets::new(n);
+
+    // Um representante por sha256: as cópias exatas têm os mesmos hashes
+    let mut by_sha: HashMap<&str, usize> = HashMap::new();
+    let mut representatives = Vec::new();
+    for (i, fingerprint) in fingerprints.iter().enumerate() {
+        match by_sha.entry(&fingerprint.sha256) {
+            Entry::Occupied(first) => sets.union(*first.get(), i, DuplicateKind::Exact),
+            Entry::Vacant(slot) => {
+                slot.insert(i);
+                representatives.push(i);
+            }
+        }
+    }
+
+    // Hashes com até PERCEPTUAL_THRESHOLD bits diferentes coincidem em pelo
+    // menos uma das PERCEPTUAL_THRESHOLD + 1 faixas
+    let mut by_band: HashMap<(u32, u64), Vec<usize>> = HashMap::new();
+    for &i in &representatives {
+        if let Some(hash) = fingerprints[i].perceptual_hash {
+            for key in bands(hash) {
+                by_band.entry(key).or_default().push(i);
+            }
+        }
+    }
+    for candidates in by_band.values() {
+        for (pos, &i) in candidates.iter().enumerate() {
+            for &j in &candidates[pos + 1..] {
+                if perceptually_equal(&fingerprints[i], &fingerprints[j]) {
+                    sets.union(i, j, DuplicateKind::Perceptual);
+                }
+            }
+        }
+    }
+
+    let mut by_text: HashMap<&str, usize> = HashMap::new();
+    for &i in &representatives {
+        let Some(text_hash) = fingerprints[i].text_hash.as_deref() else {
+            continue;
+        };
+        match by_text.entry(text_hash) {
+            Entry::Occupied(first) => {
+                let first = *first.get();
+                let kind = if perceptually_equal(&fingerprints[first], &fingerprints[i]) {
+                    DuplicateKind::Perceptual
+                } else {
+                    DuplicateKind::Text
+                };
+                sets.union(first, i, kind);
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(i);
+            }
+        }
+    }
+
+    let DisjointSets { mut parent, kinds } = sets;
+    let mut by_root: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..n {
+        by_root.entry(find(&mut parent, i)).or_default().push(i);
+    }
+
+    let mut groups: Vec<DuplicateGroup> = by_root
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(root, members)| {
+            // Empate: fica o primeiro da lista
+            let canonical = *members
+                .iter()
+                .rev()
+                .max_by_key(|&&i| rank(i))
+                .expect("grupo não vazio");
+            DuplicateGroup {
+                canonical,
+                members,
+                kind: kinds[root],
+            }
+        })
+        .collect();
+    groups.sort_by_key(|g| g.members[0]);
+    groups
+}
+
+/// Mantém o item canônico de cada grupo, com os locais de todas as cópias
+pub(crate) fn merge_duplicates<T, F>(items: Vec<T>, groups: &[DuplicateGroup], locations: F) -> Vec<T>
+where
+    F: Fn(&mut T) -> &mut Vec<PathBuf>,
+{
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+
+    for group in groups {
+        tracing::info!("{} cópias do mesmo arquivo ({:?})", group.members.len(), group.kind);
+        let copies: Vec<PathBuf> = group
+            .members
+            .iter()
+            .filter(|&&i| i != group.canonical)
+            .filter_map(|&i| items[i].take())
+            .flat_map(|mut copy| std::mem::take(locations(&mut copy)))
+            .collect();
+        if let Some(canonical) = items[group.canonical].as_mut() {
+            locations(canonical).extend(copies);
+        }
+    }
+
+    items.into_iter().flatten().collect()
+}
+
+fn perceptually_equal(a: &Fingerprint, b: &Fingerprint) -> bool {
+    a.perceptual_hash
+        .zip(b.perceptual_hash)
+        .is_some_and(|(x, y)| (x ^ y).count_ones() <= PERCEPTUAL_THRESHOLD)
+}
+
+/// Divide o hash de 64 bits em PERCEPTUAL_THRESHOLD + 1 faixas disjuntas
+fn bands(hash: u64) -> impl Iterator<Item = (u32, u64)> {
+    const BANDS: u32 = PERCEPTUAL_THRESHOLD + 1;
+    (0..BANDS).map(move |band| {
+        let (start, end) = (band * 64 / BANDS, (band + 1) * 64 / BANDS);
+        (band, (hash >> start) & ((1u64 << (end - start)) - 1))
+    })
+}
+
+/// Union-find com o critério mais fraco de cada grupo
+struct DisjointSets {
+    parent: Vec<usize>,
+    kinds: Vec<DuplicateKind>,
+}
+
+impl DisjointSets {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            kinds: vec![DuplicateKind::Exact; n],
+        }
+    }
+
+    fn union(&mut self, i: usize, j: usize, kind: DuplicateKind) {
+        let (ri, rj) = (find(&mut self.parent, i), find(&mut self.parent, j));
+        if ri != rj {
+            self.parent[rj] = ri;
+            self.kinds[ri] = self.kinds[ri].max(self.kinds[rj]);
+        }
+        self.kinds[ri] = self.kinds[ri].max(kind);
+    }
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Hash perceptual (dHash de 64 bits) de uma imagem
+///
+/// Reduz a imagem a 9x8 em tons de cinza e compara pixels vizinhos, o que
+/// resiste a recompressão, redimensionamento e pequenos ajustes de cor.
+pub fn perceptual_hash(path: &Path) -> Option<u64> {
+    let image = match image::open(path) {
+        Ok(image) => image,
+        Err(e) => {
+            tracing::debug!("Imagem {:?} não decodificada: {}", path, e);
+            return None;
+        }
+    };
+    let small = image
+        .grayscale()
+        .resize_exact(9, 8, image::imageops::FilterType::Triangle)
+        .into_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    Some(hash)
+}
+
+/// sha256 do texto sem acentos, pontuação, caixa e espaços repetidos
+///
+/// `None` para textos curtos demais (PDFs digitalizados sem OCR).
+pub fn text_hash(text: &str) -> Option<String> {
+    let normalized = normalize_text(text)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (normalized.len() >= 32).then(|| pc_core::hash::sha256_hex(normalized.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(sha: &str, perceptual: Option<u64>, text: Option<&str>) -> Fingerprint {
+        Fingerprint {
+            sha256: sha.into(),
+            perceptual_hash: perceptual,
+            text_hash: text.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_group_duplicates() {
+        let fingerprints = vec![
+            fingerprint("a", Some(0xFFFF_0000), None),
+            fingerprint("b", None, Some("t1")),
+            fingerprint("a", Some(0xFFFF_0000), None),
+            // Recompressão: dois bits diferentes
+            fingerprint("c", Some(0xFFFF_0003), None),
+            fingerprint("d", None, Some("t1")),
+            fingerprint("e", Some(0x0000_FFFF), None),
+        ];
+        let sizes = [100, 50, 100, 300, 60, 10];
+
+        let groups = group_duplicates(&fingerprints, |i| sizes[i]);
+        assert_eq!(groups.len(), 2);
+
+        assert_eq!(groups[0].members, vec![0, 2, 3]);
+        assert_eq!(groups[0].canonical, 3);
+        assert_eq!(groups[0].kind, DuplicateKind::Perceptual);
+
+        assert_eq!(groups[1].members, vec![1, 4]);
+        assert_eq!(groups[1].canonical, 4);
+        assert_eq!(groups[1].kind, DuplicateKind::Text);
+    }
+
+    #[test]
+    fn test_group_duplicates_perceptual_threshold() {
+        // Um bit diferente em cada uma das seis primeiras faixas: só a última coincide
+        let six_bits = (0..6).fold(0u64, |hash, band| hash | 1 << (band * 64 / 7));
+        let fingerprints = vec![
+            fingerprint("a", Some(0), None),
+            fingerprint("b", Some(six_bits), None),
+            fingerprint("c", Some(u64::MAX << 57), None),
+            fingerprint("d", Some(u64::MAX), None),
+        ];
+
+        let groups = group_duplicates(&fingerprints, |i| i);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].members, vec![0, 1]);
+        assert_eq!(groups[0].kind, DuplicateKind::Perceptual);
+    }
+
+    #[test]
+    fn test_text_hash_ignores_formatting() {
+        let a = text_hash("CONHECIMENTO DE TRANSPORTE Nº 1234 - Ribeirão Preto/SP");
+        let b = text_hash("Conhecimento  de transporte\nnº 1234 ribeirao preto sp");
+        assert!(a.is_some());
+        assert_eq!(a, b);
+        assert!(text_hash("  \n ").is_none());
+    }
+}
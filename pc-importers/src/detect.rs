@@ -0,0 +1,679 @@
+//! Detecção automática do tipo de arquivo pelo conteúdo
+//!
+//! A detecção combina, em ordem de confiança: assinaturas binárias (magic
+//! bytes), elemento raiz de XML, prefixos de registros EDI/AFD, cabeçalhos
+//! de e-mail, cabeçalhos de CSV conhecidos e, por último, extensão e nome
+//! do arquivo.
+
+use crate::afd_importer::AfdImporter;
+use crate::archive::{ArchiveContents, ArchiveImporter};
+use crate::csv_source::{self, CsvOptions};
+use crate::edi::{self, EdiDocumentType, NotfisImporter};
+use crate::excel_importer::{ExcelImporter, ExcelRow};
+use crate::fiscal_xml::FiscalXmlImporter;
+use crate::freight_importer::FreightOrderImporter;
+use crate::geo::Municipalities;
+use crate::pdf_importer::{PdfImporter, PdfMetadata};
+use crate::photo_importer::{PhotoImporter, PhotoMetadata};
+use crate::route_importer::RouteImporter;
+use crate::text;
+use crate::timesheet_importer::TimesheetImporter;
+use crate::Importer;
+use pc_core::Result;
+use pc_models::{Document, FreightOrder, Provenance, Route, Timesheet};
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Quantidade de bytes lidos do início do arquivo para a detecção
+const SNIFF_LEN: usize = 8 * 1024;
+
+/// Tipo de arquivo reconhecido
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    FreightCsv,
+    TimesheetCsv,
+    RouteCsv,
+    Notfis,
+    Ocoren,
+    Conemb,
+    Doccob,
+    Afd,
+    NfeXml,
+    CteXml,
+    Excel,
+    Pdf,
+    Image,
+    /// ZIP (que não seja planilha xlsx)
+    Archive,
+    /// Mensagem .eml ou caixa mbox
+    Email,
+    Unknown,
+}
+
+impl FileKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileKind::FreightCsv => "freight",
+            FileKind::TimesheetCsv => "timesheet",
+            FileKind::RouteCsv => "route",
+            FileKind::Notfis => "notfis",
+            FileKind::Ocoren => "ocoren",
+            FileKind::Conemb => "conemb",
+            FileKind::Doccob => "doccob",
+            FileKind::Afd => "afd",
+            FileKind::NfeXml => "nfe-xml",
+            FileKind::CteXml => "cte-xml",
+            FileKind::Excel => "excel",
+            FileKind::Pdf => "pdf",
+            FileKind::Image => "image",
+            FileKind::Archive => "zip",
+            FileKind::Email => "email",
+            FileKind::Unknown => "unknown",
+        }
+    }
+}
+
+/// Resultado da detecção
+#[derive(Debug, Clone)]
+pub struct Detection {
+    pub kind: FileKind,
+    /// Confiança entre 0.0 e 1.0
+    pub confidence: f32,
+    /// Motivo da decisão (para exibição ao usuário)
+    pub reason: String,
+}
+
+impl Detection {
+    pub fn new(kind: FileKind, confidence: f32, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            confidence,
+            reason: reason.into(),
+        }
+    }
+
+    fn unknown() -> Self {
+        Self::new(FileKind::Unknown, 0.0, "Nenhuma assinatura reconhecida")
+    }
+}
+
+/// Lê o início do arquivo e detecta seu tipo
+pub fn detect_file<P: AsRef<Path>>(path: P) -> Result<Detection> {
+    let path = path.as_ref();
+    Ok(sniff(path, &read_head(path)?))
+}
+
+/// Primeiros bytes do arquivo, usados na detecção
+pub fn read_head(path: &Path) -> Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| pc_core::Error::Import(format!("Erro ao abrir arquivo: {}", e)))?;
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    file.by_ref()
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
+    Ok(head)
+}
+
+/// Detecta o tipo a partir do nome e dos primeiros bytes do arquivo
+pub fn sniff(path: &Path, head: &[u8]) -> Detection {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    // Sem o arquivo no disco (amostras em memória), o início é o arquivo inteiro
+    let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or(head.len() as u64);
+    if let Some(detection) = sniff_magic(head, len, &extension) {
+        return detection;
+    }
+
+    let text = decode(head);
+
+    if let Some(detection) = sniff_xml(&text) {
+        return detection;
+    }
+
+    if let Some(detection) = sniff_fixed_width(&text) {
+        return detection;
+    }
+
+    if let Some(detection) = sniff_email(&text) {
+        return detection;
+    }
+
+    if let Some(detection) = sniff_csv_header(&text) {
+        return detection;
+    }
+
+    sniff_name(path, &extension)
+}
+
+fn sniff_magic(head: &[u8], len: u64, extension: &str) -> Option<Detection> {
+    const OLE2: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+    if head.starts_with(b"%PDF-") {
+        return Some(Detection::new(FileKind::Pdf, 1.0, "Assinatura %PDF"));
+    }
+    if head.starts_with(b"\x89PNG\r\n\x1a\n")
+        || head.starts_with(&[0xFF, 0xD8, 0xFF])
+        || head.starts_with(b"GIF8")
+        || is_bmp(head, len)
+        || (head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP"))
+        || is_heif(head)
+    {
+        return Some(Detection::new(FileKind::Image, 1.0, "Assinatura de imagem"));
+    }
+    if head.starts_with(OLE2) {
+        return Some(Detection::new(FileKind::Excel, 0.9, "Documento OLE2 (Excel 97-2003)"));
+    }
+    if head.starts_with(b"PK\x03\x04") {
+        // XLSX é um ZIP com as planilhas em xl/
+        let has_xl = head.windows(3).any(|w| w == b"xl/");
+        if has_xl || extension == "xlsx" {
+            return Some(Detection::new(FileKind::Excel, if has_xl { 1.0 } else { 0.7 }, "Pacote OOXML (xlsx)"));
+        }
+        return Some(Detection::new(FileKind::Archive, 0.9, "Assinatura ZIP"));
+    }
+    if head.starts_with(b"PK\x05\x06") {
+        return Some(Detection::new(FileKind::Archive, 0.9, "ZIP vazio"));
+    }
+    None
+}
+
+/// Bitmap do Windows: "BM" sozinho também abre CSVs e textos, então o
+/// tamanho gravado no cabeçalho precisa bater com o do arquivo e o
+/// cabeçalho DIB precisa ter um dos tamanhos conhecidos
+fn is_bmp(head: &[u8], len: u64) -> bool {
+    let le32 = |at: usize| head.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    head.starts_with(b"BM")
+        && le32(2).is_some_and(|size| u64::from(size) == len)
+        && matches!(le32(14), Some(12 | 40 | 56 | 108 | 124))
+}
+
+/// Container ISO BMFF de imagem HEIF/HEIC (fotos de iPhone)
+fn is_heif(head: &[u8]) -> bool {
+    head.get(4..8) == Some(b"ftyp")
+        && matches!(
+            head.get(8..12),
+            Some(b"heic" | b"heix" | b"hevc" | b"heim" | b"heis" | b"mif1" | b"msf1")
+        )
+}
+
+fn sniff_xml(text: &str) -> Option<Detection> {
+    let root = xml_root_element(text)?;
+    let local = root.rsplit(':').next().unwrap_or(root);
+
+    match local {
+        "nfeProc" | "NFe" => Some(Detection::new(FileKind::NfeXml, 1.0, format!("XML com raiz <{}>", root))),
+        "cteProc" | "CTe" => Some(Detection::new(FileKind::CteXml, 1.0, format!("XML com raiz <{}>", root))),
+        _ => None,
+    }
+}
+
+/// Nome do primeiro elemento do XML, ignorando declaração e comentários
+fn xml_root_element(text: &str) -> Option<&str> {
+    let mut rest = text.trim_start_matches('\u{feff}').trim_start();
+    if !rest.starts_with('<') {
+        return None;
+    }
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("<?") {
+            rest = &after[after.find("?>")? + 2..];
+        } else if let Some(after) = rest.strip_prefix("<!--") {
+            rest = &after[after.find("-->")? + 3..];
+        } else if let Some(after) = rest.strip_prefix("<!") {
+            rest = &after[after.find('>')? + 1..];
+        } else if let Some(after) = rest.strip_prefix('<') {
+            let end = after.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+            return Some(&after[..end]);
+        } else {
+            return None;
+        }
+    }
+}
+
+fn sniff_fixed_width(text: &str) -> Option<Detection> {
+    if let Some((doc, version)) = edi::detect(text) {
+        let kind = match doc {
+            EdiDocumentType::Notfis => FileKind::Notfis,
+            EdiDocumentType::Ocoren => FileKind::Ocoren,
+            EdiDocumentType::Conemb => FileKind::Conemb,
+            EdiDocumentType::Doccob => FileKind::Doccob,
+        };
+        return Some(Detection::new(kind, 0.95, format!("Registros EDI PROCEDA {}", version.as_str())));
+    }
+
+    // AFD: cabeçalho com NSR 000000000 e tipo de registro 1
+    let first = text.lines().next()?;
+    if first.starts_with("0000000001") && first.len() >= 200 {
+        return Some(Detection::new(FileKind::Afd, 0.95, "Cabeçalho de AFD (NSR 000000000, tipo 1)"));
+    }
+
+    None
+}
+
+/// Cabeçalhos que identificam uma mensagem de e-mail
+const EMAIL_HEADERS: &[&str] = &[
+    "from",
+    "to",
+    "subject",
+    "date",
+    "message-id",
+    "mime-version",
+    "received",
+    "return-path",
+    "delivered-to",
+];
+
+/// Mensagem RFC 822 (.eml) ou caixa mbox (mensagens iniciadas por "From ")
+fn sniff_email(text: &str) -> Option<Detection> {
+    let mut lines = text.lines().peekable();
+    let mbox = lines.peek()?.starts_with("From ");
+    if mbox {
+        lines.next();
+    }
+
+    // Cabeçalhos até a primeira linha em branco; linhas de continuação começam com espaço
+    let known = lines
+        .take_while(|l| !l.trim().is_empty())
+        .filter(|l| !l.starts_with([' ', '\t']))
+        .filter_map(|l| l.split_once(':'))
+        .filter(|(name, _)| EMAIL_HEADERS.contains(&name.trim().to_lowercase().as_str()))
+        .count();
+
+    match (mbox, known) {
+        (true, 1..) => Some(Detection::new(FileKind::Email, 0.95, "Caixa de e-mail mbox")),
+        (false, 3..) => Some(Detection::new(
+            FileKind::Email,
+            0.9,
+            format!("Cabeçalhos de e-mail ({} reconhecidos)", known),
+        )),
+        _ => None,
+    }
+}
+
+/// Cabeçalhos característicos de cada planilha (normalizados)
+const FREIGHT_HEADERS: &[&str] = &[
+    "NUMERO",
+    "DATA DE AGENDAMENTO",
+    "DATA DE EMISSAO",
+    "NOTAS FISCAIS",
+    "PAGADOR DO FRETE - NOME",
+    "PAGADOR DO FRETE - FONE",
+    "REMETENTE - NOME",
+    "REMETENTE - CIDADE",
+    "DESTINATARIO - NOME",
+    "DESTINATARIO - CIDADE",
+    "SOMA DOS VOLUMES",
+    "SOMA DOS PESOS",
+    "SOMA DAS NOTAS",
+    "VALOR DO FRETE",
+    "FRETE TABELADO",
+    "FORMA DE PAGAMENTO",
+    "MINUTA/CTE",
+];
+
+const TIMESHEET_HEADERS: &[&str] = &["FUNCIONARIO", "MES", "DATA", "ENTRADA", "SAIDA"];
+
+pub(crate) fn sniff_csv_header(text: &str) -> Option<Detection> {
+    let header = text.lines().find(|l| !l.trim().is_empty())?;
+    let delimiter = csv_source::sniff_delimiter(text) as char;
+    if !header.contains(delimiter) {
+        return None;
+    }
+
+    let columns: Vec<String> = header
+        .split(delimiter)
+        .map(|c| text::normalize_text(c.trim().trim_matches('"').trim()))
+        .filter(|c| !c.is_empty())
+        .collect();
+
+    let score = |known: &[&str]| -> (usize, f32) {
+        let matched = known.iter().filter(|k| columns.iter().any(|c| c == *k)).count();
+        (matched, matched as f32 / known.len() as f32)
+    };
+
+    let (freight_matched, freight_score) = score(FREIGHT_HEADERS);
+    if freight_matched >= 3 {
+        return Some(Detection::new(
+            FileKind::FreightCsv,
+            0.5 + 0.5 * freight_score,
+            format!("Cabeçalho de planilha de fretes ({} colunas reconhecidas)", freight_matched),
+        ));
+    }
+
+    let (timesheet_matched, timesheet_score) = score(TIMESHEET_HEADERS);
+    if timesheet_matched >= 3 {
+        return Some(Detection::new(
+            FileKind::TimesheetCsv,
+            0.5 + 0.5 * timesheet_score,
+            format!("Cabeçalho de planilha de ponto ({} colunas reconhecidas)", timesheet_matched),
+        ));
+    }
+
+    // Planilha de rotas: uma coluna por região administrativa, ex. "Franca (RA13)"
+    let regions = columns.iter().filter(|c| c.contains("(RA")).count();
+    if regions >= 2 {
+        return Some(Detection::new(
+            FileKind::RouteCsv,
+            0.8,
+            format!("Cabeçalho com {} regiões administrativas", regions),
+        ));
+    }
+
+    None
+}
+
+/// Último recurso: extensão e padrões de nome usados nos arquivos da empresa
+fn sniff_name(path: &Path, extension: &str) -> Detection {
+    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+    match extension {
+        "pdf" => return Detection::new(FileKind::Pdf, 0.5, "Extensão .pdf"),
+        "zip" => return Detection::new(FileKind::Archive, 0.5, "Extensão .zip"),
+        "eml" | "mbox" | "mbx" => return Detection::new(FileKind::Email, 0.5, "Extensão de e-mail"),
+        "xlsx" | "xls" => return Detection::new(FileKind::Excel, 0.5, "Extensão de planilha Excel"),
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "heic" | "heif" => {
+            return Detection::new(FileKind::Image, 0.5, "Extensão de imagem")
+        }
+        _ => {}
+    }
+
+    if extension == "csv" {
+        if filename.contains("Horas") || filename.contains("Ponto") {
+            return Detection::new(FileKind::TimesheetCsv, 0.4, "Nome do arquivo (Horas/Ponto)");
+        }
+        if filename.contains("Rotas") {
+            return Detection::new(FileKind::RouteCsv, 0.4, "Nome do arquivo (Rotas)");
+        }
+        if filename.contains("-04") || filename.contains("Planilha") || filename.contains("PRE-EMBARQUE") {
+            return Detection::new(FileKind::FreightCsv, 0.3, "Nome do arquivo (planilha de fretes)");
+        }
+    }
+
+    Detection::unknown()
+}
+
+fn decode(head: &[u8]) -> String {
+    // O corte em SNIFF_LEN pode partir um caractere multibyte ao meio
+    let head = match std::str::from_utf8(head) {
+        Err(e) if e.error_len().is_none() => &head[..e.valid_up_to()],
+        _ => head,
+    };
+    csv_source::decode(head, csv_source::detect_encoding(head)).0
+}
+
+/// Importador escolhido pela detecção automática
+pub enum AnyImporter {
+    Freight(FreightOrderImporter),
+    Timesheet(TimesheetImporter),
+    Route(RouteImporter),
+    Notfis(NotfisImporter),
+    Afd(AfdImporter),
+    Photo(PhotoImporter),
+    Pdf(PdfImporter),
+    Excel(ExcelImporter),
+    Archive(ArchiveImporter),
+    FiscalXml(FiscalXmlImporter),
+}
+
+/// Dados produzidos por qualquer importador
+#[derive(Debug, Clone)]
+pub enum ImportOutput {
+    FreightOrders(Vec<FreightOrder>),
+    Timesheets(Vec<Timesheet>),
+    Routes(Vec<Route>),
+    Photos(Vec<PhotoMetadata>),
+    Pdfs(Vec<PdfMetadata>),
+    ExcelRows(Vec<ExcelRow>),
+    /// Arquivos guardados só como documento (XML de NF-e e CT-e)
+    Documents(Vec<Document>),
+    /// Arquivos extraídos de um ZIP ou e-mail, cada um com sua saída
+    Archive(ArchiveContents),
+}
+
+impl ImportOutput {
+    /// Quantidade de registros importados
+    pub fn len(&self) -> usize {
+        match self {
+            ImportOutput::FreightOrders(v) => v.len(),
+            ImportOutput::Timesheets(v) => v.len(),
+            ImportOutput::Routes(v) => v.len(),
+            ImportOutput::Photos(v) => v.len(),
+            ImportOutput::Pdfs(v) => v.len(),
+            ImportOutput::ExcelRows(v) => v.len(),
+            ImportOutput::Documents(v) => v.len(),
+            ImportOutput::Archive(contents) => contents.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Nome da entidade produzida (para relatórios)
+    pub fn entity_name(&self) -> &'static str {
+        match self {
+            ImportOutput::FreightOrders(_) => "ordens de frete",
+            ImportOutput::Timesheets(_) => "registros de ponto",
+            ImportOutput::Routes(_) => "rotas",
+            ImportOutput::Photos(_) => "fotos",
+            ImportOutput::Pdfs(_) => "PDFs",
+            ImportOutput::ExcelRows(_) => "linhas de planilha",
+            ImportOutput::Documents(_) => "documentos",
+            ImportOutput::Archive(_) => "registros de arquivos extraídos",
+        }
+    }
+
+    /// Fotos e PDFs como documentos (None para as demais saídas)
+    pub fn to_documents(&self) -> Option<Vec<Document>> {
+        match self {
+            ImportOutput::Photos(photos) => {
+                let importer = PhotoImporter::new();
+                Some(photos.iter().map(|p| importer.to_document(p)).collect())
+            }
+            ImportOutput::Pdfs(pdfs) => {
+                let importer = PdfImporter::new();
+                Some(pdfs.iter().map(|p| importer.to_document(p)).collect())
+            }
+            ImportOutput::Documents(documents) => Some(documents.clone()),
+            _ => None,
+        }
+    }
+
+    /// Registra a procedência de um arquivo extraído de ZIP ou e-mail
+    ///
+    /// Fotos e PDFs viram documentos, que passam a apontar para o caminho
+    /// dentro do contêiner.
+    pub fn with_origem(self, origem: &Provenance) -> Self {
+        match self {
+            ImportOutput::FreightOrders(mut orders) => {
+                orders.iter_mut().for_each(|o| o.origem = Some(origem.clone()));
+                ImportOutput::FreightOrders(orders)
+            }
+            ImportOutput::Timesheets(mut timesheets) => {
+                timesheets.iter_mut().for_each(|t| t.origem = Some(origem.clone()));
+                ImportOutput::Timesheets(timesheets)
+            }
+            ImportOutput::Routes(mut routes) => {
+                routes.iter_mut().for_each(|r| r.origem = Some(origem.clone()));
+                ImportOutput::Routes(routes)
+            }
+            output @ (ImportOutput::Photos(_) | ImportOutput::Pdfs(_) | ImportOutput::Documents(_)) => {
+                let mut documents = output.to_documents().unwrap_or_default();
+                for document in &mut documents {
+                    document.caminho_origem = origem.caminho.clone();
+                    document.locais = vec![origem.caminho.clone()];
+                    document.origens = vec![origem.clone()];
+                }
+                ImportOutput::Documents(documents)
+            }
+            other => other,
+        }
+    }
+}
+
+impl AnyImporter {
+    /// Importador para o tipo detectado (None para tipos sem importador)
+    pub fn for_kind(kind: FileKind) -> Option<Self> {
+        match kind {
+            FileKind::FreightCsv => Some(AnyImporter::Freight(FreightOrderImporter::new())),
+            FileKind::TimesheetCsv => Some(AnyImporter::Timesheet(TimesheetImporter::new())),
+            FileKind::RouteCsv => Some(AnyImporter::Route(RouteImporter::new())),
+            FileKind::Notfis => Some(AnyImporter::Notfis(NotfisImporter::new())),
+            FileKind::Afd => Some(AnyImporter::Afd(AfdImporter::new())),
+            FileKind::Image => Some(AnyImporter::Photo(PhotoImporter::new())),
+            FileKind::Pdf => Some(AnyImporter::Pdf(PdfImporter::new())),
+            FileKind::Excel => Some(AnyImporter::Excel(ExcelImporter::new())),
+            FileKind::Archive | FileKind::Email => Some(AnyImporter::Archive(ArchiveImporter::new())),
+            FileKind::NfeXml | FileKind::CteXml => Some(AnyImporter::FiscalXml(FiscalXmlImporter::new())),
+            FileKind::Ocoren | FileKind::Conemb | FileKind::Doccob | FileKind::Unknown => None,
+        }
+    }
+
+    /// Nome do importador, como aceito em `pc import --type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnyImporter::Freight(_) => "freight",
+            AnyImporter::Timesheet(_) => "timesheet",
+            AnyImporter::Route(_) => "route",
+            AnyImporter::Notfis(_) => "notfis",
+            AnyImporter::Afd(_) => "afd",
+            AnyImporter::Photo(_) => "photo",
+            AnyImporter::Pdf(_) => "pdf",
+            AnyImporter::Excel(_) => "excel",
+            AnyImporter::Archive(_) => "archive",
+            AnyImporter::FiscalXml(_) => "xml",
+        }
+    }
+
+    /// Repassa ajustes de leitura aos importadores de CSV
+    pub fn with_csv_options(self, options: CsvOptions) -> Self {
+        match self {
+            AnyImporter::Freight(i) => AnyImporter::Freight(i.with_csv_options(options)),
+            AnyImporter::Timesheet(i) => AnyImporter::Timesheet(i.with_csv_options(options)),
+            AnyImporter::Route(i) => AnyImporter::Route(i.with_csv_options(options)),
+            AnyImporter::Archive(i) => AnyImporter::Archive(i.with_csv_options(options)),
+            other => other,
+        }
+    }
+
+    /// Repassa a lista de municípios às planilhas de rotas
+    pub fn with_municipalities(self, municipalities: Arc<Municipalities>) -> Self {
+        match self {
+            AnyImporter::Route(i) => AnyImporter::Route(i.with_municipalities(municipalities)),
+            AnyImporter::Archive(i) => AnyImporter::Archive(i.with_municipalities(municipalities)),
+            other => other,
+        }
+    }
+
+    /// Executa a importação do arquivo
+    pub fn run<P: AsRef<Path>>(&self, path: P) -> Result<ImportOutput> {
+        let path = path.as_ref();
+        match self {
+            AnyImporter::Freight(i) => i.import_file(path).map(ImportOutput::FreightOrders),
+            AnyImporter::Timesheet(i) => i.import_file(path).map(ImportOutput::Timesheets),
+            AnyImporter::Route(i) => i.import_file(path).map(ImportOutput::Routes),
+            AnyImporter::Notfis(i) => i.import_file(path).map(ImportOutput::FreightOrders),
+            AnyImporter::Afd(i) => i.import_file(path).map(ImportOutput::Timesheets),
+            AnyImporter::Photo(i) => i.import_photo(path).map(|m| ImportOutput::Photos(vec![m])),
+            AnyImporter::Pdf(i) => i.import_pdf(path).map(|m| ImportOutput::Pdfs(vec![m])),
+            AnyImporter::Excel(i) => i.import(path),
+            AnyImporter::Archive(i) => i.import(path).map(ImportOutput::Archive),
+            AnyImporter::FiscalXml(i) => i.import(path).map(|d| ImportOutput::Documents(vec![d])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_magic_bytes() {
+        let pdf = sniff(Path::new("documento.bin"), b"%PDF-1.7\n...");
+        assert_eq!(pdf.kind, FileKind::Pdf);
+        assert_eq!(pdf.confidence, 1.0);
+
+        let png = sniff(Path::new("foto"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+        assert_eq!(png.kind, FileKind::Image);
+    }
+
+    #[test]
+    fn test_sniff_bmp_header() {
+        // Cabeçalho de 14 bytes + BITMAPINFOHEADER (40) + um pixel
+        let mut bmp = b"BM".to_vec();
+        bmp.extend(58u32.to_le_bytes());
+        bmp.extend([0, 0, 0, 0]);
+        bmp.extend(54u32.to_le_bytes());
+        bmp.extend(40u32.to_le_bytes());
+        bmp.resize(58, 0);
+        assert_eq!(sniff(Path::new("canhoto"), &bmp).kind, FileKind::Image);
+
+        bmp.truncate(57);
+        assert_ne!(sniff(Path::new("canhoto"), &bmp).kind, FileKind::Image);
+
+        let csv = "BM;Filial;Valor
+001;Ribeirão Preto;1.250,50
+";
+        assert_ne!(sniff(Path::new("export.csv"), csv.as_bytes()).kind, FileKind::Image);
+    }
+
+    #[test]
+    fn test_sniff_xml_root() {
+        let xml = "\u{feff}<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!-- CT-e -->\n<cteProc xmlns=\"http://www.portalfiscal.inf.br/cte\" versao=\"4.00\"><CTe>";
+        assert_eq!(sniff(Path::new("a.xml"), xml.as_bytes()).kind, FileKind::CteXml);
+
+        let xml = "<nfe:nfeProc xmlns:nfe=\"http://www.portalfiscal.inf.br/nfe\">";
+        assert_eq!(sniff(Path::new("a.xml"), xml.as_bytes()).kind, FileKind::NfeXml);
+    }
+
+    #[test]
+    fn test_sniff_csv_headers() {
+        let freight = "Número;Data de Agendamento;Data de Emissão;Notas Fiscais;Pagador do Frete - Nome;Remetente - Nome\n288415;01/04/2025";
+        let detection = sniff(Path::new("qualquer.csv"), freight.as_bytes());
+        assert_eq!(detection.kind, FileKind::FreightCsv);
+        assert!(detection.confidence > 0.5);
+
+        let timesheet = "funcionario,mes,data,entrada,saida\nJoão,Abril,01/04/2025,08:00,17:00";
+        assert_eq!(sniff(Path::new("x.csv"), timesheet.as_bytes()).kind, FileKind::TimesheetCsv);
+
+        let routes = "Ribeirão Preto (RA15);Franca (RA13);Central (RA14)\nSertãozinho;Batatais;Matão";
+        assert_eq!(sniff(Path::new("x.csv"), routes.as_bytes()).kind, FileKind::RouteCsv);
+    }
+
+    #[test]
+    fn test_sniff_fixed_width() {
+        let notfis = include_str!("../tests/fixtures/edi/NOTFIS_50.txt");
+        assert_eq!(sniff(Path::new("x.txt"), notfis.as_bytes()).kind, FileKind::Notfis);
+
+        let afd = include_str!("../tests/fixtures/afd/AFD_671.txt");
+        assert_eq!(sniff(Path::new("x.txt"), afd.as_bytes()).kind, FileKind::Afd);
+    }
+
+    #[test]
+    fn test_sniff_name_fallback() {
+        let detection = sniff(Path::new("Horas abr.csv"), b"");
+        assert_eq!(detection.kind, FileKind::TimesheetCsv);
+        assert!(detection.confidence < 0.5);
+
+        assert_eq!(sniff(Path::new("notas.txt"), b"qualquer coisa").kind, FileKind::Unknown);
+    }
+
+    #[test]
+    fn test_sniff_zip_and_email() {
+        assert_eq!(sniff(Path::new("lote.bin"), b"PK\x03\x04\x14\0\0\0nfe.xml").kind, FileKind::Archive);
+        assert_eq!(sniff(Path::new("planilha.xlsx"), b"PK\x03\x04\x14\0\0\0xl/workbook.xml").kind, FileKind::Excel);
+
+        let eml = "Return-Path: <nfe@sefaz.example>\nFrom: nfe@sefaz.example\nSubject: NF-e\n\tcontinuação\nDate: Tue, 15 Apr 2025 10:00:00 -0300\n\ncorpo";
+        assert_eq!(sniff(Path::new("mensagem"), eml.as_bytes()).kind, FileKind::Email);
+        let mbox = "From nfe@sefaz.example Tue Apr 15 10:00:00 2025\nFrom: nfe@sefaz.example\n\ncorpo";
+        assert_eq!(sniff(Path::new("caixa"), mbox.as_bytes()).kind, FileKind::Email);
+    }
+}
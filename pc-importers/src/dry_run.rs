@@ -0,0 +1,142 @@
+//! Simulação de importação
+//!
+//! Lê o arquivo inteiro e calcula, contra o banco atual, quais registros
+//! seriam criados, quais mudariam (campo a campo) e quais linhas seriam
+//! rejeitadas, sem gravar nada.
+
+use crate::detect::{AnyImporter, ImportOutput};
+use crate::streaming::{RejectedRow, StreamedRow, StreamingImporter};
+use pc_core::{NaturalKey, Result};
+use pc_db::{ChangedEntity, PersonalControllerDb, Repository, UpsertDiff};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+/// Resultado da simulação de um arquivo
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DryRunReport {
+    /// Collection de destino; `None` quando o tipo ainda não é gravado no banco
+    pub collection: Option<String>,
+    /// Registros válidos lidos do arquivo
+    pub records: usize,
+    pub new: Vec<String>,
+    pub changed: Vec<ChangedEntity>,
+    pub unchanged: Vec<String>,
+    /// Linhas que não viraram registro ou que falhariam na validação
+    pub rejected: Vec<RejectedRow>,
+}
+
+impl DryRunReport {
+    fn from_diff(collection: &str, diff: UpsertDiff, mut rejected: Vec<RejectedRow>) -> Self {
+        let records = diff.new.len() + diff.changed.len() + diff.unchanged.len();
+        rejected.extend(diff.invalid.into_iter().map(|invalid| RejectedRow {
+            line: 0,
+            reason: format!("{}: {}", invalid.key, invalid.error),
+        }));
+
+        Self {
+            collection: Some(collection.to_string()),
+            records,
+            new: diff.new,
+            changed: diff.changed,
+            unchanged: diff.unchanged,
+            rejected,
+        }
+    }
+}
+
+/// Simula a importação de um importador em streaming
+pub async fn dry_run<I, T, P>(importer: &I, path: P, repository: &Repository<T>) -> Result<DryRunReport>
+where
+    I: StreamingImporter<Output = T>,
+    T: NaturalKey + Clone + Serialize + DeserializeOwned + Send + Sync,
+    P: AsRef<Path>,
+{
+    let mut records = Vec::new();
+    let mut rejected = Vec::new();
+
+    for row in importer.stream_rows(path)? {
+        match row? {
+            StreamedRow::Record(streamed) => records.push(streamed.record),
+            StreamedRow::Rejected(row) => rejected.push(row),
+        }
+    }
+
+    let diff = repository.diff_many(records).await?;
+    Ok(DryRunReport::from_diff(repository.collection_name(), diff, rejected))
+}
+
+/// Simula a importação com qualquer importador
+pub async fn dry_run_any<P: AsRef<Path>>(
+    importer: AnyImporter,
+    path: P,
+    db: &PersonalControllerDb,
+) -> Result<DryRunReport> {
+    let path = path.as_ref();
+    match importer {
+        AnyImporter::Freight(importer) => dry_run(&importer, path, &db.freight_orders).await,
+        AnyImporter::Timesheet(importer) => dry_run(&importer, path, &db.timesheets).await,
+        AnyImporter::Afd(importer) => dry_run(&importer, path, &db.timesheets).await,
+        importer => dry_run_output(importer.run(path)?, db).await,
+    }
+}
+
+/// Simula a gravação de um arquivo já lido
+pub async fn dry_run_output(output: ImportOutput, db: &PersonalControllerDb) -> Result<DryRunReport> {
+    let (collection, diff) = match output {
+        ImportOutput::FreightOrders(orders) => ("freight_orders", db.freight_orders.diff_many(orders).await?),
+        ImportOutput::Timesheets(timesheets) => ("timesheets", db.timesheets.diff_many(timesheets).await?),
+        ImportOutput::Routes(routes) => ("routes", db.routes.diff_many(routes).await?),
+        ImportOutput::Photos(_) | ImportOutput::Pdfs(_) | ImportOutput::Documents(_) => {
+            let documents = output.to_documents().unwrap_or_default();
+            ("documents", db.documents.diff_many(documents).await?)
+        }
+        // Planilhas ainda não têm collection própria
+        other => {
+            return Ok(DryRunReport {
+                records: other.len(),
+                ..DryRunReport::default()
+            })
+        }
+    };
+    Ok(DryRunReport::from_diff(collection, diff, Vec::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timesheet_importer::TimesheetImporter;
+    use pc_models::Timesheet;
+
+    #[tokio::test]
+    async fn test_dry_run_reports_diff_and_rejections() {
+        let path = std::env::temp_dir().join(format!("Horas-{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "funcionario;mes;data;entrada;saida\n\
+             João;Abril;01/04/2025;08:00;17:00\n\
+             João;Abril;02/04/2025;08:00;18:00\n\
+             João;Abril;03/04/2025;--;17:00\n\
+             ;;;;\n",
+        )
+        .unwrap();
+
+        let repository = Repository::<Timesheet>::new("timesheets");
+        let importer = TimesheetImporter::new();
+        let first: Vec<Timesheet> = importer.stream(&path).unwrap().map(|r| r.unwrap().record).collect();
+        let mut existing = first[1].clone();
+        existing.saida = chrono::NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+        repository.upsert_many(vec![first[0].clone(), existing]).await.unwrap();
+
+        let report = dry_run(&importer, &path, &repository).await.unwrap();
+
+        assert!(report.new.is_empty());
+        assert_eq!(report.unchanged.len(), 1);
+        assert_eq!(report.changed.len(), 1);
+        assert!(report.changed[0].changes.iter().any(|c| c.field == "saida"));
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 4);
+        assert_eq!(repository.count().await.unwrap(), 2);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
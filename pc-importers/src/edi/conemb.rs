@@ -0,0 +1,340 @@
+//! CONEMB - conhecimentos embarcados
+//!
+//! Layout 3.1: registros 000, 320, 321 (transportadora), 322 (conhecimento,
+//! com até 40 notas fiscais), 323 (totais). Tamanho 680.
+//!
+//! Layout 5.0: registros 000, 520, 521 (transportadora), 522 (conhecimento),
+//! 523 (valores), 524 (notas fiscais), 529 (totais). Tamanho 350.
+
+use super::{join_records, EdiDocumentType, EdiVersion, InterchangeHeader, RecordWriter, Transportadora};
+use chrono::NaiveDate;
+use pc_core::Money;
+use pc_models::FreightOrder;
+
+pub const RECORD_WIDTH_V31: usize = 680;
+pub const RECORD_WIDTH_V50: usize = 350;
+
+/// Quantidade máxima de notas no registro 322 do layout 3.1
+pub const MAX_NOTAS_V31: usize = 40;
+
+/// Conhecimento de transporte a ser informado ao embarcador
+#[derive(Debug, Clone)]
+pub struct Conhecimento {
+    pub filial_emissora: String,
+    pub serie: String,
+    pub numero: String,
+    pub chave_cte: Option<String>,
+    pub data_emissao: NaiveDate,
+    /// 'C' = CIF, 'F' = FOB
+    pub condicao_frete: char,
+    pub cnpj_remetente: String,
+    pub cnpj_destinatario: String,
+    pub cnpj_devedor: String,
+    pub volumes: f64,
+    pub peso: f64,
+    pub valor_frete: Money,
+    pub base_icms: Money,
+    /// Alíquota em %
+    pub aliquota_icms: f64,
+    pub valor_icms: Money,
+    pub frete_peso: Money,
+    pub frete_valor: Money,
+    pub pedagio: Money,
+    pub notas_fiscais: Vec<String>,
+    pub valor_notas: Money,
+}
+
+impl Conhecimento {
+    /// Monta o conhecimento a partir de uma ordem de frete com CT-e emitido
+    ///
+    /// Os CNPJs das partes não fazem parte da ordem e devem ser preenchidos
+    /// pelo chamador.
+    pub fn from_order(order: &FreightOrder) -> Option<Self> {
+        let numero = order.cte_numero.clone()?;
+
+        Some(Self {
+            filial_emissora: order.filial_coleta.clone().unwrap_or_default(),
+            serie: String::new(),
+            numero,
+            chave_cte: order.cte_chave.clone(),
+            data_emissao: order.data_emissao,
+            condicao_frete: if order.forma_pagamento.as_deref() == Some("FOB") { 'F' } else { 'C' },
+            cnpj_remetente: String::new(),
+            cnpj_destinatario: String::new(),
+            cnpj_devedor: String::new(),
+            volumes: order.volumes as f64,
+            peso: order.peso,
+            valor_frete: order.valor_frete,
+            base_icms: Money::ZERO,
+            aliquota_icms: 0.0,
+            valor_icms: Money::ZERO,
+            frete_peso: order.frete_tabelado.unwrap_or(order.valor_frete),
+            frete_valor: Money::ZERO,
+            pedagio: Money::ZERO,
+            notas_fiscais: order.notas_fiscais.clone(),
+            valor_notas: order.valor_notas,
+        })
+    }
+}
+
+/// Gerador de arquivos CONEMB
+pub struct ConembWriter {
+    version: EdiVersion,
+}
+
+impl ConembWriter {
+    pub fn new(version: EdiVersion) -> Self {
+        Self { version }
+    }
+
+    pub fn write(
+        &self,
+        header: &InterchangeHeader,
+        transportadora: &Transportadora,
+        conhecimentos: &[Conhecimento],
+    ) -> String {
+        let doc = EdiDocumentType::Conemb;
+        let total: Money = conhecimentos.iter().map(|c| c.valor_frete).sum();
+        let mut records = Vec::new();
+
+        match self.version {
+            EdiVersion::V31 => {
+                let width = RECORD_WIDTH_V31;
+                records.push(header.write(doc, width));
+                records.push(RecordWriter::new("320", width).alpha(&header.document_id(doc), 14).finish());
+                records.push(
+                    RecordWriter::new("321", width)
+                        .digits(&transportadora.cnpj, 14)
+                        .alpha(&transportadora.razao_social, 40)
+                        .finish(),
+                );
+
+                for cte in conhecimentos {
+                    if cte.notas_fiscais.len() > MAX_NOTAS_V31 {
+                        tracing::warn!(
+                            "CT-e {} tem {} notas; apenas {} cabem no CONEMB 3.1",
+                            cte.numero,
+                            cte.notas_fiscais.len(),
+                            MAX_NOTAS_V31
+                        );
+                    }
+
+                    let mut record = RecordWriter::new("322", width)
+                        .alpha(&cte.filial_emissora, 10)
+                        .alpha(&cte.serie, 5)
+                        .digits(&cte.numero, 12)
+                        .date(Some(cte.data_emissao))
+                        .raw(&cte.condicao_frete.to_string(), 1)
+                        .decimal(cte.peso, 7, 2)
+                        .money(cte.valor_frete, 15)
+                        .money(cte.base_icms, 15)
+                        .decimal(cte.aliquota_icms, 4, 2)
+                        .money(cte.valor_icms, 15)
+                        .money(cte.frete_peso, 15)
+                        .money(cte.frete_valor, 15)
+                        .num(0, 15) // SEC/CAT
+                        .num(0, 15) // ITR
+                        .num(0, 15) // Despacho
+                        .money(cte.pedagio, 15)
+                        .num(0, 15) // ADEME
+                        .raw("2", 1) // Sem substituição tributária
+                        .raw("", 3)
+                        .digits(&cte.cnpj_remetente, 14)
+                        .digits(&cte.cnpj_destinatario, 14);
+
+                    for idx in 0..MAX_NOTAS_V31 {
+                        record = match cte.notas_fiscais.get(idx) {
+                            Some(nota) => record.raw("", 3).digits(nota, 8),
+                            None => record.raw("", 11),
+                        };
+                    }
+
+                    records.push(record.raw("I", 1).raw("N", 1).finish());
+                }
+
+                records.push(
+                    RecordWriter::new("323", width)
+                        .num(conhecimentos.len() as i64, 4)
+                        .money(total, 15)
+                        .finish(),
+                );
+            }
+            EdiVersion::V50 => {
+                let width = RECORD_WIDTH_V50;
+                records.push(header.write(doc, width));
+                records.push(RecordWriter::new("520", width).alpha(&header.document_id(doc), 14).finish());
+                records.push(
+                    RecordWriter::new("521", width)
+                        .digits(&transportadora.cnpj, 14)
+                        .alpha(&transportadora.razao_social, 50)
+                        .finish(),
+                );
+
+                for cte in conhecimentos {
+                    records.push(
+                        RecordWriter::new("522", width)
+                            .alpha(&cte.filial_emissora, 10)
+                            .alpha(&cte.serie, 5)
+                            .digits(&cte.numero, 12)
+                            .date(Some(cte.data_emissao))
+                            .raw(&cte.condicao_frete.to_string(), 1)
+                            .digits(&cte.cnpj_remetente, 14)
+                            .digits(&cte.cnpj_destinatario, 14)
+                            .digits(&cte.cnpj_devedor, 14)
+                            .alpha(cte.chave_cte.as_deref().unwrap_or(""), 44)
+                            .raw("I", 1)
+                            .raw("N", 1)
+                            .finish(),
+                    );
+                    records.push(
+                        RecordWriter::new("523", width)
+                            .decimal(cte.volumes, 8, 2)
+                            .decimal(cte.peso, 9, 2)
+                            .money(cte.valor_frete, 15)
+                            .money(cte.base_icms, 15)
+                            .decimal(cte.aliquota_icms, 4, 2)
+                            .money(cte.valor_icms, 15)
+                            .money(cte.frete_peso, 15)
+                            .money(cte.frete_valor, 15)
+                            .money(cte.pedagio, 15)
+                            .finish(),
+                    );
+
+                    // O valor individual só é conhecido quando há uma única nota
+                    let valor_nota = if cte.notas_fiscais.len() == 1 { cte.valor_notas } else { Money::ZERO };
+                    for nota in &cte.notas_fiscais {
+                        records.push(
+                            RecordWriter::new("524", width)
+                                .digits(&cte.cnpj_remetente, 14)
+                                .raw("", 3)
+                                .digits(nota, 9)
+                                .date(None)
+                                .money(valor_nota, 15)
+                                .finish(),
+                        );
+                    }
+                }
+
+                records.push(
+                    RecordWriter::new("529", width)
+                        .num(conhecimentos.len() as i64, 4)
+                        .money(total, 15)
+                        .finish(),
+                );
+            }
+        }
+
+        join_records(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (InterchangeHeader, Transportadora, Vec<Conhecimento>) {
+        let header = InterchangeHeader::new(
+            "AVILA TRANSPORTES",
+            "FENIOR INDUSTRIA",
+            NaiveDate::from_ymd_opt(2025, 4, 10).unwrap().and_hms_opt(14, 30, 0).unwrap(),
+        );
+        let transportadora = Transportadora::new("12.345.678/0001-95", "Ávila Transportes Ltda");
+
+        let mut order = FreightOrder::new(
+            "288415".into(),
+            NaiveDate::from_ymd_opt(2025, 4, 7).unwrap(),
+            "FENIOR".into(),
+            "FENIOR".into(),
+            "Ribeirão Preto".into(),
+            "Comercial Franca".into(),
+            "Franca".into(),
+        );
+        order.cte_numero = Some("4521".into());
+        order.notas_fiscais = vec!["123456".into(), "123457".into()];
+        order.volumes = 12;
+        order.peso = 350.5;
+        order.valor_notas = Money::from_cents(458_090);
+        order.valor_frete = Money::from_cents(18_540);
+
+        let mut cte = Conhecimento::from_order(&order).unwrap();
+        cte.cnpj_remetente = "11222333000181".into();
+        cte.cnpj_destinatario = "44555666000172".into();
+        cte.cnpj_devedor = cte.cnpj_remetente.clone();
+
+        (header, transportadora, vec![cte])
+    }
+
+    /// Confere campos pelas posições do manual PROCEDA (de/até, inclusivas e a partir
+    /// de 1), recortando a linha sem passar pelo leitor de registros
+    fn assert_positions(line: &str, campos: &[(usize, usize, &str)]) {
+        for &(de, ate, esperado) in campos {
+            assert_eq!(&line[de - 1..ate], esperado, "registro {}, posições {:03}-{:03}", &line[..3], de, ate);
+        }
+    }
+
+    #[test]
+    fn test_from_order_requires_cte() {
+        let order = FreightOrder::new(
+            "1".into(),
+            NaiveDate::from_ymd_opt(2025, 4, 7).unwrap(),
+            "P".into(),
+            "R".into(),
+            "C".into(),
+            "D".into(),
+            "C".into(),
+        );
+        assert!(Conhecimento::from_order(&order).is_none());
+    }
+
+    #[test]
+    fn test_write_conemb_31() {
+        let (header, transportadora, ctes) = sample();
+        let content = ConembWriter::new(EdiVersion::V31).write(&header, &transportadora, &ctes);
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == RECORD_WIDTH_V31));
+
+        assert_positions(lines[0], &[(1, 3, "000"), (84, 95, "CON100414300")]);
+        assert_positions(lines[1], &[(1, 3, "320"), (4, 17, "CONHE100414300")]);
+        assert_positions(lines[2], &[(1, 3, "321"), (4, 17, "12345678000195")]);
+
+        // Registro 322, posições do manual 3.1
+        assert_positions(lines[3], &[
+            (1, 3, "322"),
+            (4, 13, "          "),          // filial emissora
+            (14, 18, "     "),              // série do conhecimento
+            (19, 30, "000000004521"),       // número do conhecimento
+            (31, 38, "07042025"),           // data de emissão
+            (39, 39, "C"),                  // condição de frete (CIF)
+            (40, 46, "0035050"),            // peso 5,2
+            (47, 61, "000000000018540"),    // valor total do frete 13,2
+            (62, 76, "000000000000000"),    // base de cálculo do ICMS
+            (77, 80, "0000"),               // alíquota do ICMS 2,2
+            (81, 95, "000000000000000"),    // valor do ICMS
+            (96, 110, "000000000018540"),   // frete peso
+            (171, 185, "000000000000000"),  // pedágio
+            (201, 201, "2"),                // sem substituição tributária
+            (233, 235, "   "),              // série da 1ª nota
+            (236, 243, "00123456"),         // número da 1ª nota
+            (247, 254, "00123457"),         // número da 2ª nota
+            (673, 673, "I"),                // ação do documento (inclusão)
+            (674, 674, "N"),                // tipo do conhecimento (normal)
+        ]);
+        assert_positions(lines[4], &[(1, 3, "323"), (4, 7, "0001"), (8, 22, "000000000018540")]);
+    }
+
+    #[test]
+    fn test_write_conemb_50() {
+        let (header, transportadora, ctes) = sample();
+        let content = ConembWriter::new(EdiVersion::V50).write(&header, &transportadora, &ctes);
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == RECORD_WIDTH_V50));
+
+        let ids: Vec<&str> = lines.iter().map(|l| &l[..3]).collect();
+        assert_eq!(ids, ["000", "520", "521", "522", "523", "524", "524", "529"]);
+        assert_positions(lines[2], &[
+            (4, 17, "12345678000195"),
+            (18, 67, &format!("{:<50}", "AVILA TRANSPORTES LTDA")),
+        ]);
+        assert_positions(lines[3], &[(19, 30, "000000004521"), (31, 38, "07042025"), (39, 39, "C")]);
+    }
+}
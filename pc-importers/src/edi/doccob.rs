@@ -0,0 +1,305 @@
+//! DOCCOB - documentos de cobrança (faturas de frete)
+//!
+//! Layout 3.1: registros 000, 350, 351 (transportadora), 352 (documento),
+//! 353 (conhecimentos), 354 (notas fiscais), 355 (totais). Tamanho 170.
+//!
+//! Layout 5.0: registros 000, 550, 551 (transportadora), 552 (documento),
+//! 553 (conhecimentos), 555 (notas fiscais), 559 (totais). Tamanho 280.
+
+use super::{join_records, Conhecimento, EdiDocumentType, EdiVersion, InterchangeHeader, RecordWriter, Transportadora};
+use chrono::NaiveDate;
+use pc_core::Money;
+use pc_models::FreightOrder;
+
+pub const RECORD_WIDTH_V31: usize = 170;
+pub const RECORD_WIDTH_V50: usize = 280;
+
+/// Conta bancária para cobrança
+#[derive(Debug, Clone, Default)]
+pub struct ContaCobranca {
+    pub banco_codigo: String,
+    pub banco_nome: String,
+    pub agencia: String,
+    pub agencia_digito: String,
+    pub conta: String,
+    pub conta_digito: String,
+}
+
+/// Documento de cobrança (fatura) agrupando conhecimentos
+#[derive(Debug, Clone)]
+pub struct DocumentoCobranca {
+    pub filial_emissora: String,
+    /// 0 = fatura, 1 = nota fiscal de serviço
+    pub tipo: u8,
+    pub serie: String,
+    pub numero: String,
+    pub data_emissao: NaiveDate,
+    pub data_vencimento: NaiveDate,
+    pub valor: Money,
+    /// "BCO" (banco) ou "CAR" (carteira)
+    pub tipo_cobranca: String,
+    pub percentual_multa: f64,
+    pub juros_dia: Money,
+    pub conta: Option<ContaCobranca>,
+    pub conhecimentos: Vec<Conhecimento>,
+}
+
+impl DocumentoCobranca {
+    /// Agrupa em uma fatura as ordens de frete com CT-e emitido
+    pub fn from_orders(
+        numero: impl Into<String>,
+        data_emissao: NaiveDate,
+        data_vencimento: NaiveDate,
+        orders: &[FreightOrder],
+    ) -> Self {
+        let conhecimentos: Vec<Conhecimento> = orders.iter().filter_map(Conhecimento::from_order).collect();
+        let valor = conhecimentos.iter().map(|c| c.valor_frete).sum();
+
+        Self {
+            filial_emissora: String::new(),
+            tipo: 0,
+            serie: String::new(),
+            numero: numero.into(),
+            data_emissao,
+            data_vencimento,
+            valor,
+            tipo_cobranca: "BCO".to_string(),
+            percentual_multa: 0.0,
+            juros_dia: Money::ZERO,
+            conta: None,
+            conhecimentos,
+        }
+    }
+}
+
+/// Gerador de arquivos DOCCOB
+pub struct DoccobWriter {
+    version: EdiVersion,
+}
+
+impl DoccobWriter {
+    pub fn new(version: EdiVersion) -> Self {
+        Self { version }
+    }
+
+    pub fn write(
+        &self,
+        header: &InterchangeHeader,
+        transportadora: &Transportadora,
+        documentos: &[DocumentoCobranca],
+    ) -> String {
+        let doc = EdiDocumentType::Doccob;
+        let total: Money = documentos.iter().map(|d| d.valor).sum();
+        let (width, ids, razao_len) = match self.version {
+            EdiVersion::V31 => (RECORD_WIDTH_V31, ["350", "351", "352", "353", "354", "355"], 40),
+            EdiVersion::V50 => (RECORD_WIDTH_V50, ["550", "551", "552", "553", "555", "559"], 50),
+        };
+
+        let mut records = vec![
+            header.write(doc, width),
+            RecordWriter::new(ids[0], width).alpha(&header.document_id(doc), 14).finish(),
+            RecordWriter::new(ids[1], width)
+                .digits(&transportadora.cnpj, 14)
+                .alpha(&transportadora.razao_social, razao_len)
+                .finish(),
+        ];
+
+        for documento in documentos {
+            let conta = documento.conta.clone().unwrap_or_default();
+            let mut record = RecordWriter::new(ids[2], width)
+                .alpha(&documento.filial_emissora, 10)
+                .num(documento.tipo as i64, 1)
+                .alpha(&documento.serie, 3)
+                .digits(&documento.numero, 10)
+                .date(Some(documento.data_emissao))
+                .date(Some(documento.data_vencimento))
+                .money(documento.valor, 15)
+                .alpha(&documento.tipo_cobranca, 3)
+                .decimal(documento.percentual_multa, 4, 2)
+                .money(documento.juros_dia, 15)
+                .date(None)
+                .num(0, 15);
+
+            if self.version == EdiVersion::V50 {
+                record = record.digits(&conta.banco_codigo, 3);
+            }
+
+            records.push(
+                record
+                    .alpha(&conta.banco_nome, 35)
+                    .digits(&conta.agencia, 4)
+                    .alpha(&conta.agencia_digito, 1)
+                    .digits(&conta.conta, 10)
+                    .alpha(&conta.conta_digito, 2)
+                    .raw("I", 1)
+                    .finish(),
+            );
+
+            for cte in &documento.conhecimentos {
+                let mut record = RecordWriter::new(ids[3], width)
+                    .alpha(&cte.filial_emissora, 10)
+                    .alpha(&cte.serie, 5)
+                    .digits(&cte.numero, 12);
+
+                if self.version == EdiVersion::V50 {
+                    record = record
+                        .alpha(cte.chave_cte.as_deref().unwrap_or(""), 44)
+                        .money(cte.valor_frete, 15);
+                }
+                records.push(record.finish());
+
+                let valor_nota = if cte.notas_fiscais.len() == 1 { cte.valor_notas } else { Money::ZERO };
+                let peso_nota = if cte.notas_fiscais.len() == 1 { cte.peso } else { 0.0 };
+                for nota in &cte.notas_fiscais {
+                    let record = match self.version {
+                        EdiVersion::V31 => RecordWriter::new(ids[4], width)
+                            .raw("", 3)
+                            .digits(nota, 8)
+                            .date(None)
+                            .decimal(peso_nota, 7, 2),
+                        EdiVersion::V50 => RecordWriter::new(ids[4], width)
+                            .raw("", 3)
+                            .digits(nota, 9)
+                            .date(None)
+                            .decimal(peso_nota, 9, 2),
+                    };
+                    records.push(
+                        record
+                            .money(valor_nota, 15)
+                            .digits(&cte.cnpj_remetente, 14)
+                            .finish(),
+                    );
+                }
+            }
+        }
+
+        records.push(
+            RecordWriter::new(ids[5], width)
+                .num(documentos.len() as i64, 4)
+                .money(total, 15)
+                .finish(),
+        );
+
+        join_records(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (InterchangeHeader, Transportadora, Vec<DocumentoCobranca>) {
+        let header = InterchangeHeader::new(
+            "AVILA TRANSPORTES",
+            "FENIOR INDUSTRIA",
+            NaiveDate::from_ymd_opt(2025, 4, 10).unwrap().and_hms_opt(14, 30, 0).unwrap(),
+        );
+        let transportadora = Transportadora::new("12.345.678/0001-95", "Ávila Transportes Ltda");
+
+        let orders: Vec<FreightOrder> = [("4521", "123456", 18_540), ("4522", "123460", 9210)]
+            .iter()
+            .map(|(cte, nota, frete)| {
+                let mut order = FreightOrder::new(
+                    cte.to_string(),
+                    NaiveDate::from_ymd_opt(2025, 4, 7).unwrap(),
+                    "FENIOR".into(),
+                    "FENIOR".into(),
+                    "Ribeirão Preto".into(),
+                    "Comercial Franca".into(),
+                    "Franca".into(),
+                );
+                order.cte_numero = Some(cte.to_string());
+                order.notas_fiscais = vec![nota.to_string()];
+                order.peso = 100.0;
+                order.valor_notas = Money::from_reais(1000);
+                order.valor_frete = Money::from_cents(*frete);
+                order
+            })
+            .collect();
+
+        let mut documento = DocumentoCobranca::from_orders(
+            "9001",
+            NaiveDate::from_ymd_opt(2025, 4, 10).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 5, 10).unwrap(),
+            &orders,
+        );
+        documento.conta = Some(ContaCobranca {
+            banco_codigo: "341".into(),
+            banco_nome: "Itaú Unibanco".into(),
+            agencia: "1234".into(),
+            agencia_digito: "5".into(),
+            conta: "67890".into(),
+            conta_digito: "1".into(),
+        });
+
+        (header, transportadora, vec![documento])
+    }
+
+    /// Confere campos pelas posições do manual PROCEDA (de/até, inclusivas e a partir
+    /// de 1), recortando a linha sem passar pelo leitor de registros
+    fn assert_positions(line: &str, campos: &[(usize, usize, &str)]) {
+        for &(de, ate, esperado) in campos {
+            assert_eq!(&line[de - 1..ate], esperado, "registro {}, posições {:03}-{:03}", &line[..3], de, ate);
+        }
+    }
+
+    #[test]
+    fn test_from_orders_sums_freight() {
+        let (_, _, documentos) = sample();
+        assert_eq!(documentos[0].conhecimentos.len(), 2);
+        assert_eq!(documentos[0].valor, Money::from_cents(27_750));
+    }
+
+    #[test]
+    fn test_write_doccob_31() {
+        let (header, transportadora, documentos) = sample();
+        let content = DoccobWriter::new(EdiVersion::V31).write(&header, &transportadora, &documentos);
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == RECORD_WIDTH_V31));
+
+        let ids: Vec<&str> = lines.iter().map(|l| &l[..3]).collect();
+        assert_eq!(ids, ["000", "350", "351", "352", "353", "354", "353", "354", "355"]);
+        assert_positions(lines[0], &[(84, 95, "COB100414300")]);
+        assert_positions(lines[1], &[(4, 17, "COBRA100414300")]);
+        assert_positions(lines[2], &[(4, 17, "12345678000195")]);
+
+        // Registro 352, posições do manual 3.1
+        assert_positions(lines[3], &[
+            (4, 13, "          "),                       // filial emissora
+            (14, 14, "0"),                               // tipo do documento (fatura)
+            (15, 17, "   "),                             // série
+            (18, 27, "0000009001"),                      // número do documento
+            (28, 35, "10042025"),                        // emissão
+            (36, 43, "10052025"),                        // vencimento
+            (44, 58, "000000000027750"),                 // valor do documento 13,2
+            (59, 61, "BCO"),                             // tipo de cobrança
+            (62, 65, "0000"),                            // % multa 2,2
+            (66, 80, "000000000000000"),                 // juros por dia de atraso
+            (104, 138, &format!("{:<35}", "ITAU UNIBANCO")), // agente cobrador
+            (139, 142, "1234"),                          // agência
+            (143, 143, "5"),                             // dígito da agência
+            (144, 153, "0000067890"),                    // conta corrente
+            (154, 155, "1 "),                            // dígito da conta
+            (156, 156, "I"),                             // ação do documento
+        ]);
+        assert_positions(lines[4], &[(4, 13, "          "), (14, 18, "     "), (19, 30, "000000004521")]);
+        assert_positions(lines[5], &[(4, 6, "   "), (7, 14, "00123456")]);
+        assert_positions(lines[8], &[(4, 7, "0001"), (8, 22, "000000000027750")]);
+    }
+
+    #[test]
+    fn test_write_doccob_50() {
+        let (header, transportadora, documentos) = sample();
+        let content = DoccobWriter::new(EdiVersion::V50).write(&header, &transportadora, &documentos);
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == RECORD_WIDTH_V50));
+
+        let ids: Vec<&str> = lines.iter().map(|l| &l[..3]).collect();
+        assert_eq!(ids, ["000", "550", "551", "552", "553", "555", "553", "555", "559"]);
+        assert_positions(lines[2], &[
+            (4, 17, "12345678000195"),
+            (18, 67, &format!("{:<50}", "AVILA TRANSPORTES LTDA")),
+        ]);
+        assert_positions(lines[3], &[(18, 27, "0000009001"), (44, 58, "000000000027750")]);
+    }
+}
@@ -0,0 +1,381 @@
+//! EDI PROCEDA (layouts de arquivos texto de posição fixa)
+//!
+//! Suporte aos documentos de intercâmbio usados entre embarcadores e
+//! transportadoras:
+//!
+//! - NOTFIS: notas fiscais a transportar (importado como `FreightOrder`)
+//! - OCOREN: ocorrências de entrega (gerado)
+//! - CONEMB: conhecimentos embarcados / faturas de frete (gerado)
+//! - DOCCOB: documentos de cobrança (gerado)
+//!
+//! As versões 3.1 e 5.0 dos layouts são suportadas. Todos os registros
+//! começam com um identificador de 3 dígitos e têm tamanho fixo.
+
+pub mod notfis;
+pub mod ocoren;
+pub mod conemb;
+pub mod doccob;
+
+pub use notfis::{NotfisDocument, NotfisImporter};
+pub use ocoren::{Ocorrencia, OcorenWriter};
+pub use conemb::{Conhecimento, ConembWriter};
+pub use doccob::{DocumentoCobranca, DoccobWriter};
+
+use crate::text::{self, normalize_text};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use pc_core::{Money, Result};
+
+/// Versão do layout PROCEDA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdiVersion {
+    V31,
+    V50,
+}
+
+impl EdiVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdiVersion::V31 => "3.1",
+            EdiVersion::V50 => "5.0",
+        }
+    }
+}
+
+/// Tipo de documento EDI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdiDocumentType {
+    Notfis,
+    Ocoren,
+    Conemb,
+    Doccob,
+}
+
+impl EdiDocumentType {
+    /// Prefixo usado na identificação do documento (registros 310, 340, ...)
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            EdiDocumentType::Notfis => "NOTFI",
+            EdiDocumentType::Ocoren => "OCORR",
+            EdiDocumentType::Conemb => "CONHE",
+            EdiDocumentType::Doccob => "COBRA",
+        }
+    }
+
+    /// Prefixo usado na identificação do intercâmbio (registro 000)
+    fn interchange_prefix(&self) -> &'static str {
+        match self {
+            EdiDocumentType::Notfis => "NOT",
+            EdiDocumentType::Ocoren => "OCO",
+            EdiDocumentType::Conemb => "CON",
+            EdiDocumentType::Doccob => "COB",
+        }
+    }
+}
+
+/// Detecta o tipo e a versão de um arquivo EDI pelo identificador do
+/// primeiro registro após o cabeçalho de intercâmbio
+pub fn detect(content: &str) -> Option<(EdiDocumentType, EdiVersion)> {
+    let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+
+    if !lines.next()?.starts_with("000") {
+        return None;
+    }
+
+    let id = lines.next()?.get(0..3)?;
+    match id {
+        "310" => Some((EdiDocumentType::Notfis, EdiVersion::V31)),
+        "500" => Some((EdiDocumentType::Notfis, EdiVersion::V50)),
+        "340" => Some((EdiDocumentType::Ocoren, EdiVersion::V31)),
+        "540" => Some((EdiDocumentType::Ocoren, EdiVersion::V50)),
+        "320" => Some((EdiDocumentType::Conemb, EdiVersion::V31)),
+        "520" => Some((EdiDocumentType::Conemb, EdiVersion::V50)),
+        "350" => Some((EdiDocumentType::Doccob, EdiVersion::V31)),
+        "550" => Some((EdiDocumentType::Doccob, EdiVersion::V50)),
+        _ => None,
+    }
+}
+
+/// Cabeçalho de intercâmbio (registro 000)
+#[derive(Debug, Clone)]
+pub struct InterchangeHeader {
+    pub remetente: String,
+    pub destinatario: String,
+    pub gerado_em: NaiveDateTime,
+}
+
+impl InterchangeHeader {
+    pub fn new(remetente: impl Into<String>, destinatario: impl Into<String>, gerado_em: NaiveDateTime) -> Self {
+        Self {
+            remetente: remetente.into(),
+            destinatario: destinatario.into(),
+            gerado_em,
+        }
+    }
+
+    /// Lê o registro 000
+    pub fn parse(record: &FixedRecord) -> Result<Self> {
+        if record.id() != "000" {
+            return Err(pc_core::Error::Import(format!(
+                "Linha {}: esperado registro 000, encontrado {}",
+                record.line_number,
+                record.id()
+            )));
+        }
+
+        let date = record.date_short(74);
+        let time = NaiveTime::parse_from_str(record.field(80, 4), "%H%M").ok();
+
+        let gerado_em = match (date, time) {
+            (Some(d), Some(t)) => d.and_time(t),
+            (Some(d), None) => d.and_time(NaiveTime::MIN),
+            _ => {
+                return Err(pc_core::Error::Import(format!(
+                    "Linha {}: data do intercâmbio inválida",
+                    record.line_number
+                )))
+            }
+        };
+
+        Ok(Self {
+            remetente: record.field(4, 35).to_string(),
+            destinatario: record.field(39, 35).to_string(),
+            gerado_em,
+        })
+    }
+
+    fn write(&self, doc: EdiDocumentType, width: usize) -> String {
+        RecordWriter::new("000", width)
+            .alpha(&self.remetente, 35)
+            .alpha(&self.destinatario, 35)
+            .raw(&self.gerado_em.format("%d%m%y").to_string(), 6)
+            .raw(&self.gerado_em.format("%H%M").to_string(), 4)
+            .alpha(&self.interchange_id(doc), 12)
+            .finish()
+    }
+
+    fn interchange_id(&self, doc: EdiDocumentType) -> String {
+        format!("{}{}0", doc.interchange_prefix(), self.gerado_em.format("%d%m%H%M"))
+    }
+
+    /// Identificação do documento (registros 310, 340, 320, 350, ...)
+    fn document_id(&self, doc: EdiDocumentType) -> String {
+        format!("{}{}0", doc.prefix(), self.gerado_em.format("%d%m%H%M"))
+    }
+}
+
+/// Dados da transportadora (registros 341, 321, 351, ...)
+#[derive(Debug, Clone)]
+pub struct Transportadora {
+    pub cnpj: String,
+    pub razao_social: String,
+}
+
+impl Transportadora {
+    pub fn new(cnpj: impl Into<String>, razao_social: impl Into<String>) -> Self {
+        Self {
+            cnpj: cnpj.into(),
+            razao_social: razao_social.into(),
+        }
+    }
+}
+
+/// Registro de posição fixa lido de um arquivo EDI
+///
+/// As posições seguem a numeração dos manuais PROCEDA (começando em 1).
+#[derive(Debug, Clone)]
+pub struct FixedRecord<'a> {
+    pub line_number: usize,
+    line: &'a str,
+}
+
+impl<'a> FixedRecord<'a> {
+    pub fn new(line_number: usize, line: &'a str) -> Self {
+        Self {
+            line_number,
+            line: line.trim_end_matches(['\r', '\n']),
+        }
+    }
+
+    /// Identificador do registro (3 primeiras posições)
+    pub fn id(&self) -> &'a str {
+        self.line.get(0..3).unwrap_or("")
+    }
+
+    /// Campo alfanumérico sem espaços nas bordas. Linhas truncadas (espaços
+    /// finais removidos por editores) retornam vazio para os campos ausentes.
+    pub fn field(&self, start: usize, len: usize) -> &'a str {
+        let from = start.saturating_sub(1);
+        let chars = self.line.char_indices().map(|(i, _)| i).chain(std::iter::once(self.line.len()));
+        let mut bounds = chars.skip(from);
+        let Some(begin) = bounds.next() else {
+            return "";
+        };
+        let end = bounds.nth(len.saturating_sub(1)).unwrap_or(self.line.len());
+        self.line[begin..end].trim()
+    }
+
+    /// Campo numérico inteiro
+    pub fn int(&self, start: usize, len: usize) -> i64 {
+        self.field(start, len).parse().unwrap_or(0)
+    }
+
+    /// Campo numérico com casas decimais implícitas
+    pub fn decimal(&self, start: usize, len: usize, decimals: u32) -> f64 {
+        self.int(start, len) as f64 / 10f64.powi(decimals as i32)
+    }
+
+    /// Valor em reais com duas casas decimais implícitas
+    pub fn money(&self, start: usize, len: usize) -> Money {
+        Money::from_cents(self.int(start, len))
+    }
+
+    /// Data no formato DDMMAAAA
+    pub fn date(&self, start: usize) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(self.field(start, 8), "%d%m%Y").ok()
+    }
+
+    /// Data no formato DDMMAA
+    pub fn date_short(&self, start: usize) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(self.field(start, 6), "%d%m%y").ok()
+    }
+
+    /// Campo opcional (vazio => None)
+    pub fn opt(&self, start: usize, len: usize) -> Option<String> {
+        let value = self.field(start, len);
+        if value.is_empty() || value.chars().all(|c| c == '0') {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
+}
+
+/// Itera sobre os registros não vazios de um arquivo EDI
+pub fn records(content: &str) -> impl Iterator<Item = FixedRecord<'_>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| FixedRecord::new(idx + 1, line))
+}
+
+/// Monta um registro de posição fixa campo a campo
+pub struct RecordWriter {
+    buf: String,
+    width: usize,
+}
+
+impl RecordWriter {
+    pub fn new(id: &str, width: usize) -> Self {
+        let mut buf = String::with_capacity(width);
+        buf.push_str(id);
+        Self { buf, width }
+    }
+
+    /// Campo alfanumérico: maiúsculo, sem acentos, alinhado à esquerda
+    pub fn alpha(mut self, value: &str, len: usize) -> Self {
+        let value: String = normalize_text(value).chars().take(len).collect();
+        self.buf.push_str(&format!("{:<len$}", value, len = len));
+        self
+    }
+
+    /// Campo numérico inteiro: alinhado à direita com zeros
+    pub fn num(mut self, value: i64, len: usize) -> Self {
+        let value = value.max(0).to_string();
+        let value = &value[value.len().saturating_sub(len)..];
+        self.buf.push_str(&format!("{:0>len$}", value, len = len));
+        self
+    }
+
+    /// Campo numérico a partir de texto (somente dígitos são mantidos)
+    pub fn digits(mut self, value: &str, len: usize) -> Self {
+        let digits = text::digits(value);
+        let digits = &digits[digits.len().saturating_sub(len)..];
+        self.buf.push_str(&format!("{:0>len$}", digits, len = len));
+        self
+    }
+
+    /// Campo numérico com casas decimais implícitas
+    pub fn decimal(self, value: f64, len: usize, decimals: u32) -> Self {
+        let scaled = (value * 10f64.powi(decimals as i32)).round() as i64;
+        self.num(scaled, len)
+    }
+
+    /// Valor em reais com duas casas decimais implícitas
+    pub fn money(self, value: Money, len: usize) -> Self {
+        self.num(value.cents(), len)
+    }
+
+    /// Data no formato DDMMAAAA (zeros quando ausente)
+    pub fn date(self, value: Option<NaiveDate>) -> Self {
+        match value {
+            Some(d) => self.raw(&d.format("%d%m%Y").to_string(), 8),
+            None => self.num(0, 8),
+        }
+    }
+
+    /// Campo já formatado
+    pub fn raw(mut self, value: &str, len: usize) -> Self {
+        self.buf.push_str(&format!("{:<len$.len$}", value, len = len));
+        self
+    }
+
+    /// Completa o registro com brancos até o tamanho do layout
+    pub fn finish(mut self) -> String {
+        let len = self.buf.chars().count();
+        if len < self.width {
+            self.buf.push_str(&" ".repeat(self.width - len));
+        }
+        self.buf
+    }
+}
+
+/// Junta as linhas geradas com CRLF, como esperado pelos sistemas PROCEDA
+pub(crate) fn join_records(records: Vec<String>) -> String {
+    let mut out = records.join("\r\n");
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_writer_fields() {
+        let record = RecordWriter::new("342", 35)
+            .alpha("São Paulo", 10)
+            .num(42, 5)
+            .decimal(1234.5, 8, 2)
+            .money(Money::from_cents(1999), 6)
+            .finish();
+
+        assert_eq!(record.len(), 35);
+        let parsed = FixedRecord::new(1, &record);
+        assert_eq!(parsed.id(), "342");
+        assert_eq!(parsed.field(4, 10), "SAO PAULO");
+        assert_eq!(parsed.int(14, 5), 42);
+        assert_eq!(parsed.decimal(19, 8, 2), 1234.5);
+        assert_eq!(parsed.field(27, 6), "001999");
+        assert_eq!(parsed.money(27, 6), Money::from_cents(1999));
+    }
+
+    #[test]
+    fn test_truncated_record() {
+        let parsed = FixedRecord::new(1, "313ABC");
+        assert_eq!(parsed.field(4, 10), "ABC");
+        assert_eq!(parsed.field(20, 5), "");
+        assert_eq!(parsed.int(20, 5), 0);
+    }
+
+    #[test]
+    fn test_detect() {
+        let notfis = include_str!("../../tests/fixtures/edi/NOTFIS_31.txt");
+        assert_eq!(detect(notfis), Some((EdiDocumentType::Notfis, EdiVersion::V31)));
+
+        let notfis = include_str!("../../tests/fixtures/edi/NOTFIS_50.txt");
+        assert_eq!(detect(notfis), Some((EdiDocumentType::Notfis, EdiVersion::V50)));
+
+        assert_eq!(detect("Numero;Data;Notas\n1;2;3"), None);
+    }
+}
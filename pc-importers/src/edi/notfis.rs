@@ -0,0 +1,413 @@
+//! NOTFIS - notas fiscais a transportar
+//!
+//! Layout 3.1: registros 000, 310, 311 (embarcadora), 312 (destinatário),
+//! 313 (nota fiscal), 314 (mercadorias), 318 (totais). Tamanho 290.
+//!
+//! Layout 5.0: registros 000, 500, 501 (embarcadora), 502 (local de coleta),
+//! 503 (destinatário), 504 (local de entrega), 505 (nota fiscal),
+//! 506 (valores), 507 (cálculo do frete), 508 (mercadorias), 509 (totais).
+//! Tamanho 320.
+
+use super::{records, EdiDocumentType, EdiVersion, FixedRecord, InterchangeHeader};
+use crate::streaming::{RowStream, Streamed, StreamedRow, StreamingImporter};
+use crate::Importer;
+use chrono::NaiveDate;
+use encoding_rs::WINDOWS_1252;
+use pc_core::{Money, Result};
+use pc_models::{FreightOrder, Lineage};
+use std::io::Read;
+use std::path::Path;
+
+pub const RECORD_WIDTH_V31: usize = 290;
+pub const RECORD_WIDTH_V50: usize = 320;
+
+/// Empresa citada no NOTFIS (embarcadora ou destinatário)
+#[derive(Debug, Clone, Default)]
+pub struct NotfisParty {
+    pub razao_social: String,
+    pub cnpj: String,
+    pub inscricao_estadual: Option<String>,
+    pub endereco: Option<String>,
+    pub cidade: String,
+    pub cep: Option<String>,
+    pub uf: String,
+    pub telefone: Option<String>,
+}
+
+/// Nota fiscal a transportar (registro 313 / 505-507)
+#[derive(Debug, Clone)]
+pub struct NotfisNota {
+    pub line_number: usize,
+    pub serie: String,
+    pub numero: String,
+    pub data_emissao: Option<NaiveDate>,
+    pub data_embarque: Option<NaiveDate>,
+    pub natureza: Option<String>,
+    /// 'C' = CIF (frete pago pelo remetente), 'F' = FOB (pago pelo destinatário)
+    pub condicao_frete: char,
+    pub chave_acesso: Option<String>,
+    pub romaneio: Option<String>,
+    pub volumes: f64,
+    pub peso: f64,
+    pub valor: Money,
+    pub valor_frete: Money,
+    pub embarcadora: NotfisParty,
+    pub destinatario: NotfisParty,
+}
+
+/// Documento NOTFIS lido de um arquivo
+#[derive(Debug, Clone)]
+pub struct NotfisDocument {
+    pub version: EdiVersion,
+    pub header: InterchangeHeader,
+    pub notas: Vec<NotfisNota>,
+}
+
+impl NotfisDocument {
+    /// Lê um NOTFIS 3.1 ou 5.0 (a versão é detectada pelos registros)
+    pub fn parse(content: &str) -> Result<Self> {
+        let version = match super::detect(content) {
+            Some((EdiDocumentType::Notfis, version)) => version,
+            _ => return Err(pc_core::Error::Import("Arquivo não é um NOTFIS PROCEDA".into())),
+        };
+
+        let mut iter = records(content);
+        let header = match iter.next() {
+            Some(record) => InterchangeHeader::parse(&record)?,
+            None => return Err(pc_core::Error::Import("Arquivo NOTFIS vazio".into())),
+        };
+
+        let mut notas = Vec::new();
+        let mut embarcadora = NotfisParty::default();
+        let mut data_embarque = None;
+        let mut destinatario = NotfisParty::default();
+
+        for record in iter {
+            match (version, record.id()) {
+                (EdiVersion::V31, "311") => {
+                    embarcadora = NotfisParty {
+                        cnpj: record.field(4, 14).to_string(),
+                        inscricao_estadual: record.opt(18, 15),
+                        endereco: record.opt(33, 40),
+                        cidade: record.field(73, 35).to_string(),
+                        cep: record.opt(108, 9),
+                        uf: record.field(117, 9).to_string(),
+                        razao_social: record.field(134, 40).to_string(),
+                        telefone: None,
+                    };
+                    data_embarque = record.date(126);
+                }
+                (EdiVersion::V31, "312") => {
+                    destinatario = NotfisParty {
+                        razao_social: record.field(4, 40).to_string(),
+                        cnpj: record.field(44, 14).to_string(),
+                        inscricao_estadual: record.opt(58, 15),
+                        endereco: record.opt(73, 40),
+                        cidade: record.field(133, 35).to_string(),
+                        cep: record.opt(168, 9),
+                        uf: record.field(186, 9).to_string(),
+                        telefone: record.opt(199, 35),
+                    };
+                }
+                (EdiVersion::V31, "313") => {
+                    let valor_frete = record.money(198, 15);
+                    notas.push(NotfisNota {
+                        line_number: record.line_number,
+                        romaneio: record.opt(4, 15),
+                        condicao_frete: condicao_frete(&record, 29),
+                        serie: record.field(30, 3).to_string(),
+                        numero: trim_zeros(record.field(33, 8)),
+                        data_emissao: record.date(41),
+                        data_embarque,
+                        natureza: record.opt(49, 15),
+                        volumes: record.decimal(79, 7, 2),
+                        valor: record.money(86, 15),
+                        peso: record.decimal(101, 7, 2),
+                        valor_frete: if valor_frete.is_positive() {
+                            valor_frete
+                        } else {
+                            record.money(130, 15)
+                        },
+                        chave_acesso: None,
+                        embarcadora: embarcadora.clone(),
+                        destinatario: destinatario.clone(),
+                    });
+                }
+                (EdiVersion::V50, "501") => {
+                    embarcadora = NotfisParty {
+                        razao_social: record.field(4, 50).to_string(),
+                        cnpj: record.field(54, 14).to_string(),
+                        inscricao_estadual: record.opt(68, 15),
+                        endereco: record.opt(108, 50),
+                        cidade: record.field(193, 35).to_string(),
+                        cep: record.opt(228, 9),
+                        uf: record.field(246, 9).to_string(),
+                        telefone: record.opt(255, 35),
+                    };
+                    data_embarque = record.date(290);
+                }
+                (EdiVersion::V50, "503") => {
+                    destinatario = NotfisParty {
+                        razao_social: record.field(4, 50).to_string(),
+                        cnpj: record.field(54, 14).to_string(),
+                        inscricao_estadual: record.opt(68, 15),
+                        endereco: record.opt(83, 50),
+                        cidade: record.field(168, 35).to_string(),
+                        cep: record.opt(203, 9),
+                        uf: record.field(221, 9).to_string(),
+                        telefone: record.opt(230, 35),
+                    };
+                }
+                (EdiVersion::V50, "505") => {
+                    notas.push(NotfisNota {
+                        line_number: record.line_number,
+                        serie: record.field(4, 3).to_string(),
+                        numero: trim_zeros(record.field(7, 9)),
+                        data_emissao: record.date(16),
+                        data_embarque,
+                        natureza: record.opt(24, 15),
+                        condicao_frete: condicao_frete(&record, 54),
+                        chave_acesso: record.opt(55, 44),
+                        romaneio: record.opt(100, 20),
+                        volumes: 0.0,
+                        peso: 0.0,
+                        valor: Money::ZERO,
+                        valor_frete: Money::ZERO,
+                        embarcadora: embarcadora.clone(),
+                        destinatario: destinatario.clone(),
+                    });
+                }
+                (EdiVersion::V50, "506") => {
+                    let Some(nota) = notas.last_mut() else {
+                        return Err(orphan(&record));
+                    };
+                    nota.volumes = record.decimal(4, 8, 2);
+                    nota.peso = record.decimal(12, 9, 2);
+                    nota.valor = record.money(45, 15);
+                }
+                (EdiVersion::V50, "507") => {
+                    let Some(nota) = notas.last_mut() else {
+                        return Err(orphan(&record));
+                    };
+                    nota.valor_frete = record.money(49, 15);
+                }
+                // Registros de controle, mercadorias e locais de coleta/entrega
+                // não são usados na ordem de frete
+                _ => {}
+            }
+        }
+
+        Ok(Self { version, header, notas })
+    }
+
+    /// Converte as notas em ordens de frete (uma ordem por nota fiscal)
+    pub fn to_freight_orders(&self, fonte_arquivo: Option<String>) -> Vec<FreightOrder> {
+        let header_date = self.header.gerado_em.date();
+
+        self.notas
+            .iter()
+            .map(|nota| {
+                let data_emissao = nota.data_emissao.or(nota.data_embarque).unwrap_or(header_date);
+                let (pagador, pagador_telefone) = if nota.condicao_frete == 'F' {
+                    (&nota.destinatario, nota.destinatario.telefone.clone())
+                } else {
+                    (&nota.embarcadora, nota.embarcadora.telefone.clone())
+                };
+
+                let mut order = FreightOrder::new(
+                    nota.numero.clone(),
+                    data_emissao,
+                    pagador.razao_social.clone(),
+                    nota.embarcadora.razao_social.clone(),
+                    nota.embarcadora.cidade.clone(),
+                    nota.destinatario.razao_social.clone(),
+                    nota.destinatario.cidade.clone(),
+                );
+
+                order.data_agendamento = nota.data_embarque;
+                order.notas_fiscais = vec![nota.numero.clone()];
+                order.pagador_telefone = pagador_telefone;
+                order.pagador_cnpj = non_empty(&pagador.cnpj);
+                order.remetente_cnpj = non_empty(&nota.embarcadora.cnpj);
+                order.destinatario_cnpj = non_empty(&nota.destinatario.cnpj);
+                order.volumes = nota.volumes.round() as i32;
+                order.peso = nota.peso;
+                order.valor_notas = nota.valor;
+                order.valor_frete = nota.valor_frete;
+                order.forma_pagamento = Some(if nota.condicao_frete == 'F' { "FOB" } else { "CIF" }.to_string());
+                order.observacoes = nota.natureza.clone();
+                order.fonte_arquivo = fonte_arquivo.clone();
+                order.linhagem = Some(Lineage::at_line(nota.line_number as u64));
+                order
+            })
+            .collect()
+    }
+}
+
+fn condicao_frete(record: &FixedRecord, pos: usize) -> char {
+    match record.field(pos, 1) {
+        "F" => 'F',
+        _ => 'C',
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty() && value.bytes().any(|b| b != b'0')).then(|| value.to_string())
+}
+
+fn trim_zeros(value: &str) -> String {
+    let trimmed = value.trim_start_matches('0');
+    if trimmed.is_empty() { value.to_string() } else { trimmed.to_string() }
+}
+
+fn orphan(record: &FixedRecord) -> pc_core::Error {
+    pc_core::Error::Import(format!(
+        "Linha {}: registro {} sem nota fiscal (505) correspondente",
+        record.line_number,
+        record.id()
+    ))
+}
+
+/// Importador de arquivos NOTFIS para ordens de frete
+pub struct NotfisImporter;
+
+impl NotfisImporter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Lê e interpreta o arquivo sem convertê-lo
+    pub fn parse_file<P: AsRef<Path>>(&self, path: P) -> Result<NotfisDocument> {
+        let file_bytes = std::fs::read(path.as_ref())
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
+
+        // Arquivos EDI são gerados em ASCII/Windows-1252
+        let (content, _, _) = WINDOWS_1252.decode(&file_bytes);
+        NotfisDocument::parse(&content)
+    }
+}
+
+impl Default for NotfisImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// As notas são lidas de uma vez (as empresas vêm em registros anteriores),
+/// mas passam pelos lotes para gravar com checkpoint por arquivo
+impl StreamingImporter for NotfisImporter {
+    type Output = FreightOrder;
+
+    fn name(&self) -> &'static str {
+        "notfis"
+    }
+
+    fn stream_rows<P: AsRef<Path>>(&self, path: P) -> Result<RowStream<'_, Self::Output>> {
+        let orders = self.import_file(path)?;
+        Ok(Box::new(orders.into_iter().map(|order| {
+            let line = order.linhagem.as_ref().and_then(|l| l.linha).unwrap_or(0);
+            Ok(StreamedRow::Record(Streamed { line, record: order }))
+        })))
+    }
+}
+
+impl Importer for NotfisImporter {
+    type Output = FreightOrder;
+
+    fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
+        let path = path.as_ref();
+        tracing::info!("Importando NOTFIS de: {:?}", path);
+
+        let document = self.parse_file(path)?;
+        let source_file = path.file_name()
+            .and_then(|n| n.to_str())
+            .map(|s| s.to_string());
+
+        let orders = document.to_freight_orders(source_file);
+        tracing::info!(
+            "Importadas {} ordens de frete (NOTFIS {})",
+            orders.len(),
+            document.version.as_str()
+        );
+        Ok(orders)
+    }
+
+    fn can_import<P: AsRef<Path>>(&self, path: P) -> bool {
+        let mut head = [0u8; 1024];
+        let Ok(mut file) = std::fs::File::open(path.as_ref()) else {
+            return false;
+        };
+        let Ok(n) = file.read(&mut head) else {
+            return false;
+        };
+
+        let (content, _, _) = WINDOWS_1252.decode(&head[..n]);
+        matches!(super::detect(&content), Some((EdiDocumentType::Notfis, _)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notfis_31() {
+        let content = include_str!("../../tests/fixtures/edi/NOTFIS_31.txt");
+        let document = NotfisDocument::parse(content).unwrap();
+
+        assert_eq!(document.version, EdiVersion::V31);
+        assert_eq!(document.header.remetente, "FENIOR INDUSTRIA");
+        assert_eq!(document.notas.len(), 3);
+
+        let nota = &document.notas[0];
+        assert_eq!(nota.numero, "123456");
+        assert_eq!(nota.serie, "1");
+        assert_eq!(nota.embarcadora.cnpj, "11222333000181");
+        assert_eq!(nota.destinatario.cidade, "FRANCA");
+        assert_eq!(nota.volumes, 12.0);
+        assert_eq!(nota.peso, 350.5);
+        assert_eq!(nota.valor, Money::from_cents(458_090));
+        assert_eq!(nota.valor_frete, Money::from_cents(18_540));
+
+        // Terceira nota pertence a outro destinatário e é FOB
+        let nota = &document.notas[2];
+        assert_eq!(nota.destinatario.razao_social, "COMERCIAL ARARAQUARA LTDA");
+        assert_eq!(nota.condicao_frete, 'F');
+    }
+
+    #[test]
+    fn test_parse_notfis_50() {
+        let content = include_str!("../../tests/fixtures/edi/NOTFIS_50.txt");
+        let document = NotfisDocument::parse(content).unwrap();
+
+        assert_eq!(document.version, EdiVersion::V50);
+        assert_eq!(document.notas.len(), 2);
+
+        let nota = &document.notas[1];
+        assert_eq!(nota.numero, "98765");
+        assert_eq!(nota.chave_acesso.as_deref().map(str::len), Some(44));
+        assert_eq!(nota.volumes, 3.0);
+        assert_eq!(nota.peso, 42.75);
+        assert_eq!(nota.valor, Money::from_cents(129_999));
+        assert_eq!(nota.valor_frete, Money::from_cents(8990));
+    }
+
+    #[test]
+    fn test_to_freight_orders() {
+        let content = include_str!("../../tests/fixtures/edi/NOTFIS_31.txt");
+        let document = NotfisDocument::parse(content).unwrap();
+        let orders = document.to_freight_orders(Some("NOTFIS_31.txt".into()));
+
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[0].numero, "123456");
+        assert_eq!(orders[0].notas_fiscais, vec!["123456".to_string()]);
+        assert_eq!(orders[0].pagador_nome, "FENIOR INDUSTRIA E COMERCIO LTDA");
+        assert_eq!(orders[0].remetente_cidade, "RIBEIRAO PRETO");
+        assert_eq!(orders[0].volumes, 12);
+
+        // FOB: pagador é o destinatário
+        assert_eq!(orders[2].pagador_nome, "COMERCIAL ARARAQUARA LTDA");
+        assert_eq!(orders[2].forma_pagamento.as_deref(), Some("FOB"));
+    }
+}
@@ -0,0 +1,247 @@
+//! OCOREN - ocorrências na entrega
+//!
+//! Layout 3.1: registros 000, 340, 341 (transportadora), 342 (ocorrência).
+//! Tamanho 120.
+//!
+//! Layout 5.0: registros 000, 540, 541 (transportadora), 542 (ocorrência).
+//! Tamanho 250.
+
+use super::{join_records, EdiDocumentType, EdiVersion, InterchangeHeader, RecordWriter, Transportadora};
+use chrono::{NaiveDate, NaiveTime};
+use pc_models::FreightOrder;
+
+pub const RECORD_WIDTH_V31: usize = 120;
+pub const RECORD_WIDTH_V50: usize = 250;
+
+/// Entrega realizada normalmente
+pub const ENTREGA_REALIZADA: u16 = 1;
+/// Entrega fora da data programada
+pub const ENTREGA_FORA_DA_DATA: u16 = 2;
+
+/// Ocorrência de transporte referente a uma nota fiscal
+#[derive(Debug, Clone)]
+pub struct Ocorrencia {
+    pub cnpj_emissor_nf: String,
+    pub serie_nf: String,
+    pub numero_nf: String,
+    pub chave_nfe: Option<String>,
+    pub codigo: u16,
+    pub data: NaiveDate,
+    pub hora: Option<NaiveTime>,
+    pub observacao: Option<String>,
+}
+
+impl Ocorrencia {
+    /// Gera uma ocorrência para cada nota fiscal da ordem de frete
+    ///
+    /// A data usada é a data de entrega (ou a de emissão, se a ordem ainda
+    /// não foi entregue).
+    pub fn from_order(order: &FreightOrder, cnpj_emissor_nf: &str, codigo: u16) -> Vec<Self> {
+        let data = order.data_entrega.unwrap_or(order.data_emissao);
+
+        order
+            .notas_fiscais
+            .iter()
+            .map(|nota| Self {
+                cnpj_emissor_nf: cnpj_emissor_nf.to_string(),
+                serie_nf: String::new(),
+                numero_nf: nota.clone(),
+                chave_nfe: None,
+                codigo,
+                data,
+                hora: None,
+                observacao: order.observacoes.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Gerador de arquivos OCOREN
+pub struct OcorenWriter {
+    version: EdiVersion,
+}
+
+impl OcorenWriter {
+    pub fn new(version: EdiVersion) -> Self {
+        Self { version }
+    }
+
+    pub fn write(
+        &self,
+        header: &InterchangeHeader,
+        transportadora: &Transportadora,
+        ocorrencias: &[Ocorrencia],
+    ) -> String {
+        let doc = EdiDocumentType::Ocoren;
+        let mut records = Vec::with_capacity(ocorrencias.len() + 3);
+
+        match self.version {
+            EdiVersion::V31 => {
+                let width = RECORD_WIDTH_V31;
+                records.push(header.write(doc, width));
+                records.push(RecordWriter::new("340", width).alpha(&header.document_id(doc), 14).finish());
+                records.push(
+                    RecordWriter::new("341", width)
+                        .digits(&transportadora.cnpj, 14)
+                        .alpha(&transportadora.razao_social, 40)
+                        .finish(),
+                );
+
+                for ocorrencia in ocorrencias {
+                    records.push(
+                        RecordWriter::new("342", width)
+                            .digits(&ocorrencia.cnpj_emissor_nf, 14)
+                            .alpha(&ocorrencia.serie_nf, 3)
+                            .digits(&ocorrencia.numero_nf, 8)
+                            .num(ocorrencia.codigo as i64, 2)
+                            .date(Some(ocorrencia.data))
+                            .raw(&format_hora(ocorrencia.hora), 4)
+                            .num(0, 2)
+                            .alpha(ocorrencia.observacao.as_deref().unwrap_or(""), 70)
+                            .finish(),
+                    );
+                }
+            }
+            EdiVersion::V50 => {
+                let width = RECORD_WIDTH_V50;
+                records.push(header.write(doc, width));
+                records.push(RecordWriter::new("540", width).alpha(&header.document_id(doc), 14).finish());
+                records.push(
+                    RecordWriter::new("541", width)
+                        .digits(&transportadora.cnpj, 14)
+                        .alpha(&transportadora.razao_social, 50)
+                        .finish(),
+                );
+
+                for ocorrencia in ocorrencias {
+                    records.push(
+                        RecordWriter::new("542", width)
+                            .digits(&ocorrencia.cnpj_emissor_nf, 14)
+                            .alpha(&ocorrencia.serie_nf, 3)
+                            .digits(&ocorrencia.numero_nf, 9)
+                            .num(ocorrencia.codigo as i64, 3)
+                            .date(Some(ocorrencia.data))
+                            .raw(&format_hora(ocorrencia.hora), 4)
+                            .num(0, 2)
+                            .alpha(ocorrencia.observacao.as_deref().unwrap_or(""), 70)
+                            .alpha(ocorrencia.chave_nfe.as_deref().unwrap_or(""), 44)
+                            .finish(),
+                    );
+                }
+            }
+        }
+
+        join_records(records)
+    }
+}
+
+fn format_hora(hora: Option<NaiveTime>) -> String {
+    hora.map(|h| h.format("%H%M").to_string()).unwrap_or_else(|| "0000".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (InterchangeHeader, Transportadora, Vec<Ocorrencia>) {
+        let header = InterchangeHeader::new(
+            "AVILA TRANSPORTES",
+            "FENIOR INDUSTRIA",
+            NaiveDate::from_ymd_opt(2025, 4, 10).unwrap().and_hms_opt(14, 30, 0).unwrap(),
+        );
+        let transportadora = Transportadora::new("12.345.678/0001-95", "Ávila Transportes Ltda");
+
+        let mut order = FreightOrder::new(
+            "288415".into(),
+            NaiveDate::from_ymd_opt(2025, 4, 7).unwrap(),
+            "FENIOR".into(),
+            "FENIOR".into(),
+            "Ribeirão Preto".into(),
+            "Comercial Franca".into(),
+            "Franca".into(),
+        );
+        order.notas_fiscais = vec!["123456".into(), "123457".into()];
+        order.data_entrega = NaiveDate::from_ymd_opt(2025, 4, 9);
+
+        let ocorrencias = Ocorrencia::from_order(&order, "11222333000181", ENTREGA_REALIZADA);
+        (header, transportadora, ocorrencias)
+    }
+
+    /// Confere campos pelas posições do manual PROCEDA (de/até, inclusivas e a partir
+    /// de 1), recortando a linha sem passar pelo leitor de registros
+    fn assert_positions(line: &str, campos: &[(usize, usize, &str)]) {
+        for &(de, ate, esperado) in campos {
+            assert_eq!(&line[de - 1..ate], esperado, "registro {}, posições {:03}-{:03}", &line[..3], de, ate);
+        }
+    }
+
+    #[test]
+    fn test_write_ocoren_31() {
+        let (header, transportadora, ocorrencias) = sample();
+        let content = OcorenWriter::new(EdiVersion::V31).write(&header, &transportadora, &ocorrencias);
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == RECORD_WIDTH_V31));
+
+        // Registro 000 (cabeçalho de intercâmbio), comum a todos os documentos
+        assert_positions(lines[0], &[
+            (1, 3, "000"),
+            (4, 38, &format!("{:<35}", "AVILA TRANSPORTES")),
+            (39, 73, &format!("{:<35}", "FENIOR INDUSTRIA")),
+            (74, 79, "100425"),
+            (80, 83, "1430"),
+            (84, 95, "OCO100414300"),
+        ]);
+        assert_positions(lines[1], &[(1, 3, "340"), (4, 17, "OCORR100414300")]);
+        assert_positions(lines[2], &[
+            (1, 3, "341"),
+            (4, 17, "12345678000195"),
+            (18, 57, &format!("{:<40}", "AVILA TRANSPORTES LTDA")),
+        ]);
+
+        // Registro 342 inteiro, campo a campo do manual 3.1
+        let expected = [
+            "342",            // 001-003 identificador
+            "11222333000181", // 004-017 CNPJ do emissor da nota
+            "   ",            // 018-020 série da nota
+            "00123456",       // 021-028 número da nota
+            "01",             // 029-030 código da ocorrência
+            "09042025",       // 031-038 data da ocorrência (DDMMAAAA)
+            "0000",           // 039-042 hora da ocorrência (HHMM)
+            "00",             // 043-044 código de observação
+            &" ".repeat(70),  // 045-114 texto livre
+            &" ".repeat(6),   // 115-120 filler
+        ]
+        .concat();
+        assert_eq!(lines[3], expected);
+        assert_positions(lines[4], &[(21, 28, "00123457")]);
+    }
+
+    #[test]
+    fn test_write_ocoren_50() {
+        let (header, transportadora, ocorrencias) = sample();
+        let content = OcorenWriter::new(EdiVersion::V50).write(&header, &transportadora, &ocorrencias);
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().all(|l| l.len() == RECORD_WIDTH_V50));
+        assert_eq!(
+            crate::edi::detect(&content),
+            Some((EdiDocumentType::Ocoren, EdiVersion::V50))
+        );
+
+        assert_positions(lines[1], &[(1, 3, "540"), (4, 17, "OCORR100414300")]);
+        assert_positions(lines[2], &[
+            (1, 3, "541"),
+            (4, 17, "12345678000195"),
+            (18, 67, &format!("{:<50}", "AVILA TRANSPORTES LTDA")),
+        ]);
+        // No 5.0 a nota tem 9 dígitos e a ocorrência 3
+        assert_positions(lines[3], &[
+            (1, 3, "542"),
+            (4, 17, "11222333000181"),
+            (18, 20, "   "),
+            (21, 29, "000123456"),
+            (30, 32, "001"),
+            (33, 40, "09042025"),
+            (41, 44, "0000"),
+        ]);
+    }
+}
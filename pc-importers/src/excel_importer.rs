@@ -1,17 +1,31 @@
 //! Excel file importer (.xlsx, .xls)
 //! Converts Excel files to internal format for processing
+//!
+//! Abas com o cabeçalho da planilha de fretes ou de ponto passam pelos
+//! mesmos importadores do CSV, e cada registro guarda a aba e a linha da
+//! planilha na linhagem. As demais abas são lidas como linhas soltas.
 
+use crate::detect::{self, FileKind, ImportOutput};
+use crate::freight_importer::FreightOrderImporter;
+use crate::timesheet_importer::TimesheetImporter;
+use calamine::{Data, Reader, Sheets};
 use pc_core::Result;
+use pc_models::{FreightOrder, Lineage, Timesheet};
+use std::io::Cursor;
 use std::path::Path;
 use serde_json::Value;
 
 pub struct ExcelImporter {
-    // Future: Add calamine or xlsx crate for native Excel reading
+    freight: FreightOrderImporter,
+    timesheet: TimesheetImporter,
 }
 
 impl ExcelImporter {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            freight: FreightOrderImporter::new(),
+            timesheet: TimesheetImporter::new(),
+        }
     }
 
     /// Check if file is an Excel file
@@ -24,14 +38,68 @@ impl ExcelImporter {
         false
     }
 
-    /// Import Excel file (placeholder - needs calamine crate)
-    pub fn import_excel(&self, path: &Path) -> Result<Vec<ExcelRow>> {
-        tracing::info!("Excel import requested for: {:?}", path);
-        tracing::warn!("Excel import not yet implemented - please convert to CSV first");
+    /// Importa a planilha: fretes ou ponto quando alguma aba tem o cabeçalho
+    /// conhecido, senão todas as linhas de todas as abas
+    ///
+    /// Uma pasta com abas de fretes e de ponto é recusada: a saída de um
+    /// arquivo é de uma entidade só.
+    pub fn import<P: AsRef<Path>>(&self, path: P) -> Result<ImportOutput> {
+        let path = path.as_ref();
+        tracing::info!("Importando planilha Excel: {:?}", path);
+
+        let source_file = path.file_name().and_then(|n| n.to_str()).map(|s| s.to_string());
+        let mut orders: Vec<FreightOrder> = Vec::new();
+        let mut timesheets: Vec<Timesheet> = Vec::new();
+        let mut rows = Vec::new();
+
+        for sheet in read_sheets(&read_file(path)?)? {
+            let Some(header) = sheet.rows.first() else {
+                continue;
+            };
+            match detect::sniff_csv_header(&header.cells.join(";")).map(|d| d.kind) {
+                Some(FileKind::FreightCsv) => {
+                    for row in &sheet.rows[1..] {
+                        match self.freight.parse_record(&row.record(), &source_file) {
+                            Some(Ok(mut order)) => {
+                                order.linhagem = Some(row.lineage(&sheet.name));
+                                orders.push(order);
+                            }
+                            Some(Err(reason)) => warn_rejected(&sheet.name, row.number, &reason),
+                            None => {}
+                        }
+                    }
+                }
+                Some(FileKind::TimesheetCsv) => {
+                    for row in &sheet.rows[1..] {
+                        match self.timesheet.parse_record(&row.record(), &source_file) {
+                            Some(Ok(mut timesheet)) => {
+                                timesheet.linhagem = Some(row.lineage(&sheet.name));
+                                timesheets.push(timesheet);
+                            }
+                            Some(Err(reason)) => warn_rejected(&sheet.name, row.number, &reason),
+                            None => {}
+                        }
+                    }
+                }
+                _ => rows.extend(sheet.into_excel_rows()),
+            }
+        }
 
-        // TODO: Implement Excel reading using calamine crate
-        // For now, return empty result
-        Ok(Vec::new())
+        match (orders.is_empty(), timesheets.is_empty()) {
+            (false, false) => Err(pc_core::Error::Import(format!(
+                "{:?} tem abas de fretes e de ponto; salve cada uma em um arquivo",
+                path
+            ))),
+            (false, true) => Ok(ImportOutput::FreightOrders(orders)),
+            (true, false) => Ok(ImportOutput::Timesheets(timesheets)),
+            (true, true) => Ok(ImportOutput::ExcelRows(rows)),
+        }
+    }
+
+    /// Todas as linhas de todas as abas, com as células como texto
+    pub fn import_excel(&self, path: &Path) -> Result<Vec<ExcelRow>> {
+        let sheets = read_sheets(&read_file(path)?)?;
+        Ok(sheets.into_iter().flat_map(Sheet::into_excel_rows).collect())
     }
 
     /// Get Excel file metadata
@@ -49,14 +117,124 @@ impl ExcelImporter {
             .ok_or_else(|| pc_core::Error::Import("Invalid filename".into()))?
             .to_string();
 
+        let content = read_file(path)?;
+        let workbook = open_workbook(&content)?;
+
         Ok(ExcelMetadata {
             filename,
             file_size,
-            sheet_count: 0, // TODO: Read actual sheet count
+            sheet_count: workbook.sheet_names().len(),
+        })
+    }
+}
+
+/// Cabeçalho e linha de uma aba, para mostrar a origem de um registro
+///
+/// `linha` é a numeração do Excel (a primeira é 1); o cabeçalho é a
+/// primeira linha preenchida da aba.
+pub fn sheet_row(content: &[u8], planilha: &str, linha: u64) -> Option<(Vec<String>, Vec<String>)> {
+    let mut workbook = open_workbook(content).ok()?;
+    let sheet = read_sheet(&mut workbook, planilha).ok()?;
+    let header = sheet.rows.first()?.cells.clone();
+    let row = sheet.rows.into_iter().find(|r| r.number == linha)?;
+    Some((header, row.cells))
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| pc_core::Error::Import(format!("Erro ao ler {:?}: {}", path, e)))
+}
+
+/// Aba lida, só com as linhas preenchidas
+struct Sheet {
+    name: String,
+    rows: Vec<SheetRow>,
+}
+
+impl Sheet {
+    fn into_excel_rows(self) -> impl Iterator<Item = ExcelRow> {
+        let name = self.name;
+        self.rows.into_iter().map(move |row| ExcelRow {
+            sheet_name: name.clone(),
+            row_number: row.number as usize,
+            data: row.cells.into_iter().map(Value::String).collect(),
+        })
+    }
+}
+
+struct SheetRow {
+    /// Número da linha no Excel (a primeira é 1)
+    number: u64,
+    cells: Vec<String>,
+}
+
+impl SheetRow {
+    fn record(&self) -> csv::StringRecord {
+        csv::StringRecord::from(self.cells.clone())
+    }
+
+    fn lineage(&self, planilha: &str) -> Lineage {
+        Lineage::at_line(self.number).with_planilha(planilha)
+    }
+}
+
+fn open_workbook(content: &[u8]) -> Result<Sheets<Cursor<&[u8]>>> {
+    calamine::open_workbook_auto_from_rs(Cursor::new(content))
+        .map_err(|e| pc_core::Error::Import(format!("Planilha Excel inválida: {}", e)))
+}
+
+fn read_sheets(content: &[u8]) -> Result<Vec<Sheet>> {
+    let mut workbook = open_workbook(content)?;
+    workbook
+        .sheet_names()
+        .into_iter()
+        .map(|name| read_sheet(&mut workbook, &name))
+        .collect()
+}
+
+fn read_sheet(workbook: &mut Sheets<Cursor<&[u8]>>, name: &str) -> Result<Sheet> {
+    let range = workbook
+        .worksheet_range(name)
+        .map_err(|e| pc_core::Error::Import(format!("Erro ao ler a aba {}: {}", name, e)))?;
+    // O range começa na primeira célula preenchida, não na linha 1
+    let first = range.start().map(|(row, _)| u64::from(row) + 1).unwrap_or(1);
+    let rows = range
+        .rows()
+        .enumerate()
+        .map(|(i, cells)| SheetRow {
+            number: first + i as u64,
+            cells: cells.iter().map(cell_text).collect(),
         })
+        .filter(|row| row.cells.iter().any(|c| !c.is_empty()))
+        .collect();
+    Ok(Sheet { name: name.to_string(), rows })
+}
+
+/// Célula como texto no formato das planilhas exportadas em CSV: datas
+/// dd/mm/aaaa, horários HH:MM e decimais com vírgula
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) => s.trim().to_string(),
+        Data::Int(n) => n.to_string(),
+        Data::Float(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", *f as i64),
+        Data::Float(f) => f.to_string().replace('.', ","),
+        Data::Bool(b) => if *b { "SIM" } else { "NÃO" }.to_string(),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            // Horário sem data: o Excel guarda só a fração do dia
+            Some(value) if dt.is_duration() || dt.as_f64() < 1.0 => value.format("%H:%M").to_string(),
+            Some(value) if dt.as_f64().fract() == 0.0 => value.format("%d/%m/%Y").to_string(),
+            Some(value) => value.format("%d/%m/%Y %H:%M").to_string(),
+            None => dt.as_f64().to_string().replace('.', ","),
+        },
+        Data::DateTimeIso(s) | Data::DurationIso(s) => s.clone(),
+        Data::Error(e) => e.to_string(),
     }
 }
 
+fn warn_rejected(planilha: &str, linha: u64, reason: &str) {
+    tracing::warn!("Aba {}, linha {} ignorada: {}", planilha, linha, reason);
+}
+
 impl Default for ExcelImporter {
     fn default() -> Self {
         Self::new()
@@ -80,6 +258,7 @@ pub struct ExcelMetadata {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
 
     #[test]
     fn test_is_excel() {
@@ -91,4 +270,45 @@ mod tests {
         assert!(!importer.is_excel(Path::new("test.csv")));
         assert!(!importer.is_excel(Path::new("test.txt")));
     }
+
+    #[test]
+    fn test_import_timesheet_sheet_keeps_sheet_and_row() {
+        let mut workbook = Workbook::new();
+        let notes = workbook.add_worksheet();
+        notes.set_name("Observações").unwrap();
+        notes.write_string(0, 0, "Conferido por Ana").unwrap();
+
+        let sheet = workbook.add_worksheet();
+        sheet.set_name("Abril").unwrap();
+        let date = Format::new().set_num_format("dd/mm/yyyy");
+        let time = Format::new().set_num_format("hh:mm");
+        // Cabeçalho na linha 2 do Excel, como nas planilhas com título
+        for (col, header) in ["Funcionário", "Mês", "Data", "Entrada", "Saída"].iter().enumerate() {
+            sheet.write_string(1, col as u16, *header).unwrap();
+        }
+        sheet.write_string(2, 0, "João").unwrap();
+        sheet.write_string(2, 1, "Abril").unwrap();
+        sheet.write_datetime_with_format(2, 2, ExcelDateTime::from_ymd(2025, 4, 1).unwrap(), &date).unwrap();
+        sheet.write_datetime_with_format(2, 3, ExcelDateTime::from_hms(8, 0, 0).unwrap(), &time).unwrap();
+        sheet.write_datetime_with_format(2, 4, ExcelDateTime::from_hms(17, 30, 0).unwrap(), &time).unwrap();
+
+        let path = std::env::temp_dir().join(format!("Ponto-{}.xlsx", uuid::Uuid::new_v4()));
+        std::fs::write(&path, workbook.save_to_buffer().unwrap()).unwrap();
+        let output = ExcelImporter::new().import(&path).unwrap();
+
+        let ImportOutput::Timesheets(timesheets) = output else {
+            panic!("esperado registros de ponto");
+        };
+        assert_eq!(timesheets.len(), 1);
+        assert_eq!(timesheets[0].funcionario, "João");
+        assert_eq!(timesheets[0].entrada.format("%H:%M").to_string(), "08:00");
+        let linhagem = timesheets[0].linhagem.as_ref().unwrap();
+        assert_eq!(linhagem.planilha.as_deref(), Some("Abril"));
+        assert_eq!(linhagem.linha, Some(3));
+
+        let (header, row) = sheet_row(&std::fs::read(&path).unwrap(), "Abril", 3).unwrap();
+        assert_eq!(header[4], "Saída");
+        assert_eq!(row, ["João", "Abril", "01/04/2025", "08:00", "17:30"]);
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,562 @@
+//! Exportação de collections e consultas
+//!
+//! Qualquer entidade serializável sai como CSV, CSV para o Excel brasileiro
+//! (`;`, vírgula decimal, datas dd/mm/aaaa e Windows-1252), XLSX com células
+//! tipadas, JSON ou JSONL. Campos aninhados viram colunas com caminho
+//! pontuado ("metadata.created_at"); as colunas e sua ordem podem ser
+//! escolhidas, e por padrão seguem os campos do primeiro registro.
+
+use chrono::{DateTime, NaiveDate};
+use encoding_rs::WINDOWS_1252;
+use pc_core::Result;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Linhas de dados que cabem em uma planilha (a primeira é o cabeçalho)
+const XLSX_MAX_ROWS: usize = 1_048_575;
+
+/// Formato do arquivo exportado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// CSV para o Excel em português: `;`, vírgula decimal e Windows-1252
+    CsvExcel,
+    Xlsx,
+    Json,
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::CsvExcel => "csv-excel",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Json => "json",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv | ExportFormat::CsvExcel => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Json => "json",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::CsvExcel => "text/csv; charset=windows-1252",
+            ExportFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    /// Formato pela extensão do arquivo de saída
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "xlsx" => Some(ExportFormat::Xlsx),
+            "json" => Some(ExportFormat::Json),
+            "jsonl" | "ndjson" => Some(ExportFormat::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = pc_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "csv-excel" | "csv-br" | "excel" => Ok(ExportFormat::CsvExcel),
+            "xlsx" => Ok(ExportFormat::Xlsx),
+            "json" => Ok(ExportFormat::Json),
+            "jsonl" | "ndjson" => Ok(ExportFormat::Jsonl),
+            other => Err(pc_core::Error::Validation(format!(
+                "Formato de exportação desconhecido: {} (use csv, csv-excel, xlsx, json ou jsonl)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Filtro simples sobre um campo: `campo=valor` (igual) ou `campo~valor` (contém)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordFilter {
+    pub field: String,
+    pub value: String,
+    /// `true` para "contém", sem diferenciar maiúsculas
+    pub contains: bool,
+}
+
+impl RecordFilter {
+    fn matches(&self, record: &Map<String, Value>) -> bool {
+        let Some(value) = record.get(&self.field) else {
+            return false;
+        };
+        let text = cell_text(value, false);
+        if self.contains {
+            text.to_lowercase().contains(&self.value.to_lowercase())
+        } else {
+            text == self.value
+        }
+    }
+}
+
+impl FromStr for RecordFilter {
+    type Err = pc_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (field, value, contains) = match s.split_once(['=', '~']) {
+            Some((field, value)) => (field, value, s.as_bytes()[field.len()] == b'~'),
+            None => {
+                return Err(pc_core::Error::Validation(format!(
+                    "Filtro inválido: {} (use campo=valor ou campo~valor)",
+                    s
+                )))
+            }
+        };
+        Ok(Self {
+            field: field.trim().to_string(),
+            value: value.trim().to_string(),
+            contains,
+        })
+    }
+}
+
+/// Escreve registros em um dos [`ExportFormat`]s
+pub struct Exporter {
+    format: ExportFormat,
+    columns: Option<Vec<String>>,
+    filters: Vec<RecordFilter>,
+    sheet_name: String,
+}
+
+impl Exporter {
+    pub fn new(format: ExportFormat) -> Self {
+        Self {
+            format,
+            columns: None,
+            filters: Vec::new(),
+            sheet_name: "Dados".to_string(),
+        }
+    }
+
+    /// Colunas exportadas, na ordem dada (caminhos pontuados para campos aninhados)
+    pub fn with_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let columns: Vec<String> = columns.into_iter().map(Into::into).collect();
+        self.columns = (!columns.is_empty()).then_some(columns);
+        self
+    }
+
+    /// Exporta só os registros que atendem a todos os filtros
+    pub fn with_filters(mut self, filters: Vec<RecordFilter>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Nome da aba no XLSX
+    pub fn with_sheet_name(mut self, name: impl Into<String>) -> Self {
+        self.sheet_name = name.into();
+        self
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    /// Escreve os registros e retorna quantos foram exportados
+    ///
+    /// CSV, JSON e JSONL são escritos registro a registro; o XLSX é montado
+    /// em memória e escrito ao final.
+    pub fn export<T, I, W>(&self, records: I, writer: W) -> Result<usize>
+    where
+        T: Serialize,
+        I: IntoIterator<Item = T>,
+        W: Write,
+    {
+        let rows = records
+            .into_iter()
+            .map(|record| {
+                serde_json::to_value(record)
+                    .map_err(|e| pc_core::Error::Internal(format!("Erro ao serializar registro: {}", e)))
+            })
+            .map(|value| value.map(flatten))
+            .filter(|row| row.as_ref().map_or(true, |row| self.filters.iter().all(|f| f.matches(row))));
+
+        match self.format {
+            ExportFormat::Csv => self.write_csv(rows, writer, false),
+            ExportFormat::CsvExcel => self.write_csv(rows, writer, true),
+            ExportFormat::Xlsx => self.write_xlsx(rows, writer),
+            ExportFormat::Json => self.write_json(rows, writer),
+            ExportFormat::Jsonl => self.write_jsonl(rows, writer),
+        }
+    }
+
+    /// Confere as colunas escolhidas contra um registro de exemplo
+    ///
+    /// Útil antes de começar a enviar a exportação, quando um erro no meio
+    /// do arquivo não pode mais virar uma resposta de erro.
+    pub fn check_columns<T: Serialize>(&self, sample: &T) -> Result<()> {
+        let value = serde_json::to_value(sample)
+            .map_err(|e| pc_core::Error::Internal(format!("Erro ao serializar registro: {}", e)))?;
+        self.columns_for(&flatten(value)).map(|_| ())
+    }
+
+    /// Colunas escolhidas, conferidas contra o primeiro registro
+    fn columns_for(&self, first: &Map<String, Value>) -> Result<Vec<String>> {
+        let Some(columns) = &self.columns else {
+            return Ok(first.keys().cloned().collect());
+        };
+        if let Some(unknown) = columns.iter().find(|c| !first.contains_key(*c)) {
+            let available: Vec<&str> = first.keys().map(String::as_str).collect();
+            return Err(pc_core::Error::Validation(format!(
+                "Coluna desconhecida: {} (disponíveis: {})",
+                unknown,
+                available.join(", ")
+            )));
+        }
+        Ok(columns.clone())
+    }
+
+    fn write_csv<I, W>(&self, rows: I, mut writer: W, excel: bool) -> Result<usize>
+    where
+        I: Iterator<Item = Result<Map<String, Value>>>,
+        W: Write,
+    {
+        let mut builder = csv::WriterBuilder::new();
+        builder.delimiter(if excel { b';' } else { b',' });
+        let mut columns = None;
+        let mut count = 0;
+
+        for row in rows {
+            let row = row?;
+            let columns = match &columns {
+                Some(columns) => columns,
+                None => {
+                    let names = self.columns_for(&row)?;
+                    write_csv_line(&builder, &names, &mut writer, excel)?;
+                    columns.insert(names)
+                }
+            };
+            let cells: Vec<String> = columns
+                .iter()
+                .map(|c| row.get(c).map(|v| cell_text(v, excel)).unwrap_or_default())
+                .collect();
+            write_csv_line(&builder, &cells, &mut writer, excel)?;
+            count += 1;
+        }
+        if let (None, Some(names)) = (&columns, &self.columns) {
+            write_csv_line(&builder, names, &mut writer, excel)?;
+        }
+        writer.flush().map_err(io_error)?;
+        Ok(count)
+    }
+
+    fn write_xlsx<I, W>(&self, rows: I, mut writer: W) -> Result<usize>
+    where
+        I: Iterator<Item = Result<Map<String, Value>>>,
+        W: Write,
+    {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(&self.sheet_name).map_err(xlsx_error)?;
+
+        let header = Format::new().set_bold();
+        let date = Format::new().set_num_format("dd/mm/yyyy");
+        let datetime = Format::new().set_num_format("dd/mm/yyyy hh:mm");
+        let mut columns: Option<Vec<String>> = None;
+        let mut count = 0;
+
+        for row in rows {
+            let row = row?;
+            if columns.is_none() {
+                let names = self.columns_for(&row)?;
+                for (col, name) in names.iter().enumerate() {
+                    worksheet.write_string_with_format(0, col as u16, name, &header).map_err(xlsx_error)?;
+                }
+                columns = Some(names);
+            }
+            if count >= XLSX_MAX_ROWS {
+                return Err(pc_core::Error::Validation(format!(
+                    "XLSX comporta no máximo {} linhas; use CSV ou JSONL",
+                    XLSX_MAX_ROWS
+                )));
+            }
+
+            let line = (count + 1) as u32;
+            for (col, name) in columns.iter().flatten().enumerate() {
+                let col = col as u16;
+                let result = match row.get(name) {
+                    None | Some(Value::Null) => continue,
+                    Some(Value::Bool(b)) => worksheet.write_boolean(line, col, *b),
+                    Some(Value::Number(n)) => match n.as_f64() {
+                        Some(n) => worksheet.write_number(line, col, n),
+                        None => worksheet.write_string(line, col, n.to_string()),
+                    },
+                    Some(Value::String(s)) => match excel_datetime(s) {
+                        Some((value, true)) => worksheet.write_datetime_with_format(line, col, &value, &datetime),
+                        Some((value, false)) => worksheet.write_datetime_with_format(line, col, &value, &date),
+                        None => worksheet.write_string(line, col, s),
+                    },
+                    Some(other) => worksheet.write_string(line, col, cell_text(other, false)),
+                };
+                result.map_err(xlsx_error)?;
+            }
+            count += 1;
+        }
+
+        if columns.is_none() {
+            for (col, name) in self.columns.iter().flatten().enumerate() {
+                worksheet.write_string_with_format(0, col as u16, name, &header).map_err(xlsx_error)?;
+            }
+        }
+        worksheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+        worksheet.autofit();
+
+        let buffer = workbook.save_to_buffer().map_err(xlsx_error)?;
+        writer.write_all(&buffer).and_then(|_| writer.flush()).map_err(io_error)?;
+        Ok(count)
+    }
+
+    fn write_json<I, W>(&self, rows: I, mut writer: W) -> Result<usize>
+    where
+        I: Iterator<Item = Result<Map<String, Value>>>,
+        W: Write,
+    {
+        writer.write_all(b"[").map_err(io_error)?;
+        let mut columns = None;
+        let mut count = 0;
+        for row in rows {
+            let object = self.select(row?, &mut columns)?;
+            writer.write_all(if count == 0 { b"\n  " } else { b",\n  " }).map_err(io_error)?;
+            serde_json::to_writer(&mut writer, &object)
+                .map_err(|e| pc_core::Error::Internal(format!("Erro ao escrever JSON: {}", e)))?;
+            count += 1;
+        }
+        writer
+            .write_all(if count == 0 { b"]\n" } else { b"\n]\n" })
+            .and_then(|_| writer.flush())
+            .map_err(io_error)?;
+        Ok(count)
+    }
+
+    fn write_jsonl<I, W>(&self, rows: I, mut writer: W) -> Result<usize>
+    where
+        I: Iterator<Item = Result<Map<String, Value>>>,
+        W: Write,
+    {
+        let mut columns = None;
+        let mut count = 0;
+        for row in rows {
+            let object = self.select(row?, &mut columns)?;
+            serde_json::to_writer(&mut writer, &object)
+                .map_err(|e| pc_core::Error::Internal(format!("Erro ao escrever JSONL: {}", e)))?;
+            writer.write_all(b"\n").map_err(io_error)?;
+            count += 1;
+        }
+        writer.flush().map_err(io_error)?;
+        Ok(count)
+    }
+
+    /// Registro só com as colunas escolhidas, na ordem pedida
+    fn select(&self, row: Map<String, Value>, columns: &mut Option<Vec<String>>) -> Result<Map<String, Value>> {
+        let columns = match columns {
+            Some(columns) => columns,
+            None => columns.insert(self.columns_for(&row)?),
+        };
+        let mut row = row;
+        Ok(columns
+            .iter()
+            .map(|c| (c.clone(), row.remove(c).unwrap_or(Value::Null)))
+            .collect())
+    }
+}
+
+/// Objeto com os campos aninhados em caminhos pontuados
+fn flatten(value: Value) -> Map<String, Value> {
+    fn walk(prefix: &str, value: Value, out: &mut Map<String, Value>) {
+        match value {
+            Value::Object(object) if !object.is_empty() => {
+                for (key, value) in object {
+                    let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                    walk(&path, value, out);
+                }
+            }
+            other => {
+                out.insert(prefix.to_string(), other);
+            }
+        }
+    }
+
+    let mut out = Map::new();
+    match value {
+        Value::Object(_) => walk("", value, &mut out),
+        other => {
+            out.insert("valor".to_string(), other);
+        }
+    }
+    out
+}
+
+/// Texto da célula no CSV; no formato do Excel brasileiro, com vírgula decimal e datas dd/mm/aaaa
+fn cell_text(value: &Value, excel: bool) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) if excel => if *b { "Sim" } else { "Não" }.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) if excel => n.to_string().replace('.', ","),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) if excel => match excel_datetime(s) {
+            Some(_) => br_datetime(s),
+            None => s.clone(),
+        },
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(|v| cell_text(v, excel)).collect::<Vec<_>>().join(" | "),
+        Value::Object(_) => value.to_string(),
+    }
+}
+
+/// Data ("2025-04-01") ou data e hora (RFC 3339) como data do Excel; `true` se tiver hora
+fn excel_datetime(text: &str) -> Option<(ExcelDateTime, bool)> {
+    use chrono::{Datelike, Timelike};
+
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        let value = ExcelDateTime::from_ymd(date.year() as u16, date.month() as u8, date.day() as u8).ok()?;
+        return Some((value, false));
+    }
+    let datetime = DateTime::parse_from_rfc3339(text).ok()?.naive_utc();
+    let value = ExcelDateTime::from_ymd(datetime.year() as u16, datetime.month() as u8, datetime.day() as u8)
+        .ok()?
+        .and_hms(datetime.hour() as u16, datetime.minute() as u8, datetime.second())
+        .ok()?;
+    Some((value, true))
+}
+
+fn br_datetime(text: &str) -> String {
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return date.format("%d/%m/%Y").to_string();
+    }
+    match DateTime::parse_from_rfc3339(text) {
+        Ok(datetime) => datetime.naive_utc().format("%d/%m/%Y %H:%M:%S").to_string(),
+        Err(_) => text.to_string(),
+    }
+}
+
+/// Escreve uma linha do CSV, convertida para Windows-1252 se pedido
+///
+/// Cada linha é formatada inteira antes da conversão, para nenhum caractere
+/// ficar dividido entre dois pedaços da saída.
+fn write_csv_line<W: Write>(builder: &csv::WriterBuilder, cells: &[String], writer: &mut W, excel: bool) -> Result<()> {
+    let mut line = builder.from_writer(Vec::new());
+    line.write_record(cells).map_err(csv_error)?;
+    let line = line
+        .into_inner()
+        .map_err(|e| pc_core::Error::Internal(format!("Erro ao escrever CSV: {}", e)))?;
+    let result = if excel {
+        writer.write_all(&WINDOWS_1252.encode(&String::from_utf8_lossy(&line)).0)
+    } else {
+        writer.write_all(&line)
+    };
+    result.map_err(io_error)
+}
+
+fn csv_error(e: csv::Error) -> pc_core::Error {
+    pc_core::Error::Internal(format!("Erro ao escrever CSV: {}", e))
+}
+
+fn xlsx_error(e: rust_xlsxwriter::XlsxError) -> pc_core::Error {
+    pc_core::Error::Internal(format!("Erro ao gerar XLSX: {}", e))
+}
+
+fn io_error(e: std::io::Error) -> pc_core::Error {
+    pc_core::Error::Internal(format!("Erro ao gravar exportação: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pc_core::Money;
+    use pc_models::FreightOrder;
+
+    fn orders() -> Vec<FreightOrder> {
+        ["288415", "288416"]
+            .iter()
+            .enumerate()
+            .map(|(i, numero)| {
+                let mut order = FreightOrder::new(
+                    numero.to_string(),
+                    NaiveDate::from_ymd_opt(2025, 4, 1 + i as u32).unwrap(),
+                    "FENIOR".into(),
+                    "FENIOR".into(),
+                    "Ribeirão Preto".into(),
+                    "Comercial Franca".into(),
+                    "Franca".into(),
+                );
+                order.valor_frete = Money::from_cents(125_050 + 100 * i as i64);
+                order
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_csv_excel_uses_semicolon_comma_and_windows_1252() {
+        let exporter = Exporter::new(ExportFormat::CsvExcel)
+            .with_columns(["numero", "remetente_cidade", "valor_frete", "data_emissao"]);
+        let mut output = Vec::new();
+        assert_eq!(exporter.export(orders(), &mut output).unwrap(), 2);
+
+        let (text, _, had_errors) = WINDOWS_1252.decode(&output);
+        assert!(!had_errors);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "numero;remetente_cidade;valor_frete;data_emissao");
+        assert_eq!(lines[1], "288415;Ribeirão Preto;1250,5;01/04/2025");
+        // "ã" em um único byte
+        assert!(output.contains(&0xE3));
+
+        let err = Exporter::new(ExportFormat::Csv).with_columns(["nao_existe"]).export(orders(), Vec::new());
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_jsonl_filters_and_flattens() {
+        let exporter = Exporter::new(ExportFormat::Jsonl)
+            .with_columns(["numero", "metadata.created_by"])
+            .with_filters(vec!["numero=288416".parse().unwrap()]);
+        let mut output = Vec::new();
+        assert_eq!(exporter.export(orders(), &mut output).unwrap(), 1);
+
+        let line: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(line, serde_json::json!({"numero": "288416", "metadata.created_by": null}));
+
+        let contains: RecordFilter = "destinatario_cidade~FRAN".parse().unwrap();
+        assert!(contains.contains);
+    }
+
+    #[test]
+    fn test_xlsx_is_a_zip_with_typed_cells() {
+        let mut output = Vec::new();
+        let count = Exporter::new(ExportFormat::Xlsx)
+            .with_sheet_name("freight_orders")
+            .export(orders(), &mut output)
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(&output[..2], b"PK");
+    }
+}
@@ -0,0 +1,74 @@
+//! XML de NF-e e CT-e
+//!
+//! Os campos ainda não são lidos: o XML é guardado como documento, do mesmo
+//! jeito quando chega sozinho ou dentro de um ZIP ou e-mail.
+
+use crate::detect::{self, FileKind};
+use pc_core::Result;
+use pc_models::Document;
+use std::path::Path;
+
+/// Importador de XML fiscal (NF-e e CT-e)
+#[derive(Debug, Clone, Default)]
+pub struct FiscalXmlImporter;
+
+impl FiscalXmlImporter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Guarda o XML como documento; recusa XMLs que não são NF-e nem CT-e
+    pub fn import(&self, path: &Path) -> Result<Document> {
+        let detection = detect::detect_file(path)?;
+        match detection.kind {
+            kind @ (FileKind::NfeXml | FileKind::CteXml) => {
+                let sha256 = pc_core::hash::sha256_file(path)?;
+                Ok(fiscal_document(path, kind, &sha256))
+            }
+            _ => Err(pc_core::Error::Import(format!(
+                "{} não é XML de NF-e nem de CT-e ({})",
+                path.display(),
+                detection.reason
+            ))),
+        }
+    }
+}
+
+/// XML de NF-e ou CT-e guardado como documento
+fn fiscal_document(path: &Path, kind: FileKind, sha256: &str) -> Document {
+    let tipo = if kind == FileKind::CteXml { "CT-e" } else { "NF-e" };
+    let mut document = Document::new(
+        path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        tipo.to_string(),
+        path.to_string_lossy().to_string(),
+        sha256.to_string(),
+        "application/xml".to_string(),
+    );
+    document.tamanho = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_cte_and_reject_other_xml() {
+        let dir = std::env::temp_dir().join(format!("fiscal-xml-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cte = dir.join("cte-4512.xml");
+        std::fs::write(&cte, "<?xml version=\"1.0\"?><cteProc xmlns=\"http://www.portalfiscal.inf.br/cte\"><CTe/></cteProc>")
+            .unwrap();
+        let document = FiscalXmlImporter::new().import(&cte).unwrap();
+        assert_eq!(document.tipo, "CT-e");
+        assert_eq!(document.titulo, "cte-4512.xml");
+        assert_eq!(document.sha256, pc_core::hash::sha256_file(&cte).unwrap());
+
+        let pom = dir.join("pom.xml");
+        std::fs::write(&pom, "<?xml version=\"1.0\"?><project/>").unwrap();
+        assert!(FiscalXmlImporter::new().import(&pom).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,23 +1,32 @@
 //! Freight order importer
 
-use pc_core::Result;
-use pc_models::FreightOrder;
+use pc_core::{Money, Result};
+use pc_models::{FreightOrder, Lineage};
+use crate::csv_source::{CsvFormat, CsvOptions};
+use crate::streaming::{RejectedRow, RowStream, StreamedRow, StreamingImporter, Streamed};
 use crate::Importer;
 use chrono::NaiveDate;
-use encoding_rs::WINDOWS_1252;
 use std::path::Path;
 use regex::Regex;
 
 pub struct FreightOrderImporter {
     date_regex: Regex,
+    csv_options: CsvOptions,
 }
 
 impl FreightOrderImporter {
     pub fn new() -> Self {
         Self {
             date_regex: Regex::new(r"(\d{2})/(\d{2})/(\d{4})").unwrap(),
+            csv_options: CsvOptions::default(),
         }
     }
+
+    /// Força encoding, delimitador ou aspas em vez de detectá-los
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
     
     fn parse_date(&self, date_str: &str) -> Option<NaiveDate> {
         if let Some(caps) = self.date_regex.captures(date_str) {
@@ -32,13 +41,16 @@ impl FreightOrderImporter {
     
     fn parse_float(&self, value: &str) -> f64 {
         value
-            .replace("R$", "")
             .replace(".", "")
             .replace(",", ".")
             .trim()
             .parse()
             .unwrap_or(0.0)
     }
+
+    fn parse_money(&self, value: &str) -> Option<Money> {
+        Money::parse(value).ok()
+    }
     
     fn parse_int(&self, value: &str) -> i32 {
         value.trim().parse().unwrap_or(0)
@@ -51,6 +63,86 @@ impl FreightOrderImporter {
             .filter(|s| !s.is_empty())
             .collect()
     }
+    
+    /// Converte uma linha do CSV em ordem de frete
+    ///
+    /// `None` para linhas sem número (vazias, totais); `Some(Err)` com o
+    /// motivo quando a linha tem número mas não pode ser importada.
+    pub(crate) fn parse_record(
+        &self,
+        record: &csv::StringRecord,
+        source_file: &Option<String>,
+    ) -> Option<std::result::Result<FreightOrder, String>> {
+        // Coluna 0: Número
+        let numero = record.get(0).unwrap_or("").trim();
+        if numero.is_empty() {
+            return None;
+        }
+
+        if record.len() < 10 {
+            return Some(Err(format!(
+                "Ordem {}: {} colunas, esperado ao menos 10",
+                numero,
+                record.len()
+            )));
+        }
+        
+        // Colunas principais (baseado no CSV real)
+        let data_agendamento = record.get(1).and_then(|s| self.parse_date(s));
+        let data_emissao = record.get(2).and_then(|s| self.parse_date(s));
+        let notas_fiscais = record.get(3).map(|s| self.split_notas(s)).unwrap_or_default();
+        let pagador_nome = record.get(4).unwrap_or("").trim().to_string();
+        let pagador_telefone = record.get(5).map(|s| s.trim().to_string());
+        let remetente_nome = record.get(6).unwrap_or("").trim().to_string();
+        let remetente_cidade = record.get(7).unwrap_or("").trim().to_string();
+        let destinatario_nome = record.get(8).unwrap_or("").trim().to_string();
+        let destinatario_cidade = record.get(9).unwrap_or("").trim().to_string();
+        
+        if pagador_nome.is_empty() || remetente_nome.is_empty() || destinatario_nome.is_empty() {
+            return Some(Err(format!("Ordem {}: pagador, remetente ou destinatário vazio", numero)));
+        }
+        
+        let volumes = record.get(10).map(|s| self.parse_int(s)).unwrap_or(0);
+        let peso = record.get(11).map(|s| self.parse_float(s)).unwrap_or(0.0);
+        let valor_notas = record.get(12).and_then(|s| self.parse_money(s)).unwrap_or_default();
+        let valor_frete = record.get(13).and_then(|s| self.parse_money(s)).unwrap_or_default();
+        let frete_tabelado = record.get(14).and_then(|s| self.parse_money(s));
+        
+        let filial_coleta = record.get(26).map(|s| s.trim().to_string());
+        let motorista_coleta = record.get(27).map(|s| s.trim().to_string());
+        let filial_entrega = record.get(28).map(|s| s.trim().to_string());
+        let motorista_entrega = record.get(29).map(|s| s.trim().to_string());
+        
+        let Some(data_emissao) = data_emissao else {
+            return Some(Err(format!("Ordem {} sem data de emissão", numero)));
+        };
+        
+        let mut order = FreightOrder::new(
+            numero.to_string(),
+            data_emissao,
+            pagador_nome,
+            remetente_nome,
+            remetente_cidade,
+            destinatario_nome,
+            destinatario_cidade,
+        );
+        
+        order.data_agendamento = data_agendamento;
+        order.notas_fiscais = notas_fiscais;
+        order.pagador_telefone = pagador_telefone;
+        order.volumes = volumes;
+        order.peso = peso;
+        order.valor_notas = valor_notas;
+        order.valor_frete = valor_frete;
+        order.frete_tabelado = frete_tabelado;
+        order.filial_coleta = filial_coleta;
+        order.motorista_coleta = motorista_coleta;
+        order.filial_entrega = filial_entrega;
+        order.motorista_entrega = motorista_entrega;
+        order.fonte_arquivo = source_file.clone();
+        
+        Some(Ok(order))
+    }
 }
 
 impl Default for FreightOrderImporter {
@@ -59,110 +151,53 @@ impl Default for FreightOrderImporter {
     }
 }
 
-impl Importer for FreightOrderImporter {
+impl StreamingImporter for FreightOrderImporter {
     type Output = FreightOrder;
-    
-    fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
+
+    fn name(&self) -> &'static str {
+        "freight"
+    }
+
+    fn stream_rows<P: AsRef<Path>>(&self, path: P) -> Result<RowStream<'_, Self::Output>> {
         let path = path.as_ref();
         tracing::info!("Importando ordens de frete de: {:?}", path);
-        
-        // Lê o arquivo em Windows-1252 (encoding comum em CSVs brasileiros)
-        let file_bytes = std::fs::read(path)
-            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
-        
-        let (content, _, had_errors) = WINDOWS_1252.decode(&file_bytes);
-        if had_errors {
-            tracing::warn!("Erros de encoding detectados no arquivo");
-        }
-        
-        let mut reader = csv::ReaderBuilder::new()
-            .delimiter(b';')
-            .has_headers(true)
-            .flexible(true)
-            .from_reader(content.as_bytes());
-        
-        let mut orders = Vec::new();
+
+        // Encoding e delimitador variam entre o sistema antigo (Windows-1252, `;`) e exportações novas
+        let (_, reader) = CsvFormat::open(path, &self.csv_options)?;
         let source_file = path.file_name()
             .and_then(|n| n.to_str())
             .map(|s| s.to_string());
-        
-        for (idx, result) in reader.records().enumerate() {
-            let record = match result {
-                Ok(r) => r,
-                Err(e) => {
-                    tracing::warn!("Erro na linha {}: {}", idx + 1, e);
-                    continue;
-                }
-            };
-            
-            // Pula linhas vazias ou com dados insuficientes
-            if record.len() < 10 {
-                continue;
-            }
-            
-            // Coluna 0: Número
-            let numero = record.get(0).unwrap_or("").trim();
-            if numero.is_empty() {
-                continue;
+
+        Ok(Box::new(reader.into_records().filter_map(move |result| match result {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line()).unwrap_or(0);
+                self.parse_record(&record, &source_file).map(|parsed| match parsed {
+                    Ok(mut order) => {
+                        order.linhagem = Some(Lineage::at_line(line));
+                        Ok(StreamedRow::Record(Streamed { line, record: order }))
+                    }
+                    Err(reason) => Ok(StreamedRow::Rejected(RejectedRow { line, reason })),
+                })
             }
-            
-            // Colunas principais (baseado no CSV real)
-            let data_agendamento = record.get(1).and_then(|s| self.parse_date(s));
-            let data_emissao = record.get(2).and_then(|s| self.parse_date(s));
-            let notas_fiscais = record.get(3).map(|s| self.split_notas(s)).unwrap_or_default();
-            let pagador_nome = record.get(4).unwrap_or("").trim().to_string();
-            let pagador_telefone = record.get(5).map(|s| s.trim().to_string());
-            let remetente_nome = record.get(6).unwrap_or("").trim().to_string();
-            let remetente_cidade = record.get(7).unwrap_or("").trim().to_string();
-            let destinatario_nome = record.get(8).unwrap_or("").trim().to_string();
-            let destinatario_cidade = record.get(9).unwrap_or("").trim().to_string();
-            
-            if pagador_nome.is_empty() || remetente_nome.is_empty() || destinatario_nome.is_empty() {
-                continue;
+            Err(e) if e.is_io_error() => {
+                Some(Err(pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e))))
             }
-            
-            let volumes = record.get(10).map(|s| self.parse_int(s)).unwrap_or(0);
-            let peso = record.get(11).map(|s| self.parse_float(s)).unwrap_or(0.0);
-            let valor_notas = record.get(12).map(|s| self.parse_float(s)).unwrap_or(0.0);
-            let valor_frete = record.get(13).map(|s| self.parse_float(s)).unwrap_or(0.0);
-            let frete_tabelado = record.get(14).map(|s| self.parse_float(s));
-            
-            let filial_coleta = record.get(26).map(|s| s.trim().to_string());
-            let motorista_coleta = record.get(27).map(|s| s.trim().to_string());
-            let filial_entrega = record.get(28).map(|s| s.trim().to_string());
-            let motorista_entrega = record.get(29).map(|s| s.trim().to_string());
-            
-            let Some(data_emissao) = data_emissao else {
-                tracing::warn!("Ordem {} sem data de emissão", numero);
-                continue;
-            };
-            
-            let mut order = FreightOrder::new(
-                numero.to_string(),
-                data_emissao,
-                pagador_nome,
-                remetente_nome,
-                remetente_cidade,
-                destinatario_nome,
-                destinatario_cidade,
-            );
-            
-            order.data_agendamento = data_agendamento;
-            order.notas_fiscais = notas_fiscais;
-            order.pagador_telefone = pagador_telefone;
-            order.volumes = volumes;
-            order.peso = peso;
-            order.valor_notas = valor_notas;
-            order.valor_frete = valor_frete;
-            order.frete_tabelado = frete_tabelado;
-            order.filial_coleta = filial_coleta;
-            order.motorista_coleta = motorista_coleta;
-            order.filial_entrega = filial_entrega;
-            order.motorista_entrega = motorista_entrega;
-            order.fonte_arquivo = source_file.clone();
-            
-            orders.push(order);
-        }
+            Err(e) => Some(Ok(StreamedRow::Rejected(RejectedRow {
+                line: e.position().map(|p| p.line()).unwrap_or(0),
+                reason: e.to_string(),
+            }))),
+        })))
+    }
+}
+
+impl Importer for FreightOrderImporter {
+    type Output = FreightOrder;
+    
+    fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
+        let orders = self
+            .stream(path)?
+            .map(|item| item.map(|s| s.record))
+            .collect::<Result<Vec<_>>>()?;
         
         tracing::info!("Importadas {} ordens de frete", orders.len());
         Ok(orders)
@@ -192,9 +227,11 @@ mod tests {
     }
     
     #[test]
-    fn test_parse_float() {
+    fn test_parse_numbers() {
         let importer = FreightOrderImporter::new();
-        assert_eq!(importer.parse_float("R$ 1.234,56"), 1234.56);
-        assert_eq!(importer.parse_float("90"), 90.0);
+        assert_eq!(importer.parse_float("1.234,5"), 1234.5);
+        assert_eq!(importer.parse_money("R$ 1.234,56"), Some(Money::from_cents(123_456)));
+        assert_eq!(importer.parse_money("90"), Some(Money::from_reais(90)));
+        assert_eq!(importer.parse_money(""), None);
     }
 }
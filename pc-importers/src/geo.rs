@@ -0,0 +1,274 @@
+//! Coordenadas de cidades, municípios e distâncias
+//!
+//! Tabela embutida com as cidades atendidas pela operação (regiões de
+//! Ribeirão Preto, Franca e Central), usada para comparar a posição GPS
+//! de fotos com a cidade de destino das ordens de frete e para padronizar
+//! a grafia das cidades nas planilhas de rotas.
+
+use crate::csv_source;
+use crate::text::normalize_text;
+use pc_core::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Similaridade mínima para aceitar um nome com erro de digitação
+const MIN_SIMILARITY: f64 = 0.9;
+
+/// Raio médio da Terra, em km
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+const UFS: &[&str] = &[
+    "AC", "AL", "AM", "AP", "BA", "CE", "DF", "ES", "GO", "MA", "MG", "MS", "MT", "PA",
+    "PB", "PE", "PI", "PR", "RJ", "RN", "RO", "RR", "RS", "SC", "SE", "SP", "TO",
+];
+
+/// (cidade, UF, latitude, longitude)
+const CITIES: &[(&str, &str, f64, f64)] = &[
+    ("Ribeirão Preto", "SP", -21.1775, -47.8103),
+    ("Franca", "SP", -20.5386, -47.4008),
+    ("Sertãozinho", "SP", -21.1378, -47.9903),
+    ("Batatais", "SP", -20.8911, -47.5850),
+    ("Jardinópolis", "SP", -21.0178, -47.7639),
+    ("Cravinhos", "SP", -21.3403, -47.7294),
+    ("Serrana", "SP", -21.2114, -47.5953),
+    ("Brodowski", "SP", -20.9856, -47.6583),
+    ("Pontal", "SP", -21.0228, -48.0372),
+    ("Cajuru", "SP", -21.2753, -47.3036),
+    ("Altinópolis", "SP", -21.0214, -47.3742),
+    ("Orlândia", "SP", -20.7203, -47.8867),
+    ("Sales Oliveira", "SP", -20.7697, -47.8378),
+    ("Nuporanga", "SP", -20.7306, -47.7428),
+    ("Morro Agudo", "SP", -20.7314, -48.0578),
+    ("São Joaquim da Barra", "SP", -20.5811, -47.8547),
+    ("Ituverava", "SP", -20.3394, -47.7806),
+    ("Igarapava", "SP", -20.0386, -47.7469),
+    ("Guaíra", "SP", -20.3181, -48.3106),
+    ("Patrocínio Paulista", "SP", -20.6394, -47.2817),
+    ("Pedregulho", "SP", -20.2569, -47.4767),
+    ("Bebedouro", "SP", -20.9494, -48.4792),
+    ("Barretos", "SP", -20.5572, -48.5678),
+    ("Jaboticabal", "SP", -21.2550, -48.3222),
+    ("Monte Alto", "SP", -21.2614, -48.4967),
+    ("Matão", "SP", -21.6031, -48.3658),
+    ("Araraquara", "SP", -21.7944, -48.1756),
+    ("Américo Brasiliense", "SP", -21.7281, -48.1147),
+    ("São Carlos", "SP", -22.0175, -47.8908),
+    ("Mococa", "SP", -21.4678, -47.0047),
+    ("São José do Rio Pardo", "SP", -21.5956, -46.8886),
+    ("Araras", "SP", -22.3572, -47.3842),
+    ("Rio Claro", "SP", -22.4114, -47.5614),
+    ("Limeira", "SP", -22.5647, -47.4017),
+    ("Piracicaba", "SP", -22.7253, -47.6492),
+    ("Campinas", "SP", -22.9056, -47.0608),
+    ("São Paulo", "SP", -23.5505, -46.6333),
+    ("São José do Rio Preto", "SP", -20.8113, -49.3758),
+    ("Uberaba", "MG", -19.7472, -47.9381),
+    ("Uberlândia", "MG", -18.9186, -48.2772),
+];
+
+/// Localiza cidades pelo nome, ignorando acentos, caixa e sufixo de UF
+pub struct CityLocator {
+    cities: HashMap<String, (f64, f64)>,
+}
+
+impl CityLocator {
+    pub fn new() -> Self {
+        let mut locator = Self {
+            cities: HashMap::new(),
+        };
+        for (name, _, latitude, longitude) in CITIES {
+            locator = locator.with_city(name, *latitude, *longitude);
+        }
+        locator
+    }
+
+    /// Acrescenta (ou corrige) uma cidade
+    pub fn with_city(mut self, name: &str, latitude: f64, longitude: f64) -> Self {
+        self.cities.insert(normalize_city(name), (latitude, longitude));
+        self
+    }
+
+    /// Latitude e longitude da cidade, se conhecida
+    pub fn locate(&self, city: &str) -> Option<(f64, f64)> {
+        self.cities.get(&normalize_city(city)).copied()
+    }
+}
+
+impl Default for CityLocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lista de municípios com a grafia oficial, para padronizar nomes de cidades
+#[derive(Debug, Clone)]
+pub struct Municipalities {
+    /// (nome normalizado, UF) -> grafia oficial; sem UF vale para qualquer uma
+    names: HashMap<(String, Option<&'static str>), String>,
+}
+
+impl Municipalities {
+    /// Lista com as cidades da tabela embutida
+    pub fn new() -> Self {
+        CITIES
+            .iter()
+            .fold(Self { names: HashMap::new() }, |list, (name, uf, _, _)| list.with_name(&format!("{}/{}", name, uf)))
+    }
+
+    /// Acrescenta um município, com a UF no final ("Guará/SP") ou sem ela
+    pub fn with_name(mut self, name: &str) -> Self {
+        let (name, uf) = split_uf(name);
+        self.names.insert((normalize_city(name), uf), name.to_string());
+        self
+    }
+
+    /// Acrescenta os municípios de um arquivo com um por linha, como
+    /// "Franca/SP" ou "Franca;SP" (ex.: a lista do IBGE exportada da planilha)
+    pub fn load<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler {:?}: {}", path, e)))?;
+        let (content, _) = csv_source::decode(&bytes, csv_source::detect_encoding(&bytes));
+        for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let mut fields = line.split([';', ',', '\t']).map(str::trim);
+            let name = fields.next().unwrap_or_default();
+            self = match fields.next() {
+                Some(uf) if UFS.iter().any(|u| u.eq_ignore_ascii_case(uf)) => self.with_name(&format!("{}/{}", name, uf)),
+                // Cabeçalho ("Município;UF") ou linha sem UF válida
+                Some(_) => continue,
+                None => self.with_name(name),
+            };
+        }
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Grafia oficial da cidade, tolerando acentos, caixa e pequenos erros de
+    /// digitação; com a UF no nome, só os municípios daquela UF são candidatos
+    pub fn canonical(&self, city: &str) -> Option<&str> {
+        let (name, uf) = split_uf(city);
+        let key = normalize_city(name);
+        if uf.is_some() {
+            let exact = self.names.get(&(key.clone(), uf)).or_else(|| self.names.get(&(key.clone(), None)));
+            if let Some(name) = exact {
+                return Some(name);
+            }
+        }
+
+        self.names
+            .iter()
+            .filter(|((_, entry_uf), _)| uf.is_none() || entry_uf.is_none() || *entry_uf == uf)
+            .map(|((candidate, _), name)| (strsim::normalized_levenshtein(&key, candidate), name))
+            .filter(|(score, _)| *score >= MIN_SIMILARITY)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+impl Default for Municipalities {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// "Ribeirão Preto/SP", "RIBEIRAO PRETO - SP" e "Ribeirão  Preto" viram "RIBEIRAO PRETO"
+pub fn normalize_city(name: &str) -> String {
+    let normalized = normalize_text(name);
+    let mut words: Vec<&str> = normalized
+        .split(|c: char| c.is_whitespace() || c == '/' || c == '-' || c == '(' || c == ')')
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // Remove a UF do final, se houver
+    if words.len() > 1 && words.last().is_some_and(|w| UFS.contains(w)) {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// Separa a UF do final do nome: "Franca/SP" e "Franca - SP" viram ("Franca", Some("SP"))
+pub fn split_uf(city: &str) -> (&str, Option<&'static str>) {
+    let trimmed = city.trim().trim_end_matches(')');
+    let Some((rest, last)) = trimmed.len().checked_sub(2).and_then(|i| Some((trimmed.get(..i)?, trimmed.get(i..)?))) else {
+        return (city.trim(), None);
+    };
+
+    let uf = UFS.iter().find(|uf| uf.eq_ignore_ascii_case(last));
+    match uf {
+        Some(uf) if rest.ends_with(['/', '-', ' ', '(']) && !rest.trim().is_empty() => {
+            (rest.trim_end_matches(['/', '-', ' ', '(']).trim(), Some(*uf))
+        }
+        _ => (city.trim(), None),
+    }
+}
+
+/// Distância em km entre dois pontos (fórmula de haversine)
+pub fn distance_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_ignores_accents_and_state() {
+        let locator = CityLocator::new();
+        assert_eq!(locator.locate("Ribeirão Preto"), locator.locate("RIBEIRAO PRETO/SP"));
+        assert_eq!(locator.locate("Franca - SP"), Some((-20.5386, -47.4008)));
+        assert!(locator.locate("Cidade Inexistente").is_none());
+
+        assert_eq!(split_uf("Franca/SP"), ("Franca", Some("SP")));
+        assert_eq!(split_uf("Uberaba - mg"), ("Uberaba", Some("MG")));
+        assert_eq!(split_uf("Matão"), ("Matão", None));
+    }
+
+    #[test]
+    fn test_municipalities_canonical() {
+        let municipalities = Municipalities::new().with_name("Guará/SP");
+        assert_eq!(municipalities.canonical("SERTAOZINHO"), Some("Sertãozinho"));
+        assert_eq!(municipalities.canonical("São Joaquim da Barra - SP"), Some("São Joaquim da Barra"));
+        assert_eq!(municipalities.canonical("Jardinopoli"), Some("Jardinópolis"));
+        assert_eq!(municipalities.canonical("guara"), Some("Guará"));
+        assert!(municipalities.canonical("Franco").is_none());
+    }
+
+    #[test]
+    fn test_municipalities_by_state() {
+        let path = std::env::temp_dir().join(format!("municipios-{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "Município;UF\nGuaíra;PR\nSanta Rita do Passa Quatro;SP\nPalmeira;\n").unwrap();
+        let municipalities = Municipalities::new().load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(municipalities.len(), CITIES.len() + 2);
+        assert_eq!(municipalities.canonical("santa rita do passa quatro - SP"), Some("Santa Rita do Passa Quatro"));
+        assert_eq!(municipalities.canonical("GUAIRA/PR"), Some("Guaíra"));
+        // Com a UF, só os municípios dela são candidatos, mesmo com erro de digitação
+        assert_eq!(municipalities.canonical("Jardinopoli/SP"), Some("Jardinópolis"));
+        assert!(municipalities.canonical("Jardinopolis/MG").is_none());
+        assert!(municipalities.canonical("Uberaba/SP").is_none());
+    }
+
+    #[test]
+    fn test_distance_km() {
+        let locator = CityLocator::new();
+        let ribeirao = locator.locate("Ribeirão Preto").unwrap();
+        let franca = locator.locate("Franca").unwrap();
+
+        // ~85 km em linha reta
+        let distance = distance_km(ribeirao, franca);
+        assert!((80.0..90.0).contains(&distance), "{}", distance);
+        assert_eq!(distance_km(franca, franca), 0.0);
+    }
+}
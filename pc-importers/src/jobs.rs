@@ -0,0 +1,121 @@
+//! Acompanhamento das importações
+//!
+//! Cada importação vira um [`ImportJob`] no pc-db, atualizado a cada lote
+//! gravado. O cancelamento é pedido no job (pela CLI ou pela API) e
+//! atendido pelo importador no lote seguinte.
+
+use crate::streaming::RejectedRow;
+use pc_core::Result;
+use pc_db::{GenericRepository, Repository};
+use pc_models::ImportJob;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Usuário do sistema operacional, para registrar quem disparou a importação
+pub fn current_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|u| !u.trim().is_empty())
+}
+
+/// Job em andamento, gravado no pc-db a cada atualização
+pub struct JobTracker<'a> {
+    jobs: &'a Repository<ImportJob>,
+    job: ImportJob,
+}
+
+impl<'a> JobTracker<'a> {
+    /// Registra o job e o marca como em processamento
+    pub async fn start(
+        jobs: &'a Repository<ImportJob>,
+        arquivo: &Path,
+        importador: &str,
+        iniciado_por: Option<String>,
+    ) -> Result<Self> {
+        let mut job = ImportJob::new(arquivo.display().to_string(), importador.to_string(), iniciado_por);
+        job.start();
+        jobs.save(&job).await?;
+        tracing::info!("Job {} iniciado: {} ({})", job.id, job.arquivo, job.importador);
+        Ok(Self { jobs, job })
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.job.id
+    }
+
+    pub fn job(&self) -> &ImportJob {
+        &self.job
+    }
+
+    /// Importador escolhido depois da detecção do tipo do arquivo
+    pub fn set_importer(&mut self, importador: &str) {
+        self.job.importador = importador.to_string();
+    }
+
+    /// Linha descartada; entra no relatório gravado com o próximo lote
+    pub fn reject(&mut self, rejected: &RejectedRow) {
+        self.job.reject(rejected.line, rejected.reason.clone());
+    }
+
+    /// Soma um lote gravado e salva o progresso
+    pub async fn record_batch(&mut self, novos: usize, atualizados: usize, inalterados: usize) -> Result<()> {
+        self.job.record_batch(novos, atualizados, inalterados);
+        // Não sobrescreve um cancelamento gravado por outro processo
+        self.refresh_cancel().await?;
+        self.jobs.save(&self.job).await
+    }
+
+    /// Falha com [`pc_core::Error::Cancelled`] se o cancelamento foi pedido
+    pub async fn check_cancelled(&mut self) -> Result<()> {
+        self.refresh_cancel().await?;
+        if self.job.cancelamento_solicitado {
+            return Err(pc_core::Error::Cancelled(format!("Importação de {} cancelada", self.job.arquivo)));
+        }
+        Ok(())
+    }
+
+    async fn refresh_cancel(&mut self) -> Result<()> {
+        if let Some(stored) = self.jobs.refresh(&self.job.id).await? {
+            self.job.cancelamento_solicitado |= stored.cancelamento_solicitado;
+        }
+        Ok(())
+    }
+
+    /// Registra o resultado final da importação e devolve o job
+    pub async fn finish<T>(mut self, result: &Result<T>) -> Result<ImportJob> {
+        match result {
+            Ok(_) => self.job.complete(),
+            Err(pc_core::Error::Cancelled(_)) => self.job.cancelled(),
+            Err(e) => self.job.fail(e.to_string()),
+        }
+        self.jobs.save(&self.job).await?;
+        tracing::info!("Job {} terminou: {}", self.job.id, self.job.status);
+        Ok(self.job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_core::Status;
+
+    #[tokio::test]
+    async fn test_cancel_is_seen_before_next_batch() {
+        let jobs = Repository::<ImportJob>::new("import_jobs");
+        let mut tracker = JobTracker::start(&jobs, Path::new("Fretes.csv"), "freight", Some("joao".into()))
+            .await
+            .unwrap();
+        tracker.check_cancelled().await.unwrap();
+        tracker.record_batch(10, 0, 0).await.unwrap();
+
+        jobs.request_cancel(&tracker.id()).await.unwrap();
+        let result = tracker.check_cancelled().await;
+        assert!(matches!(result, Err(pc_core::Error::Cancelled(_))));
+
+        let job = tracker.finish(&result).await.unwrap();
+        assert_eq!(job.status, Status::Cancelled);
+        assert_eq!(job.novos, 10);
+        assert_eq!(jobs.find_by_id(&job.id).await.unwrap().unwrap().status, Status::Cancelled);
+    }
+}
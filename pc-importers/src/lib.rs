@@ -6,8 +6,51 @@ pub mod freight_importer;
 pub mod timesheet_importer;
 pub mod route_importer;
 pub mod photo_importer;
+pub mod photo_linking;
 pub mod excel_importer;
-pub mod pdf_importer;use pc_core::Result;
+pub mod pdf_importer;
+pub mod afd_importer;
+pub mod edi;
+pub mod detect;
+pub mod csv_source;
+pub mod streaming;
+pub mod bulk;
+pub mod dry_run;
+pub mod watch;
+pub mod geo;
+pub mod dedup;
+pub mod company_resolution;
+pub mod profile;
+pub mod jobs;
+pub mod export;
+pub mod bank_statement;
+pub mod reconciliation;
+pub mod cnab;
+pub mod archive;
+pub mod fiscal_xml;
+pub mod registry;
+pub mod lineage;
+pub mod text;
+
+pub use archive::{ArchiveContents, ArchiveEntry, ArchiveImporter};
+pub use dedup::{DuplicateGroup, DuplicateKind};
+pub use detect::{AnyImporter, Detection, FileKind, ImportOutput};
+pub use company_resolution::{CompanyResolution, CompanyResolver};
+pub use bank_statement::BankStatementImporter;
+pub use reconciliation::{PaymentMatcher, ReconciliationSummary};
+pub use bulk::{BulkImport, BulkProgress, BulkSummary};
+pub use export::{ExportFormat, Exporter, RecordFilter};
+pub use fiscal_xml::FiscalXmlImporter;
+pub use dry_run::{dry_run, dry_run_any, DryRunReport};
+pub use jobs::JobTracker;
+pub use lineage::{LineageReport, LineageStamp};
+pub use profile::{ProfileReport, Profiler};
+pub use registry::{FileSample, ImportOptions, ImporterRegistry, RegisteredImporter, SelectedImporter};
+pub use photo_linking::{LinkSuggestion, PhotoLinker};
+pub use streaming::{import_in_batches, import_in_batches_tracked, StreamingImporter};
+pub use watch::{FolderWatcher, WatchReport, WatchStatus};
+
+use pc_core::Result;
 use std::path::Path;
 
 /// Trait para importadores genéricos
@@ -20,25 +63,3 @@ pub trait Importer {
     /// Valida se o arquivo é compatível com este importador
     fn can_import<P: AsRef<Path>>(&self, path: P) -> bool;
 }
-
-/// Detecta automaticamente o tipo de arquivo e retorna o importador apropriado
-pub fn auto_detect_importer<P: AsRef<Path>>(path: P) -> Result<Box<dyn std::any::Any>> {
-    let path = path.as_ref();
-    let filename = path.file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| pc_core::Error::Import("Nome de arquivo inválido".into()))?;
-
-    // Detecta por nome do arquivo
-    if filename.contains("Horas") || filename.contains("Ponto") {
-        Ok(Box::new(timesheet_importer::TimesheetImporter::new()))
-    } else if filename.contains("Rotas") {
-        Ok(Box::new(route_importer::RouteImporter::new()))
-    } else if filename.contains("-04") || filename.contains("Planilha") {
-        Ok(Box::new(freight_importer::FreightOrderImporter::new()))
-    } else {
-        Err(pc_core::Error::Import(format!(
-            "Não foi possível detectar o tipo do arquivo: {}",
-            filename
-        )))
-    }
-}
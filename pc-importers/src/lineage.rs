@@ -0,0 +1,346 @@
+//! Linhagem dos registros importados
+//!
+//! Os importadores anotam a linha (ou a coluna) de cada registro; ao gravar,
+//! o arquivo é copiado para o blob store e a linhagem recebe o hash, o
+//! caminho, o importador e o job. Com a cópia, a linha original pode ser
+//! mostrada mesmo depois que o arquivo foi movido ou sobrescrito.
+
+use crate::csv_source;
+use crate::detect::{self, AnyImporter, FileKind, ImportOutput};
+use crate::excel_importer;
+use pc_core::{NaturalKey, Result};
+use pc_db::{GenericRepository, PersonalControllerDb, Repository};
+use pc_models::{HasLineage, Lineage};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Versão registrada na linhagem pelos importadores embutidos
+pub const IMPORTER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Dados da linhagem comuns a todos os registros de um arquivo
+#[derive(Debug, Clone)]
+pub struct LineageStamp {
+    pub sha256: String,
+    pub caminho: String,
+    pub importador: String,
+    pub versao: String,
+    pub job_id: Option<Uuid>,
+}
+
+impl LineageStamp {
+    pub fn new(sha256: impl Into<String>, caminho: impl Into<String>, importador: &str, versao: &str) -> Self {
+        Self {
+            sha256: sha256.into(),
+            caminho: caminho.into(),
+            importador: importador.to_string(),
+            versao: versao.to_string(),
+            job_id: None,
+        }
+    }
+
+    /// Linhagem de um arquivo em disco, pelo caminho absoluto
+    pub fn for_file(path: &Path, sha256: impl Into<String>, importador: &str, versao: &str) -> Self {
+        let caminho = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        Self::new(sha256, caminho.display().to_string(), importador, versao)
+    }
+
+    pub fn with_job(mut self, job_id: Option<Uuid>) -> Self {
+        self.job_id = job_id;
+        self
+    }
+
+    /// Completa a linhagem anotada pelo importador (ou cria a do arquivo inteiro)
+    pub fn apply<T: HasLineage>(&self, entity: &mut T) {
+        let linhagem = entity.linhagem_mut().get_or_insert_with(Lineage::new);
+        linhagem.arquivo_sha256 = self.sha256.clone();
+        linhagem.caminho = self.caminho.clone();
+        linhagem.importador = self.importador.clone();
+        linhagem.versao_importador = self.versao.clone();
+        linhagem.job_id = self.job_id;
+        linhagem.importado_em = chrono::Utc::now();
+    }
+
+    pub fn apply_all<T: HasLineage>(&self, entities: &mut [T]) {
+        entities.iter_mut().for_each(|e| self.apply(e));
+    }
+}
+
+impl ImportOutput {
+    /// Completa a linhagem de todos os registros lidos do arquivo
+    ///
+    /// Fotos e PDFs viram documentos. Os arquivos extraídos de ZIP ou e-mail
+    /// recebem a linhagem própria: hash, caminho dentro do contêiner e o
+    /// importador que os leu, mantendo a versão e o job do contêiner.
+    pub fn with_lineage(self, stamp: &LineageStamp) -> Self {
+        match self {
+            ImportOutput::FreightOrders(mut orders) => {
+                stamp.apply_all(&mut orders);
+                ImportOutput::FreightOrders(orders)
+            }
+            ImportOutput::Timesheets(mut timesheets) => {
+                stamp.apply_all(&mut timesheets);
+                ImportOutput::Timesheets(timesheets)
+            }
+            ImportOutput::Routes(mut routes) => {
+                stamp.apply_all(&mut routes);
+                ImportOutput::Routes(routes)
+            }
+            output @ (ImportOutput::Photos(_) | ImportOutput::Pdfs(_) | ImportOutput::Documents(_)) => {
+                let mut documents = output.to_documents().unwrap_or_default();
+                stamp.apply_all(&mut documents);
+                ImportOutput::Documents(documents)
+            }
+            ImportOutput::Archive(mut contents) => {
+                for entry in &mut contents.entries {
+                    let importador = AnyImporter::for_kind(entry.kind).map(|i| i.name()).unwrap_or(entry.kind.as_str());
+                    let entry_stamp = LineageStamp::new(entry.sha256.clone(), entry.origem.caminho.clone(), importador, &stamp.versao)
+                        .with_job(stamp.job_id);
+                    if let Ok(output) = std::mem::replace(&mut entry.output, Err(String::new())) {
+                        entry.output = Ok(output.with_lineage(&entry_stamp));
+                    }
+                }
+                ImportOutput::Archive(contents)
+            }
+            other => other,
+        }
+    }
+}
+
+/// Coluna e valor da linha original
+#[derive(Debug, Clone, Serialize)]
+pub struct CampoOriginal {
+    pub coluna: String,
+    pub valor: String,
+}
+
+/// Linhagem de um registro, com a linha original do arquivo
+#[derive(Debug, Clone, Serialize)]
+pub struct LineageReport {
+    /// Collection do registro
+    pub entidade: String,
+    pub id: Uuid,
+    pub chave: String,
+    pub linhagem: Lineage,
+    /// `false` se a cópia do arquivo não está no blob store
+    pub arquivo_disponivel: bool,
+    /// Primeira linha do arquivo (cabeçalho das planilhas; no Excel, da
+    /// aba); vazio para PDFs, imagens e ZIPs
+    pub cabecalho: Option<String>,
+    /// Linha de onde o registro foi lido, como está no arquivo
+    pub linha_original: Option<String>,
+    /// Linha original separada pelas colunas do cabeçalho
+    pub campos: Vec<CampoOriginal>,
+}
+
+/// Collection pelo nome usado na CLI e na API
+fn collection_name(entity: &str) -> Option<&'static str> {
+    match entity.replace('-', "_").as_str() {
+        "freight_orders" | "freight" => Some("freight_orders"),
+        "timesheets" | "timesheet" => Some("timesheets"),
+        "routes" | "route" => Some("routes"),
+        "documents" | "document" => Some("documents"),
+        "payments" | "payment" => Some("payments"),
+        _ => None,
+    }
+}
+
+/// Busca o registro pelo UUID ou pela chave natural e monta a linhagem
+///
+/// `entity` aceita o nome da collection (`freight_orders`, `freight-orders`)
+/// ou do importador (`freight`).
+pub async fn lookup(db: &PersonalControllerDb, entity: &str, id: &str) -> Result<LineageReport> {
+    let collection = collection_name(entity).ok_or_else(|| {
+        pc_core::Error::Validation(format!(
+            "Entidade sem linhagem: {} (use freight_orders, timesheets, routes, documents ou payments)",
+            entity
+        ))
+    })?;
+
+    let found = match collection {
+        "freight_orders" => find(&db.freight_orders, id).await?,
+        "timesheets" => find(&db.timesheets, id).await?,
+        "routes" => find(&db.routes, id).await?,
+        "documents" => find(&db.documents, id).await?,
+        _ => find(&db.payments, id).await?,
+    };
+    let (id, chave, linhagem) =
+        found.ok_or_else(|| pc_core::Error::NotFound(format!("{} {} não encontrado", collection, id)))?;
+    let linhagem = linhagem.ok_or_else(|| {
+        pc_core::Error::NotFound(format!(
+            "{} {} não tem linhagem (importado antes do registro de procedência)",
+            collection, chave
+        ))
+    })?;
+
+    let mut report = LineageReport {
+        entidade: collection.to_string(),
+        id,
+        chave,
+        arquivo_disponivel: false,
+        cabecalho: None,
+        linha_original: None,
+        campos: Vec::new(),
+        linhagem,
+    };
+
+    if let Some(content) = db.blobs.get(&report.linhagem.arquivo_sha256)? {
+        report.arquivo_disponivel = true;
+        if is_binary(&report.linhagem.caminho, &content) {
+            // Das planilhas Excel, a linha sai da aba gravada na linhagem
+            if let (Some(planilha), Some(linha)) = (&report.linhagem.planilha, report.linhagem.linha) {
+                if let Some((cabecalho, celulas)) = excel_importer::sheet_row(&content, planilha, linha) {
+                    if celulas != cabecalho {
+                        report.campos = cabecalho
+                            .iter()
+                            .zip(&celulas)
+                            .map(|(coluna, valor)| CampoOriginal { coluna: coluna.clone(), valor: valor.clone() })
+                            .collect();
+                    }
+                    report.cabecalho = Some(cabecalho.join(";"));
+                    report.linha_original = Some(celulas.join(";"));
+                }
+            }
+            return Ok(report);
+        }
+        let (text, _) = csv_source::decode(&content, csv_source::detect_encoding(&content));
+        let mut lines = text.lines();
+        report.cabecalho = lines.next().map(|l| l.trim_end_matches('\r').to_string());
+        report.linha_original = report
+            .linhagem
+            .linha
+            .and_then(|linha| text.lines().nth((linha as usize).checked_sub(1)?))
+            .map(|l| l.trim_end_matches('\r').to_string());
+        if let (Some(cabecalho), Some(linha)) = (&report.cabecalho, &report.linha_original) {
+            if report.linhagem.linha != Some(1) {
+                report.campos = split_fields(cabecalho, linha);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Arquivos sem linhas de texto para mostrar
+fn is_binary(caminho: &str, content: &[u8]) -> bool {
+    matches!(
+        detect::sniff(Path::new(caminho), content).kind,
+        FileKind::Pdf | FileKind::Image | FileKind::Excel | FileKind::Archive
+    )
+}
+
+async fn find<T>(repository: &Repository<T>, id: &str) -> Result<Option<(Uuid, String, Option<Lineage>)>>
+where
+    T: NaturalKey + HasLineage + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let entity = match Uuid::parse_str(id) {
+        Ok(uuid) => repository.find_by_id(&uuid).await?,
+        Err(_) => repository.find_by_natural_key(id).await?,
+    };
+    Ok(entity.map(|e| (*e.id(), e.natural_key(), e.linhagem().cloned())))
+}
+
+/// Pareia a linha com o cabeçalho, usando o delimitador do arquivo
+fn split_fields(cabecalho: &str, linha: &str) -> Vec<CampoOriginal> {
+    let content = format!("{}\n{}\n", cabecalho, linha);
+    let delimiter = csv_source::sniff_delimiter(&content);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let Ok(headers) = reader.headers().cloned() else {
+        return Vec::new();
+    };
+    let Some(Ok(record)) = reader.records().next() else {
+        return Vec::new();
+    };
+    // Arquivos de largura fixa não têm delimitador: nada a parear
+    if headers.len() < 2 {
+        return Vec::new();
+    }
+
+    headers
+        .iter()
+        .zip(record.iter())
+        .map(|(coluna, valor)| CampoOriginal {
+            coluna: coluna.trim().to_string(),
+            valor: valor.trim().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{import_file, ImportOptions, ImporterRegistry};
+    use crate::JobTracker;
+
+    #[tokio::test]
+    async fn test_lookup_shows_original_row() {
+        let path = std::env::temp_dir().join(format!("Horas-{}.csv", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "funcionario;mes;data;entrada;saida\nJoão;Abril;01/04/2025;08:00;17:00\nMaria;Abril;01/04/2025;09:00;18:00\n",
+        )
+        .unwrap();
+
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+        let importer = ImporterRegistry::default().detect(&path).unwrap().importer;
+        let mut job = JobTracker::start(&db.import_jobs, &path, importer.name(), None).await.unwrap();
+        import_file(importer.as_ref(), &db, &path, &ImportOptions::default(), &mut job, |_| {})
+            .await
+            .unwrap();
+        let job_id = job.id();
+        std::fs::remove_file(&path).unwrap();
+
+        let maria = db.timesheets.find_all().await.unwrap().into_iter().find(|t| t.funcionario == "Maria").unwrap();
+        let report = lookup(&db, "timesheets", &maria.id.to_string()).await.unwrap();
+
+        assert_eq!(report.linhagem.linha, Some(3));
+        assert_eq!(report.linhagem.importador, "timesheet");
+        assert_eq!(report.linhagem.job_id, Some(job_id));
+        assert_eq!(report.linhagem.arquivo_sha256.len(), 64);
+        assert!(report.arquivo_disponivel);
+        assert_eq!(report.linha_original.as_deref(), Some("Maria;Abril;01/04/2025;09:00;18:00"));
+        assert_eq!(report.campos[4].coluna, "saida");
+        assert_eq!(report.campos[4].valor, "18:00");
+
+        assert!(matches!(lookup(&db, "companies", "1").await, Err(pc_core::Error::Validation(_))));
+        assert!(matches!(lookup(&db, "timesheets", "ninguém").await, Err(pc_core::Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_archive_entries_keep_their_own_lineage() {
+        let dir = std::env::temp_dir().join(format!("pc-lineage-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lote.zip");
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.start_file("ponto/abril.csv", zip::write::SimpleFileOptions::default()).unwrap();
+        std::io::Write::write_all(&mut writer, b"funcionario;mes;data;entrada;saida\nJo\xc3\xa3o;Abril;01/04/2025;08:00;17:00\n").unwrap();
+        std::fs::write(&path, writer.finish().unwrap().into_inner()).unwrap();
+
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+        let importer = ImporterRegistry::default().detect(&path).unwrap().importer;
+        let mut job = JobTracker::start(&db.import_jobs, &path, importer.name(), None).await.unwrap();
+        import_file(importer.as_ref(), &db, &path, &ImportOptions::default(), &mut job, |_| {})
+            .await
+            .unwrap();
+
+        let timesheet = db.timesheets.find_all().await.unwrap().remove(0);
+        let report = lookup(&db, "timesheet", &timesheet.id.to_string()).await.unwrap();
+        assert!(report.linhagem.caminho.ends_with("lote.zip/ponto/abril.csv"));
+        assert_eq!(report.linhagem.importador, "timesheet");
+        assert_eq!(report.linhagem.job_id, Some(job.id()));
+        assert_eq!(report.linha_original.as_deref(), Some("João;Abril;01/04/2025;08:00;17:00"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_binary_sources_have_no_text_lines() {
+        assert!(is_binary("/backup/Fretes.xlsx", b"PK\x03\x04\x14\0\0\0xl/workbook.xml"));
+        assert!(is_binary("/backup/cte.pdf", b"%PDF-1.7\n"));
+        assert!(!is_binary("/backup/Horas.csv", "funcionario;mes;data\nJoão;Abril;01/04/2025\n".as_bytes()));
+    }
+}
@@ -1,5 +1,6 @@
 //! PDF document importer with text extraction capabilities
 
+use crate::dedup::{self, DuplicateGroup, Fingerprint};
 use pc_core::Result;
 use pc_models::Document;
 use std::path::{Path, PathBuf};
@@ -46,19 +47,43 @@ impl PdfImporter {
 
         // Detect document type from filename
         let doc_type = self.detect_document_type(&filename);
+        let pages = self.extract_pages(path);
+        let text_content = pages
+            .as_ref()
+            .map(|pages| pages.join("\n"))
+            .filter(|text| !text.trim().is_empty());
 
         Ok(PdfMetadata {
             id: Uuid::new_v4(),
             path: path.to_path_buf(),
             filename,
             file_size,
+            sha256: pc_core::hash::sha256_file(path)?,
+            text_hash: text_content.as_deref().and_then(dedup::text_hash),
+            locations: vec![path.to_path_buf()],
             document_type: doc_type,
             imported_at: Utc::now(),
-            page_count: None, // TODO: Extract actual page count
-            text_content: None, // TODO: Extract text using pdf-extract or similar
+            page_count: pages.map(|pages| pages.len()),
+            text_content,
         })
     }
 
+    /// Extract the text of each page (None for encrypted or malformed PDFs)
+    fn extract_pages(&self, path: &Path) -> Option<Vec<String>> {
+        // pdf-extract panics on some malformed files
+        match std::panic::catch_unwind(|| pdf_extract::extract_text_by_pages(path)) {
+            Ok(Ok(pages)) => Some(pages),
+            Ok(Err(e)) => {
+                tracing::warn!("Failed to extract text from {:?}: {}", path, e);
+                None
+            }
+            Err(_) => {
+                tracing::warn!("Failed to extract text from {:?}: malformed PDF", path);
+                None
+            }
+        }
+    }
+
     /// Import all PDFs from a directory
     pub fn import_directory(&self, dir: &Path) -> Result<Vec<PdfMetadata>> {
         tracing::info!("Importing PDFs from directory: {:?}", dir);
@@ -83,10 +108,33 @@ impl PdfImporter {
             }
         }
 
+        let pdfs = self.deduplicate(pdfs);
         tracing::info!("Imported {} PDFs", pdfs.len());
         Ok(pdfs)
     }
 
+    /// Keep one PDF per group of identical files or files with the same text
+    ///
+    /// The canonical PDF is one with extractable text, then the largest; the
+    /// paths of every copy end up in its `locations`.
+    pub fn deduplicate(&self, pdfs: Vec<PdfMetadata>) -> Vec<PdfMetadata> {
+        let groups = self.duplicate_groups(&pdfs);
+        dedup::merge_duplicates(pdfs, &groups, |pdf| &mut pdf.locations)
+    }
+
+    /// Groups of duplicated PDFs, ranked like [`deduplicate`](Self::deduplicate)
+    pub fn duplicate_groups(&self, pdfs: &[PdfMetadata]) -> Vec<DuplicateGroup> {
+        let fingerprints: Vec<Fingerprint> = pdfs
+            .iter()
+            .map(|p| Fingerprint {
+                sha256: p.sha256.clone(),
+                perceptual_hash: None,
+                text_hash: p.text_hash.clone(),
+            })
+            .collect();
+        dedup::group_duplicates(&fingerprints, |i| (pdfs[i].text_content.is_some(), pdfs[i].file_size))
+    }
+
     /// Detect document type from filename
     fn detect_document_type(&self, filename: &str) -> String {
         let lower = filename.to_lowercase();
@@ -112,26 +160,22 @@ impl PdfImporter {
 
     /// Convert PDF metadata to Document for storage
     pub fn to_document(&self, metadata: &PdfMetadata) -> Document {
-        Document {
-            id: metadata.id,
-            title: metadata.filename.clone(),
-            content: format!(
-                "PDF Document: {}\nType: {}\nSize: {} bytes\nPages: {:?}",
-                metadata.filename,
-                metadata.document_type,
-                metadata.file_size,
-                metadata.page_count
-            ),
-            document_type: metadata.document_type.clone(),
-            source: Some(metadata.path.to_string_lossy().to_string()),
-            created_at: metadata.imported_at,
-            metadata: serde_json::json!({
-                "filename": metadata.filename,
-                "file_size": metadata.file_size,
-                "page_count": metadata.page_count,
-                "document_type": metadata.document_type,
-            }),
-        }
+        let mut document = Document::new(
+            metadata.filename.clone(),
+            metadata.document_type.clone(),
+            metadata.path.to_string_lossy().to_string(),
+            metadata.sha256.clone(),
+            "application/pdf".to_string(),
+        );
+        document.id = metadata.id;
+        document.tamanho = metadata.file_size;
+        document.texto = metadata.text_content.clone();
+        document.locais = metadata.locations.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        document.atributos = serde_json::json!({
+            "page_count": metadata.page_count,
+            "text_hash": metadata.text_hash,
+        });
+        document
     }
 }
 
@@ -147,6 +191,11 @@ pub struct PdfMetadata {
     pub path: PathBuf,
     pub filename: String,
     pub file_size: u64,
+    pub sha256: String,
+    /// Hash of the normalized text, shared by re-exported copies of the same document
+    pub text_hash: Option<String>,
+    /// Every path where this PDF was found (copies included)
+    pub locations: Vec<PathBuf>,
     pub document_type: String,
     pub imported_at: chrono::DateTime<Utc>,
     pub page_count: Option<usize>,
@@ -169,6 +218,38 @@ mod tests {
         assert_eq!(importer.detect_document_type("random_document.pdf"), "PDF");
     }
 
+    #[test]
+    fn test_deduplicate_prefers_pdf_with_text() {
+        let pdf = |name: &str, sha: &str, text: Option<&str>, size: u64| PdfMetadata {
+            id: Uuid::new_v4(),
+            path: PathBuf::from(name),
+            filename: name.into(),
+            file_size: size,
+            sha256: sha.into(),
+            text_hash: text.and_then(dedup::text_hash),
+            locations: vec![PathBuf::from(name)],
+            document_type: "CT-e".into(),
+            imported_at: Utc::now(),
+            page_count: Some(1),
+            text_content: text.map(Into::into),
+        };
+        let text = "DACTE Conhecimento de Transporte Eletrônico 35250412345678000190";
+
+        let pdfs = PdfImporter::new().deduplicate(vec![
+            pdf("backup/cte.pdf", "a", None, 9000),
+            pdf("cte.pdf", "b", Some(text), 4000),
+            pdf("email/CTE 123.pdf", "c", Some(&text.to_uppercase()), 5000),
+            pdf("backup/copia/cte.pdf", "a", None, 9000),
+            pdf("outro.pdf", "d", Some("Fatura de serviços de transporte rodoviário nº 998"), 3000),
+        ]);
+
+        assert_eq!(pdfs.len(), 3);
+        assert_eq!(pdfs[0].filename, "backup/cte.pdf");
+        assert_eq!(pdfs[0].locations.len(), 2);
+        assert_eq!(pdfs[1].filename, "email/CTE 123.pdf");
+        assert_eq!(pdfs[1].locations, vec![PathBuf::from("email/CTE 123.pdf"), PathBuf::from("cte.pdf")]);
+    }
+
     #[test]
     fn test_is_pdf() {
         let importer = PdfImporter::new();
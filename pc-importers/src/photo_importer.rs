@@ -1,9 +1,11 @@
 //! Photo/Image importer with OCR capabilities
 
+use crate::dedup::{self, DuplicateGroup, Fingerprint};
 use pc_core::Result;
 use pc_models::Document;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
-use chrono::Utc;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use uuid::Uuid;
 
 pub struct PhotoImporter {
@@ -20,6 +22,10 @@ impl PhotoImporter {
                 "bmp".to_string(),
                 "gif".to_string(),
                 "webp".to_string(),
+                "heic".to_string(),
+                "heif".to_string(),
+                "tif".to_string(),
+                "tiff".to_string(),
             ],
         }
     }
@@ -51,18 +57,83 @@ impl PhotoImporter {
             .ok_or_else(|| pc_core::Error::Import("Invalid filename".into()))?
             .to_string();
 
-        // Extract date from filename if present (e.g., "2025-05-07")
-        let date_from_filename = self.extract_date_from_filename(&filename);
+        // EXIF capture time wins over a date in the filename (e.g., "2025-05-07")
+        let exif = self.read_exif(path);
+        let captured_at = exif
+            .as_ref()
+            .and_then(|e| e.taken_at)
+            .map(|t| t.date())
+            .or_else(|| self.extract_date_from_filename(&filename));
+
+        let mut tags = Vec::new();
+        if let Some(exif) = &exif {
+            tags.push("exif".to_string());
+            if exif.gps.is_some() {
+                tags.push("gps".to_string());
+            }
+            if let Some(make) = &exif.camera_make {
+                tags.push(make.to_lowercase());
+            }
+        }
 
         Ok(PhotoMetadata {
             id: Uuid::new_v4(),
             path: path.to_path_buf(),
             filename,
             file_size,
-            captured_at: date_from_filename,
+            sha256: pc_core::hash::sha256_file(path)?,
+            perceptual_hash: dedup::perceptual_hash(path),
+            locations: vec![path.to_path_buf()],
+            captured_at,
             imported_at: Utc::now(),
-            description: None,
-            tags: Vec::new(),
+            description: exif.as_ref().and_then(|e| e.description.clone()),
+            tags,
+            exif,
+        })
+    }
+
+    /// Read EXIF from JPEG, TIFF, HEIF/HEIC, PNG or WebP (None if absent or unreadable)
+    pub fn read_exif(&self, path: &Path) -> Option<ExifData> {
+        let file = std::fs::File::open(path).ok()?;
+        let exif = match exif::Reader::new().read_from_container(&mut std::io::BufReader::new(file)) {
+            Ok(exif) => exif,
+            Err(e) => {
+                tracing::debug!("No EXIF in {:?}: {}", path, e);
+                return None;
+            }
+        };
+
+        let text = |tag| {
+            exif.get_field(tag, exif::In::PRIMARY).and_then(|f| match &f.value {
+                exif::Value::Ascii(values) => values
+                    .first()
+                    .map(|v| String::from_utf8_lossy(v).trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string())
+                    .filter(|v| !v.is_empty()),
+                _ => None,
+            })
+        };
+        let uint = |tag| exif.get_field(tag, exif::In::PRIMARY).and_then(|f| f.value.get_uint(0));
+
+        let taken_at = [exif::Tag::DateTimeOriginal, exif::Tag::DateTimeDigitized, exif::Tag::DateTime]
+            .into_iter()
+            .find_map(|tag| match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+                exif::Value::Ascii(values) => {
+                    let dt = exif::DateTime::from_ascii(values.first()?).ok()?;
+                    NaiveDate::from_ymd_opt(dt.year.into(), dt.month.into(), dt.day.into())?
+                        .and_hms_opt(dt.hour.into(), dt.minute.into(), dt.second.into())
+                }
+                _ => None,
+            });
+
+        Some(ExifData {
+            taken_at,
+            gps: read_gps(&exif),
+            camera_make: text(exif::Tag::Make),
+            camera_model: text(exif::Tag::Model),
+            description: text(exif::Tag::ImageDescription),
+            width: uint(exif::Tag::PixelXDimension),
+            height: uint(exif::Tag::PixelYDimension),
+            orientation: uint(exif::Tag::Orientation),
         })
     }
 
@@ -90,10 +161,40 @@ impl PhotoImporter {
             }
         }
 
+        let photos = self.deduplicate(photos);
         tracing::info!("Imported {} photos", photos.len());
         Ok(photos)
     }
 
+    /// Keep one photo per group of exact or visually identical copies
+    ///
+    /// The canonical photo is the largest one (best resolution); the paths
+    /// of every copy end up in its `locations`.
+    pub fn deduplicate(&self, photos: Vec<PhotoMetadata>) -> Vec<PhotoMetadata> {
+        let groups = self.duplicate_groups(&photos);
+        dedup::merge_duplicates(photos, &groups, |photo| &mut photo.locations)
+    }
+
+    /// Groups of duplicated photos, ranked like [`deduplicate`](Self::deduplicate)
+    pub fn duplicate_groups(&self, photos: &[PhotoMetadata]) -> Vec<DuplicateGroup> {
+        let fingerprints: Vec<Fingerprint> = photos
+            .iter()
+            .map(|p| Fingerprint {
+                sha256: p.sha256.clone(),
+                perceptual_hash: p.perceptual_hash,
+                text_hash: None,
+            })
+            .collect();
+        dedup::group_duplicates(&fingerprints, |i| {
+            let pixels = photos[i]
+                .exif
+                .as_ref()
+                .and_then(|e| Some(u64::from(e.width?) * u64::from(e.height?)))
+                .unwrap_or(0);
+            (pixels, photos[i].file_size)
+        })
+    }
+
     /// Extract date from filename (e.g., "Captura de tela 2025-05-07 154629.png")
     fn extract_date_from_filename(&self, filename: &str) -> Option<chrono::NaiveDate> {
         use regex::Regex;
@@ -111,24 +212,39 @@ impl PhotoImporter {
 
     /// Convert photo metadata to Document for storage
     pub fn to_document(&self, metadata: &PhotoMetadata) -> Document {
-        Document {
-            id: metadata.id,
-            title: metadata.filename.clone(),
-            content: format!(
-                "Photo: {}\nSize: {} bytes\nCaptured: {:?}",
-                metadata.filename,
-                metadata.file_size,
-                metadata.captured_at
-            ),
-            document_type: "photo".to_string(),
-            source: Some(metadata.path.to_string_lossy().to_string()),
-            created_at: metadata.imported_at,
-            metadata: serde_json::json!({
-                "filename": metadata.filename,
-                "file_size": metadata.file_size,
-                "captured_at": metadata.captured_at,
-                "tags": metadata.tags,
-            }),
+        let mut document = Document::new(
+            metadata.filename.clone(),
+            "Foto".to_string(),
+            metadata.path.to_string_lossy().to_string(),
+            metadata.sha256.clone(),
+            self.mime_type(&metadata.path).to_string(),
+        );
+        document.id = metadata.id;
+        document.tamanho = metadata.file_size;
+        document.texto = metadata.description.clone();
+        document.locais = metadata.locations.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        document.atributos = serde_json::json!({
+            "captured_at": metadata.captured_at,
+            "tags": metadata.tags,
+            "exif": metadata.exif,
+            "perceptual_hash": metadata.perceptual_hash.map(|h| format!("{:016x}", h)),
+        });
+        document
+    }
+
+    /// MIME type from the file extension
+    fn mime_type(&self, path: &Path) -> &'static str {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+        match ext.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "bmp" => "image/bmp",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "heic" => "image/heic",
+            "heif" => "image/heif",
+            "tif" | "tiff" => "image/tiff",
+            _ => "application/octet-stream",
         }
     }
 }
@@ -139,16 +255,71 @@ impl Default for PhotoImporter {
     }
 }
 
+/// Degrees/minutes/seconds with N/S or E/W reference into signed decimal degrees
+fn read_gps(exif: &exif::Exif) -> Option<GpsPosition> {
+    let coordinate = |value_tag, ref_tag, negative: &[u8]| {
+        let degrees = match &exif.get_field(value_tag, exif::In::PRIMARY)?.value {
+            exif::Value::Rational(parts) if parts.len() >= 3 => {
+                parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0
+            }
+            _ => return None,
+        };
+        let is_negative = match &exif.get_field(ref_tag, exif::In::PRIMARY)?.value {
+            exif::Value::Ascii(values) => values.first().is_some_and(|v| v.as_slice() == negative),
+            _ => false,
+        };
+        degrees.is_finite().then_some(if is_negative { -degrees } else { degrees })
+    };
+
+    let latitude = coordinate(exif::Tag::GPSLatitude, exif::Tag::GPSLatitudeRef, b"S")?;
+    let longitude = coordinate(exif::Tag::GPSLongitude, exif::Tag::GPSLongitudeRef, b"W")?;
+    let altitude = exif
+        .get_field(exif::Tag::GPSAltitude, exif::In::PRIMARY)
+        .and_then(|f| match &f.value {
+            exif::Value::Rational(parts) => parts.first().map(|r| r.to_f64()),
+            _ => None,
+        });
+
+    Some(GpsPosition { latitude, longitude, altitude })
+}
+
+/// GPS position in decimal degrees (south and west are negative)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GpsPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+}
+
+/// Metadata read from the photo's EXIF block
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExifData {
+    pub taken_at: Option<NaiveDateTime>,
+    pub gps: Option<GpsPosition>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub description: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub orientation: Option<u32>,
+}
+
 #[derive(Debug, Clone)]
 pub struct PhotoMetadata {
     pub id: Uuid,
     pub path: PathBuf,
     pub filename: String,
     pub file_size: u64,
+    pub sha256: String,
+    /// dHash used to find resized or recompressed copies
+    pub perceptual_hash: Option<u64>,
+    /// Every path where this photo was found (copies included)
+    pub locations: Vec<PathBuf>,
     pub captured_at: Option<chrono::NaiveDate>,
     pub imported_at: chrono::DateTime<Utc>,
     pub description: Option<String>,
     pub tags: Vec<String>,
+    pub exif: Option<ExifData>,
 }
 
 #[cfg(test)]
@@ -177,4 +348,77 @@ mod tests {
         assert!(!importer.is_image(Path::new("test.txt")));
         assert!(!importer.is_image(Path::new("test.csv")));
     }
+
+    #[test]
+    fn test_import_directory_keeps_one_copy() {
+        let dir = std::env::temp_dir().join(format!("pc-photos-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let gradient = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 128]));
+        gradient.save(dir.join("entrega.png")).unwrap();
+        std::fs::copy(dir.join("entrega.png"), dir.join("entrega (1).png")).unwrap();
+        // Cópia reduzida: outro sha256, mesma imagem
+        image::imageops::resize(&gradient, 32, 24, image::imageops::FilterType::Triangle)
+            .save(dir.join("entrega-pequena.png"))
+            .unwrap();
+
+        let photos = PhotoImporter::new().import_directory(&dir).unwrap();
+        assert_eq!(photos.len(), 1);
+        assert_eq!(photos[0].locations.len(), 3);
+        assert_eq!(photos[0].locations[0], photos[0].path);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_import_photo_reads_exif() {
+        use exif::experimental::Writer;
+        use exif::{Field, In, Rational, Tag, Value};
+
+        let ascii = |tag, text: &str| Field { tag, ifd_num: In::PRIMARY, value: Value::Ascii(vec![text.as_bytes().to_vec()]) };
+        let dms = |d, m, s| Value::Rational(vec![
+            Rational { num: d, denom: 1 },
+            Rational { num: m, denom: 1 },
+            Rational { num: s, denom: 100 },
+        ]);
+        let fields = [
+            ascii(Tag::Make, "Samsung"),
+            ascii(Tag::Model, "SM-A515F"),
+            ascii(Tag::DateTimeOriginal, "2025:04:03 14:25:10"),
+            ascii(Tag::GPSLatitudeRef, "S"),
+            Field { tag: Tag::GPSLatitude, ifd_num: In::PRIMARY, value: dms(20, 32, 1900) },
+            ascii(Tag::GPSLongitudeRef, "W"),
+            Field { tag: Tag::GPSLongitude, ifd_num: In::PRIMARY, value: dms(47, 24, 300) },
+        ];
+
+        let mut writer = Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+
+        // JPEG mínimo: SOI, APP1 com o bloco Exif, EOI
+        let tiff = tiff.into_inner();
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&tiff);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+
+        let path = std::env::temp_dir().join(format!("entrega-{}.jpg", Uuid::new_v4()));
+        std::fs::write(&path, jpeg).unwrap();
+
+        let photo = PhotoImporter::new().import_photo(&path).unwrap();
+        let exif = photo.exif.unwrap();
+        assert_eq!(photo.captured_at, NaiveDate::from_ymd_opt(2025, 4, 3));
+        assert_eq!(exif.taken_at.unwrap().to_string(), "2025-04-03 14:25:10");
+        assert_eq!(exif.camera_model.as_deref(), Some("SM-A515F"));
+        let gps = exif.gps.unwrap();
+        assert!((gps.latitude + 20.5386).abs() < 1e-3);
+        assert!((gps.longitude + 47.4008).abs() < 1e-3);
+        assert_eq!(photo.tags, vec!["exif", "gps", "samsung"]);
+
+        std::fs::remove_file(path).unwrap();
+    }
 }
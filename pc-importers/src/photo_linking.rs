@@ -0,0 +1,213 @@
+//! Sugestão de vínculos entre fotos e ordens de frete
+//!
+//! Os comprovantes de entrega fotografados pelos motoristas são associados
+//! às ordens por três sinais: número de nota fiscal (ou da ordem) no nome
+//! do arquivo, data da foto perto da entrega e posição GPS perto da cidade
+//! de destino. As sugestões viram [`PhotoLink`]s para o operador revisar.
+
+use crate::geo::{distance_km, CityLocator};
+use crate::photo_importer::PhotoMetadata;
+use crate::text::{digit_runs, strip_zeros};
+use pc_models::{FreightOrder, PhotoLink};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+const SCORE_NOTA_FISCAL: f32 = 0.5;
+const SCORE_NUMERO_ORDEM: f32 = 0.4;
+const SCORE_DIA_ENTREGA: f32 = 0.3;
+const SCORE_APOS_EMISSAO: f32 = 0.15;
+const SCORE_GPS_PERTO: f32 = 0.3;
+const SCORE_GPS_REGIAO: f32 = 0.15;
+
+/// Vínculo sugerido, com a pontuação e os motivos
+#[derive(Debug, Clone)]
+pub struct LinkSuggestion {
+    pub photo_path: PathBuf,
+    pub order_id: Uuid,
+    pub numero: String,
+    pub score: f32,
+    pub reasons: Vec<String>,
+}
+
+impl LinkSuggestion {
+    pub fn into_link(self) -> PhotoLink {
+        PhotoLink::suggest(
+            self.photo_path.to_string_lossy().into_owned(),
+            self.order_id,
+            self.numero,
+            self.score,
+            self.reasons,
+        )
+    }
+}
+
+/// Calcula as sugestões de vínculo
+pub struct PhotoLinker {
+    locator: CityLocator,
+    /// Dias após a emissão em que a foto ainda pode ser da entrega
+    max_days: i64,
+    min_score: f32,
+    max_per_photo: usize,
+}
+
+impl PhotoLinker {
+    pub fn new() -> Self {
+        Self {
+            locator: CityLocator::new(),
+            max_days: 7,
+            // Só a data não basta: é preciso um segundo sinal
+            min_score: 0.35,
+            max_per_photo: 3,
+        }
+    }
+
+    pub fn with_locator(mut self, locator: CityLocator) -> Self {
+        self.locator = locator;
+        self
+    }
+
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Melhores ordens para uma foto, da maior para a menor pontuação
+    pub fn suggest(&self, photo: &PhotoMetadata, orders: &[FreightOrder]) -> Vec<LinkSuggestion> {
+        let numbers = digit_runs(&photo.filename);
+
+        let mut suggestions: Vec<LinkSuggestion> = orders
+            .iter()
+            .filter_map(|order| {
+                let mut score = 0.0;
+                let mut reasons = Vec::new();
+
+                if let Some(nf) = order
+                    .notas_fiscais
+                    .iter()
+                    .find(|nf| numbers.contains(&strip_zeros(nf)))
+                {
+                    score += SCORE_NOTA_FISCAL;
+                    reasons.push(format!("NF {} no nome do arquivo", nf));
+                } else if numbers.contains(&strip_zeros(&order.numero)) {
+                    score += SCORE_NUMERO_ORDEM;
+                    reasons.push(format!("Ordem {} no nome do arquivo", order.numero));
+                }
+
+                if let Some(date) = photo.captured_at {
+                    let delivery = order.data_entrega.or(order.data_agendamento);
+                    let days = (date - order.data_emissao).num_days();
+                    if delivery == Some(date) {
+                        score += SCORE_DIA_ENTREGA;
+                        reasons.push(format!("Foto do dia da entrega ({})", date.format("%d/%m/%Y")));
+                    } else if (0..=self.max_days).contains(&days) {
+                        score += SCORE_APOS_EMISSAO;
+                        reasons.push(format!("Foto {} dia(s) após a emissão", days));
+                    }
+                }
+
+                let gps = photo.exif.as_ref().and_then(|e| e.gps);
+                if let (Some(gps), Some(city)) = (gps, self.locator.locate(&order.destinatario_cidade)) {
+                    let km = distance_km((gps.latitude, gps.longitude), city);
+                    if km <= 10.0 {
+                        score += SCORE_GPS_PERTO;
+                        reasons.push(format!("GPS a {:.1} km de {}", km, order.destinatario_cidade));
+                    } else if km <= 30.0 {
+                        score += SCORE_GPS_REGIAO;
+                        reasons.push(format!("GPS a {:.0} km de {}", km, order.destinatario_cidade));
+                    }
+                }
+
+                let score = f32::min(score, 1.0);
+                (score >= self.min_score).then(|| LinkSuggestion {
+                    photo_path: photo.path.clone(),
+                    order_id: order.id,
+                    numero: order.numero.clone(),
+                    score,
+                    reasons,
+                })
+            })
+            .collect();
+
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.numero.cmp(&b.numero)));
+        suggestions.truncate(self.max_per_photo);
+        suggestions
+    }
+
+    /// Sugestões para várias fotos
+    pub fn suggest_all(&self, photos: &[PhotoMetadata], orders: &[FreightOrder]) -> Vec<LinkSuggestion> {
+        photos.iter().flat_map(|photo| self.suggest(photo, orders)).collect()
+    }
+}
+
+impl Default for PhotoLinker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::photo_importer::{ExifData, GpsPosition};
+    use chrono::{NaiveDate, Utc};
+
+    fn order(numero: &str, nf: &str, cidade: &str) -> FreightOrder {
+        let mut order = FreightOrder::new(
+            numero.into(),
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            "FENIOR".into(),
+            "FENIOR".into(),
+            "Ribeirão Preto".into(),
+            "Cliente".into(),
+            cidade.into(),
+        );
+        order.notas_fiscais = vec![nf.into()];
+        order.data_entrega = NaiveDate::from_ymd_opt(2025, 4, 3);
+        order
+    }
+
+    fn photo(filename: &str, gps: Option<(f64, f64)>) -> PhotoMetadata {
+        PhotoMetadata {
+            id: Uuid::new_v4(),
+            path: PathBuf::from(filename),
+            filename: filename.into(),
+            file_size: 0,
+            sha256: String::new(),
+            perceptual_hash: None,
+            locations: vec![PathBuf::from(filename)],
+            captured_at: NaiveDate::from_ymd_opt(2025, 4, 3),
+            imported_at: Utc::now(),
+            description: None,
+            tags: Vec::new(),
+            exif: Some(ExifData {
+                gps: gps.map(|(latitude, longitude)| GpsPosition { latitude, longitude, altitude: None }),
+                ..ExifData::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_suggest_by_nota_fiscal_date_and_gps() {
+        let orders = vec![order("1001", "000012345", "Franca/SP"), order("1002", "777", "Matão")];
+        let linker = PhotoLinker::new();
+
+        let suggestions = linker.suggest(&photo("canhoto NF 12345.jpg", Some((-20.54, -47.40))), &orders);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].numero, "1001");
+        assert_eq!(suggestions[0].score, 1.0);
+        assert_eq!(suggestions[0].reasons.len(), 3);
+    }
+
+    #[test]
+    fn test_date_alone_is_not_enough() {
+        let orders = vec![order("1001", "12345", "Franca"), order("1002", "777", "Matão")];
+        let linker = PhotoLinker::new();
+
+        assert!(linker.suggest(&photo("IMG_20250403.jpg", None), &orders).is_empty());
+
+        // Com GPS perto de Matão, a ordem 1002 passa a ser sugerida
+        let suggestions = linker.suggest(&photo("IMG_20250403.jpg", Some((-21.60, -48.37))), &orders);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].numero, "1002");
+    }
+}
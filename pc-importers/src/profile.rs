@@ -0,0 +1,543 @@
+//! Perfil e nota de qualidade de arquivos de dados
+//!
+//! Antes de confiar em uma fonte nova, o arquivo é analisado coluna a
+//! coluna (preenchimento, valores distintos, distribuição, datas, números
+//! fora da curva, CNPJs e telefones inválidos). Fretes e pontos também
+//! passam pelo parser do importador e pela validação dos modelos. Tudo vira
+//! uma nota de 0 a 100.
+
+use crate::csv_source::{CsvFormat, CsvOptions};
+use crate::detect::{self, FileKind};
+use crate::freight_importer::FreightOrderImporter;
+use crate::streaming::{StreamedRow, StreamingImporter};
+use crate::timesheet_importer::TimesheetImporter;
+use chrono::NaiveDate;
+use pc_core::{Entity, Money, Result, Violations};
+use pc_models::brdoc;
+use pc_models::validation::{is_email, is_phone};
+use pc_models::FreightOrder;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Parcela mínima de valores de um tipo para classificar a coluna
+const TYPE_SHARE: f64 = 0.8;
+
+/// Exemplos guardados por problema
+const MAX_EXAMPLES: usize = 5;
+
+/// Tipo inferido de uma coluna
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    Empty,
+    Text,
+    Integer,
+    Decimal,
+    Date,
+    Cnpj,
+    Cpf,
+    Phone,
+    Email,
+}
+
+impl ColumnKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColumnKind::Empty => "vazia",
+            ColumnKind::Text => "texto",
+            ColumnKind::Integer => "inteiro",
+            ColumnKind::Decimal => "decimal",
+            ColumnKind::Date => "data",
+            ColumnKind::Cnpj => "cnpj",
+            ColumnKind::Cpf => "cpf",
+            ColumnKind::Phone => "telefone",
+            ColumnKind::Email => "email",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NumericSummary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub zeros: usize,
+    pub negatives: usize,
+    /// Valores além de 3 intervalos interquartis dos quartis
+    pub outliers: usize,
+}
+
+/// Perfil de uma coluna
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub kind: ColumnKind,
+    pub filled: usize,
+    pub fill_rate: f64,
+    pub distinct: usize,
+    pub top_values: Vec<ValueCount>,
+    pub date_range: Option<(NaiveDate, NaiveDate)>,
+    pub numbers: Option<NumericSummary>,
+    /// Valores preenchidos que não batem com o tipo (data, número, CNPJ...)
+    pub invalid: usize,
+    pub invalid_examples: Vec<String>,
+}
+
+/// Problema encontrado nos registros interpretados pelo importador
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordIssue {
+    pub issue: String,
+    pub count: usize,
+    /// Primeiras linhas afetadas
+    pub lines: Vec<u64>,
+}
+
+/// Resultado da análise de um arquivo
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileReport {
+    pub file: PathBuf,
+    pub kind: String,
+    pub encoding: String,
+    pub delimiter: char,
+    pub rows: usize,
+    pub columns: Vec<ColumnProfile>,
+    /// Registros lidos pelo importador (fretes e pontos)
+    pub records: Option<usize>,
+    pub record_issues: Vec<RecordIssue>,
+    /// Nota de 0 a 100
+    pub score: f64,
+}
+
+/// Analisa arquivos CSV
+pub struct Profiler {
+    csv_options: CsvOptions,
+    top_values: usize,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            csv_options: CsvOptions::default(),
+            top_values: 5,
+        }
+    }
+
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
+
+    /// Quantos valores mais frequentes listar por coluna
+    pub fn with_top_values(mut self, top_values: usize) -> Self {
+        self.top_values = top_values;
+        self
+    }
+
+    pub fn profile<P: AsRef<Path>>(&self, path: P) -> Result<ProfileReport> {
+        let path = path.as_ref();
+        let kind = detect::detect_file(path)?.kind;
+        let (format, mut reader) = CsvFormat::open(path, &self.csv_options)?;
+
+        let headers = reader
+            .headers()
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler cabeçalho: {}", e)))?
+            .clone();
+        let mut columns: Vec<ColumnStats> = headers.iter().map(ColumnStats::new).collect();
+
+        let mut rows = 0;
+        for record in reader.records() {
+            let record = record.map_err(|e| pc_core::Error::Import(format!("Erro ao ler CSV: {}", e)))?;
+            rows += 1;
+            for (i, value) in record.iter().enumerate() {
+                if i >= columns.len() {
+                    columns.push(ColumnStats::new(&format!("coluna_{}", i + 1)));
+                }
+                columns[i].add(value);
+            }
+        }
+
+        let columns: Vec<ColumnProfile> = columns.into_iter().map(|c| c.finish(rows, self.top_values)).collect();
+        let (records, record_issues) = match kind {
+            FileKind::FreightCsv => {
+                let importer = FreightOrderImporter::new().with_csv_options(self.csv_options);
+                check_records(&importer, path, freight_issues)?
+            }
+            FileKind::TimesheetCsv => {
+                let importer = TimesheetImporter::new().with_csv_options(self.csv_options);
+                check_records(&importer, path, |_| Vec::new())?
+            }
+            _ => (None, Vec::new()),
+        };
+
+        let score = quality_score(&columns, rows, records, &record_issues);
+        Ok(ProfileReport {
+            file: path.to_path_buf(),
+            kind: kind.as_str().to_string(),
+            encoding: format.encoding.as_str().to_string(),
+            delimiter: format.delimiter as char,
+            rows,
+            columns,
+            records,
+            record_issues,
+            score,
+        })
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Acumula os valores de uma coluna
+struct ColumnStats {
+    name: String,
+    hint: Option<ColumnKind>,
+    filled: usize,
+    values: HashMap<String, usize>,
+    dates: Vec<NaiveDate>,
+    integers: usize,
+    numbers: Vec<f64>,
+}
+
+impl ColumnStats {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            hint: kind_from_header(name),
+            filled: 0,
+            values: HashMap::new(),
+            dates: Vec::new(),
+            integers: 0,
+            numbers: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        self.filled += 1;
+        *self.values.entry(value.to_string()).or_default() += 1;
+
+        if let Some(date) = parse_date(value) {
+            self.dates.push(date);
+        } else if let Some(number) = parse_number(value) {
+            // "1.234" é milhar; "10.00" tem casas decimais
+            let no_decimals = parse_number(&value.replace('.', "")) == Some(number);
+            if !value.contains(',') && no_decimals {
+                self.integers += 1;
+            }
+            self.numbers.push(number);
+        }
+    }
+
+    fn finish(self, rows: usize, top_values: usize) -> ColumnProfile {
+        let share = |count: usize| count as f64 / self.filled.max(1) as f64;
+        let kind = match self.hint {
+            _ if self.filled == 0 => ColumnKind::Empty,
+            Some(hint) => hint,
+            None if share(self.dates.len()) >= TYPE_SHARE => ColumnKind::Date,
+            None if share(self.integers) >= TYPE_SHARE => ColumnKind::Integer,
+            None if share(self.numbers.len()) >= TYPE_SHARE => ColumnKind::Decimal,
+            None => ColumnKind::Text,
+        };
+
+        let is_valid = |value: &str| match kind {
+            ColumnKind::Empty | ColumnKind::Text => true,
+            ColumnKind::Date => parse_date(value).is_some(),
+            ColumnKind::Integer | ColumnKind::Decimal => parse_number(value).is_some(),
+            ColumnKind::Cnpj => brdoc::cnpj::is_valid(value),
+            ColumnKind::Cpf => brdoc::cpf::is_valid(value),
+            ColumnKind::Phone => is_phone(value),
+            ColumnKind::Email => is_email(value),
+        };
+
+        let mut sorted: Vec<(&String, &usize)> = self.values.iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+        let mut invalid = 0;
+        let mut invalid_examples = Vec::new();
+        for (value, count) in &sorted {
+            if !is_valid(value) {
+                invalid += **count;
+                if invalid_examples.len() < MAX_EXAMPLES {
+                    invalid_examples.push((*value).clone());
+                }
+            }
+        }
+
+        let numbers = matches!(kind, ColumnKind::Integer | ColumnKind::Decimal)
+            .then(|| summarize(self.numbers.clone()))
+            .flatten();
+
+        ColumnProfile {
+            kind,
+            filled: self.filled,
+            fill_rate: if rows == 0 { 0.0 } else { self.filled as f64 / rows as f64 },
+            distinct: self.values.len(),
+            top_values: sorted
+                .iter()
+                .take(top_values)
+                .map(|(value, count)| ValueCount {
+                    value: (*value).clone(),
+                    count: **count,
+                })
+                .collect(),
+            date_range: (kind == ColumnKind::Date)
+                .then(|| Some((*self.dates.iter().min()?, *self.dates.iter().max()?)))
+                .flatten(),
+            numbers,
+            invalid,
+            invalid_examples,
+            name: self.name,
+        }
+    }
+}
+
+/// Colunas de documento e contato são reconhecidas pelo nome
+fn kind_from_header(header: &str) -> Option<ColumnKind> {
+    let header = crate::text::normalize_text(header);
+    let words: Vec<&str> = header.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+    let has = |names: &[&str]| words.iter().any(|w| names.contains(w));
+
+    if has(&["CNPJ"]) {
+        Some(ColumnKind::Cnpj)
+    } else if has(&["CPF"]) {
+        Some(ColumnKind::Cpf)
+    } else if has(&["TELEFONE", "FONE", "TEL", "CELULAR", "WHATSAPP"]) {
+        Some(ColumnKind::Phone)
+    } else if has(&["EMAIL", "MAIL"]) {
+        Some(ColumnKind::Email)
+    } else {
+        None
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    ["%d/%m/%Y", "%Y-%m-%d", "%d-%m-%Y", "%d/%m/%y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// Números como os importadores os leem ([`Money::parse`]): "1.234,56",
+/// "R$ 10,00" e "1.234" (milhar) no formato brasileiro, "12.5" com ponto
+/// decimal; arredondados ao centavo
+fn parse_number(value: &str) -> Option<f64> {
+    Money::parse(value).ok().map(Money::to_f64)
+}
+
+/// Motivo de rejeição sem o que identifica a linha
+///
+/// Os motivos começam pela linha, funcionário ou ordem ("Linha 12: marcação
+/// sem par: NSR ...", "João: data inválida '31/02'"): fica o trecho depois do
+/// primeiro ':', até o próximo ':' e sem valores entre aspas ou parênteses.
+fn rejection_kind(reason: &str) -> &str {
+    let motivo = match reason.split_once(':') {
+        Some((_, rest)) => rest.split(':').next().unwrap_or(rest),
+        None => reason,
+    };
+    motivo.split(['\'', '(']).next().unwrap_or(motivo).trim()
+}
+
+fn summarize(mut numbers: Vec<f64>) -> Option<NumericSummary> {
+    if numbers.is_empty() {
+        return None;
+    }
+    numbers.sort_by(f64::total_cmp);
+
+    let n = numbers.len();
+    let outliers = if n >= 4 {
+        let (q1, q3) = (numbers[n / 4], numbers[3 * n / 4]);
+        let fence = 3.0 * (q3 - q1);
+        numbers.iter().filter(|&&v| v < q1 - fence || v > q3 + fence).count()
+    } else {
+        0
+    };
+
+    Some(NumericSummary {
+        min: numbers[0],
+        max: numbers[n - 1],
+        mean: numbers.iter().sum::<f64>() / n as f64,
+        zeros: numbers.iter().filter(|&&v| v == 0.0).count(),
+        negatives: numbers.iter().filter(|&&v| v < 0.0).count(),
+        outliers,
+    })
+}
+
+/// Passa o arquivo pelo importador e pela validação do modelo
+fn check_records<I, F>(importer: &I, path: &Path, extra: F) -> Result<(Option<usize>, Vec<RecordIssue>)>
+where
+    I: StreamingImporter,
+    I::Output: Entity,
+    F: Fn(&I::Output) -> Vec<String>,
+{
+    let mut issues: BTreeMap<String, RecordIssue> = BTreeMap::new();
+    let mut add = |issue: String, line: u64| {
+        let entry = issues.entry(issue.clone()).or_insert(RecordIssue {
+            issue,
+            count: 0,
+            lines: Vec::new(),
+        });
+        entry.count += 1;
+        if entry.lines.len() < MAX_EXAMPLES {
+            entry.lines.push(line);
+        }
+    };
+
+    let mut records = 0;
+    for row in importer.stream_rows(path)? {
+        match row? {
+            StreamedRow::Record(streamed) => {
+                records += 1;
+                let mut violations = Violations::new();
+                streamed.record.check(&mut violations);
+                for violation in violations {
+                    add(violation.message, streamed.line);
+                }
+                for issue in extra(&streamed.record) {
+                    add(issue, streamed.line);
+                }
+            }
+            StreamedRow::Rejected(rejected) => {
+                add(format!("Linha rejeitada: {}", rejection_kind(&rejected.reason)), rejected.line)
+            }
+        }
+    }
+
+    let mut issues: Vec<RecordIssue> = issues.into_values().collect();
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.count));
+    Ok((Some(records), issues))
+}
+
+/// Valores suspeitos em ordens de frete
+fn freight_issues(order: &FreightOrder) -> Vec<String> {
+    let mut issues = Vec::new();
+    if order.valor_frete.is_zero() {
+        issues.push("valor_frete zerado".to_string());
+    }
+    if order.peso <= 0.0 {
+        issues.push("peso zerado ou negativo".to_string());
+    }
+    issues
+}
+
+/// Nota de 0 a 100
+///
+/// Metade vem do preenchimento das colunas, um quarto da validade dos
+/// valores e um quarto dos registros sem problemas no importador (ou da
+/// ausência de números fora da curva, para arquivos sem importador).
+fn quality_score(columns: &[ColumnProfile], rows: usize, records: Option<usize>, issues: &[RecordIssue]) -> f64 {
+    if rows == 0 || columns.is_empty() {
+        return 0.0;
+    }
+
+    let fill = columns.iter().map(|c| c.fill_rate).sum::<f64>() / columns.len() as f64;
+
+    let filled: usize = columns.iter().map(|c| c.filled).sum();
+    let invalid: usize = columns.iter().map(|c| c.invalid).sum();
+    let validity = 1.0 - invalid as f64 / filled.max(1) as f64;
+
+    let consistency = match records {
+        Some(records) => {
+            let affected: usize = issues.iter().map(|i| i.count).sum();
+            1.0 - (affected as f64 / (records.max(1)) as f64).min(1.0)
+        }
+        None => {
+            let outliers: usize = columns.iter().filter_map(|c| c.numbers.as_ref()).map(|n| n.outliers).sum();
+            1.0 - (outliers as f64 / rows as f64).min(1.0)
+        }
+    };
+
+    let score = 50.0 * fill + 25.0 * validity + 25.0 * consistency;
+    (score * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_columns() {
+        let path = std::env::temp_dir().join(format!("clientes-{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "nome;cnpj;telefone;desde;limite\n\
+             Comercial Franca;12.345.678/0001-95;(16) 3333-4444;01/04/2025;1.500,00\n\
+             Fenior;123;16 99999-8888;15/03/2024;2000\n\
+             Atacadão;;3333;;1.800,50\n\
+             Fenior;11222333000181;;02/01/2025;999999\n",
+        )
+        .unwrap();
+
+        let report = Profiler::new().profile(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(report.rows, 4);
+
+        let column = |name: &str| report.columns.iter().find(|c| c.name == name).unwrap();
+        assert_eq!(column("nome").distinct, 3);
+        assert_eq!(column("nome").top_values[0].value, "Fenior");
+
+        let cnpj = column("cnpj");
+        assert_eq!(cnpj.kind, ColumnKind::Cnpj);
+        assert_eq!(cnpj.fill_rate, 0.75);
+        assert_eq!(cnpj.invalid_examples, vec!["123"]);
+
+        assert_eq!(column("telefone").invalid, 1);
+        assert_eq!(column("desde").kind, ColumnKind::Date);
+        assert_eq!(
+            column("desde").date_range,
+            Some((NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(), NaiveDate::from_ymd_opt(2025, 4, 1).unwrap()))
+        );
+        assert_eq!(column("limite").kind, ColumnKind::Decimal);
+        assert_eq!(column("limite").numbers.as_ref().unwrap().max, 999999.0);
+
+        assert!(report.records.is_none());
+        assert!(report.score > 50.0 && report.score < 100.0, "{}", report.score);
+    }
+
+    #[test]
+    fn test_profile_freight_records() {
+        let path = std::env::temp_dir().join(format!("Fretes-{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "Número;Data de Agendamento;Data de Emissão;Notas Fiscais;Pagador do Frete - Nome;Pagador do Frete - Telefone;\
+             Remetente - Nome;Remetente - Cidade;Destinatário - Nome;Destinatário - Cidade;Volumes;Peso;Valor NF;Valor Frete\n\
+             1001;01/04/2025;01/04/2025;123;Fenior;16 3333-4444;Fenior;Ribeirão Preto;Comercial Franca;Franca;1;10,5;100,00;50,00\n\
+             1002;01/04/2025;01/04/2025;124;Fenior;16 3333-4444;Fenior;Ribeirão Preto;Comercial Franca;Franca;1;0;100,00;0\n\
+             1003;01/04/2025;01/04/2025;125;;;Fenior;Ribeirão Preto;Comercial Franca;Franca;1;8;100,00;40,00\n",
+        )
+        .unwrap();
+
+        let report = Profiler::new().profile(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(report.kind, "freight");
+        assert_eq!(report.records, Some(2));
+        let issue = |name: &str| report.record_issues.iter().find(|i| i.issue == name).map(|i| i.count);
+        assert_eq!(issue("valor_frete zerado"), Some(1));
+        assert_eq!(issue("peso zerado ou negativo"), Some(1));
+        assert_eq!(report.record_issues.iter().map(|i| i.count).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_numbers_and_rejections_like_importers() {
+        assert_eq!(parse_number("1.234"), Some(1234.0));
+        assert_eq!(parse_number("R$ 1.234,56"), Some(1234.56));
+        assert_eq!(parse_number("12.5"), Some(12.5));
+        assert_eq!(parse_number("abc"), None);
+
+        assert_eq!(rejection_kind("Linha 12: marcação sem par: NSR 5 empregado 123"), "marcação sem par");
+        assert_eq!(rejection_kind("João: data inválida '31/02/2025'"), "data inválida");
+        assert_eq!(rejection_kind("Linha 3: NSR fora de sequência (esperado 4, encontrado 7)"), "NSR fora de sequência");
+    }
+}
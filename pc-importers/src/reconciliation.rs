@@ -0,0 +1,358 @@
+//! Conciliação de pagamentos
+//!
+//! Cada crédito do extrato é comparado com as ordens de frete e notas fiscais
+//! em aberto por valor, data, pagador (nome ou CNPJ) e número de documento
+//! citado no histórico. Um candidato forte e isolado concilia o lançamento
+//! automaticamente; pagamentos parciais e casos ambíguos vão para revisão.
+
+use crate::company_resolution::normalize_company_name;
+use crate::text::{digit_runs, digits, strip_zeros};
+use pc_models::{FreightOrder, Invoice, MatchStatus, PaymentMatch, PaymentTransaction};
+use pc_core::{Money, NaturalKey, Rounding, Status};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const SCORE_VALOR_IGUAL: f64 = 0.4;
+const SCORE_VALOR_PROXIMO: f64 = 0.25;
+const SCORE_VALOR_PARCIAL: f64 = 0.15;
+const SCORE_DOCUMENTO: f64 = 0.35;
+const SCORE_CNPJ: f64 = 0.35;
+const SCORE_PAGADOR: f64 = 0.25;
+const SCORE_DATA: f64 = 0.1;
+const SCORE_FORMA: f64 = 0.05;
+
+/// Diferença aceita como "valor próximo" (tarifas, descontos de boleto)
+const TOLERANCIA_VALOR: f64 = 0.02;
+/// Menor fração do valor considerada pagamento parcial
+const MIN_PARCIAL: f64 = 0.1;
+/// Pagamentos podem chegar um pouco antes da emissão (adiantamentos)
+const DIAS_ANTES_EMISSAO: i64 = 3;
+
+/// Ordem ou nota a receber, no formato comum usado na pontuação
+struct Receivable {
+    entidade: &'static str,
+    id: Uuid,
+    numero: String,
+    valor: Money,
+    emissao: NaiveDate,
+    pagadores: Vec<String>,
+    cnpjs: Vec<String>,
+    /// Números citáveis no histórico, com o rótulo do motivo
+    documentos: Vec<(&'static str, String)>,
+    forma_pagamento: Option<String>,
+}
+
+impl Receivable {
+    fn from_order(order: &FreightOrder) -> Self {
+        let mut documentos = vec![("Ordem", order.numero.clone())];
+        documentos.extend(order.notas_fiscais.iter().map(|nf| ("NF", nf.clone())));
+        documentos.extend(order.cte_numero.iter().map(|cte| ("CT-e", cte.clone())));
+        Self {
+            entidade: "freight_order",
+            id: order.id,
+            numero: order.numero.clone(),
+            valor: order.valor_frete,
+            emissao: order.data_emissao,
+            pagadores: vec![order.pagador_nome.clone()],
+            cnpjs: [&order.pagador_cnpj, &order.remetente_cnpj, &order.destinatario_cnpj]
+                .into_iter()
+                .flatten()
+                .map(|c| digits(c))
+                .filter(|c| !c.is_empty())
+                .collect(),
+            documentos,
+            forma_pagamento: order.forma_pagamento.clone(),
+        }
+    }
+
+    fn from_invoice(invoice: &Invoice) -> Self {
+        Self {
+            entidade: "invoice",
+            id: invoice.id,
+            numero: invoice.numero.clone(),
+            valor: invoice.valor_total,
+            emissao: invoice.data_emissao,
+            pagadores: vec![invoice.destinatario_nome.clone()],
+            cnpjs: Vec::new(),
+            documentos: vec![("NF", invoice.numero.clone())],
+            forma_pagamento: None,
+        }
+    }
+}
+
+/// Resultado de uma conciliação
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReconciliationSummary {
+    pub matched: usize,
+    pub review: usize,
+    pub unmatched: usize,
+    /// Débitos e lançamentos já revisados pelo operador
+    pub skipped: usize,
+}
+
+/// Concilia lançamentos com ordens de frete e notas fiscais
+pub struct PaymentMatcher {
+    receivables: Vec<Receivable>,
+    /// Ordem ou nota já conciliada -> lançamento que a conciliou
+    reconciled: HashMap<Uuid, String>,
+    auto_threshold: f64,
+    review_threshold: f64,
+    /// Folga entre o melhor e o segundo candidato para conciliar sozinho
+    margin: f64,
+    /// Dias após a emissão em que o pagamento ainda é esperado
+    window_days: i64,
+    max_candidates: usize,
+}
+
+impl PaymentMatcher {
+    /// Ordens canceladas não recebem pagamento
+    pub fn new(orders: &[FreightOrder], invoices: &[Invoice]) -> Self {
+        let receivables = orders
+            .iter()
+            .filter(|o| o.status != Status::Cancelled && o.valor_frete.is_positive())
+            .map(Receivable::from_order)
+            .chain(invoices.iter().filter(|i| i.valor_total.is_positive()).map(Receivable::from_invoice))
+            .collect();
+        Self {
+            receivables,
+            reconciled: HashMap::new(),
+            auto_threshold: 0.7,
+            review_threshold: 0.4,
+            margin: 0.15,
+            window_days: 90,
+            max_candidates: 5,
+        }
+    }
+
+    /// Lançamentos já gravados: ordens e notas conciliadas por eles saem da disputa
+    pub fn with_transactions(mut self, transactions: &[PaymentTransaction]) -> Self {
+        for transaction in transactions {
+            if let Some(matched) = &transaction.conciliado_com {
+                if matches!(transaction.status, MatchStatus::Matched | MatchStatus::Accepted) {
+                    self.reconciled.insert(matched.entidade_id, transaction.natural_key());
+                }
+            }
+        }
+        self
+    }
+
+    pub fn with_thresholds(mut self, auto: f64, review: f64) -> Self {
+        self.auto_threshold = auto;
+        self.review_threshold = review;
+        self
+    }
+
+    pub fn with_window_days(mut self, days: i64) -> Self {
+        self.window_days = days;
+        self
+    }
+
+    /// Atualiza candidatos e status dos lançamentos, na ordem recebida
+    pub fn reconcile(&mut self, transactions: &mut [PaymentTransaction]) -> ReconciliationSummary {
+        let mut summary = ReconciliationSummary::default();
+
+        for transaction in transactions.iter_mut() {
+            if !transaction.is_credit() || transaction.is_reviewed() {
+                summary.skipped += 1;
+                continue;
+            }
+
+            let key = transaction.natural_key();
+            let (candidates, exact) = self.candidates(transaction, &key);
+            let best = candidates.first();
+            let second = candidates.get(1).map_or(0.0, |c| c.pontuacao);
+
+            transaction.conciliado_com = None;
+            transaction.status = match best {
+                Some(best) if exact && best.pontuacao >= self.auto_threshold && best.pontuacao - second >= self.margin => {
+                    self.reconciled.insert(best.entidade_id, key);
+                    transaction.conciliado_com = Some(best.clone());
+                    summary.matched += 1;
+                    MatchStatus::Matched
+                }
+                Some(_) => {
+                    summary.review += 1;
+                    MatchStatus::Review
+                }
+                None => {
+                    summary.unmatched += 1;
+                    MatchStatus::Unmatched
+                }
+            };
+            transaction.candidatos = candidates;
+        }
+
+        summary
+    }
+
+    /// Candidatos do lançamento, do melhor para o pior; `true` se o melhor tem o valor exato
+    fn candidates(&self, transaction: &PaymentTransaction, key: &str) -> (Vec<PaymentMatch>, bool) {
+        let numbers = digit_runs(&transaction.descricao);
+        let texto = normalize_company_name(&format!(
+            "{} {}",
+            transaction.pagador_nome.as_deref().unwrap_or(""),
+            transaction.descricao
+        ));
+
+        let mut scored: Vec<(PaymentMatch, bool)> = self
+            .receivables
+            .iter()
+            .filter(|r| self.reconciled.get(&r.id).is_none_or(|k| k == key))
+            .filter_map(|r| {
+                let days = (transaction.data - r.emissao).num_days();
+                if !(-DIAS_ANTES_EMISSAO..=self.window_days).contains(&days) {
+                    return None;
+                }
+
+                let mut score = 0.0;
+                let mut motivos = Vec::new();
+
+                let diff = (transaction.valor - r.valor).abs();
+                let exact = diff.is_zero();
+                if exact {
+                    score += SCORE_VALOR_IGUAL;
+                    motivos.push(format!("Valor igual ({})", r.valor));
+                } else if diff <= r.valor.scale(TOLERANCIA_VALOR, Rounding::HalfUp) {
+                    score += SCORE_VALOR_PROXIMO;
+                    motivos.push(format!("Valor próximo ({} de {})", transaction.valor, r.valor));
+                } else if transaction.valor < r.valor
+                    && transaction.valor >= r.valor.scale(MIN_PARCIAL, Rounding::HalfUp)
+                {
+                    score += SCORE_VALOR_PARCIAL;
+                    motivos.push(format!("Pagamento parcial ({} de {})", transaction.valor, r.valor));
+                }
+
+                if let Some((label, numero)) = r
+                    .documentos
+                    .iter()
+                    .find(|(_, n)| numbers.contains(&strip_zeros(n)))
+                {
+                    score += SCORE_DOCUMENTO;
+                    motivos.push(format!("{} {} no histórico", label, numero));
+                }
+
+                let documento = transaction.pagador_documento.as_deref();
+                if documento.is_some_and(|d| r.cnpjs.iter().any(|c| c == d)) {
+                    score += SCORE_CNPJ;
+                    motivos.push("CNPJ do pagador".to_string());
+                } else if let Some(pagador) = r.pagadores.iter().find(|p| {
+                    let nome = normalize_company_name(p);
+                    !nome.is_empty() && contains_words(&texto, &nome)
+                }) {
+                    score += SCORE_PAGADOR;
+                    motivos.push(format!("Pagador {} no lançamento", pagador));
+                }
+
+                // Só a data ou só a forma de pagamento não fazem um candidato
+                if score == 0.0 {
+                    return None;
+                }
+                if days >= 0 {
+                    score += SCORE_DATA;
+                    motivos.push(format!("Pago {} dia(s) após a emissão", days));
+                }
+                if r.forma_pagamento.as_deref().is_some_and(|f| transaction.forma.matches(f)) {
+                    score += SCORE_FORMA;
+                    motivos.push(format!("Forma de pagamento {}", transaction.forma));
+                }
+
+                let pontuacao = f64::min(score, 1.0);
+                (pontuacao >= self.review_threshold).then(|| {
+                    let candidate = PaymentMatch {
+                        entidade: r.entidade.to_string(),
+                        entidade_id: r.id,
+                        numero: r.numero.clone(),
+                        valor: r.valor,
+                        pontuacao,
+                        motivos,
+                    };
+                    (candidate, exact)
+                })
+            })
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.0.pontuacao
+                .total_cmp(&a.0.pontuacao)
+                .then_with(|| a.0.numero.cmp(&b.0.numero))
+        });
+        scored.truncate(self.max_candidates);
+        let exact = scored.first().is_some_and(|(_, exact)| *exact);
+        (scored.into_iter().map(|(c, _)| c).collect(), exact)
+    }
+}
+
+/// Nome inteiro, palavra por palavra, dentro do texto
+fn contains_words(texto: &str, nome: &str) -> bool {
+    format!(" {} ", texto).contains(&format!(" {} ", nome))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(numero: &str, pagador: &str, valor: Money) -> FreightOrder {
+        let mut order = FreightOrder::new(
+            numero.into(),
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            pagador.into(),
+            pagador.into(),
+            "Ribeirão Preto".into(),
+            "Cliente".into(),
+            "Franca".into(),
+        );
+        order.valor_frete = valor;
+        order
+    }
+
+    fn credit(descricao: &str, valor: Money) -> PaymentTransaction {
+        PaymentTransaction::new(
+            "itau".into(),
+            Uuid::new_v4().to_string(),
+            NaiveDate::from_ymd_opt(2025, 4, 10).unwrap(),
+            valor,
+            descricao.into(),
+        )
+    }
+
+    #[test]
+    fn test_exact_amount_and_payer_is_matched() {
+        let orders = vec![order("288415", "FENIOR LTDA", Money::from_cents(125_050)), order("288416", "Comercial Franca", Money::from_reais(800))];
+        let mut transactions = vec![
+            credit("PIX RECEBIDO FENIOR", Money::from_cents(125_050)),
+            credit("TED COMERCIAL FRANCA ORDEM 288416", Money::from_reais(400)),
+            credit("PIX RECEBIDO FULANO", Money::from_reais(99)),
+        ];
+
+        let summary = PaymentMatcher::new(&orders, &[]).reconcile(&mut transactions);
+        assert_eq!(summary, ReconciliationSummary { matched: 1, review: 1, unmatched: 1, skipped: 0 });
+
+        assert_eq!(transactions[0].status, MatchStatus::Matched);
+        assert_eq!(transactions[0].conciliado_com.as_ref().unwrap().numero, "288415");
+
+        // Metade do valor, com o número da ordem: parcial, vai para revisão
+        assert_eq!(transactions[1].status, MatchStatus::Review);
+        assert_eq!(transactions[1].candidatos[0].numero, "288416");
+        assert!(transactions[1].candidatos[0].motivos.iter().any(|m| m.starts_with("Pagamento parcial")));
+    }
+
+    #[test]
+    fn test_ambiguous_and_already_reconciled() {
+        // Duas ordens do mesmo pagador com o mesmo valor: o operador decide
+        let orders = vec![order("100", "FENIOR", Money::from_reais(500)), order("101", "FENIOR", Money::from_reais(500))];
+        let mut first = vec![credit("PIX FENIOR", Money::from_reais(500))];
+        PaymentMatcher::new(&orders, &[]).reconcile(&mut first);
+        assert_eq!(first[0].status, MatchStatus::Review);
+        assert_eq!(first[0].candidatos.len(), 2);
+
+        // Com a ordem 100 já conciliada, a 101 é a única que sobra
+        first[0].accept(&orders[0].id);
+        let mut second = vec![credit("PIX FENIOR", Money::from_reais(500))];
+        let summary = PaymentMatcher::new(&orders, &[])
+            .with_transactions(&first)
+            .reconcile(&mut second);
+        assert_eq!(summary.matched, 1);
+        assert_eq!(second[0].conciliado_com.as_ref().unwrap().numero, "101");
+    }
+}
@@ -0,0 +1,620 @@
+//! Registro de importadores
+//!
+//! Cada importador se registra com um nome, as extensões que aceita, um
+//! detector de conteúdo, a entidade que produz e como gravar o resultado.
+//! A CLI, o upload da API, o watcher e a importação em massa escolhem e
+//! executam importadores pelo registro; um importador novo é só mais uma
+//! chamada a [`ImporterRegistry::register`].
+
+use crate::afd_importer::AfdImporter;
+use crate::archive::ArchiveImporter;
+use crate::csv_source::CsvOptions;
+use crate::detect::{self, AnyImporter, Detection, FileKind, ImportOutput};
+use crate::dry_run::{self, DryRunReport};
+use crate::edi::NotfisImporter;
+use crate::excel_importer::ExcelImporter;
+use crate::fiscal_xml::FiscalXmlImporter;
+use crate::geo::Municipalities;
+use crate::freight_importer::FreightOrderImporter;
+use crate::jobs::JobTracker;
+use crate::lineage::{LineageStamp, IMPORTER_VERSION};
+use crate::pdf_importer::PdfImporter;
+use crate::photo_importer::PhotoImporter;
+use crate::route_importer::RouteImporter;
+use crate::streaming::{import_in_batches_tracked, BatchImportReport, DEFAULT_BATCH_SIZE};
+use crate::timesheet_importer::TimesheetImporter;
+use async_trait::async_trait;
+use pc_core::Result;
+use pc_db::{PersonalControllerDb, UpsertReport};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Ajustes de uma importação
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    pub csv: CsvOptions,
+    /// Registros por lote nos importadores em streaming
+    pub batch_size: usize,
+    /// Municípios para padronizar as cidades das rotas (padrão: a tabela embutida)
+    pub municipalities: Option<Arc<Municipalities>>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            csv: CsvOptions::default(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            municipalities: None,
+        }
+    }
+}
+
+/// Início de um arquivo, entregue aos detectores de cada importador
+#[derive(Debug, Clone)]
+pub struct FileSample {
+    pub path: PathBuf,
+    pub head: Vec<u8>,
+    /// Tipo reconhecido pela detecção embutida ([`detect::sniff`])
+    pub detection: Detection,
+}
+
+impl FileSample {
+    pub fn new(path: impl Into<PathBuf>, head: Vec<u8>) -> Self {
+        let path = path.into();
+        let detection = detect::sniff(&path, &head);
+        Self { path, head, detection }
+    }
+
+    /// Lê o início do arquivo
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(Self::new(path, detect::read_head(path)?))
+    }
+
+    /// Extensão em minúsculas, sem o ponto
+    pub fn extension(&self) -> String {
+        self.path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default()
+    }
+}
+
+/// Importador que pode ser descoberto pelo registro
+#[async_trait]
+pub trait RegisteredImporter: Send + Sync {
+    /// Nome aceito em `pc import --type` e gravado nos jobs
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// Versão gravada na linhagem dos registros
+    fn version(&self) -> &'static str {
+        IMPORTER_VERSION
+    }
+
+    /// Extensões aceitas, em minúsculas e sem o ponto
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Collection onde o resultado é gravado; `None` quando varia com o
+    /// conteúdo (arquivos compactados) ou ainda não é gravado
+    fn entity(&self) -> Option<&'static str>;
+
+    /// Reconhece o arquivo; `None` se não é deste importador
+    ///
+    /// Por padrão reconhece só pela extensão, com confiança baixa.
+    fn sniff(&self, sample: &FileSample) -> Option<Detection> {
+        let extension = sample.extension();
+        self.extensions().contains(&extension.as_str()).then(|| {
+            Detection::new(FileKind::Unknown, 0.5, format!("Extensão .{}", extension))
+        })
+    }
+
+    /// Lê o arquivo inteiro (bloqueante)
+    fn read(&self, path: &Path, options: &ImportOptions) -> Result<ImportOutput>;
+
+    /// Grava o resultado da leitura; `None` se o tipo não é gravado
+    async fn persist(&self, db: &PersonalControllerDb, output: ImportOutput) -> Result<Option<UpsertReport>> {
+        store(db, output).await
+    }
+
+    /// `true` se [`stream`](Self::stream) grava o arquivo em lotes; a
+    /// importação em massa não lê esses arquivos de antemão
+    fn streams(&self) -> bool {
+        false
+    }
+
+    /// Lê e grava em lotes, sem carregar o arquivo inteiro
+    ///
+    /// `None` se o importador não lê em streaming; nesse caso o arquivo é
+    /// lido com [`read`](Self::read) e gravado com [`persist`](Self::persist).
+    ///
+    /// `sha256` é o hash da cópia do arquivo no blob store.
+    async fn stream(
+        &self,
+        _db: &PersonalControllerDb,
+        _path: &Path,
+        _sha256: &str,
+        _options: &ImportOptions,
+        _job: &mut JobTracker<'_>,
+    ) -> Result<Option<BatchImportReport>> {
+        Ok(None)
+    }
+
+    /// Simula a importação sem gravar nada
+    async fn dry_run(&self, db: &PersonalControllerDb, path: &Path, options: &ImportOptions) -> Result<DryRunReport> {
+        dry_run::dry_run_output(self.read(path, options)?, db).await
+    }
+}
+
+/// Grava qualquer saída de importador na collection correspondente
+pub async fn store(db: &PersonalControllerDb, output: ImportOutput) -> Result<Option<UpsertReport>> {
+    let report = match output {
+        ImportOutput::FreightOrders(orders) => Some(db.freight_orders.upsert_many(orders).await?),
+        ImportOutput::Timesheets(timesheets) => Some(db.timesheets.upsert_many(timesheets).await?),
+        ImportOutput::Routes(routes) => Some(db.routes.upsert_many(routes).await?),
+        ImportOutput::Photos(_) | ImportOutput::Pdfs(_) | ImportOutput::Documents(_) => match output.to_documents() {
+            Some(documents) => Some(db.store_documents(documents).await?),
+            None => None,
+        },
+        ImportOutput::Archive(contents) => Some(crate::archive::store(db, &contents).await?),
+        // Abas não reconhecidas não têm collection própria
+        ImportOutput::ExcelRows(_) => None,
+    };
+    Ok(report)
+}
+
+/// Importa um arquivo com o importador escolhido, atualizando o job
+///
+/// Usa o streaming quando o importador oferece; senão lê o arquivo,
+/// entrega a saída a `on_read` (para exibição) e grava com a linhagem. Uma
+/// cópia do arquivo vai para o blob store, para consultas de linhagem.
+pub async fn import_file<F>(
+    importer: &dyn RegisteredImporter,
+    db: &PersonalControllerDb,
+    path: &Path,
+    options: &ImportOptions,
+    job: &mut JobTracker<'_>,
+    on_read: F,
+) -> Result<Option<BatchImportReport>>
+where
+    F: FnOnce(&ImportOutput) + Send,
+{
+    let sha256 = db.blobs.put_file(path)?;
+    import_stored_file(importer, db, path, &sha256, options, job, on_read).await
+}
+
+/// Como [`import_file`], para um arquivo já copiado para o blob store
+///
+/// `sha256` é o hash devolvido por `put_file`; quem já precisou do hash
+/// (para pular arquivos já importados) não lê o arquivo de novo.
+pub async fn import_stored_file<F>(
+    importer: &dyn RegisteredImporter,
+    db: &PersonalControllerDb,
+    path: &Path,
+    sha256: &str,
+    options: &ImportOptions,
+    job: &mut JobTracker<'_>,
+    on_read: F,
+) -> Result<Option<BatchImportReport>>
+where
+    F: FnOnce(&ImportOutput) + Send,
+{
+    if let Some(report) = importer.stream(db, path, sha256, options, job).await? {
+        return Ok(Some(report));
+    }
+
+    let output = importer.read(path, options)?;
+    on_read(&output);
+    job.check_cancelled().await?;
+
+    let stamp = LineageStamp::for_file(path, sha256, importer.name(), importer.version()).with_job(Some(job.id()));
+    let output = output.with_lineage(&stamp);
+
+    let report = importer.persist(db, output).await?.map(BatchImportReport::from);
+    if let Some(report) = &report {
+        job.record_batch(report.new, report.updated, report.unchanged).await?;
+    }
+    Ok(report)
+}
+
+/// Importador escolhido para um arquivo
+#[derive(Clone)]
+pub struct SelectedImporter {
+    pub importer: Arc<dyn RegisteredImporter>,
+    /// Presente quando o importador foi escolhido pelo conteúdo
+    pub detection: Option<Detection>,
+}
+
+/// Importadores conhecidos, na ordem de registro
+#[derive(Clone)]
+pub struct ImporterRegistry {
+    importers: Vec<Arc<dyn RegisteredImporter>>,
+}
+
+impl ImporterRegistry {
+    /// Registro vazio (sem os importadores embutidos)
+    pub fn empty() -> Self {
+        Self { importers: Vec::new() }
+    }
+
+    /// Registro com os importadores embutidos
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        for builtin in BUILTINS {
+            registry.register(builtin.clone());
+        }
+        registry
+    }
+
+    /// Registra um importador, substituindo outro de mesmo nome
+    pub fn register<I: RegisteredImporter + 'static>(&mut self, importer: I) -> &mut Self {
+        let importer: Arc<dyn RegisteredImporter> = Arc::new(importer);
+        match self.importers.iter_mut().find(|i| i.name() == importer.name()) {
+            Some(existing) => *existing = importer,
+            None => self.importers.push(importer),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn RegisteredImporter>> {
+        self.importers.iter().find(|i| i.name() == name).cloned()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn RegisteredImporter>> {
+        self.importers.iter()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.importers.iter().map(|i| i.name()).collect()
+    }
+
+    /// Importadores que aceitam a extensão (sem o ponto)
+    pub fn for_extension(&self, extension: &str) -> Vec<Arc<dyn RegisteredImporter>> {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        self.importers
+            .iter()
+            .filter(|i| i.extensions().contains(&extension.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Importador que reconhece a amostra com maior confiança
+    ///
+    /// Em caso de empate vale o registrado primeiro.
+    pub fn find(&self, sample: &FileSample) -> Option<SelectedImporter> {
+        let mut best: Option<SelectedImporter> = None;
+        for importer in &self.importers {
+            let Some(detection) = importer.sniff(sample) else {
+                continue;
+            };
+            let better = best
+                .as_ref()
+                .and_then(|b| b.detection.as_ref())
+                .is_none_or(|b| detection.confidence > b.confidence);
+            if better {
+                best = Some(SelectedImporter {
+                    importer: Arc::clone(importer),
+                    detection: Some(detection),
+                });
+            }
+        }
+        best
+    }
+
+    /// Detecta o importador pelo conteúdo do arquivo
+    pub fn detect(&self, path: &Path) -> Result<SelectedImporter> {
+        let sample = FileSample::read(path)?;
+        self.find(&sample).ok_or_else(|| unsupported(path, &sample.detection))
+    }
+
+    /// Importador pelo nome, ou pela detecção quando o nome é `auto`
+    pub fn resolve(&self, name: &str, path: &Path) -> Result<SelectedImporter> {
+        if name == "auto" {
+            return self.detect(path);
+        }
+        self.get(name)
+            .map(|importer| SelectedImporter { importer, detection: None })
+            .ok_or_else(|| {
+                pc_core::Error::Validation(format!(
+                    "Tipo desconhecido: {} (use auto, {})",
+                    name,
+                    self.names().join(", ")
+                ))
+            })
+    }
+}
+
+impl Default for ImporterRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Erro para arquivos que nenhum importador reconhece
+fn unsupported(path: &Path, detection: &Detection) -> pc_core::Error {
+    if detection.kind == FileKind::Unknown {
+        pc_core::Error::Import(format!("Não foi possível detectar o tipo do arquivo: {}", path.display()))
+    } else {
+        pc_core::Error::Import(format!(
+            "Arquivo {} reconhecido como {} ({}), mas não há importador para esse tipo",
+            path.display(),
+            detection.kind.as_str(),
+            detection.reason
+        ))
+    }
+}
+
+/// Importador embutido, reconhecido pela detecção de [`detect::sniff`]
+#[derive(Clone)]
+struct Builtin {
+    name: &'static str,
+    description: &'static str,
+    extensions: &'static [&'static str],
+    entity: Option<&'static str>,
+    kinds: &'static [FileKind],
+    build: fn() -> AnyImporter,
+}
+
+impl Builtin {
+    fn importer(&self, options: &ImportOptions) -> AnyImporter {
+        let importer = (self.build)().with_csv_options(options.csv);
+        match &options.municipalities {
+            Some(list) => importer.with_municipalities(Arc::clone(list)),
+            None => importer,
+        }
+    }
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "freight",
+        description: "Planilha de ordens de frete do TMS",
+        extensions: &["csv"],
+        entity: Some("freight_orders"),
+        kinds: &[FileKind::FreightCsv],
+        build: || AnyImporter::Freight(FreightOrderImporter::new()),
+    },
+    Builtin {
+        name: "timesheet",
+        description: "Planilha de ponto",
+        extensions: &["csv"],
+        entity: Some("timesheets"),
+        kinds: &[FileKind::TimesheetCsv],
+        build: || AnyImporter::Timesheet(TimesheetImporter::new()),
+    },
+    Builtin {
+        name: "route",
+        description: "Planilha de rotas por região administrativa",
+        extensions: &["csv"],
+        entity: Some("routes"),
+        kinds: &[FileKind::RouteCsv],
+        build: || AnyImporter::Route(RouteImporter::new()),
+    },
+    Builtin {
+        name: "notfis",
+        description: "EDI PROCEDA NOTFIS (notas fiscais para transporte)",
+        extensions: &["txt"],
+        entity: Some("freight_orders"),
+        kinds: &[FileKind::Notfis],
+        build: || AnyImporter::Notfis(NotfisImporter::new()),
+    },
+    Builtin {
+        name: "afd",
+        description: "AFD do relógio de ponto (Portaria 671)",
+        extensions: &["txt"],
+        entity: Some("timesheets"),
+        kinds: &[FileKind::Afd],
+        build: || AnyImporter::Afd(AfdImporter::new()),
+    },
+    Builtin {
+        name: "photo",
+        description: "Fotos de canhotos e avarias",
+        extensions: &["png", "jpg", "jpeg", "gif", "bmp", "webp", "heic", "heif"],
+        entity: Some("documents"),
+        kinds: &[FileKind::Image],
+        build: || AnyImporter::Photo(PhotoImporter::new()),
+    },
+    Builtin {
+        name: "pdf",
+        description: "Documentos em PDF",
+        extensions: &["pdf"],
+        entity: Some("documents"),
+        kinds: &[FileKind::Pdf],
+        build: || AnyImporter::Pdf(PdfImporter::new()),
+    },
+    Builtin {
+        name: "excel",
+        description: "Planilhas Excel; abas de fretes ou de ponto são importadas",
+        extensions: &["xlsx", "xls"],
+        entity: None,
+        kinds: &[FileKind::Excel],
+        build: || AnyImporter::Excel(ExcelImporter::new()),
+    },
+    Builtin {
+        name: "archive",
+        description: "ZIPs e e-mails (.eml, .mbox), importando cada arquivo extraído",
+        extensions: &["zip", "eml", "mbox", "mbx"],
+        entity: None,
+        kinds: &[FileKind::Archive, FileKind::Email],
+        build: || AnyImporter::Archive(ArchiveImporter::new()),
+    },
+    Builtin {
+        name: "xml",
+        description: "XML de NF-e e CT-e, guardado como documento",
+        extensions: &["xml"],
+        entity: Some("documents"),
+        kinds: &[FileKind::NfeXml, FileKind::CteXml],
+        build: || AnyImporter::FiscalXml(FiscalXmlImporter::new()),
+    },
+];
+
+#[async_trait]
+impl RegisteredImporter for Builtin {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        self.extensions
+    }
+
+    fn entity(&self) -> Option<&'static str> {
+        self.entity
+    }
+
+    fn sniff(&self, sample: &FileSample) -> Option<Detection> {
+        self.kinds
+            .contains(&sample.detection.kind)
+            .then(|| sample.detection.clone())
+    }
+
+    fn read(&self, path: &Path, options: &ImportOptions) -> Result<ImportOutput> {
+        self.importer(options).run(path)
+    }
+
+    fn streams(&self) -> bool {
+        matches!(
+            (self.build)(),
+            AnyImporter::Freight(_) | AnyImporter::Timesheet(_) | AnyImporter::Afd(_) | AnyImporter::Notfis(_)
+        )
+    }
+
+    async fn stream(
+        &self,
+        db: &PersonalControllerDb,
+        path: &Path,
+        sha256: &str,
+        options: &ImportOptions,
+        job: &mut JobTracker<'_>,
+    ) -> Result<Option<BatchImportReport>> {
+        // Planilhas de fretes e de ponto são lidas em streaming e gravadas em lotes;
+        // o NOTFIS e o AFD são interpretados de uma vez e gravados em lotes
+        let report = match self.importer(options) {
+            AnyImporter::Freight(importer) => {
+                import_in_batches_tracked(&importer, path, sha256, &db.freight_orders, &db.import_checkpoints, options.batch_size, job)
+                    .await?
+            }
+            AnyImporter::Timesheet(importer) => {
+                import_in_batches_tracked(&importer, path, sha256, &db.timesheets, &db.import_checkpoints, options.batch_size, job)
+                    .await?
+            }
+            AnyImporter::Notfis(importer) => {
+                import_in_batches_tracked(&importer, path, sha256, &db.freight_orders, &db.import_checkpoints, options.batch_size, job)
+                    .await?
+            }
+            // O AFD é pareado de uma vez, mas passa pelos lotes para levar as inconsistências ao job
+            AnyImporter::Afd(importer) => {
+                import_in_batches_tracked(&importer, path, sha256, &db.timesheets, &db.import_checkpoints, options.batch_size, job)
+                    .await?
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(report))
+    }
+
+    async fn dry_run(&self, db: &PersonalControllerDb, path: &Path, options: &ImportOptions) -> Result<DryRunReport> {
+        dry_run::dry_run_any(self.importer(options), path, db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_models::Timesheet;
+
+    /// Importador de teste: lista de funcionários em .ponto, uma por linha
+    struct PontoImporter;
+
+    #[async_trait]
+    impl RegisteredImporter for PontoImporter {
+        fn name(&self) -> &'static str {
+            "ponto"
+        }
+
+        fn description(&self) -> &'static str {
+            "Lista de funcionários"
+        }
+
+        fn extensions(&self) -> &'static [&'static str] {
+            &["ponto"]
+        }
+
+        fn entity(&self) -> Option<&'static str> {
+            Some("timesheets")
+        }
+
+        fn read(&self, path: &Path, _options: &ImportOptions) -> Result<ImportOutput> {
+            let content = std::fs::read_to_string(path).map_err(|e| pc_core::Error::Import(e.to_string()))?;
+            let data = chrono::NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+            let hora = |h| chrono::NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+            let timesheets = content
+                .lines()
+                .map(|nome| Timesheet::new(nome.to_string(), "Abril".into(), data, hora(8), hora(17)))
+                .collect();
+            Ok(ImportOutput::Timesheets(timesheets))
+        }
+    }
+
+    #[test]
+    fn test_builtins_follow_detection() {
+        let registry = ImporterRegistry::default();
+        assert!(registry.names().starts_with(&["freight", "timesheet", "route"]));
+
+        let sample = FileSample::new("x.csv", b"funcionario;mes;data;entrada;saida\n".to_vec());
+        let selected = registry.find(&sample).unwrap();
+        assert_eq!(selected.importer.name(), "timesheet");
+        assert_eq!(selected.importer.entity(), Some("timesheets"));
+
+        assert!(registry.find(&FileSample::new("notas.txt", b"qualquer coisa".to_vec())).is_none());
+        assert_eq!(registry.for_extension(".ZIP")[0].name(), "archive");
+
+        let nfe = FileSample::new("nfe.xml", b"<nfeProc xmlns=\"http://www.portalfiscal.inf.br/nfe\">".to_vec());
+        let selected = registry.find(&nfe).unwrap();
+        assert_eq!(selected.importer.name(), "xml");
+        assert_eq!(selected.importer.entity(), Some("documents"));
+        assert!(matches!(
+            registry.resolve("planilha", Path::new("x.csv")),
+            Err(pc_core::Error::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_registered_importer_is_detected_and_run() {
+        let path = std::env::temp_dir().join(format!("equipe-{}.ponto", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "João\nMaria\n").unwrap();
+
+        let mut registry = ImporterRegistry::default();
+        assert!(registry.detect(&path).is_err());
+        registry.register(PontoImporter);
+
+        let selected = registry.detect(&path).unwrap();
+        assert_eq!(selected.importer.name(), "ponto");
+
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+        let mut job = JobTracker::start(&db.import_jobs, &path, "ponto", None).await.unwrap();
+        let mut lidos = 0;
+        let report = import_file(
+            selected.importer.as_ref(),
+            &db,
+            &path,
+            &ImportOptions::default(),
+            &mut job,
+            |output| lidos = output.len(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(lidos, 2);
+        assert_eq!(report.new, 2);
+        assert_eq!(db.timesheets.count().await.unwrap(), 2);
+        assert_eq!(job.job().novos, 2);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
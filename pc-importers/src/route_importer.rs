@@ -1,16 +1,52 @@
 //! Route importer
+//!
+//! A planilha de rotas tem uma coluna por região, com o nome da região no
+//! cabeçalho (ex. "Franca (RA13)") e as cidades atendidas abaixo, na ordem
+//! de entrega. Cada coluna de região vira uma [`Route`] com o nome do
+//! cabeçalho; reimportar a planilha atualiza as rotas pelo nome.
 
-use pc_core::Result;
-use pc_models::Route;
+use pc_core::{NaturalKey, Result};
+use pc_models::{Lineage, Route};
+use crate::csv_source::{CsvOptions, CsvSource};
+use crate::geo::{split_uf, Municipalities};
 use crate::Importer;
-use encoding_rs::WINDOWS_1252;
 use std::path::Path;
+use std::sync::Arc;
 
-pub struct RouteImporter;
+pub struct RouteImporter {
+    csv_options: CsvOptions,
+    municipalities: Arc<Municipalities>,
+}
 
 impl RouteImporter {
     pub fn new() -> Self {
-        Self
+        Self {
+            csv_options: CsvOptions::default(),
+            municipalities: Arc::new(Municipalities::new()),
+        }
+    }
+
+    /// Força encoding, delimitador ou aspas em vez de detectá-los
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
+
+    /// Lista usada para padronizar a grafia das cidades
+    pub fn with_municipalities(mut self, municipalities: Arc<Municipalities>) -> Self {
+        self.municipalities = municipalities;
+        self
+    }
+
+    /// Grafia oficial da cidade; nomes fora da lista ficam como vieram, sem a UF
+    fn city_name(&self, raw: &str) -> String {
+        match self.municipalities.canonical(raw) {
+            Some(name) => name.to_string(),
+            None => {
+                tracing::warn!("Cidade fora da lista de municípios: {}", raw);
+                split_uf(raw).0.to_string()
+            }
+        }
     }
 }
 
@@ -20,58 +56,83 @@ impl Default for RouteImporter {
     }
 }
 
+/// "Ribeirão Preto (RA15)" -> "Ribeirão Preto"
+fn region_of(header: &str) -> String {
+    header.split('(').next().unwrap_or(header).trim().to_string()
+}
+
 impl Importer for RouteImporter {
     type Output = Route;
-    
+
     fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
         let path = path.as_ref();
         tracing::info!("Importando rotas de: {:?}", path);
-        
-        let file_bytes = std::fs::read(path)
-            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
-        
-        let (content, _, _) = WINDOWS_1252.decode(&file_bytes);
-        
-        let mut reader = csv::ReaderBuilder::new()
-            .delimiter(b';')
-            .has_headers(true)
-            .flexible(true)
-            .from_reader(content.as_bytes());
-        
-        let mut routes = Vec::new();
-        
-        for result in reader.records() {
-            let record = match result {
-                Ok(r) => r,
-                Err(_) => continue,
-            };
-            
-            // Processa as cidades das rotas
-            for (col_idx, field) in record.iter().enumerate() {
+
+        let source = CsvSource::read(path, &self.csv_options)?;
+        let mut reader = source.reader();
+        let headers = reader
+            .headers()
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler cabeçalho de rotas: {}", e)))?
+            .clone();
+
+        // Rota de cada coluna. Regiões em células mescladas exportam o nome só
+        // na primeira coluna; as seguintes, em branco, pertencem à mesma rota.
+        let mut routes: Vec<Route> = Vec::new();
+        let mut column_route: Vec<Option<usize>> = Vec::with_capacity(headers.len());
+        let mut current = None;
+        for header in headers.iter().map(str::trim) {
+            if !header.is_empty() {
+                let mut route = Route::new(header.to_string(), region_of(header));
+                route.linhagem = Some(Lineage::at_column(header));
+                let key = route.natural_key();
+                current = Some(match routes.iter().position(|r| r.natural_key() == key) {
+                    Some(idx) => idx,
+                    None => {
+                        routes.push(route);
+                        routes.len() - 1
+                    }
+                });
+            }
+            column_route.push(current);
+        }
+
+        let mut columns: Vec<Vec<String>> = vec![Vec::new(); column_route.len()];
+        for (idx, result) in reader.records().enumerate() {
+            let record = result
+                .map_err(|e| pc_core::Error::Import(format!("Linha {} da planilha de rotas: {}", idx + 2, e)))?;
+            for (column, field) in columns.iter_mut().zip(record.iter()) {
                 let cidade = field.trim();
-                if cidade.is_empty() || cidade.starts_with('�') {
-                    continue;
+                if !cidade.is_empty() {
+                    column.push(cidade.to_string());
+                }
+            }
+        }
+
+        // Cidades na ordem da coluna, de cima para baixo
+        for (column, route) in columns.iter().zip(&column_route) {
+            let Some(route) = route else {
+                if !column.is_empty() {
+                    tracing::warn!("Coluna sem região no cabeçalho ignorada ({} cidades)", column.len());
                 }
-                
-                // Extrai região baseada na coluna
-                let regiao = match col_idx {
-                    0..=7 => "Ribeirão Preto (RA15)",
-                    8..=15 => "Franca",
-                    16..=23 => "Central (RA14 - Araraquara)",
-                    _ => "Araras",
-                };
-                
-                let nome = format!("{} - {}", regiao, cidade);
-                let mut route = Route::new(nome, regiao.to_string());
-                route.add_cidade(cidade.to_string());
-                routes.push(route);
+                continue;
+            };
+            for cidade in column {
+                let cidade = self.city_name(cidade);
+                routes[*route].add_cidade(cidade);
             }
         }
-        
+
+        routes.retain(|route| {
+            if route.cidades.is_empty() {
+                tracing::warn!("Região {} sem cidades ignorada", route.nome);
+            }
+            !route.cidades.is_empty()
+        });
+
         tracing::info!("Importadas {} rotas", routes.len());
         Ok(routes)
     }
-    
+
     fn can_import<P: AsRef<Path>>(&self, path: P) -> bool {
         let path = path.as_ref();
         if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
@@ -81,3 +142,72 @@ impl Importer for RouteImporter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_db::{GenericRepository, Repository};
+
+    fn write_csv(content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("Rotas-{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_one_route_per_region_column() {
+        // "Franca (RA13)" ocupa duas colunas (célula mesclada na planilha)
+        let path = write_csv(
+            "Ribeirão Preto (RA15);Franca (RA13);;Central (RA14)\n\
+             SERTAOZINHO;Franca/SP;Batatais;Matão\n\
+             Jardinopolis;Patrocinio Paulista;;\n\
+             Cravinhos;;Cidade Nova;\n",
+        );
+
+        let routes = RouteImporter::new().import_file(&path).unwrap();
+        assert_eq!(routes.len(), 3);
+
+        assert_eq!(routes[0].nome, "Ribeirão Preto (RA15)");
+        assert_eq!(routes[0].regiao, "Ribeirão Preto");
+        assert_eq!(routes[0].cidades, vec!["Sertãozinho", "Jardinópolis", "Cravinhos"]);
+
+        assert_eq!(
+            routes[1].cidades,
+            vec!["Franca", "Patrocínio Paulista", "Batatais", "Cidade Nova"]
+        );
+        assert_eq!(routes[2].cidades, vec!["Matão"]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reimport_updates_existing_routes() {
+        let repository = Repository::<Route>::new("routes");
+        let first = write_csv("Franca (RA13);Central (RA14)\nFranca;Matão\n");
+        repository
+            .upsert_many(RouteImporter::new().import_file(&first).unwrap())
+            .await
+            .unwrap();
+
+        // Dado cadastrado à mão, que não vem da planilha
+        let mut franca = repository.find_by_natural_key("franca (ra13)").await.unwrap().unwrap();
+        franca.distancia_km = Some(85.0);
+        repository.update(&franca).await.unwrap();
+
+        let second = write_csv("FRANCA (RA13);Central (RA14)\nFranca;Matão\nBatatais;Araraquara\n");
+        let report = repository
+            .upsert_many(RouteImporter::new().import_file(&second).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(report.updated.len(), 2);
+        assert_eq!(repository.count().await.unwrap(), 2);
+        let franca = repository.find_by_id(&franca.id).await.unwrap().unwrap();
+        assert_eq!(franca.cidades, vec!["Franca", "Batatais"]);
+        assert_eq!(franca.distancia_km, Some(85.0));
+        assert_eq!(franca.natural_key(), "franca (ra13)");
+
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+}
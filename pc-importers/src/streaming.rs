@@ -0,0 +1,364 @@
+//! Importação incremental de arquivos grandes
+//!
+//! Os registros são lidos um a um (memória limitada ao lote corrente),
+//! gravados no pc-db em lotes e o progresso fica em um [`ImportCheckpoint`],
+//! permitindo retomar da última linha gravada após uma falha.
+
+use crate::jobs::JobTracker;
+use crate::lineage::{LineageStamp, IMPORTER_VERSION};
+use pc_core::{NaturalKey, Result, Violations};
+use pc_db::{GenericRepository, Repository, UpsertReport};
+use pc_models::{HasLineage, ImportCheckpoint};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+/// Tamanho de lote padrão para gravação no banco
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Registro lido, com a linha do arquivo em que começa
+#[derive(Debug, Clone)]
+pub struct Streamed<T> {
+    pub line: u64,
+    pub record: T,
+}
+
+/// Linha descartada por dados inválidos ou incompletos
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedRow {
+    pub line: u64,
+    pub reason: String,
+}
+
+/// Linha lida: um registro válido ou a rejeição com o motivo
+#[derive(Debug, Clone)]
+pub enum StreamedRow<T> {
+    Record(Streamed<T>),
+    Rejected(RejectedRow),
+}
+
+/// Iterador de registros; erros de leitura interrompem a importação
+pub type RecordStream<'a, T> = Box<dyn Iterator<Item = Result<Streamed<T>>> + Send + 'a>;
+
+/// Iterador de linhas, incluindo as rejeitadas
+pub type RowStream<'a, T> = Box<dyn Iterator<Item = Result<StreamedRow<T>>> + Send + 'a>;
+
+/// Importadores capazes de ler o arquivo sem carregá-lo inteiro na memória
+pub trait StreamingImporter {
+    type Output: Send;
+
+    /// Nome do importador, gravado na linhagem dos registros
+    fn name(&self) -> &'static str;
+
+    /// Abre o arquivo e devolve cada linha conforme é lida, inclusive as rejeitadas
+    fn stream_rows<P: AsRef<Path>>(&self, path: P) -> Result<RowStream<'_, Self::Output>>;
+
+    /// Abre o arquivo e devolve só os registros válidos
+    fn stream<P: AsRef<Path>>(&self, path: P) -> Result<RecordStream<'_, Self::Output>> {
+        Ok(Box::new(self.stream_rows(path)?.filter_map(|row| match row {
+            Ok(StreamedRow::Record(record)) => Some(Ok(record)),
+            Ok(StreamedRow::Rejected(rejected)) => {
+                tracing::warn!("Linha {} rejeitada: {}", rejected.line, rejected.reason);
+                None
+            }
+            Err(e) => Some(Err(e)),
+        })))
+    }
+}
+
+/// Resultado de uma importação em lotes
+#[derive(Debug, Clone, Default)]
+pub struct BatchImportReport {
+    pub new: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub batches: usize,
+    /// Linha a partir da qual a importação foi retomada
+    pub resumed_from: Option<u64>,
+    pub last_line: u64,
+}
+
+impl BatchImportReport {
+    pub fn total(&self) -> usize {
+        self.new + self.updated + self.unchanged
+    }
+}
+
+impl From<UpsertReport> for BatchImportReport {
+    /// Upsert feito de uma vez, como um único lote
+    fn from(report: UpsertReport) -> Self {
+        Self {
+            new: report.new.len(),
+            updated: report.updated.len(),
+            unchanged: report.unchanged.len(),
+            batches: 1,
+            ..Self::default()
+        }
+    }
+}
+
+/// Lê o arquivo em streaming e grava os registros em lotes de `batch_size`
+///
+/// Após cada lote o checkpoint do arquivo é atualizado; se existir um
+/// checkpoint para o mesmo conteúdo (mesmo sha256), as linhas já gravadas
+/// são puladas. Ao terminar, o checkpoint é removido.
+pub async fn import_in_batches<I, T, P>(
+    importer: &I,
+    path: P,
+    repository: &Repository<T>,
+    checkpoints: &Repository<ImportCheckpoint>,
+    batch_size: usize,
+) -> Result<BatchImportReport>
+where
+    I: StreamingImporter<Output = T>,
+    T: NaturalKey + HasLineage + Clone + Serialize + DeserializeOwned + Send + Sync,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let sha256 = pc_core::hash::sha256_file(path)?;
+    run_batches(importer, path, &sha256, repository, checkpoints, batch_size, None).await
+}
+
+/// Como [`import_in_batches`], atualizando o job a cada lote
+///
+/// `sha256` é o hash já calculado ao copiar o arquivo para o blob store,
+/// para não reler o arquivo inteiro só para o checkpoint. As linhas rejeitadas vão para o relatório de erros do job. Se o
+/// cancelamento for pedido, a importação para após o lote corrente com
+/// [`pc_core::Error::Cancelled`], mantendo o checkpoint para retomar depois.
+pub async fn import_in_batches_tracked<I, T, P>(
+    importer: &I,
+    path: P,
+    sha256: &str,
+    repository: &Repository<T>,
+    checkpoints: &Repository<ImportCheckpoint>,
+    batch_size: usize,
+    job: &mut JobTracker<'_>,
+) -> Result<BatchImportReport>
+where
+    I: StreamingImporter<Output = T>,
+    T: NaturalKey + HasLineage + Clone + Serialize + DeserializeOwned + Send + Sync,
+    P: AsRef<Path>,
+{
+    run_batches(importer, path.as_ref(), sha256, repository, checkpoints, batch_size, Some(job)).await
+}
+
+fn reject(job: Option<&mut JobTracker<'_>>, rejected: &RejectedRow) {
+    match job {
+        Some(job) => job.reject(rejected),
+        None => tracing::warn!("Linha {} rejeitada: {}", rejected.line, rejected.reason),
+    }
+}
+
+async fn run_batches<I, T>(
+    importer: &I,
+    path: &Path,
+    sha256: &str,
+    repository: &Repository<T>,
+    checkpoints: &Repository<ImportCheckpoint>,
+    batch_size: usize,
+    mut job: Option<&mut JobTracker<'_>>,
+) -> Result<BatchImportReport>
+where
+    I: StreamingImporter<Output = T>,
+    T: NaturalKey + HasLineage + Clone + Serialize + DeserializeOwned + Send + Sync,
+{
+    let batch_size = batch_size.max(1);
+    let fonte_arquivo = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| pc_core::Error::Import("Nome de arquivo inválido".into()))?
+        .to_string();
+    let sha256 = sha256.to_string();
+    let stamp = LineageStamp::for_file(path, sha256.clone(), importer.name(), IMPORTER_VERSION)
+        .with_job(job.as_deref().map(|job| job.id()));
+
+    let mut report = BatchImportReport::default();
+    let mut checkpoint = match checkpoints.find_by_natural_key(&fonte_arquivo).await? {
+        Some(existing) if existing.sha256 == sha256 => {
+            tracing::info!(
+                "Retomando {} a partir da linha {}",
+                fonte_arquivo,
+                existing.ultima_linha
+            );
+            report.resumed_from = Some(existing.ultima_linha);
+            existing
+        }
+        // Conteúdo mudou desde a última tentativa: recomeça, reaproveitando o registro
+        Some(stale) => {
+            let mut checkpoint = ImportCheckpoint::new(fonte_arquivo, sha256);
+            checkpoint.id = stale.id;
+            checkpoint
+        }
+        None => ImportCheckpoint::new(fonte_arquivo, sha256),
+    };
+    let skip_until = checkpoint.ultima_linha;
+
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut batch_last_line = skip_until;
+
+    for row in importer.stream_rows(path)? {
+        let item = match row? {
+            StreamedRow::Record(item) => item,
+            StreamedRow::Rejected(rejected) if rejected.line > skip_until => {
+                reject(job.as_deref_mut(), &rejected);
+                continue;
+            }
+            StreamedRow::Rejected(_) => continue,
+        };
+        if item.line <= skip_until {
+            continue;
+        }
+
+        // Registro que o repositório recusaria: rejeita só a linha, com todas as violações
+        let mut violations = Violations::new();
+        item.record.check(&mut violations);
+        if !violations.is_empty() {
+            reject(job.as_deref_mut(), &RejectedRow { line: item.line, reason: violations.to_string() });
+            continue;
+        }
+
+        batch_last_line = item.line;
+        let mut record = item.record;
+        stamp.apply(&mut record);
+        batch.push(record);
+
+        if batch.len() >= batch_size {
+            commit_batch(repository, checkpoints, &mut checkpoint, &mut batch, batch_last_line, &mut report, job.as_deref_mut())
+                .await?;
+        }
+    }
+
+    if !batch.is_empty() {
+        commit_batch(repository, checkpoints, &mut checkpoint, &mut batch, batch_last_line, &mut report, job).await?;
+    }
+
+    report.last_line = batch_last_line;
+    checkpoints.delete(&checkpoint.id).await?;
+    repository.compact()?;
+    checkpoints.compact()?;
+
+    tracing::info!(
+        "Importação em lotes concluída: {} registros em {} lotes",
+        report.total(),
+        report.batches
+    );
+    Ok(report)
+}
+
+async fn commit_batch<T>(
+    repository: &Repository<T>,
+    checkpoints: &Repository<ImportCheckpoint>,
+    checkpoint: &mut ImportCheckpoint,
+    batch: &mut Vec<T>,
+    last_line: u64,
+    report: &mut BatchImportReport,
+    mut job: Option<&mut JobTracker<'_>>,
+) -> Result<()>
+where
+    T: NaturalKey + Clone + Serialize + DeserializeOwned + Send + Sync,
+{
+    // Cancelado: o lote não é gravado e o checkpoint fica no lote anterior
+    if let Some(job) = job.as_deref_mut() {
+        job.check_cancelled().await?;
+    }
+
+    let records = batch.len();
+    let upsert = repository.upsert_many(std::mem::take(batch)).await?;
+    report.new += upsert.new.len();
+    report.updated += upsert.updated.len();
+    report.unchanged += upsert.unchanged.len();
+    report.batches += 1;
+
+    checkpoint.advance(last_line, records);
+    checkpoints.upsert_many(vec![checkpoint.clone()]).await?;
+    tracing::debug!("Lote {} gravado até a linha {}", report.batches, last_line);
+
+    if let Some(job) = job {
+        job.record_batch(upsert.new.len(), upsert.updated.len(), upsert.unchanged.len()).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timesheet_importer::TimesheetImporter;
+    use pc_models::Timesheet;
+
+    fn write_csv(rows: usize) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("Horas-{}.csv", uuid::Uuid::new_v4()));
+        let mut content = String::from("funcionario;mes;data;entrada;saida\n");
+        for day in 1..=rows {
+            content.push_str(&format!("João;Abril;{:02}/04/2025;08:00;17:00\n", day));
+        }
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_import_in_batches() {
+        let path = write_csv(25);
+        let repository = Repository::<Timesheet>::new("timesheets");
+        let checkpoints = Repository::<ImportCheckpoint>::new("import_checkpoints");
+
+        let report = import_in_batches(&TimesheetImporter::new(), &path, &repository, &checkpoints, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(report.new, 25);
+        assert_eq!(report.batches, 3);
+        assert_eq!(report.last_line, 26);
+        assert_eq!(checkpoints.count().await.unwrap(), 0);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tracked_import_reports_rejected_rows() {
+        let path = write_csv(5);
+        let mut content = std::fs::read_to_string(&path).unwrap();
+        content.push_str("João;Abril;31/02/2025;08:00;17:00\n");
+        std::fs::write(&path, content).unwrap();
+
+        let repository = Repository::<Timesheet>::new("timesheets");
+        let checkpoints = Repository::<ImportCheckpoint>::new("import_checkpoints");
+        let jobs = Repository::<pc_models::ImportJob>::new("import_jobs");
+        let mut job = JobTracker::start(&jobs, &path, "timesheet", None).await.unwrap();
+
+        let sha256 = pc_core::hash::sha256_file(&path).unwrap();
+        let result =
+            import_in_batches_tracked(&TimesheetImporter::new(), &path, &sha256, &repository, &checkpoints, 2, &mut job).await;
+        let job = job.finish(&result).await.unwrap();
+
+        assert_eq!(job.status, pc_core::Status::Completed);
+        assert_eq!(job.novos, 5);
+        assert_eq!(job.rejeitados, 1);
+        assert_eq!(job.erros[0].linha, Some(7));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_checkpoint() {
+        let path = write_csv(25);
+        let repository = Repository::<Timesheet>::new("timesheets");
+        let checkpoints = Repository::<ImportCheckpoint>::new("import_checkpoints");
+
+        // Simula uma importação interrompida após gravar as linhas 2..=11
+        let mut checkpoint = ImportCheckpoint::new(
+            path.file_name().unwrap().to_string_lossy().into_owned(),
+            pc_core::hash::sha256_file(&path).unwrap(),
+        );
+        checkpoint.advance(11, 10);
+        checkpoints.upsert_many(vec![checkpoint]).await.unwrap();
+
+        let report = import_in_batches(&TimesheetImporter::new(), &path, &repository, &checkpoints, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(report.resumed_from, Some(11));
+        assert_eq!(report.new, 15);
+        assert_eq!(repository.count().await.unwrap(), 15);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+//! Normalização de textos e números lidos dos arquivos
+//!
+//! Nomes, cidades e cabeçalhos são comparados sem acentos e em maiúsculas;
+//! CNPJs, CEPs e números de ordem, só pelos dígitos.
+
+/// Remove acentos e converte para maiúsculas (os layouts só aceitam ASCII)
+pub fn normalize_text(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' | 'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+            'é' | 'è' | 'ê' | 'ë' | 'É' | 'È' | 'Ê' | 'Ë' => 'E',
+            'í' | 'ì' | 'î' | 'ï' | 'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' | 'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+            'ú' | 'ù' | 'û' | 'ü' | 'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+            'ç' | 'Ç' => 'C',
+            'ñ' | 'Ñ' => 'N',
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_ascii_uppercase(),
+            _ => ' ',
+        })
+        .collect()
+}
+
+/// Só os dígitos: "12.345.678/0001-95" vira "12345678000195"
+pub fn digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Sequências de 3+ dígitos, sem zeros à esquerda ("IMG_000123_45" dá ["123"])
+pub fn digit_runs(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| run.len() >= 3)
+        .map(strip_zeros)
+        .collect()
+}
+
+pub fn strip_zeros(number: &str) -> String {
+    number.trim().trim_start_matches('0').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_and_digits() {
+        assert_eq!(normalize_text("Ribeirão Preto/SP"), "RIBEIRAO PRETO/SP");
+        assert_eq!(digits("12.345.678/0001-95"), "12345678000195");
+        assert_eq!(digit_runs("CTe 000288415 - 12/04 nf 4512"), ["288415", "4512"]);
+        assert_eq!(strip_zeros(" 0042 "), "42");
+    }
+}
@@ -1,16 +1,18 @@
 //! Timesheet importer
 
 use pc_core::Result;
-use pc_models::Timesheet;
+use pc_models::{Lineage, Timesheet};
+use crate::csv_source::{CsvFormat, CsvOptions};
+use crate::streaming::{RejectedRow, RowStream, StreamedRow, StreamingImporter, Streamed};
 use crate::Importer;
 use chrono::{NaiveDate, NaiveTime};
-use encoding_rs::WINDOWS_1252;
 use std::path::Path;
 use regex::Regex;
 
 pub struct TimesheetImporter {
     date_regex: Regex,
     time_regex: Regex,
+    csv_options: CsvOptions,
 }
 
 impl TimesheetImporter {
@@ -18,8 +20,15 @@ impl TimesheetImporter {
         Self {
             date_regex: Regex::new(r"(\d{2})/(\d{2})/(\d{4})").unwrap(),
             time_regex: Regex::new(r"(\d{1,2}):(\d{2})").unwrap(),
+            csv_options: CsvOptions::default(),
         }
     }
+
+    /// Força encoding, delimitador ou aspas em vez de detectá-los
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
     
     fn parse_date(&self, date_str: &str) -> Option<NaiveDate> {
         if let Some(caps) = self.date_regex.captures(date_str) {
@@ -41,6 +50,49 @@ impl TimesheetImporter {
             None
         }
     }
+    
+    /// Converte uma linha do CSV em registro de ponto
+    ///
+    /// `None` para linhas sem funcionário; `Some(Err)` com o motivo quando
+    /// data ou horários são inválidos.
+    pub(crate) fn parse_record(
+        &self,
+        record: &csv::StringRecord,
+        source_file: &Option<String>,
+    ) -> Option<std::result::Result<Timesheet, String>> {
+        let funcionario = record.get(0).unwrap_or("").trim();
+        if funcionario.is_empty() {
+            return None;
+        }
+
+        if record.len() < 5 {
+            return Some(Err(format!("{}: {} colunas, esperado 5", funcionario, record.len())));
+        }
+        
+        let mes = record.get(1).unwrap_or("").trim().to_string();
+        let field = record.get(2).unwrap_or("");
+        let Some(data) = self.parse_date(field) else {
+            return Some(Err(format!("{}: data inválida '{}'", funcionario, field)));
+        };
+        let field = record.get(3).unwrap_or("");
+        let Some(entrada) = self.parse_time(field) else {
+            return Some(Err(format!("{}: entrada inválida '{}'", funcionario, field)));
+        };
+        let field = record.get(4).unwrap_or("");
+        let Some(saida) = self.parse_time(field) else {
+            return Some(Err(format!("{}: saída inválida '{}'", funcionario, field)));
+        };
+        
+        let mut timesheet = Timesheet::new(
+            funcionario.to_string(),
+            mes,
+            data,
+            entrada,
+            saida,
+        );
+        timesheet.fonte_arquivo = source_file.clone();
+        Some(Ok(timesheet))
+    }
 }
 
 impl Default for TimesheetImporter {
@@ -49,64 +101,52 @@ impl Default for TimesheetImporter {
     }
 }
 
-impl Importer for TimesheetImporter {
+impl StreamingImporter for TimesheetImporter {
     type Output = Timesheet;
-    
-    fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
+
+    fn name(&self) -> &'static str {
+        "timesheet"
+    }
+
+    fn stream_rows<P: AsRef<Path>>(&self, path: P) -> Result<RowStream<'_, Self::Output>> {
         let path = path.as_ref();
         tracing::info!("Importando timesheets de: {:?}", path);
-        
-        let file_bytes = std::fs::read(path)
-            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e)))?;
-        
-        let (content, _, _) = WINDOWS_1252.decode(&file_bytes);
-        
-        let mut reader = csv::ReaderBuilder::new()
-            .delimiter(b';')
-            .has_headers(true)
-            .flexible(true)
-            .from_reader(content.as_bytes());
-        
-        let mut timesheets = Vec::new();
+
+        let (_, reader) = CsvFormat::open(path, &self.csv_options)?;
         let source_file = path.file_name()
             .and_then(|n| n.to_str())
             .map(|s| s.to_string());
-        
-        for (idx, result) in reader.records().enumerate() {
-            let record = match result {
-                Ok(r) => r,
-                Err(e) => {
-                    tracing::warn!("Erro na linha {}: {}", idx + 1, e);
-                    continue;
-                }
-            };
-            
-            if record.len() < 5 {
-                continue;
-            }
-            
-            let funcionario = record.get(0).unwrap_or("").trim();
-            if funcionario.is_empty() {
-                continue;
+
+        Ok(Box::new(reader.into_records().filter_map(move |result| match result {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line()).unwrap_or(0);
+                self.parse_record(&record, &source_file).map(|parsed| match parsed {
+                    Ok(mut timesheet) => {
+                        timesheet.linhagem = Some(Lineage::at_line(line));
+                        Ok(StreamedRow::Record(Streamed { line, record: timesheet }))
+                    }
+                    Err(reason) => Ok(StreamedRow::Rejected(RejectedRow { line, reason })),
+                })
             }
-            
-            let mes = record.get(1).unwrap_or("").trim().to_string();
-            let data = record.get(2).and_then(|s| self.parse_date(s));
-            let entrada = record.get(3).and_then(|s| self.parse_time(s));
-            let saida = record.get(4).and_then(|s| self.parse_time(s));
-            
-            if let (Some(data), Some(entrada), Some(saida)) = (data, entrada, saida) {
-                let mut timesheet = Timesheet::new(
-                    funcionario.to_string(),
-                    mes,
-                    data,
-                    entrada,
-                    saida,
-                );
-                timesheet.fonte_arquivo = source_file.clone();
-                timesheets.push(timesheet);
+            Err(e) if e.is_io_error() => {
+                Some(Err(pc_core::Error::Import(format!("Erro ao ler arquivo: {}", e))))
             }
-        }
+            Err(e) => Some(Ok(StreamedRow::Rejected(RejectedRow {
+                line: e.position().map(|p| p.line()).unwrap_or(0),
+                reason: e.to_string(),
+            }))),
+        })))
+    }
+}
+
+impl Importer for TimesheetImporter {
+    type Output = Timesheet;
+    
+    fn import_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Self::Output>> {
+        let timesheets = self
+            .stream(path)?
+            .map(|item| item.map(|s| s.record))
+            .collect::<Result<Vec<_>>>()?;
         
         tracing::info!("Importados {} registros de ponto", timesheets.len());
         Ok(timesheets)
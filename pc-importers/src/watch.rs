@@ -0,0 +1,376 @@
+//! Monitoramento de pastas para importação automática
+//!
+//! O TMS grava os arquivos do dia em uma pasta compartilhada. O watcher
+//! verifica a pasta periodicamente; quando um arquivo fica estável (mesmo
+//! tamanho e data de modificação por `stable_for`), ele é detectado,
+//! importado e movido para `processed/` ou `failed/`, com um relatório
+//! `<arquivo>.report.json` ao lado.
+//!
+//! Usa polling em vez de eventos do sistema de arquivos, que não são
+//! confiáveis em compartilhamentos de rede.
+
+use crate::bulk::FileStamp;
+use crate::csv_source::CsvOptions;
+use crate::jobs::JobTracker;
+use crate::registry::{self, ImportOptions, ImporterRegistry};
+use chrono::{DateTime, Utc};
+use pc_core::Result;
+use pc_db::PersonalControllerDb;
+use pc_models::ImportedFile;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Subpasta dos arquivos importados com sucesso
+pub const PROCESSED_DIR: &str = "processed";
+/// Subpasta dos arquivos que falharam
+pub const FAILED_DIR: &str = "failed";
+/// Registrado como `iniciado_por` nos jobs do watcher
+pub const WATCHER_USER: &str = "watcher";
+
+/// Situação final de um arquivo processado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchStatus {
+    Importado,
+    /// Mesmo conteúdo já importado antes; nada foi gravado
+    JaImportado,
+    Falhou,
+}
+
+/// Relatório gravado ao lado de cada arquivo processado
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchReport {
+    pub arquivo: String,
+    pub destino: PathBuf,
+    pub status: WatchStatus,
+    pub tipo: Option<String>,
+    pub confianca: Option<f32>,
+    pub sha256: Option<String>,
+    pub registros: usize,
+    pub novos: usize,
+    pub atualizados: usize,
+    pub inalterados: usize,
+    pub erro: Option<String>,
+    /// Job da importação, consultável em `pc imports list` e na API
+    pub job_id: Option<Uuid>,
+    pub processado_em: DateTime<Utc>,
+}
+
+impl WatchReport {
+    fn new(arquivo: String) -> Self {
+        Self {
+            arquivo,
+            destino: PathBuf::new(),
+            status: WatchStatus::Falhou,
+            tipo: None,
+            confianca: None,
+            sha256: None,
+            registros: 0,
+            novos: 0,
+            atualizados: 0,
+            inalterados: 0,
+            erro: None,
+            job_id: None,
+            processado_em: Utc::now(),
+        }
+    }
+}
+
+/// Monitora uma pasta e importa os arquivos que chegam
+pub struct FolderWatcher {
+    dir: PathBuf,
+    poll_interval: Duration,
+    stable_for: Duration,
+    csv_options: CsvOptions,
+    registry: Arc<ImporterRegistry>,
+    /// Arquivos vistos e ainda não estáveis: último carimbo e desde quando
+    pending: HashMap<PathBuf, (FileStamp, Instant)>,
+}
+
+impl FolderWatcher {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            poll_interval: Duration::from_secs(5),
+            stable_for: Duration::from_secs(10),
+            csv_options: CsvOptions::default(),
+            registry: Arc::new(ImporterRegistry::default()),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Tempo sem alterações para considerar o arquivo completo
+    pub fn with_stable_for(mut self, stable_for: Duration) -> Self {
+        self.stable_for = stable_for;
+        self
+    }
+
+    pub fn with_csv_options(mut self, options: CsvOptions) -> Self {
+        self.csv_options = options;
+        self
+    }
+
+    /// Importadores disponíveis (padrão: os embutidos)
+    pub fn with_registry(mut self, registry: Arc<ImporterRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Monitora a pasta até a tarefa ser cancelada
+    ///
+    /// Erros de uma verificação (pasta de rede indisponível, por exemplo)
+    /// são registrados e a verificação é repetida no próximo ciclo.
+    pub async fn run(&mut self, db: &PersonalControllerDb) {
+        tracing::info!("Monitorando {:?} a cada {:?}", self.dir, self.poll_interval);
+        loop {
+            match self.poll(db).await {
+                Ok(reports) => {
+                    for report in reports {
+                        tracing::info!(
+                            "{}: {:?}, {} registros -> {:?}",
+                            report.arquivo,
+                            report.status,
+                            report.registros,
+                            report.destino
+                        );
+                    }
+                }
+                Err(e) => tracing::error!("Erro ao verificar {:?}: {}", self.dir, e),
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Uma verificação da pasta: processa os arquivos que ficaram estáveis
+    pub async fn poll(&mut self, db: &PersonalControllerDb) -> Result<Vec<WatchReport>> {
+        let entries = std::fs::read_dir(&self.dir)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao ler {:?}: {}", self.dir, e)))?;
+
+        let mut seen = Vec::new();
+        let mut ready = Vec::new();
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !path.is_file() || name.starts_with('.') || name.ends_with(".report.json") {
+                continue;
+            }
+            // Arquivo sumiu entre a listagem e a leitura dos metadados
+            let Ok(stamp) = FileStamp::of(&path) else {
+                continue;
+            };
+
+            seen.push(path.clone());
+            match self.pending.get(&path) {
+                Some((previous, since)) if *previous == stamp => {
+                    if since.elapsed() >= self.stable_for {
+                        ready.push(path);
+                    }
+                }
+                _ => {
+                    self.pending.insert(path, (stamp, Instant::now()));
+                }
+            }
+        }
+        self.pending.retain(|path, _| seen.contains(path));
+
+        let mut reports = Vec::with_capacity(ready.len());
+        for path in ready {
+            self.pending.remove(&path);
+            reports.push(self.process(db, &path).await?);
+        }
+        Ok(reports)
+    }
+
+    /// Importa um arquivo estável e o move com o relatório
+    async fn process(&self, db: &PersonalControllerDb, path: &Path) -> Result<WatchReport> {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut report = WatchReport::new(name);
+
+        let mut job = JobTracker::start(&db.import_jobs, path, "auto", Some(WATCHER_USER.to_string())).await?;
+        let result = self.import(db, path, &mut report, &mut job).await;
+        if let Err(e) = &result {
+            tracing::warn!("Falha ao importar {:?}: {}", path, e);
+            report.status = WatchStatus::Falhou;
+            report.erro = Some(e.to_string());
+        }
+        report.job_id = Some(job.finish(&result).await?.id);
+
+        let subdir = match report.status {
+            WatchStatus::Falhou => FAILED_DIR,
+            _ => PROCESSED_DIR,
+        };
+        report.destino = move_to(path, &self.dir.join(subdir))?;
+        report.processado_em = Utc::now();
+
+        let report_path = report.destino.with_file_name(format!(
+            "{}.report.json",
+            report.destino.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        let content = serde_json::to_vec_pretty(&report)
+            .map_err(|e| pc_core::Error::Internal(format!("Erro ao serializar relatório: {}", e)))?;
+        std::fs::write(&report_path, content)
+            .map_err(|e| pc_core::Error::Import(format!("Erro ao gravar {:?}: {}", report_path, e)))?;
+
+        Ok(report)
+    }
+
+    async fn import(
+        &self,
+        db: &PersonalControllerDb,
+        path: &Path,
+        report: &mut WatchReport,
+        job: &mut JobTracker<'_>,
+    ) -> Result<()> {
+        let owned = path.to_path_buf();
+        let registry = Arc::clone(&self.registry);
+        let options = ImportOptions {
+            csv: self.csv_options,
+            ..ImportOptions::default()
+        };
+
+        // A detecção lê o início do arquivo
+        let selected = tokio::task::spawn_blocking(move || registry.detect(&owned))
+            .await
+            .map_err(|e| pc_core::Error::Internal(format!("Tarefa de importação falhou: {}", e)))??;
+
+        let importer = selected.importer;
+        report.tipo = Some(importer.name().to_string());
+        report.confianca = selected.detection.map(|d| d.confidence);
+        job.set_importer(importer.name());
+
+        let sha256 = db.blobs.put_file(path)?;
+        report.sha256 = Some(sha256.clone());
+        let fonte_arquivo = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        if db.imported_files.is_already_imported(&fonte_arquivo, &sha256).await? {
+            report.status = WatchStatus::JaImportado;
+            return Ok(());
+        }
+
+        // Mesmo caminho da CLI e da API: streaming em lotes quando o importador oferece
+        let mut lidos = None;
+        let imported =
+            registry::import_stored_file(importer.as_ref(), db, path, &sha256, &options, job, |output| lidos = Some(output.len()))
+                .await?;
+
+        report.status = WatchStatus::Importado;
+        if let Some(imported) = imported {
+            report.novos = imported.new;
+            report.atualizados = imported.updated;
+            report.inalterados = imported.unchanged;
+            db.imported_files
+                .record(ImportedFile::new(fonte_arquivo, sha256, importer.name().to_string(), imported.total()))
+                .await?;
+        }
+        report.registros = lidos.unwrap_or(report.novos + report.atualizados + report.inalterados);
+        Ok(())
+    }
+}
+
+/// Move o arquivo para `dest_dir`, prefixando a data se já houver um homônimo
+fn move_to(path: &Path, dest_dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dest_dir)
+        .map_err(|e| pc_core::Error::Import(format!("Erro ao criar {:?}: {}", dest_dir, e)))?;
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let mut dest = dest_dir.join(&name);
+    if dest.exists() {
+        dest = dest_dir.join(format!("{}-{}", Utc::now().format("%Y%m%d-%H%M%S"), name));
+    }
+
+    std::fs::rename(path, &dest)
+        .map_err(|e| pc_core::Error::Import(format!("Erro ao mover {:?} para {:?}: {}", path, dest, e)))?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pc_db::GenericRepository;
+
+    fn watched_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pc-watch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_poll_waits_for_stable_file_then_imports() {
+        let dir = watched_dir();
+        std::fs::write(
+            dir.join("Horas.csv"),
+            "funcionario;mes;data;entrada;saida\nJoão;Abril;01/04/2025;08:00;17:00\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notas.txt"), "sem formato conhecido").unwrap();
+
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+        let mut watcher = FolderWatcher::new(&dir).with_stable_for(Duration::ZERO);
+
+        // Primeira passada só registra os arquivos
+        assert!(watcher.poll(&db).await.unwrap().is_empty());
+
+        let mut reports = watcher.poll(&db).await.unwrap();
+        reports.sort_by(|a, b| a.arquivo.cmp(&b.arquivo));
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].status, WatchStatus::Importado);
+        assert_eq!(reports[0].novos, 1);
+        assert_eq!(reports[1].status, WatchStatus::Falhou);
+
+        assert!(dir.join("processed/Horas.csv").exists());
+        assert!(dir.join("processed/Horas.csv.report.json").exists());
+        assert!(dir.join("failed/notas.txt.report.json").exists());
+        assert_eq!(db.timesheets.count().await.unwrap(), 1);
+
+        let jobs = db.import_jobs.recent(10).await.unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs
+            .iter()
+            .any(|j| j.importador == "timesheet" && j.status == pc_core::Status::Completed && j.novos == 1));
+        assert!(jobs.iter().any(|j| j.status == pc_core::Status::Failed && !j.erros.is_empty()));
+        assert!(reports[0].job_id.is_some());
+        // Gravado pelo registro de importadores, com a linhagem do job
+        let timesheet = db.timesheets.find_all().await.unwrap().remove(0);
+        assert_eq!(timesheet.linhagem.unwrap().job_id, reports[0].job_id);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_same_file_dropped_again_is_not_reimported() {
+        let dir = watched_dir();
+        let content = "funcionario;mes;data;entrada;saida\nJoão;Abril;01/04/2025;08:00;17:00\n";
+        let db = PersonalControllerDb::connect("memory").await.unwrap();
+        let mut watcher = FolderWatcher::new(&dir).with_stable_for(Duration::ZERO);
+
+        let mut statuses = Vec::new();
+        for _ in 0..2 {
+            std::fs::write(dir.join("Horas.csv"), content).unwrap();
+            watcher.poll(&db).await.unwrap();
+            statuses.extend(watcher.poll(&db).await.unwrap().into_iter().map(|r| r.status));
+        }
+
+        assert_eq!(statuses, vec![WatchStatus::Importado, WatchStatus::JaImportado]);
+
+        let processed: Vec<_> = std::fs::read_dir(dir.join(PROCESSED_DIR)).unwrap().collect();
+        assert_eq!(processed.len(), 4);
+        assert_eq!(db.imported_files.count().await.unwrap(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
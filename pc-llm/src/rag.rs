@@ -150,7 +150,7 @@ impl Document {
                  Número: {}\n\
                  Remetente: {} - {}\n\
                  Destinatário: {} - {}\n\
-                 Valor do frete: R$ {:.2}\n\
+                 Valor do frete: {}\n\
                  Status: {:?}",
                 order.id,
                 order.numero,
@@ -0,0 +1,193 @@
+//! Boleto (título de cobrança bancária) model
+
+use chrono::NaiveDate;
+use pc_core::validation::codes;
+use pc_core::{AuditMetadata, Entity, Money, NaturalKey, Result, Violations};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Situação do título no banco, atualizada pelos arquivos de retorno
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoletoStatus {
+    /// Enviado na remessa, aguardando confirmação do banco
+    Emitido,
+    Registrado,
+    Rejeitado,
+    Pago,
+    /// Baixado sem pagamento (por instrução ou decurso de prazo)
+    Baixado,
+}
+
+impl fmt::Display for BoletoStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoletoStatus::Emitido => write!(f, "Emitido"),
+            BoletoStatus::Registrado => write!(f, "Registrado"),
+            BoletoStatus::Rejeitado => write!(f, "Rejeitado"),
+            BoletoStatus::Pago => write!(f, "Pago"),
+            BoletoStatus::Baixado => write!(f, "Baixado"),
+        }
+    }
+}
+
+impl std::str::FromStr for BoletoStatus {
+    type Err = pc_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "emitido" => Ok(BoletoStatus::Emitido),
+            "registrado" => Ok(BoletoStatus::Registrado),
+            "rejeitado" => Ok(BoletoStatus::Rejeitado),
+            "pago" => Ok(BoletoStatus::Pago),
+            "baixado" => Ok(BoletoStatus::Baixado),
+            other => Err(pc_core::Error::Validation(format!("Situação de boleto desconhecida: {}", other))),
+        }
+    }
+}
+
+/// Ocorrência informada pelo banco no retorno
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoletoOcorrencia {
+    /// Código de movimento do retorno ("02", "06"...)
+    pub codigo: String,
+    pub descricao: String,
+    /// Motivos de rejeição ou baixa, como vieram do banco
+    pub motivos: Vec<String>,
+    pub data: Option<NaiveDate>,
+    pub arquivo: Option<String>,
+}
+
+/// Boleto de cobrança de uma ordem de frete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boleto {
+    pub id: Uuid,
+    pub freight_order_id: Uuid,
+    pub numero_ordem: String,
+    pub pagador_id: Uuid,
+    /// Código do banco (341, 237, 001...)
+    pub banco: String,
+    /// Nosso número, sem zeros à esquerda
+    pub nosso_numero: String,
+    /// Número sequencial da remessa em que o título foi enviado
+    pub remessa: u32,
+    pub emissao: NaiveDate,
+    pub vencimento: NaiveDate,
+    pub valor: Money,
+    pub situacao: BoletoStatus,
+    pub valor_pago: Option<Money>,
+    pub data_pagamento: Option<NaiveDate>,
+    pub data_credito: Option<NaiveDate>,
+    pub ocorrencias: Vec<BoletoOcorrencia>,
+    pub metadata: AuditMetadata,
+}
+
+impl Boleto {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        freight_order_id: Uuid,
+        numero_ordem: String,
+        pagador_id: Uuid,
+        banco: String,
+        nosso_numero: u64,
+        remessa: u32,
+        emissao: NaiveDate,
+        vencimento: NaiveDate,
+        valor: Money,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            freight_order_id,
+            numero_ordem,
+            pagador_id,
+            banco,
+            nosso_numero: nosso_numero.to_string(),
+            remessa,
+            emissao,
+            vencimento,
+            valor,
+            situacao: BoletoStatus::Emitido,
+            valor_pago: None,
+            data_pagamento: None,
+            data_credito: None,
+            ocorrencias: Vec::new(),
+            metadata: AuditMetadata::default(),
+        }
+    }
+
+    /// Registra uma ocorrência do retorno; `situacao` é `None` para ocorrências informativas
+    ///
+    /// Um título pago não volta a registrado ou baixado se os retornos
+    /// forem processados fora de ordem.
+    pub fn apply(&mut self, ocorrencia: BoletoOcorrencia, situacao: Option<BoletoStatus>) {
+        if self.ocorrencias.contains(&ocorrencia) {
+            return;
+        }
+        if let Some(situacao) = situacao {
+            if self.situacao != BoletoStatus::Pago || situacao == BoletoStatus::Pago {
+                self.situacao = situacao;
+            }
+        }
+        self.ocorrencias.push(ocorrencia);
+    }
+
+    /// Ainda cobra a ordem (não rejeitado nem baixado)
+    pub fn is_active(&self) -> bool {
+        !matches!(self.situacao, BoletoStatus::Rejeitado | BoletoStatus::Baixado)
+    }
+}
+
+impl Entity for Boleto {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "boleto"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        if self.nosso_numero.is_empty() || !self.nosso_numero.chars().all(|c| c.is_ascii_digit()) {
+            v.add("nosso_numero", codes::INVALID_FORMAT);
+        }
+        v.positive("valor", self.valor);
+        v.not_before("vencimento", Some(self.vencimento), "emissao", self.emissao);
+    }
+}
+
+impl NaturalKey for Boleto {
+    fn natural_key(&self) -> String {
+        format!("{}|{}", self.banco, self.nosso_numero)
+    }
+
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.metadata = existing.metadata.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paid_boleto_is_not_reverted() {
+        let date = NaiveDate::from_ymd_opt(2025, 4, 10).unwrap();
+        let mut boleto = Boleto::new(Uuid::new_v4(), "288415".into(), Uuid::new_v4(), "237".into(), 42, 1, date, date, Money::from_reais(100));
+        let ocorrencia = |codigo: &str| BoletoOcorrencia {
+            codigo: codigo.into(),
+            descricao: String::new(),
+            motivos: Vec::new(),
+            data: Some(date),
+            arquivo: None,
+        };
+
+        boleto.apply(ocorrencia("06"), Some(BoletoStatus::Pago));
+        boleto.apply(ocorrencia("02"), Some(BoletoStatus::Registrado));
+        boleto.apply(ocorrencia("02"), Some(BoletoStatus::Registrado));
+
+        assert_eq!(boleto.situacao, BoletoStatus::Pago);
+        assert_eq!(boleto.ocorrencias.len(), 2);
+        assert!(boleto.is_active());
+    }
+}
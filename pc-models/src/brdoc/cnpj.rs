@@ -0,0 +1,101 @@
+//! CNPJ: 12 posições de raiz e ordem mais 2 dígitos verificadores
+//!
+//! A partir de julho de 2026 a Receita Federal emite CNPJs alfanuméricos:
+//! as 12 primeiras posições aceitam letras maiúsculas, os verificadores
+//! continuam numéricos. No módulo 11 cada caractere vale o código ASCII
+//! menos 48, o que mantém o cálculo dos CNPJs numéricos.
+
+use super::{mod11, strip_separators, weighted_sum, weights};
+use pc_core::Result;
+
+/// Remove pontuação e espaços, em maiúsculas
+pub fn normalize(value: &str) -> String {
+    strip_separators(value)
+}
+
+/// Valor do caractere no módulo 11 ('0' = 0, 'A' = 17)
+fn value_of(c: char) -> Option<u32> {
+    (c.is_ascii_digit() || c.is_ascii_uppercase()).then(|| c as u32 - '0' as u32)
+}
+
+/// Dígitos verificadores de uma base de 12 posições
+pub fn check_digits(base: &str) -> Option<String> {
+    let mut values: Vec<u32> = base.chars().map(value_of).collect::<Option<_>>()?;
+    if values.len() != 12 {
+        return None;
+    }
+    for len in [12, 13] {
+        let dv = mod11(weighted_sum(&values, &weights(len, 9)));
+        values.push(dv);
+    }
+    Some(format!("{}{}", values[12], values[13]))
+}
+
+/// Normaliza e confere os dígitos verificadores
+pub fn validate(value: &str) -> Result<String> {
+    let cnpj = normalize(value);
+    let invalid = |motivo: &str| pc_core::Error::Validation(format!("CNPJ inválido '{}': {}", value, motivo));
+
+    if cnpj.len() != 14 || !cnpj.is_ascii() {
+        return Err(invalid("deve ter 14 posições"));
+    }
+    if !cnpj[12..].chars().all(|c| c.is_ascii_digit()) || !cnpj[..12].chars().all(|c| value_of(c).is_some()) {
+        return Err(invalid("caracteres inválidos"));
+    }
+    if cnpj.chars().all(|c| cnpj.starts_with(c)) {
+        return Err(invalid("dígitos repetidos"));
+    }
+    if check_digits(&cnpj[..12]).as_deref() != Some(&cnpj[12..]) {
+        return Err(invalid("dígitos verificadores não conferem"));
+    }
+    Ok(cnpj)
+}
+
+pub fn is_valid(value: &str) -> bool {
+    validate(value).is_ok()
+}
+
+/// CNPJ no formato alfanumérico (com letras na raiz ou na ordem)
+pub fn is_alphanumeric(value: &str) -> bool {
+    normalize(value).chars().any(|c| c.is_ascii_uppercase())
+}
+
+/// Raiz (8 primeiras posições), comum à matriz e às filiais
+pub fn root(value: &str) -> Option<String> {
+    validate(value).ok().map(|cnpj| cnpj[..8].to_string())
+}
+
+/// 00.000.000/0000-00; `None` se o CNPJ é inválido
+pub fn format(value: &str) -> Option<String> {
+    validate(value).ok().map(|cnpj| format_normalized(&cnpj))
+}
+
+pub(crate) fn format_normalized(cnpj: &str) -> String {
+    format!("{}.{}.{}/{}-{}", &cnpj[..2], &cnpj[2..5], &cnpj[5..8], &cnpj[8..12], &cnpj[12..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_cnpj() {
+        assert_eq!(validate("11.222.333/0001-81").unwrap(), "11222333000181");
+        assert!(is_valid("12.345.678/0001-95"));
+        assert!(!is_valid("12.345.678/0001-90"));
+        assert!(!is_valid("00000000000000"));
+        assert!(!is_valid("1122233300018"));
+        assert_eq!(root("11.222.333/0001-81").as_deref(), Some("11222333"));
+    }
+
+    #[test]
+    fn test_alphanumeric_cnpj() {
+        // Exemplo da nota técnica da Receita Federal
+        assert_eq!(check_digits("12ABC34501DE").as_deref(), Some("35"));
+        assert_eq!(validate("12.abc.345/01de-35").unwrap(), "12ABC34501DE35");
+        assert!(is_alphanumeric("12.ABC.345/01DE-35"));
+        assert!(!is_valid("12.ABC.345/01DE-36"));
+        assert!(!is_valid("12.ABC.345/01DE-3X"));
+        assert_eq!(format("12ABC34501DE35").as_deref(), Some("12.ABC.345/01DE-35"));
+    }
+}
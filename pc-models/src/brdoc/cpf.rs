@@ -0,0 +1,75 @@
+//! CPF: 11 dígitos, os dois últimos verificadores (módulo 11)
+
+use super::{mod11, strip_separators, weighted_sum, weights};
+use pc_core::Result;
+
+/// Remove pontuação e espaços
+pub fn normalize(value: &str) -> String {
+    strip_separators(value)
+}
+
+/// Dígitos verificadores de uma base de 9 dígitos
+pub fn check_digits(base: &str) -> Option<String> {
+    let mut values: Vec<u32> = base.chars().map(|c| c.to_digit(10)).collect::<Option<_>>()?;
+    if values.len() != 9 {
+        return None;
+    }
+    for len in [9, 10] {
+        let dv = mod11(weighted_sum(&values, &weights(len, 11)));
+        values.push(dv);
+    }
+    Some(format!("{}{}", values[9], values[10]))
+}
+
+/// Normaliza e confere os dígitos verificadores
+pub fn validate(value: &str) -> Result<String> {
+    let cpf = normalize(value);
+    let invalid = |motivo: &str| pc_core::Error::Validation(format!("CPF inválido '{}': {}", value, motivo));
+
+    if cpf.len() != 11 || !cpf.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid("deve ter 11 dígitos"));
+    }
+    // 000.000.000-00, 111.111.111-11... passam no módulo 11 mas não existem
+    if cpf.chars().all(|c| cpf.starts_with(c)) {
+        return Err(invalid("dígitos repetidos"));
+    }
+    if check_digits(&cpf[..9]).as_deref() != Some(&cpf[9..]) {
+        return Err(invalid("dígitos verificadores não conferem"));
+    }
+    Ok(cpf)
+}
+
+pub fn is_valid(value: &str) -> bool {
+    validate(value).is_ok()
+}
+
+/// 000.000.000-00; `None` se o CPF é inválido
+pub fn format(value: &str) -> Option<String> {
+    validate(value).ok().map(|cpf| format_normalized(&cpf))
+}
+
+pub(crate) fn format_normalized(cpf: &str) -> String {
+    format!("{}.{}.{}-{}", &cpf[..3], &cpf[3..6], &cpf[6..9], &cpf[9..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpf_check_digits() {
+        assert_eq!(check_digits("529982247").as_deref(), Some("25"));
+        assert_eq!(validate("529.982.247-25").unwrap(), "52998224725");
+        assert!(is_valid("12345678909"));
+        assert!(!is_valid("12345678901"));
+        assert!(!is_valid("111.111.111-11"));
+        assert!(!is_valid("5299822472"));
+        assert!(!is_valid("52998224A25"));
+    }
+
+    #[test]
+    fn test_cpf_format() {
+        assert_eq!(format("52998224725").as_deref(), Some("529.982.247-25"));
+        assert_eq!(format("52998224726"), None);
+    }
+}
@@ -0,0 +1,242 @@
+//! Inscrição estadual: tamanho, prefixo e dígitos verificadores por UF
+//!
+//! Regras do roteiro de conferência do SINTEGRA. "ISENTO" é aceito em
+//! qualquer UF.
+
+use super::{mod11, weighted_sum, weights};
+use pc_core::Result;
+
+/// Valor usado por contribuintes dispensados da inscrição
+pub const ISENTO: &str = "ISENTO";
+
+/// UFs com regra de inscrição estadual
+pub const UFS: [&str; 27] = [
+    "AC", "AL", "AM", "AP", "BA", "CE", "DF", "ES", "GO", "MA", "MG", "MS", "MT", "PA", "PB", "PE", "PI", "PR",
+    "RJ", "RN", "RO", "RR", "RS", "SC", "SE", "SP", "TO",
+];
+
+/// Só letras e dígitos, em maiúsculas ("110.042.490.114" vira "110042490114")
+pub fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Normaliza e confere a inscrição pela regra da UF
+pub fn validate(uf: &str, value: &str) -> Result<String> {
+    let uf = uf.trim().to_uppercase();
+    if !UFS.contains(&uf.as_str()) {
+        return Err(pc_core::Error::Validation(format!("UF desconhecida para inscrição estadual: '{}'", uf)));
+    }
+
+    let mut ie = normalize(value);
+    if ie == ISENTO {
+        return Ok(ie);
+    }
+    // Vazia ou só zeros passa no módulo 11 depois de completada com zeros
+    if ie.is_empty() || ie.chars().all(|c| c == '0') {
+        return Err(pc_core::Error::Validation(format!("Inscrição estadual vazia para {}: '{}'", uf, value)));
+    }
+    // Em MT a inscrição costuma circular sem os zeros à esquerda
+    if uf == "MT" && ie.len() < 11 && ie.chars().all(|c| c.is_ascii_digit()) {
+        ie = format!("{:0>11}", ie);
+    }
+
+    if check(&uf, &ie) {
+        Ok(ie)
+    } else {
+        Err(pc_core::Error::Validation(format!("Inscrição estadual inválida para {}: '{}'", uf, value)))
+    }
+}
+
+pub fn is_valid(uf: &str, value: &str) -> bool {
+    validate(uf, value).is_ok()
+}
+
+fn check(uf: &str, ie: &str) -> bool {
+    // Produtor rural de SP: P + 12 dígitos
+    if let Some(rural) = ie.strip_prefix('P') {
+        return uf == "SP" && digits(rural).is_some_and(|d| d.len() == 12 && d[8] == sp_dv1(&d));
+    }
+    let Some(d) = digits(ie) else {
+        return false;
+    };
+
+    match (uf, d.len()) {
+        ("AC", 13) | ("DF", 13) => {
+            ie.starts_with(if uf == "AC" { "01" } else { "07" }) && dv_at(&d, 11, mod11) && dv_at(&d, 12, mod11)
+        }
+        ("AL", 9) => ie.starts_with("24") && dv_at(&d, 8, times_ten),
+        ("AP", 9) => ie.starts_with("03") && d[8] == ap_dv(&d),
+        ("AM" | "CE" | "ES" | "MA" | "MS" | "PA" | "PB" | "PI" | "SC" | "SE", 9) => {
+            let prefixes: &[&str] = match uf {
+                "MA" => &["12"],
+                "PA" => &["15"],
+                "MS" => &["28", "50"],
+                _ => &[""],
+            };
+            prefixes.iter().any(|p| ie.starts_with(p)) && dv_at(&d, 8, mod11)
+        }
+        ("BA", 8 | 9) => ba_check(&d),
+        ("GO", 9) => matches!(d[0] * 10 + d[1], 10 | 11 | 15 | 20..=29) && d[8] == go_dv(&d),
+        ("MG", 13) => d[11] == mg_dv1(&d) && dv_at_with(&d, 12, 11, mod11),
+        ("MT", 11) => dv_at(&d, 10, mod11),
+        ("PE", 9) => dv_at(&d, 7, mod11) && dv_at(&d, 8, mod11),
+        ("PE", 14) => d[13] == over_ten(weighted_sum(&d[..13], &[5, 4, 3, 2, 1, 9, 8, 7, 6, 5, 4, 3, 2])),
+        ("PR", 10) => dv_at_with(&d, 8, 7, mod11) && dv_at_with(&d, 9, 7, mod11),
+        ("RJ", 8) => dv_at_with(&d, 7, 7, mod11),
+        ("RN", 9 | 10) => ie.starts_with("20") && dv_at_with(&d, d.len() - 1, d.len() as u32, times_ten),
+        ("RO", 14) => d[13] == over_ten(weighted_sum(&d[..13], &weights(13, 9))),
+        ("RR", 9) => ie.starts_with("24") && d[8] == weighted_sum(&d[..8], &[1, 2, 3, 4, 5, 6, 7, 8]) % 9,
+        ("RS", 10) => dv_at(&d, 9, mod11),
+        ("SP", 12) => d[8] == sp_dv1(&d) && d[11] == weighted_sum(&d[..11], &[3, 2, 10, 9, 8, 7, 6, 5, 4, 3, 2]) % 11 % 10,
+        ("TO", 9) => dv_at(&d, 8, mod11),
+        // Formato antigo: as posições 3 e 4 (tipo de empresa) ficam fora do cálculo
+        ("TO", 11) => {
+            let base: Vec<u32> = d[..2].iter().chain(&d[4..10]).copied().collect();
+            matches!(&ie[2..4], "01" | "02" | "03" | "99") && d[10] == mod11(weighted_sum(&base, &weights(8, 9)))
+        }
+        _ => false,
+    }
+}
+
+fn digits(value: &str) -> Option<Vec<u32>> {
+    value.chars().map(|c| c.to_digit(10)).collect()
+}
+
+/// Dígito na posição `pos`, calculado sobre as anteriores com pesos 2 a 9
+fn dv_at(d: &[u32], pos: usize, rule: fn(u32) -> u32) -> bool {
+    dv_at_with(d, pos, 9, rule)
+}
+
+/// Como [`dv_at`], com pesos de 2 a `max`
+fn dv_at_with(d: &[u32], pos: usize, max: u32, rule: fn(u32) -> u32) -> bool {
+    d[pos] == rule(weighted_sum(&d[..pos], &weights(pos, max)))
+}
+
+/// AL e RN: (soma × 10) mod 11, com 10 virando 0
+fn times_ten(sum: u32) -> u32 {
+    (sum * 10 % 11) % 10
+}
+
+/// PE (formato antigo) e RO: 11 - resto, subtraindo 10 quando passa de 9
+fn over_ten(sum: u32) -> u32 {
+    (11 - sum % 11) % 10
+}
+
+fn ap_dv(d: &[u32]) -> u32 {
+    let base = d[..8].iter().fold(0, |acc, v| acc * 10 + v);
+    let (p, dv_11) = match base {
+        ..=3_017_000 => (5, 0),
+        3_017_001..=3_019_022 => (9, 1),
+        _ => (0, 0),
+    };
+    match 11 - (p + weighted_sum(&d[..8], &weights(8, 9))) % 11 {
+        10 => 0,
+        11 => dv_11,
+        dv => dv,
+    }
+}
+
+/// BA: o segundo dígito é calculado primeiro; o módulo (10 ou 11) depende
+/// do primeiro dígito (8 posições) ou do segundo (9 posições)
+fn ba_check(d: &[u32]) -> bool {
+    let n = d.len();
+    let modulo = if matches!(d[n - 8], 6 | 7 | 9) { 11 } else { 10 };
+    let rule = |sum: u32| match (modulo, sum % modulo) {
+        (10, 0) => 0,
+        (10, r) => 10 - r,
+        _ => mod11(sum),
+    };
+
+    let base = &d[..n - 2];
+    let dv2 = rule(weighted_sum(base, &weights(n - 2, n as u32 - 1)));
+    let with_dv2: Vec<u32> = base.iter().copied().chain([dv2]).collect();
+    let dv1 = rule(weighted_sum(&with_dv2, &weights(n - 1, n as u32)));
+    d[n - 2] == dv1 && d[n - 1] == dv2
+}
+
+fn go_dv(d: &[u32]) -> u32 {
+    let base = d[..8].iter().fold(0, |acc, v| acc * 10 + v);
+    match weighted_sum(&d[..8], &weights(8, 9)) % 11 {
+        0 => 0,
+        1 if (10_103_105..=10_119_997).contains(&base) => 1,
+        1 => 0,
+        r => 11 - r,
+    }
+}
+
+/// MG: município com um zero inserido, pesos 1 e 2 alternados e soma dos
+/// algarismos dos produtos
+fn mg_dv1(d: &[u32]) -> u32 {
+    let expanded: Vec<u32> = d[..3].iter().copied().chain([0]).chain(d[3..11].iter().copied()).collect();
+    let sum: u32 = expanded
+        .iter()
+        .enumerate()
+        .map(|(i, v)| v * if i % 2 == 0 { 1 } else { 2 })
+        .map(|p| p / 10 + p % 10)
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+fn sp_dv1(d: &[u32]) -> u32 {
+    weighted_sum(&d[..8], &[1, 3, 4, 5, 6, 7, 8, 10]) % 11 % 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ie_examples_per_uf() {
+        let examples = [
+            ("AC", "01.004.823/001-12"),
+            ("AL", "240000048"),
+            ("AP", "030123459"),
+            ("BA", "123456-63"),
+            ("BA", "1000003-06"),
+            ("CE", "06000001-5"),
+            ("DF", "07300001001-09"),
+            ("GO", "10.987.654-7"),
+            ("MA", "120000385"),
+            ("MG", "062.307.904/0081"),
+            ("MT", "0013000001-9"),
+            ("PA", "15-999999-5"),
+            ("PB", "06000001-5"),
+            ("PE", "0321418-40"),
+            ("PE", "18.1.001.0000004-9"),
+            ("PI", "012345679"),
+            ("PR", "123.45678-50"),
+            ("RJ", "99.999.99-3"),
+            ("RN", "20.040.040-1"),
+            ("RN", "20.0.040.040-0"),
+            ("RO", "0000000062521-3"),
+            ("RR", "24006628-1"),
+            ("RS", "224/3658792"),
+            ("SC", "251.040.852"),
+            ("SE", "27123456-3"),
+            ("SP", "110.042.490.114"),
+            ("SP", "P-01100424.3/002"),
+            ("TO", "29.01.022783-6"),
+        ];
+        for (uf, ie) in examples {
+            assert!(is_valid(uf, ie), "{} {}", uf, ie);
+        }
+    }
+
+    #[test]
+    fn test_ie_rejects_wrong_digits_and_uf() {
+        assert!(!is_valid("SP", "110.042.490.115"));
+        assert!(!is_valid("SP", "11004249011"));
+        assert!(!is_valid("RJ", "110.042.490.114"));
+        assert!(!is_valid("MG", "062.307.904/0082"));
+        assert!(!is_valid("MT", ""));
+        assert!(!is_valid("MT", "0000"));
+        assert!(!is_valid("AM", "000.000.000"));
+        assert_eq!(validate("sp", "isento").unwrap(), ISENTO);
+        assert_eq!(validate("SP", "110.042.490.114").unwrap(), "110042490114");
+        assert!(matches!(validate("XX", "123"), Err(pc_core::Error::Validation(_))));
+    }
+}
@@ -0,0 +1,99 @@
+//! Documentos brasileiros: CPF, CNPJ e inscrição estadual
+//!
+//! Validação dos dígitos verificadores, normalização e formatação. O CNPJ
+//! aceita o formato alfanumérico emitido pela Receita Federal a partir de
+//! julho de 2026; a inscrição estadual segue a regra de cada UF.
+
+pub mod cnpj;
+pub mod cpf;
+pub mod ie;
+
+use pc_core::Result;
+use serde::{Deserialize, Serialize};
+
+/// CPF ou CNPJ, normalizado e com os dígitos verificadores conferidos
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TaxId {
+    Cpf(String),
+    Cnpj(String),
+}
+
+impl TaxId {
+    /// Reconhece o documento pelo tamanho (11 para CPF, 14 para CNPJ)
+    pub fn parse(value: &str) -> Result<Self> {
+        match strip_separators(value).len() {
+            11 => cpf::validate(value).map(TaxId::Cpf),
+            14 => cnpj::validate(value).map(TaxId::Cnpj),
+            _ => Err(pc_core::Error::Validation(format!("Documento inválido: '{}' não é CPF nem CNPJ", value))),
+        }
+    }
+
+    /// Sem pontuação
+    pub fn as_str(&self) -> &str {
+        match self {
+            TaxId::Cpf(value) | TaxId::Cnpj(value) => value,
+        }
+    }
+
+    /// Com a máscara usual (000.000.000-00 ou 00.000.000/0000-00)
+    pub fn formatted(&self) -> String {
+        match self {
+            TaxId::Cpf(value) => cpf::format_normalized(value),
+            TaxId::Cnpj(value) => cnpj::format_normalized(value),
+        }
+    }
+}
+
+impl std::fmt::Display for TaxId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.formatted())
+    }
+}
+
+/// Remove pontuação e espaços, em maiúsculas
+fn strip_separators(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !matches!(c, '.' | '-' | '/' | ' ' | '\t'))
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Pesos do módulo 11, da direita para a esquerda: 2, 3, ..., `max`, 2, 3...
+fn weights(len: usize, max: u32) -> Vec<u32> {
+    let mut weights: Vec<u32> = (0..len).map(|i| 2 + (i as u32) % (max - 1)).collect();
+    weights.reverse();
+    weights
+}
+
+fn weighted_sum(values: &[u32], weights: &[u32]) -> u32 {
+    values.iter().zip(weights).map(|(v, w)| v * w).sum()
+}
+
+/// Dígito do módulo 11: restos 0 e 1 dão 0, os demais 11 - resto
+fn mod11(sum: u32) -> u32 {
+    match sum % 11 {
+        0 | 1 => 0,
+        r => 11 - r,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tax_id_by_length() {
+        assert_eq!(TaxId::parse("529.982.247-25").unwrap(), TaxId::Cpf("52998224725".into()));
+        let cnpj = TaxId::parse("11222333000181").unwrap();
+        assert_eq!(cnpj.to_string(), "11.222.333/0001-81");
+        assert!(TaxId::parse("123").is_err());
+        assert!(TaxId::parse("11222333000182").is_err());
+    }
+
+    #[test]
+    fn test_weights() {
+        assert_eq!(weights(12, 9), [5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2]);
+        assert_eq!(weights(8, 9), [9, 8, 7, 6, 5, 4, 3, 2]);
+    }
+}
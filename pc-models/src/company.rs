@@ -1,7 +1,9 @@
 //! Company model
 
-use chrono::{DateTime, Utc};
-use pc_core::{AuditMetadata, Entity, Embeddable, Result};
+use crate::brdoc;
+use crate::validation::Rules;
+use pc_core::validation::codes;
+use pc_core::{AuditMetadata, Entity, Embeddable, NaturalKey, Violations};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -91,16 +93,14 @@ impl Company {
         self
     }
 
-    /// Valida CNPJ (formato simplificado)
+    /// Valida CNPJ, numérico ou alfanumérico (dígitos verificadores)
     pub fn validate_cnpj(cnpj: &str) -> bool {
-        let digits: String = cnpj.chars().filter(|c| c.is_numeric()).collect();
-        digits.len() == 14
+        brdoc::cnpj::is_valid(cnpj)
     }
 
-    /// Valida CPF (formato simplificado)
+    /// Valida CPF (dígitos verificadores)
     pub fn validate_cpf(cpf: &str) -> bool {
-        let digits: String = cpf.chars().filter(|c| c.is_numeric()).collect();
-        digits.len() == 11
+        brdoc::cpf::is_valid(cpf)
     }
 }
 
@@ -113,32 +113,39 @@ impl Entity for Company {
         "company"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.nome.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Nome não pode ser vazio".into()));
-        }
+    fn check(&self, v: &mut Violations) {
+        v.required("nome", &self.nome);
+        v.required("cidade", &self.cidade);
+        v.required("estado", &self.estado);
 
-        if self.cidade.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Cidade não pode ser vazia".into()));
+        let uf_valida = brdoc::ie::UFS.contains(&self.estado.trim().to_uppercase().as_str());
+        if !self.estado.trim().is_empty() && !uf_valida {
+            v.add("estado", codes::INVALID_UF);
         }
 
-        if self.estado.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Estado não pode ser vazio".into()));
+        v.cnpj("cnpj", self.cnpj.as_deref());
+        v.cpf("cpf", self.cpf.as_deref());
+        // Sem UF conhecida não há regra para conferir a inscrição
+        if uf_valida {
+            v.inscricao_estadual("inscricao_estadual", &self.estado, self.inscricao_estadual.as_deref());
         }
 
-        if let Some(cnpj) = &self.cnpj {
-            if !Self::validate_cnpj(cnpj) {
-                return Err(pc_core::Error::Validation("CNPJ inválido".into()));
-            }
-        }
+        v.cep("cep", self.cep.as_deref());
+        v.phone("telefone", self.telefone.as_deref());
+        v.phone("celular", self.celular.as_deref());
+        v.email("email", self.email.as_deref());
+    }
+}
 
-        if let Some(cpf) = &self.cpf {
-            if !Self::validate_cpf(cpf) {
-                return Err(pc_core::Error::Validation("CPF inválido".into()));
-            }
-        }
+/// Empresas resolvidas a partir das ordens têm IDs estáveis (derivados do
+/// CNPJ ou do nome e cidade), então o próprio ID serve de chave natural
+impl NaturalKey for Company {
+    fn natural_key(&self) -> String {
+        self.id.to_string()
+    }
 
-        Ok(())
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.metadata = existing.metadata.clone();
     }
 }
 
@@ -194,8 +201,32 @@ mod tests {
 
     #[test]
     fn test_cnpj_validation() {
-        assert!(Company::validate_cnpj("12.345.678/0001-90"));
-        assert!(Company::validate_cnpj("12345678000190"));
+        assert!(Company::validate_cnpj("12.345.678/0001-95"));
+        assert!(Company::validate_cnpj("12345678000195"));
+        assert!(Company::validate_cnpj("12.ABC.345/01DE-35"));
+        assert!(!Company::validate_cnpj("12.345.678/0001-90"));
         assert!(!Company::validate_cnpj("123456"));
     }
+
+    #[test]
+    fn test_validate_checks_documents() {
+        let mut company = Company::new("Fenior".into(), "Franca".into(), "SP".into(), CompanyType::Cliente)
+            .with_cnpj("11.222.333/0001-81".into());
+        company.inscricao_estadual = Some("110.042.490.114".into());
+        assert!(company.validate().is_ok());
+
+        company.estado = "MG".into();
+        assert!(matches!(company.validate(), Err(pc_core::Error::Invalid(_))));
+
+        // Todas as violações de uma vez, cada uma com o seu campo
+        company.estado = "XX".into();
+        company.cnpj = Some("11.222.333/0001-82".into());
+        company.email = Some("fenior".into());
+        let Err(pc_core::Error::Invalid(violations)) = company.validate() else {
+            panic!("esperava violações");
+        };
+        let fields: Vec<&str> = violations.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["estado", "cnpj", "email"]);
+        assert_eq!(violations.iter().nth(1).unwrap().code, codes::INVALID_CNPJ);
+    }
 }
@@ -0,0 +1,114 @@
+//! Company match review model
+
+use chrono::{DateTime, Utc};
+use pc_core::validation::codes;
+use pc_core::{AuditMetadata, Entity, NaturalKey, Violations};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::LinkStatus;
+
+/// Empresa candidata para um nome citado nas ordens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompanyCandidate {
+    pub empresa_id: Uuid,
+    pub nome: String,
+    pub cidade: String,
+    /// Similaridade, de 0 a 1
+    pub pontuacao: f64,
+}
+
+/// Nome de empresa que não pôde ser resolvido automaticamente
+///
+/// Aceitar aponta a empresa correta; rejeitar faz a próxima resolução
+/// cadastrar uma empresa nova para o nome.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyReview {
+    pub id: Uuid,
+    /// Nome e cidade normalizados, usados para reconhecer o mesmo caso
+    pub chave: String,
+    pub nome: String,
+    pub cidade: String,
+    pub telefone: Option<String>,
+    pub cnpj: Option<String>,
+    pub candidatos: Vec<CompanyCandidate>,
+    /// Ordens de frete que citam o nome
+    pub ordens: Vec<String>,
+    pub status: LinkStatus,
+    pub empresa_id: Option<Uuid>,
+    pub revisado_em: Option<DateTime<Utc>>,
+    pub metadata: AuditMetadata,
+}
+
+impl CompanyReview {
+    pub fn new(chave: String, nome: String, cidade: String, candidatos: Vec<CompanyCandidate>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            chave,
+            nome,
+            cidade,
+            telefone: None,
+            cnpj: None,
+            candidatos,
+            ordens: Vec::new(),
+            status: LinkStatus::Suggested,
+            empresa_id: None,
+            revisado_em: None,
+            metadata: AuditMetadata::default(),
+        }
+    }
+
+    /// Operador confirma que o nome é da empresa `empresa_id`
+    pub fn accept(&mut self, empresa_id: Uuid) {
+        self.status = LinkStatus::Accepted;
+        self.empresa_id = Some(empresa_id);
+        self.revisado_em = Some(Utc::now());
+    }
+
+    /// Operador indica que nenhum candidato serve
+    pub fn reject(&mut self) {
+        self.status = LinkStatus::Rejected;
+        self.empresa_id = None;
+        self.revisado_em = Some(Utc::now());
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == LinkStatus::Suggested
+    }
+}
+
+impl Entity for CompanyReview {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "company_review"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        v.required("chave", &self.chave);
+        if self.status == LinkStatus::Accepted && self.empresa_id.is_none() {
+            v.add("empresa_id", codes::REQUIRED);
+        }
+    }
+}
+
+impl NaturalKey for CompanyReview {
+    fn natural_key(&self) -> String {
+        self.chave.clone()
+    }
+
+    /// Nova resolução não desfaz a decisão do operador e soma as ordens
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.status = existing.status;
+        self.empresa_id = existing.empresa_id;
+        self.revisado_em = existing.revisado_em;
+        self.metadata = existing.metadata.clone();
+
+        let mut ordens = existing.ordens.clone();
+        ordens.extend(self.ordens.drain(..).filter(|o| !existing.ordens.contains(o)));
+        self.ordens = ordens;
+    }
+}
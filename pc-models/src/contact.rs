@@ -1,6 +1,7 @@
 //! Contact model
 
-use pc_core::{AuditMetadata, Entity, Result};
+use pc_core::{AuditMetadata, Entity, Violations};
+use crate::validation::Rules;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -52,10 +53,10 @@ impl Entity for Contact {
         "contact"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.nome.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Nome não pode ser vazio".into()));
-        }
-        Ok(())
+    fn check(&self, v: &mut Violations) {
+        v.required("nome", &self.nome);
+        v.email("email", self.email.as_deref());
+        v.phone("telefone", self.telefone.as_deref());
+        v.phone("celular", self.celular.as_deref());
     }
 }
@@ -0,0 +1,181 @@
+//! Document (attachment) model
+
+use pc_core::validation::codes;
+use pc_core::{AuditMetadata, Entity, NaturalKey, Violations};
+use crate::{Lineage, Provenance};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tipo usado para vincular documentos a funcionários (sem entidade própria)
+pub const ENTIDADE_FUNCIONARIO: &str = "employee";
+
+/// Vínculo de um documento com outra entidade
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentLink {
+    /// Tipo da entidade, como em [`Entity::entity_type`] ("freight_order", "company", "cte"...)
+    pub entidade: String,
+    pub entidade_id: Uuid,
+}
+
+impl DocumentLink {
+    pub fn new(entidade: impl Into<String>, entidade_id: Uuid) -> Self {
+        Self {
+            entidade: entidade.into(),
+            entidade_id,
+        }
+    }
+
+    pub fn to<E: Entity>(entity: &E) -> Self {
+        Self::new(entity.entity_type(), *entity.id())
+    }
+
+    pub fn employee(funcionario_id: Uuid) -> Self {
+        Self::new(ENTIDADE_FUNCIONARIO, funcionario_id)
+    }
+}
+
+/// Documento anexado (PDF, foto, planilha...)
+///
+/// O conteúdo fica no blob store do pc-db, endereçado pelo `sha256`; o mesmo
+/// arquivo encontrado em vários lugares é um único documento com vários locais.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    pub id: Uuid,
+    pub titulo: String,
+    /// "CT-e", "NF-e", "Foto", "Comprovante"...
+    pub tipo: String,
+    pub caminho_origem: String,
+    /// Todos os caminhos onde o arquivo foi encontrado
+    pub locais: Vec<String>,
+    pub sha256: String,
+    pub mime_type: String,
+    pub tamanho: u64,
+    pub texto: Option<String>,
+    /// Dados específicos do tipo (EXIF, páginas, ...)
+    pub atributos: serde_json::Value,
+    pub vinculos: Vec<DocumentLink>,
+    /// ZIPs e e-mails em que o arquivo chegou
+    #[serde(default)]
+    pub origens: Vec<Provenance>,
+    /// Arquivo, linha, importador e job de onde o registro veio
+    #[serde(default)]
+    pub linhagem: Option<Lineage>,
+    pub metadata: AuditMetadata,
+}
+
+impl Document {
+    pub fn new(titulo: String, tipo: String, caminho_origem: String, sha256: String, mime_type: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            titulo,
+            tipo,
+            locais: vec![caminho_origem.clone()],
+            caminho_origem,
+            sha256,
+            mime_type,
+            tamanho: 0,
+            texto: None,
+            atributos: serde_json::Value::Null,
+            vinculos: Vec::new(),
+            origens: Vec::new(),
+            linhagem: None,
+            metadata: AuditMetadata::default(),
+        }
+    }
+
+    /// Vincula o documento a uma entidade (sem repetir vínculos)
+    pub fn link(&mut self, link: DocumentLink) {
+        if !self.vinculos.contains(&link) {
+            self.vinculos.push(link);
+        }
+    }
+
+    pub fn unlink(&mut self, link: &DocumentLink) {
+        self.vinculos.retain(|l| l != link);
+    }
+
+    pub fn is_linked_to(&self, entidade: &str, entidade_id: &Uuid) -> bool {
+        self.vinculos
+            .iter()
+            .any(|l| l.entidade == entidade && &l.entidade_id == entidade_id)
+    }
+}
+
+impl Entity for Document {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "document"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        v.required("titulo", &self.titulo);
+        if self.sha256.len() != 64 || !self.sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            v.add("sha256", codes::INVALID_FORMAT);
+        }
+    }
+}
+
+impl NaturalKey for Document {
+    fn natural_key(&self) -> String {
+        self.sha256.clone()
+    }
+
+    /// Reimportações e cópias somam locais e vínculos ao documento existente
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.metadata = existing.metadata.clone();
+
+        let mut locais = existing.locais.clone();
+        locais.extend(self.locais.drain(..).filter(|l| !existing.locais.contains(l)));
+        self.locais = locais;
+
+        let mut vinculos = existing.vinculos.clone();
+        vinculos.extend(self.vinculos.drain(..).filter(|v| !existing.vinculos.contains(v)));
+        self.vinculos = vinculos;
+
+        let mut origens = existing.origens.clone();
+        origens.extend(self.origens.drain(..).filter(|o| !existing.origens.contains(o)));
+        self.origens = origens;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(caminho: &str) -> Document {
+        Document::new(
+            "CTE 123.pdf".into(),
+            "CT-e".into(),
+            caminho.into(),
+            "a".repeat(64),
+            "application/pdf".into(),
+        )
+    }
+
+    #[test]
+    fn test_adopt_identity_merges_locations_and_links() {
+        let order_id = Uuid::new_v4();
+        let mut existing = document("/backup/CTE 123.pdf");
+        existing.link(DocumentLink::new("freight_order", order_id));
+
+        let mut copy = document("/email/CTE 123.pdf");
+        copy.link(DocumentLink::new("freight_order", order_id));
+        copy.link(DocumentLink::employee(Uuid::nil()));
+        let mut origem = Provenance::new("/email/caixa.mbox", "caixa.mbox/1/CTE 123.pdf");
+        origem.remetente = Some("faturamento@fenior.com.br".into());
+        existing.origens.push(origem.clone());
+        copy.origens.push(origem);
+        copy.adopt_identity(&existing);
+
+        assert_eq!(copy.id, existing.id);
+        assert_eq!(copy.locais, vec!["/backup/CTE 123.pdf", "/email/CTE 123.pdf"]);
+        assert_eq!(copy.vinculos.len(), 2);
+        assert!(copy.is_linked_to("freight_order", &order_id));
+        assert!(copy.is_linked_to(ENTIDADE_FUNCIONARIO, &Uuid::nil()));
+        assert_eq!(copy.origens.len(), 1);
+    }
+}
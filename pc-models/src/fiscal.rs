@@ -1,7 +1,9 @@
 //! Fiscal models (invoices, CT-e, etc)
 
 use chrono::NaiveDate;
-use pc_core::{AuditMetadata, Entity, Result};
+use crate::validation::{number_matches_key, Rules, MODELO_CTE, MODELO_NFE};
+use pc_core::validation::codes;
+use pc_core::{AuditMetadata, Entity, Money, NaturalKey, Violation, Violations};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -20,10 +22,10 @@ pub struct Invoice {
     pub destinatario_id: Option<Uuid>,
     pub destinatario_nome: String,
 
-    pub valor_total: f64,
-    pub valor_produtos: f64,
-    pub valor_frete: Option<f64>,
-    pub valor_icms: Option<f64>,
+    pub valor_total: Money,
+    pub valor_produtos: Money,
+    pub valor_frete: Option<Money>,
+    pub valor_icms: Option<Money>,
 
     pub natureza_operacao: Option<String>,
     pub cfop: Option<String>,
@@ -43,16 +45,34 @@ impl Entity for Invoice {
         "invoice"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.numero.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Número não pode ser vazio".into()));
+    fn check(&self, v: &mut Violations) {
+        v.required("numero", &self.numero);
+        v.required("emitente_nome", &self.emitente_nome);
+        v.non_negative("valor_total", self.valor_total);
+        v.non_negative("valor_produtos", self.valor_produtos);
+        v.non_negative("valor_frete", self.valor_frete.unwrap_or_default());
+        v.non_negative("valor_icms", self.valor_icms.unwrap_or_default());
+
+        if let Some(chave) = self.chave_acesso.as_deref().filter(|c| !c.trim().is_empty()) {
+            if v.access_key("chave_acesso", chave, Some(MODELO_NFE)) && !number_matches_key(&self.numero, chave) {
+                v.push(Violation::new("numero", codes::MISMATCH).with_param("other", "chave_acesso"));
+            }
         }
+    }
+}
 
-        if self.valor_total < 0.0 {
-            return Err(pc_core::Error::Validation("Valor não pode ser negativo".into()));
+impl NaturalKey for Invoice {
+    /// Chave de acesso; notas sem chave pelo número e série
+    fn natural_key(&self) -> String {
+        match self.chave_acesso.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            Some(chave) => chave.to_string(),
+            None => format!("{}|{}", self.numero.trim(), self.serie.as_deref().unwrap_or("").trim()),
         }
+    }
 
-        Ok(())
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.metadata = existing.metadata.clone();
     }
 }
 
@@ -71,8 +91,8 @@ pub struct Cte {
     pub expedidor_id: Option<Uuid>,
     pub recebedor_id: Option<Uuid>,
 
-    pub valor_total: f64,
-    pub valor_receber: f64,
+    pub valor_total: Money,
+    pub valor_receber: Money,
 
     pub modal: String, // Rodoviário, Aéreo, etc
     pub tipo_servico: String,
@@ -94,15 +114,31 @@ impl Entity for Cte {
         "cte"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.numero.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Número não pode ser vazio".into()));
-        }
+    fn check(&self, v: &mut Violations) {
+        v.required("numero", &self.numero);
+        v.non_negative("valor_total", self.valor_total);
+        v.non_negative("valor_receber", self.valor_receber);
+        v.not_greater("valor_receber", self.valor_receber, "valor_total", self.valor_total);
 
-        if self.chave_acesso.len() != 44 {
-            return Err(pc_core::Error::Validation("Chave de acesso inválida".into()));
+        if v.access_key("chave_acesso", &self.chave_acesso, Some(MODELO_CTE))
+            && !number_matches_key(&self.numero, &self.chave_acesso)
+        {
+            v.push(Violation::new("numero", codes::MISMATCH).with_param("other", "chave_acesso"));
+        }
+        // Notas referenciadas pela chave (as que vêm só com o número ficam de fora)
+        for (i, nota) in self.notas_fiscais.iter().enumerate().filter(|(_, n)| n.trim().len() == 44) {
+            v.access_key(&format!("notas_fiscais[{}]", i), nota, Some(MODELO_NFE));
         }
+    }
+}
+
+impl NaturalKey for Cte {
+    fn natural_key(&self) -> String {
+        self.chave_acesso.trim().to_string()
+    }
 
-        Ok(())
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.metadata = existing.metadata.clone();
     }
 }
@@ -1,7 +1,9 @@
 //! Freight order model
 
 use chrono::NaiveDate;
-use pc_core::{AuditMetadata, Entity, Embeddable, Result, Status};
+use pc_core::{AuditMetadata, Entity, Embeddable, Money, NaturalKey, Status, Violations};
+use crate::validation::{Rules, MODELO_CTE};
+use crate::{Lineage, Provenance};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -19,25 +21,31 @@ pub struct FreightOrder {
     pub cte_numero: Option<String>,
     pub cte_chave: Option<String>,
 
-    // Partes envolvidas (IDs de Company)
-    pub pagador_id: Uuid,
+    // Partes envolvidas (IDs de Company, preenchidos pela resolução de empresas)
+    pub pagador_id: Option<Uuid>,
     pub pagador_nome: String,
     pub pagador_telefone: Option<String>,
+    #[serde(default)]
+    pub pagador_cnpj: Option<String>,
 
     pub remetente_id: Option<Uuid>,
     pub remetente_nome: String,
     pub remetente_cidade: String,
+    #[serde(default)]
+    pub remetente_cnpj: Option<String>,
 
     pub destinatario_id: Option<Uuid>,
     pub destinatario_nome: String,
     pub destinatario_cidade: String,
+    #[serde(default)]
+    pub destinatario_cnpj: Option<String>,
 
     // Carga
     pub volumes: i32,
     pub peso: f64,
-    pub valor_notas: f64,
-    pub valor_frete: f64,
-    pub frete_tabelado: Option<f64>,
+    pub valor_notas: Money,
+    pub valor_frete: Money,
+    pub frete_tabelado: Option<Money>,
 
     // Operação
     pub filial_coleta: Option<String>,
@@ -51,6 +59,12 @@ pub struct FreightOrder {
 
     // Origem dos dados
     pub fonte_arquivo: Option<String>,
+    /// ZIP ou e-mail de onde a planilha foi extraída
+    #[serde(default)]
+    pub origem: Option<Provenance>,
+    /// Arquivo, linha, importador e job de onde o registro veio
+    #[serde(default)]
+    pub linhagem: Option<Lineage>,
 
     // Auditoria
     pub metadata: AuditMetadata,
@@ -75,19 +89,22 @@ impl FreightOrder {
             notas_fiscais: Vec::new(),
             cte_numero: None,
             cte_chave: None,
-            pagador_id: Uuid::new_v4(), // Temporary - will be resolved
+            pagador_id: None,
             pagador_nome,
             pagador_telefone: None,
+            pagador_cnpj: None,
             remetente_id: None,
             remetente_nome,
             remetente_cidade,
+            remetente_cnpj: None,
             destinatario_id: None,
             destinatario_nome,
             destinatario_cidade,
+            destinatario_cnpj: None,
             volumes: 0,
             peso: 0.0,
-            valor_notas: 0.0,
-            valor_frete: 0.0,
+            valor_notas: Money::ZERO,
+            valor_frete: Money::ZERO,
             frete_tabelado: None,
             filial_coleta: None,
             motorista_coleta: None,
@@ -97,6 +114,8 @@ impl FreightOrder {
             status: Status::Pending,
             observacoes: None,
             fonte_arquivo: None,
+            origem: None,
+            linhagem: None,
             metadata: AuditMetadata::default(),
         }
     }
@@ -111,28 +130,39 @@ impl Entity for FreightOrder {
         "freight_order"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.numero.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Número não pode ser vazio".into()));
-        }
+    fn check(&self, v: &mut Violations) {
+        v.required("numero", &self.numero);
+        v.required("pagador_nome", &self.pagador_nome);
+        v.non_negative("volumes", self.volumes);
+        v.non_negative("peso", self.peso);
+        v.non_negative("valor_notas", self.valor_notas);
+        v.non_negative("valor_frete", self.valor_frete);
+        v.non_negative("frete_tabelado", self.frete_tabelado.unwrap_or_default());
 
-        if self.pagador_nome.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Pagador não pode ser vazio".into()));
-        }
+        v.not_before("data_agendamento", self.data_agendamento, "data_emissao", self.data_emissao);
+        v.not_before("data_entrega", self.data_entrega, "data_emissao", self.data_emissao);
 
-        if self.volumes < 0 {
-            return Err(pc_core::Error::Validation("Volumes não pode ser negativo".into()));
-        }
-
-        if self.peso < 0.0 {
-            return Err(pc_core::Error::Validation("Peso não pode ser negativo".into()));
+        if let Some(chave) = self.cte_chave.as_deref().filter(|c| !c.trim().is_empty()) {
+            v.access_key("cte_chave", chave, Some(MODELO_CTE));
         }
+        v.tax_id("pagador_cnpj", self.pagador_cnpj.as_deref().filter(|c| !c.trim().is_empty()));
+        v.tax_id("remetente_cnpj", self.remetente_cnpj.as_deref().filter(|c| !c.trim().is_empty()));
+        v.tax_id("destinatario_cnpj", self.destinatario_cnpj.as_deref().filter(|c| !c.trim().is_empty()));
+        v.phone("pagador_telefone", self.pagador_telefone.as_deref());
+    }
+}
 
-        if self.valor_frete < 0.0 {
-            return Err(pc_core::Error::Validation("Valor do frete não pode ser negativo".into()));
-        }
+impl NaturalKey for FreightOrder {
+    fn natural_key(&self) -> String {
+        self.numero.trim().to_string()
+    }
 
-        Ok(())
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.pagador_id = self.pagador_id.or(existing.pagador_id);
+        self.remetente_id = self.remetente_id.or(existing.remetente_id);
+        self.destinatario_id = self.destinatario_id.or(existing.destinatario_id);
+        self.metadata = existing.metadata.clone();
     }
 }
 
@@ -192,8 +222,8 @@ pub struct Manifest {
 
     pub volumes: i32,
     pub peso: f64,
-    pub valor_notas: f64,
-    pub valor_frete: f64,
+    pub valor_notas: Money,
+    pub valor_frete: Money,
 
     pub status: String, // Finalizada, Pendente, Em Atraso
     pub observacoes: Option<String>,
@@ -210,11 +240,19 @@ impl Entity for Manifest {
         "manifest"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.numero.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Número não pode ser vazio".into()));
+    fn check(&self, v: &mut Violations) {
+        v.required("numero", &self.numero);
+        v.non_negative("volumes", self.volumes);
+        v.non_negative("peso", self.peso);
+        v.non_negative("valor_notas", self.valor_notas);
+        v.non_negative("valor_frete", self.valor_frete);
+
+        v.not_before("data_agendamento", self.data_agendamento, "data_emissao", self.data_emissao);
+        v.not_before("data_entrega", self.data_entrega, "data_emissao", self.data_emissao);
+
+        if let Some(chave) = self.chave_acesso.as_deref().filter(|c| !c.trim().is_empty()) {
+            v.access_key("chave_acesso", chave, None);
         }
-        Ok(())
     }
 }
 
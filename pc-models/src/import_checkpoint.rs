@@ -0,0 +1,66 @@
+//! Import checkpoint model
+
+use chrono::{DateTime, Utc};
+use pc_core::{AuditMetadata, Entity, NaturalKey, Violations};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Progresso de uma importação em lotes, para retomar após uma falha
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportCheckpoint {
+    pub id: Uuid,
+    pub fonte_arquivo: String,
+    /// Hash do arquivo; se o conteúdo mudar, a importação recomeça do início
+    pub sha256: String,
+    /// Última linha do arquivo cujo lote foi gravado
+    pub ultima_linha: u64,
+    pub registros_gravados: usize,
+    pub atualizado_em: DateTime<Utc>,
+    pub metadata: AuditMetadata,
+}
+
+impl ImportCheckpoint {
+    pub fn new(fonte_arquivo: String, sha256: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            fonte_arquivo,
+            sha256,
+            ultima_linha: 0,
+            registros_gravados: 0,
+            atualizado_em: Utc::now(),
+            metadata: AuditMetadata::default(),
+        }
+    }
+
+    /// Registra um lote gravado até a linha informada
+    pub fn advance(&mut self, ultima_linha: u64, registros: usize) {
+        self.ultima_linha = ultima_linha;
+        self.registros_gravados += registros;
+        self.atualizado_em = Utc::now();
+    }
+}
+
+impl Entity for ImportCheckpoint {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "import_checkpoint"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        v.required("fonte_arquivo", &self.fonte_arquivo);
+    }
+}
+
+impl NaturalKey for ImportCheckpoint {
+    fn natural_key(&self) -> String {
+        self.fonte_arquivo.clone()
+    }
+
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.metadata = existing.metadata.clone();
+    }
+}
@@ -0,0 +1,201 @@
+//! Import job model
+
+use chrono::{DateTime, Utc};
+use pc_core::{AuditMetadata, Entity, Status, Violations};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Máximo de erros guardados no relatório; os demais só entram na contagem
+pub const MAX_ERROS: usize = 100;
+
+/// Erro registrado durante a importação
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportJobError {
+    /// Linha do arquivo (`None` para erros do arquivo inteiro)
+    pub linha: Option<u64>,
+    pub mensagem: String,
+}
+
+/// Execução de uma importação, acompanhada do início ao fim
+///
+/// Criado como `Pending`, passa a `Processing` ao começar a leitura e termina
+/// em `Completed`, `Failed` ou `Cancelled`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub arquivo: String,
+    pub importador: String,
+    pub status: Status,
+    /// Usuário (ou processo, como o watcher) que disparou a importação
+    pub iniciado_por: Option<String>,
+    pub registros_lidos: usize,
+    pub novos: usize,
+    pub atualizados: usize,
+    pub inalterados: usize,
+    pub rejeitados: usize,
+    pub erros: Vec<ImportJobError>,
+    pub criado_em: DateTime<Utc>,
+    pub iniciado_em: Option<DateTime<Utc>>,
+    pub concluido_em: Option<DateTime<Utc>>,
+    /// Cancelamento pedido durante o processamento; atendido no próximo lote
+    pub cancelamento_solicitado: bool,
+    pub metadata: AuditMetadata,
+}
+
+impl ImportJob {
+    pub fn new(arquivo: String, importador: String, iniciado_por: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            arquivo,
+            importador,
+            status: Status::Pending,
+            iniciado_por,
+            registros_lidos: 0,
+            novos: 0,
+            atualizados: 0,
+            inalterados: 0,
+            rejeitados: 0,
+            erros: Vec::new(),
+            criado_em: Utc::now(),
+            iniciado_em: None,
+            concluido_em: None,
+            cancelamento_solicitado: false,
+            metadata: AuditMetadata::default(),
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.status = Status::Processing;
+        self.iniciado_em = Some(Utc::now());
+    }
+
+    /// Soma os registros gravados em um lote
+    pub fn record_batch(&mut self, novos: usize, atualizados: usize, inalterados: usize) {
+        self.novos += novos;
+        self.atualizados += atualizados;
+        self.inalterados += inalterados;
+        self.registros_lidos += novos + atualizados + inalterados;
+    }
+
+    /// Registra uma linha descartada
+    pub fn reject(&mut self, linha: u64, mensagem: String) {
+        self.rejeitados += 1;
+        self.registros_lidos += 1;
+        self.push_error(Some(linha), mensagem);
+    }
+
+    pub fn complete(&mut self) {
+        self.finish(Status::Completed);
+    }
+
+    pub fn fail(&mut self, mensagem: String) {
+        self.push_error(None, mensagem);
+        self.finish(Status::Failed);
+    }
+
+    /// Pede o cancelamento; retorna `false` se o job já terminou
+    ///
+    /// Jobs pendentes são cancelados na hora; em processamento, o importador
+    /// para no próximo lote e chama [`ImportJob::cancelled`].
+    pub fn request_cancel(&mut self) -> bool {
+        match self.status {
+            Status::Pending => {
+                self.cancelamento_solicitado = true;
+                self.finish(Status::Cancelled);
+                true
+            }
+            Status::Processing => {
+                self.cancelamento_solicitado = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// O importador parou atendendo ao cancelamento
+    pub fn cancelled(&mut self) {
+        self.finish(Status::Cancelled);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, Status::Completed | Status::Failed | Status::Cancelled)
+    }
+
+    /// Tempo de processamento (até agora, se ainda estiver em andamento)
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        let inicio = self.iniciado_em?;
+        Some(self.concluido_em.unwrap_or_else(Utc::now) - inicio)
+    }
+
+    fn finish(&mut self, status: Status) {
+        self.status = status;
+        self.concluido_em = Some(Utc::now());
+    }
+
+    fn push_error(&mut self, linha: Option<u64>, mensagem: String) {
+        if self.erros.len() < MAX_ERROS {
+            self.erros.push(ImportJobError { linha, mensagem });
+        }
+    }
+}
+
+impl Entity for ImportJob {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "import_job"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        v.required("arquivo", &self.arquivo);
+        v.required("importador", &self.importador);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> ImportJob {
+        ImportJob::new("Fretes.csv".into(), "freight".into(), Some("joao".into()))
+    }
+
+    #[test]
+    fn test_job_lifecycle() {
+        let mut job = job();
+        assert_eq!(job.status, Status::Pending);
+        assert!(job.duration().is_none());
+
+        job.start();
+        job.record_batch(2, 1, 0);
+        job.reject(7, "Data inválida".into());
+        job.complete();
+
+        assert_eq!(job.status, Status::Completed);
+        assert_eq!(job.registros_lidos, 4);
+        assert_eq!(job.rejeitados, 1);
+        assert_eq!(job.erros[0].linha, Some(7));
+        assert!(job.is_finished());
+        assert!(job.duration().is_some());
+    }
+
+    #[test]
+    fn test_request_cancel() {
+        let mut pending = job();
+        assert!(pending.request_cancel());
+        assert_eq!(pending.status, Status::Cancelled);
+
+        let mut running = job();
+        running.start();
+        assert!(running.request_cancel());
+        assert_eq!(running.status, Status::Processing);
+        assert!(running.cancelamento_solicitado);
+        running.cancelled();
+        assert_eq!(running.status, Status::Cancelled);
+
+        // Job terminado não volta atrás
+        assert!(!running.request_cancel());
+    }
+}
@@ -0,0 +1,62 @@
+//! Imported file model
+
+use chrono::{DateTime, Utc};
+use pc_core::validation::codes;
+use pc_core::{AuditMetadata, Entity, NaturalKey, Violations};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Registro de um arquivo já importado, usado para pular reimportações
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedFile {
+    pub id: Uuid,
+    /// Nome do arquivo, o mesmo gravado em `fonte_arquivo` nas entidades
+    pub fonte_arquivo: String,
+    pub sha256: String,
+    pub importador: String,
+    pub registros: usize,
+    pub importado_em: DateTime<Utc>,
+    pub metadata: AuditMetadata,
+}
+
+impl ImportedFile {
+    pub fn new(fonte_arquivo: String, sha256: String, importador: String, registros: usize) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            fonte_arquivo,
+            sha256,
+            importador,
+            registros,
+            importado_em: Utc::now(),
+            metadata: AuditMetadata::default(),
+        }
+    }
+}
+
+impl Entity for ImportedFile {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "imported_file"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        v.required("fonte_arquivo", &self.fonte_arquivo);
+        if self.sha256.len() != 64 || !self.sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            v.add("sha256", codes::INVALID_FORMAT);
+        }
+    }
+}
+
+impl NaturalKey for ImportedFile {
+    fn natural_key(&self) -> String {
+        self.fonte_arquivo.clone()
+    }
+
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.metadata = existing.metadata.clone();
+    }
+}
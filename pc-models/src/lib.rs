@@ -2,6 +2,7 @@
 //!
 //! Modelos de dados para todas as entidades do sistema.
 
+pub mod brdoc;
 pub mod company;
 pub mod freight;
 pub mod timesheet;
@@ -9,11 +10,32 @@ pub mod contact;
 pub mod route;
 pub mod fiscal;
 pub mod validation;
+pub mod imported_file;
+pub mod import_checkpoint;
+pub mod photo_link;
+pub mod document;
+pub mod company_review;
+pub mod import_job;
+pub mod payment;
+pub mod boleto;
+pub mod provenance;
+pub mod lineage;
 
+pub use brdoc::TaxId;
 pub use company::{Company, CompanyType};
 pub use freight::{FreightOrder, Manifest};
 pub use timesheet::Timesheet;
 pub use contact::Contact;
 pub use route::Route;
 pub use fiscal::{Invoice, Cte};
-pub use validation::Validate;
+pub use validation::Rules;
+pub use imported_file::ImportedFile;
+pub use import_checkpoint::ImportCheckpoint;
+pub use photo_link::{LinkStatus, PhotoLink};
+pub use document::{Document, DocumentLink};
+pub use company_review::{CompanyCandidate, CompanyReview};
+pub use import_job::{ImportJob, ImportJobError};
+pub use boleto::{Boleto, BoletoOcorrencia, BoletoStatus};
+pub use provenance::Provenance;
+pub use lineage::{HasLineage, Lineage};
+pub use payment::{MatchStatus, PaymentMatch, PaymentMethod, PaymentTransaction};
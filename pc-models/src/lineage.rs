@@ -0,0 +1,133 @@
+//! Linhagem dos registros importados
+//!
+//! Cada registro guarda de qual arquivo (pelo sha256), aba e linha veio, com
+//! o importador e o job que o gravaram. O hash aponta para a cópia do
+//! arquivo no blob store, de onde a linha original pode ser relida.
+
+use crate::{Document, FreightOrder, PaymentTransaction, Route, Timesheet};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Origem exata de um registro importado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lineage {
+    /// sha256 do arquivo importado (chave da cópia no blob store)
+    pub arquivo_sha256: String,
+    /// Caminho do arquivo na importação; para arquivos extraídos de ZIP ou
+    /// e-mail, o caminho dentro do contêiner
+    pub caminho: String,
+    /// Aba da planilha, para registros lidos de arquivos Excel
+    pub planilha: Option<String>,
+    /// Linha do arquivo em que o registro começa (a primeira é 1)
+    pub linha: Option<u64>,
+    /// Coluna de origem, para registros lidos por coluna (rotas)
+    pub coluna: Option<String>,
+    pub importador: String,
+    pub versao_importador: String,
+    pub job_id: Option<Uuid>,
+    pub importado_em: DateTime<Utc>,
+}
+
+impl Lineage {
+    /// Linhagem do arquivo inteiro; arquivo, importador e job são
+    /// preenchidos ao gravar
+    pub fn new() -> Self {
+        Self {
+            arquivo_sha256: String::new(),
+            caminho: String::new(),
+            planilha: None,
+            linha: None,
+            coluna: None,
+            importador: String::new(),
+            versao_importador: String::new(),
+            job_id: None,
+            importado_em: Utc::now(),
+        }
+    }
+
+    /// Registro lido de uma linha do arquivo
+    pub fn at_line(linha: u64) -> Self {
+        Self {
+            linha: Some(linha),
+            ..Self::new()
+        }
+    }
+
+    /// Registro lido de uma coluna (identificada pelo cabeçalho)
+    pub fn at_column(coluna: impl Into<String>) -> Self {
+        Self {
+            coluna: Some(coluna.into()),
+            ..Self::new()
+        }
+    }
+
+    /// Aba da planilha de onde a linha foi lida
+    pub fn with_planilha(mut self, planilha: impl Into<String>) -> Self {
+        self.planilha = Some(planilha.into());
+        self
+    }
+}
+
+impl Default for Lineage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Entidades que guardam a linhagem da importação
+pub trait HasLineage {
+    fn linhagem(&self) -> Option<&Lineage>;
+
+    fn linhagem_mut(&mut self) -> &mut Option<Lineage>;
+}
+
+impl HasLineage for FreightOrder {
+    fn linhagem(&self) -> Option<&Lineage> {
+        self.linhagem.as_ref()
+    }
+
+    fn linhagem_mut(&mut self) -> &mut Option<Lineage> {
+        &mut self.linhagem
+    }
+}
+
+impl HasLineage for Timesheet {
+    fn linhagem(&self) -> Option<&Lineage> {
+        self.linhagem.as_ref()
+    }
+
+    fn linhagem_mut(&mut self) -> &mut Option<Lineage> {
+        &mut self.linhagem
+    }
+}
+
+impl HasLineage for Route {
+    fn linhagem(&self) -> Option<&Lineage> {
+        self.linhagem.as_ref()
+    }
+
+    fn linhagem_mut(&mut self) -> &mut Option<Lineage> {
+        &mut self.linhagem
+    }
+}
+
+impl HasLineage for Document {
+    fn linhagem(&self) -> Option<&Lineage> {
+        self.linhagem.as_ref()
+    }
+
+    fn linhagem_mut(&mut self) -> &mut Option<Lineage> {
+        &mut self.linhagem
+    }
+}
+
+impl HasLineage for PaymentTransaction {
+    fn linhagem(&self) -> Option<&Lineage> {
+        self.linhagem.as_ref()
+    }
+
+    fn linhagem_mut(&mut self) -> &mut Option<Lineage> {
+        &mut self.linhagem
+    }
+}
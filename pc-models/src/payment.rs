@@ -0,0 +1,265 @@
+//! Payment transaction model (extrato bancário)
+
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::Lineage;
+use pc_core::validation::codes;
+use pc_core::{AuditMetadata, Entity, Money, NaturalKey, Violations};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Meio de pagamento, deduzido do histórico do extrato
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentMethod {
+    Pix,
+    Boleto,
+    Transferencia,
+    Outro,
+}
+
+impl PaymentMethod {
+    /// "PIX RECEBIDO", "TED", "LIQUIDACAO BOLETO"...
+    pub fn from_description(descricao: &str) -> Self {
+        let descricao = descricao.to_uppercase();
+        if descricao.contains("PIX") {
+            PaymentMethod::Pix
+        } else if descricao.contains("BOLETO") || descricao.contains("COBRANCA") || descricao.contains("COBRANÇA") {
+            PaymentMethod::Boleto
+        } else if ["TED", "DOC", "TRANSF", "TEF"].iter().any(|t| descricao.contains(t)) {
+            PaymentMethod::Transferencia
+        } else {
+            PaymentMethod::Outro
+        }
+    }
+
+    /// Se a `forma_pagamento` da ordem ("PIX", "Boleto bancário"...) é este meio
+    pub fn matches(&self, forma_pagamento: &str) -> bool {
+        *self != PaymentMethod::Outro && PaymentMethod::from_description(forma_pagamento) == *self
+    }
+}
+
+impl fmt::Display for PaymentMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentMethod::Pix => write!(f, "PIX"),
+            PaymentMethod::Boleto => write!(f, "Boleto"),
+            PaymentMethod::Transferencia => write!(f, "Transferência"),
+            PaymentMethod::Outro => write!(f, "Outro"),
+        }
+    }
+}
+
+/// Situação da conciliação de uma transação
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchStatus {
+    /// Nenhuma ordem ou nota candidata
+    Unmatched,
+    /// Conciliada automaticamente
+    Matched,
+    /// Candidatas parciais ou ambíguas, aguardando o operador
+    Review,
+    /// Conciliada pelo operador
+    Accepted,
+    /// Operador indicou que nenhuma candidata serve
+    Rejected,
+}
+
+impl fmt::Display for MatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchStatus::Unmatched => write!(f, "Sem correspondência"),
+            MatchStatus::Matched => write!(f, "Conciliado"),
+            MatchStatus::Review => write!(f, "Em revisão"),
+            MatchStatus::Accepted => write!(f, "Aceito"),
+            MatchStatus::Rejected => write!(f, "Rejeitado"),
+        }
+    }
+}
+
+/// Ordem de frete ou nota fiscal candidata a um pagamento
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentMatch {
+    /// "freight_order" ou "invoice"
+    pub entidade: String,
+    pub entidade_id: Uuid,
+    pub numero: String,
+    pub valor: Money,
+    /// Confiança, de 0 a 1
+    pub pontuacao: f64,
+    /// Por que a transação foi associada (valor, data, pagador, documento...)
+    pub motivos: Vec<String>,
+}
+
+/// Lançamento de um extrato bancário
+///
+/// Créditos são conciliados com ordens de frete e notas fiscais em aberto;
+/// débitos ficam registrados sem conciliação.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentTransaction {
+    pub id: Uuid,
+    /// Conta do extrato (agência/conta ou ACCTID do OFX)
+    pub conta: String,
+    /// Identificador do lançamento no banco (FITID), ou derivado do conteúdo
+    pub fitid: String,
+    pub data: NaiveDate,
+    /// Positivo para créditos, negativo para débitos
+    pub valor: Money,
+    pub descricao: String,
+    /// Nome do pagador, quando o banco informa
+    pub pagador_nome: Option<String>,
+    /// CPF/CNPJ encontrado no lançamento, só dígitos
+    pub pagador_documento: Option<String>,
+    pub forma: PaymentMethod,
+    pub candidatos: Vec<PaymentMatch>,
+    pub status: MatchStatus,
+    /// Ordem ou nota conciliada
+    pub conciliado_com: Option<PaymentMatch>,
+    pub revisado_em: Option<DateTime<Utc>>,
+    pub fonte_arquivo: Option<String>,
+    /// Arquivo, linha, importador e job de onde o registro veio
+    #[serde(default)]
+    pub linhagem: Option<Lineage>,
+    pub metadata: AuditMetadata,
+}
+
+impl PaymentTransaction {
+    pub fn new(conta: String, fitid: String, data: NaiveDate, valor: Money, descricao: String) -> Self {
+        let forma = PaymentMethod::from_description(&descricao);
+        Self {
+            id: Uuid::new_v4(),
+            conta,
+            fitid,
+            data,
+            valor,
+            descricao,
+            pagador_nome: None,
+            pagador_documento: None,
+            forma,
+            candidatos: Vec::new(),
+            status: MatchStatus::Unmatched,
+            conciliado_com: None,
+            revisado_em: None,
+            fonte_arquivo: None,
+            linhagem: None,
+            metadata: AuditMetadata::default(),
+        }
+    }
+
+    pub fn is_credit(&self) -> bool {
+        self.valor.is_positive()
+    }
+
+    /// Operador concilia com a candidata `entidade_id`; retorna `false` se ela não está entre as candidatas
+    pub fn accept(&mut self, entidade_id: &Uuid) -> bool {
+        let Some(candidate) = self.candidatos.iter().find(|c| c.entidade_id == *entidade_id) else {
+            return false;
+        };
+        self.conciliado_com = Some(candidate.clone());
+        self.status = MatchStatus::Accepted;
+        self.revisado_em = Some(Utc::now());
+        true
+    }
+
+    /// Operador indica que nenhuma candidata serve
+    pub fn reject(&mut self) {
+        self.conciliado_com = None;
+        self.status = MatchStatus::Rejected;
+        self.revisado_em = Some(Utc::now());
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == MatchStatus::Review
+    }
+
+    /// Já decidida pelo operador; a conciliação automática não mexe mais nela
+    pub fn is_reviewed(&self) -> bool {
+        matches!(self.status, MatchStatus::Accepted | MatchStatus::Rejected)
+    }
+}
+
+impl Entity for PaymentTransaction {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "payment_transaction"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        v.required("fitid", &self.fitid);
+        if self.valor.is_zero() {
+            v.add("valor", codes::ZERO);
+        }
+        if matches!(self.status, MatchStatus::Matched | MatchStatus::Accepted) && self.conciliado_com.is_none() {
+            v.add("conciliado_com", codes::REQUIRED);
+        }
+    }
+}
+
+impl NaturalKey for PaymentTransaction {
+    fn natural_key(&self) -> String {
+        format!("{}|{}", self.conta.trim(), self.fitid.trim())
+    }
+
+    /// Reimportar o extrato não desfaz a decisão do operador
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.metadata = existing.metadata.clone();
+        if existing.is_reviewed() {
+            self.status = existing.status;
+            self.candidatos = existing.candidatos.clone();
+            self.conciliado_com = existing.conciliado_com.clone();
+            self.revisado_em = existing.revisado_em;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(descricao: &str) -> PaymentTransaction {
+        PaymentTransaction::new(
+            "0001/12345-6".into(),
+            "202504030001".into(),
+            NaiveDate::from_ymd_opt(2025, 4, 3).unwrap(),
+            Money::from_cents(125_050),
+            descricao.into(),
+        )
+    }
+
+    #[test]
+    fn test_payment_method_from_description() {
+        assert_eq!(transaction("PIX RECEBIDO - COMERCIAL FRANCA").forma, PaymentMethod::Pix);
+        assert_eq!(transaction("LIQUIDACAO BOLETO 000123").forma, PaymentMethod::Boleto);
+        assert_eq!(transaction("TED 033 0001 FENIOR").forma, PaymentMethod::Transferencia);
+        assert_eq!(transaction("TARIFA PACOTE").forma, PaymentMethod::Outro);
+        assert!(PaymentMethod::Boleto.matches("Boleto bancário"));
+        assert!(!PaymentMethod::Outro.matches("Dinheiro"));
+    }
+
+    #[test]
+    fn test_reimport_keeps_review() {
+        let order = PaymentMatch {
+            entidade: "freight_order".into(),
+            entidade_id: Uuid::new_v4(),
+            numero: "288415".into(),
+            valor: Money::from_cents(125_050),
+            pontuacao: 0.6,
+            motivos: vec!["Valor igual".into()],
+        };
+        let mut reviewed = transaction("PIX RECEBIDO");
+        reviewed.candidatos = vec![order.clone()];
+        reviewed.status = MatchStatus::Review;
+        assert!(!reviewed.accept(&Uuid::new_v4()));
+        assert!(reviewed.accept(&order.entidade_id));
+        reviewed.validate().unwrap();
+
+        let mut reimported = transaction("PIX RECEBIDO");
+        reimported.adopt_identity(&reviewed);
+        assert_eq!(reimported.id, reviewed.id);
+        assert_eq!(reimported.status, MatchStatus::Accepted);
+        assert_eq!(reimported.conciliado_com, Some(order));
+    }
+}
@@ -0,0 +1,98 @@
+//! Photo to freight order link model
+
+use chrono::{DateTime, Utc};
+use pc_core::{AuditMetadata, Entity, NaturalKey, Violations};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Situação de uma sugestão de vínculo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkStatus {
+    Suggested,
+    Accepted,
+    Rejected,
+}
+
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkStatus::Suggested => write!(f, "Sugerido"),
+            LinkStatus::Accepted => write!(f, "Aceito"),
+            LinkStatus::Rejected => write!(f, "Rejeitado"),
+        }
+    }
+}
+
+/// Vínculo entre uma foto (comprovante de entrega) e uma ordem de frete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoLink {
+    pub id: Uuid,
+    pub foto_caminho: String,
+    pub freight_order_id: Uuid,
+    pub numero_ordem: String,
+    /// Confiança da sugestão, de 0 a 1
+    pub pontuacao: f32,
+    /// Por que a foto foi associada à ordem (data, GPS, nota fiscal...)
+    pub motivos: Vec<String>,
+    pub status: LinkStatus,
+    pub revisado_em: Option<DateTime<Utc>>,
+    pub metadata: AuditMetadata,
+}
+
+impl PhotoLink {
+    pub fn suggest(
+        foto_caminho: String,
+        freight_order_id: Uuid,
+        numero_ordem: String,
+        pontuacao: f32,
+        motivos: Vec<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            foto_caminho,
+            freight_order_id,
+            numero_ordem,
+            pontuacao,
+            motivos,
+            status: LinkStatus::Suggested,
+            revisado_em: None,
+            metadata: AuditMetadata::default(),
+        }
+    }
+
+    /// Operador confirma (ou descarta) a sugestão
+    pub fn review(&mut self, accepted: bool) {
+        self.status = if accepted { LinkStatus::Accepted } else { LinkStatus::Rejected };
+        self.revisado_em = Some(Utc::now());
+    }
+}
+
+impl Entity for PhotoLink {
+    fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    fn entity_type(&self) -> &str {
+        "photo_link"
+    }
+
+    fn check(&self, v: &mut Violations) {
+        v.required("foto_caminho", &self.foto_caminho);
+        v.in_range("pontuacao", self.pontuacao, 0.0, 1.0);
+    }
+}
+
+impl NaturalKey for PhotoLink {
+    fn natural_key(&self) -> String {
+        format!("{}|{}", self.foto_caminho, self.numero_ordem)
+    }
+
+    /// Uma nova sugestão não desfaz a revisão do operador
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.status = existing.status;
+        self.revisado_em = existing.revisado_em;
+        self.metadata = existing.metadata.clone();
+    }
+}
@@ -0,0 +1,36 @@
+//! Procedência de arquivos recebidos dentro de ZIPs e e-mails
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// De onde veio um arquivo extraído de um ZIP ou anexo de e-mail
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Arquivo recebido (o .zip, .mbox ou .eml em disco)
+    pub arquivo: String,
+    /// Caminho do arquivo dentro dele, com os contêineres aninhados
+    /// ("lote-abril.zip/notas/nfe.xml", "caixa.mbox/3/anexo.zip/cte.xml")
+    pub caminho: String,
+    /// E-mail que trouxe o anexo (o mais próximo, se houver encaminhamentos)
+    pub remetente: Option<String>,
+    pub assunto: Option<String>,
+    pub data_email: Option<DateTime<Utc>>,
+    pub message_id: Option<String>,
+}
+
+impl Provenance {
+    pub fn new(arquivo: impl Into<String>, caminho: impl Into<String>) -> Self {
+        Self {
+            arquivo: arquivo.into(),
+            caminho: caminho.into(),
+            remetente: None,
+            assunto: None,
+            data_email: None,
+            message_id: None,
+        }
+    }
+
+    pub fn is_email(&self) -> bool {
+        self.remetente.is_some() || self.message_id.is_some()
+    }
+}
@@ -1,6 +1,7 @@
 //! Route model
 
-use pc_core::{AuditMetadata, Entity, Result};
+use pc_core::{AuditMetadata, Entity, Money, NaturalKey, Violations};
+use crate::{Lineage, Provenance};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -15,10 +16,16 @@ pub struct Route {
     pub distancia_km: Option<f64>,
     pub tempo_estimado_horas: Option<f64>,
     pub pedagios: Option<i32>,
-    pub custo_pedagio: Option<f64>,
+    pub custo_pedagio: Option<Money>,
 
     pub ativo: bool,
     pub observacoes: Option<String>,
+    /// ZIP ou e-mail de onde a planilha foi extraída
+    #[serde(default)]
+    pub origem: Option<Provenance>,
+    /// Arquivo, linha, importador e job de onde o registro veio
+    #[serde(default)]
+    pub linhagem: Option<Lineage>,
 
     pub metadata: AuditMetadata,
 }
@@ -36,6 +43,8 @@ impl Route {
             custo_pedagio: None,
             ativo: true,
             observacoes: None,
+            origem: None,
+            linhagem: None,
             metadata: AuditMetadata::default(),
         }
     }
@@ -56,10 +65,28 @@ impl Entity for Route {
         "route"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.nome.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Nome não pode ser vazio".into()));
+    fn check(&self, v: &mut Violations) {
+        v.required("nome", &self.nome);
+    }
+}
+
+impl NaturalKey for Route {
+    /// Nome sem diferença de caixa e espaços, para "FRANCA (RA13)" e "Franca  (RA13)" serem a mesma rota
+    fn natural_key(&self) -> String {
+        self.nome.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+    }
+
+    /// A planilha traz só nome, região e cidades; os dados cadastrados à mão são mantidos
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.distancia_km = self.distancia_km.or(existing.distancia_km);
+        self.tempo_estimado_horas = self.tempo_estimado_horas.or(existing.tempo_estimado_horas);
+        self.pedagios = self.pedagios.or(existing.pedagios);
+        self.custo_pedagio = self.custo_pedagio.or(existing.custo_pedagio);
+        self.ativo = existing.ativo;
+        if self.observacoes.is_none() {
+            self.observacoes = existing.observacoes.clone();
         }
-        Ok(())
+        self.metadata = existing.metadata.clone();
     }
 }
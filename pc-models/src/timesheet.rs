@@ -1,7 +1,8 @@
 //! Timesheet model
 
-use chrono::{NaiveDate, NaiveTime, Duration};
-use pc_core::{AuditMetadata, Entity, Embeddable, Result};
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use pc_core::{AuditMetadata, Entity, Embeddable, NaturalKey, Violations};
+use crate::{Lineage, Provenance};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -19,6 +20,12 @@ pub struct Timesheet {
     pub saldo_minutos: i32,
     pub observacoes: Option<String>,
     pub fonte_arquivo: Option<String>,
+    /// ZIP ou e-mail de onde o arquivo foi extraído
+    #[serde(default)]
+    pub origem: Option<Provenance>,
+    /// Arquivo, linha, importador e job de onde o registro veio
+    #[serde(default)]
+    pub linhagem: Option<Lineage>,
     pub metadata: AuditMetadata,
 }
 
@@ -44,6 +51,8 @@ impl Timesheet {
             saldo_minutos: 0,
             observacoes: None,
             fonte_arquivo: None,
+            origem: None,
+            linhagem: None,
             metadata: AuditMetadata::default(),
         }
     }
@@ -78,17 +87,27 @@ impl Entity for Timesheet {
         "timesheet"
     }
 
-    fn validate(&self) -> Result<()> {
-        if self.funcionario.trim().is_empty() {
-            return Err(pc_core::Error::Validation("Funcionário não pode ser vazio".into()));
-        }
+    fn check(&self, v: &mut Violations) {
+        v.required("funcionario", &self.funcionario);
+        // Saída anterior à entrada é aceita: o turno passou da meia-noite
+        v.in_range("total_minutos", self.total_minutos, 0, 24 * 60);
+    }
+}
 
-        if self.saida < self.entrada {
-            // Pode ser válido se passou da meia-noite, mas vamos alertar
-            // return Err(pc_core::Error::Validation("Saída anterior à entrada".into()));
-        }
+impl NaturalKey for Timesheet {
+    fn natural_key(&self) -> String {
+        format!(
+            "{}|{}|{}",
+            self.funcionario.trim().to_uppercase(),
+            self.data,
+            self.entrada.format("%H:%M")
+        )
+    }
 
-        Ok(())
+    fn adopt_identity(&mut self, existing: &Self) {
+        self.id = existing.id;
+        self.funcionario_id = self.funcionario_id.or(existing.funcionario_id);
+        self.metadata = existing.metadata.clone();
     }
 }
 
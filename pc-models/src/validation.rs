@@ -1,267 +1,184 @@
-//! Validation module for data models
-
-use crate::*;
-use anyhow::{anyhow, Result};
-
-pub trait Validate {
-    fn validate(&self) -> Result<()>;
+//! Regras de validação compartilhadas pelos modelos
+//!
+//! Complementam as regras genéricas de [`Violations`] com documentos,
+//! contatos e chaves de acesso de NF-e e CT-e.
+
+use crate::brdoc::{self, TaxId};
+use pc_core::validation::codes;
+use pc_core::{Violation, Violations};
+
+/// Modelo da NF-e na chave de acesso
+pub const MODELO_NFE: &str = "55";
+/// Modelo do CT-e na chave de acesso
+pub const MODELO_CTE: &str = "57";
+
+fn digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
 }
 
-impl Validate for Company {
-    fn validate(&self) -> Result<()> {
-        // Nome obrigatório e não vazio
-        if self.nome.trim().is_empty() {
-            return Err(anyhow!("Company name cannot be empty"));
-        }
-
-        // CNPJ validation (basic)
-        if !self.cnpj.is_empty() {
-            let cnpj_digits: String = self.cnpj.chars().filter(|c| c.is_numeric()).collect();
-            if cnpj_digits.len() != 14 {
-                return Err(anyhow!("CNPJ must have 14 digits"));
-            }
-        }
+/// E-mail com usuário e domínio
+pub fn is_email(email: &str) -> bool {
+    match email.trim().split_once('@') {
+        Some((user, domain)) => !user.is_empty() && domain.contains('.') && !domain.contains('@'),
+        None => false,
+    }
+}
 
-        // Email validation (basic)
-        if let Some(email) = &self.email {
-            if !email.is_empty() && !email.contains('@') {
-                return Err(anyhow!("Invalid email format"));
-            }
-        }
+/// Telefone com DDD: 10 ou 11 dígitos
+pub fn is_phone(phone: &str) -> bool {
+    matches!(digits(phone).len(), 10 | 11)
+}
 
-        // Telefone validation
-        if let Some(tel) = &self.telefone {
-            if !tel.is_empty() {
-                let tel_digits: String = tel.chars().filter(|c| c.is_numeric()).collect();
-                if tel_digits.len() < 10 || tel_digits.len() > 11 {
-                    return Err(anyhow!("Phone number must have 10 or 11 digits"));
-                }
-            }
-        }
+pub fn is_cep(cep: &str) -> bool {
+    digits(cep).len() == 8
+}
 
-        Ok(())
-    }
+/// Dígito verificador da chave de acesso (módulo 11 sobre as 43 primeiras posições)
+pub fn access_key_check_digit(key: &str) -> Option<u32> {
+    let values: Vec<u32> = key.chars().take(43).map(|c| c.to_digit(10)).collect::<Option<_>>()?;
+    let sum: u32 = values.iter().rev().enumerate().map(|(i, v)| v * (2 + i as u32 % 8)).sum();
+    Some(match sum % 11 {
+        0 | 1 => 0,
+        r => 11 - r,
+    })
 }
 
-impl Validate for FreightOrder {
-    fn validate(&self) -> Result<()> {
-        // Número do pedido obrigatório
-        if self.numero_pedido.trim().is_empty() {
-            return Err(anyhow!("Order number cannot be empty"));
-        }
+/// Regras dos modelos sobre [`Violations`]
+pub trait Rules {
+    fn cnpj(&mut self, field: &str, value: Option<&str>);
+    fn cpf(&mut self, field: &str, value: Option<&str>);
+    /// CPF ou CNPJ, pelo tamanho
+    fn tax_id(&mut self, field: &str, value: Option<&str>);
+    fn uf(&mut self, field: &str, value: &str);
+    fn inscricao_estadual(&mut self, field: &str, uf: &str, value: Option<&str>);
+    fn email(&mut self, field: &str, value: Option<&str>);
+    fn phone(&mut self, field: &str, value: Option<&str>);
+    fn cep(&mut self, field: &str, value: Option<&str>);
+    /// Chave de acesso de 44 dígitos, com dígito verificador e o modelo
+    /// esperado (55 para NF-e, 57 para CT-e); devolve se a chave é válida
+    fn access_key(&mut self, field: &str, value: &str, modelo: Option<&str>) -> bool;
+}
 
-        // Valor do frete não pode ser negativo
-        if self.valor_frete < 0.0 {
-            return Err(anyhow!("Freight value cannot be negative"));
+impl Rules for Violations {
+    fn cnpj(&mut self, field: &str, value: Option<&str>) {
+        if value.is_some_and(|v| !brdoc::cnpj::is_valid(v)) {
+            self.add(field, codes::INVALID_CNPJ);
         }
+    }
 
-        // Peso não pode ser negativo
-        if self.peso < 0.0 {
-            return Err(anyhow!("Weight cannot be negative"));
+    fn cpf(&mut self, field: &str, value: Option<&str>) {
+        if value.is_some_and(|v| !brdoc::cpf::is_valid(v)) {
+            self.add(field, codes::INVALID_CPF);
         }
+    }
 
-        // Datas: data de entrega deve ser posterior à data de coleta
-        if let (Some(coleta), Some(entrega)) = (self.data_coleta, self.data_entrega) {
-            if entrega < coleta {
-                return Err(anyhow!("Delivery date cannot be before pickup date"));
+    fn tax_id(&mut self, field: &str, value: Option<&str>) {
+        if let Some(value) = value {
+            if TaxId::parse(value).is_err() {
+                let code = if brdoc::cpf::normalize(value).len() == 11 { codes::INVALID_CPF } else { codes::INVALID_CNPJ };
+                self.add(field, code);
             }
         }
-
-        // Status deve ser válido
-        match self.status.as_str() {
-            "pendente" | "em_transito" | "entregue" | "cancelado" => Ok(()),
-            _ => Err(anyhow!("Invalid status: {}", self.status)),
-        }
     }
-}
-
-impl Validate for Timesheet {
-    fn validate(&self) -> Result<()> {
-        // Data obrigatória
-        if self.data.is_none() {
-            return Err(anyhow!("Date is required"));
-        }
 
-        // Horas não podem ser negativas
-        if self.horas_trabalhadas < 0.0 {
-            return Err(anyhow!("Hours worked cannot be negative"));
+    fn uf(&mut self, field: &str, value: &str) {
+        if !brdoc::ie::UFS.contains(&value.trim().to_uppercase().as_str()) {
+            self.add(field, codes::INVALID_UF);
         }
+    }
 
-        // Horas não podem exceder 24h por dia
-        if self.horas_trabalhadas > 24.0 {
-            return Err(anyhow!("Hours worked cannot exceed 24 hours per day"));
+    fn inscricao_estadual(&mut self, field: &str, uf: &str, value: Option<&str>) {
+        if value.is_some_and(|v| !brdoc::ie::is_valid(uf, v)) {
+            self.push(Violation::new(field, codes::INVALID_IE).with_param("uf", uf.trim().to_uppercase()));
         }
+    }
 
-        // Valor hora não pode ser negativo
-        if self.valor_hora < 0.0 {
-            return Err(anyhow!("Hourly rate cannot be negative"));
+    fn email(&mut self, field: &str, value: Option<&str>) {
+        if value.is_some_and(|v| !v.trim().is_empty() && !is_email(v)) {
+            self.add(field, codes::INVALID_EMAIL);
         }
-
-        Ok(())
     }
-}
 
-impl Validate for Route {
-    fn validate(&self) -> Result<()> {
-        // Origem e destino obrigatórios
-        if self.origem.trim().is_empty() {
-            return Err(anyhow!("Origin cannot be empty"));
+    fn phone(&mut self, field: &str, value: Option<&str>) {
+        if value.is_some_and(|v| !v.trim().is_empty() && !is_phone(v)) {
+            self.add(field, codes::INVALID_PHONE);
         }
+    }
 
-        if self.destino.trim().is_empty() {
-            return Err(anyhow!("Destination cannot be empty"));
+    fn cep(&mut self, field: &str, value: Option<&str>) {
+        if value.is_some_and(|v| !v.trim().is_empty() && !is_cep(v)) {
+            self.add(field, codes::INVALID_CEP);
         }
+    }
 
-        // Origem e destino não podem ser iguais
-        if self.origem.trim().eq_ignore_ascii_case(self.destino.trim()) {
-            return Err(anyhow!("Origin and destination cannot be the same"));
+    fn access_key(&mut self, field: &str, value: &str, modelo: Option<&str>) -> bool {
+        let key = value.trim();
+        if !self.digits(field, key, 44) {
+            return false;
         }
-
-        // Distância não pode ser negativa
-        if self.distancia_km < 0.0 {
-            return Err(anyhow!("Distance cannot be negative"));
+        if access_key_check_digit(key) != key[43..].chars().next().and_then(|c| c.to_digit(10)) {
+            self.add(field, codes::CHECK_DIGIT);
+            return false;
         }
-
-        // Tempo estimado não pode ser negativo
-        if self.tempo_estimado_horas < 0.0 {
-            return Err(anyhow!("Estimated time cannot be negative"));
+        if let Some(modelo) = modelo.filter(|m| key[20..22] != **m) {
+            self.push(Violation::new(field, codes::MISMATCH).with_param("other", format!("modelo {}", modelo)));
+            return false;
         }
-
-        Ok(())
+        true
     }
 }
 
-// Helper functions for common validations
-
-pub fn validate_cpf(cpf: &str) -> Result<()> {
-    let cpf_digits: String = cpf.chars().filter(|c| c.is_numeric()).collect();
-
-    if cpf_digits.len() != 11 {
-        return Err(anyhow!("CPF must have 11 digits"));
-    }
-
-    // Check for known invalid CPFs (all same digit)
-    if cpf_digits.chars().all(|c| c == cpf_digits.chars().next().unwrap()) {
-        return Err(anyhow!("Invalid CPF"));
-    }
-
-    Ok(())
-}
-
-pub fn validate_cnpj(cnpj: &str) -> Result<()> {
-    let cnpj_digits: String = cnpj.chars().filter(|c| c.is_numeric()).collect();
-
-    if cnpj_digits.len() != 14 {
-        return Err(anyhow!("CNPJ must have 14 digits"));
-    }
-
-    // Check for known invalid CNPJs (all same digit)
-    if cnpj_digits.chars().all(|c| c == cnpj_digits.chars().next().unwrap()) {
-        return Err(anyhow!("Invalid CNPJ"));
-    }
-
-    Ok(())
-}
-
-pub fn validate_cep(cep: &str) -> Result<()> {
-    let cep_digits: String = cep.chars().filter(|c| c.is_numeric()).collect();
-
-    if cep_digits.len() != 8 {
-        return Err(anyhow!("CEP must have 8 digits"));
-    }
-
-    Ok(())
-}
-
-pub fn validate_phone(phone: &str) -> Result<()> {
-    let phone_digits: String = phone.chars().filter(|c| c.is_numeric()).collect();
-
-    if phone_digits.len() < 10 || phone_digits.len() > 11 {
-        return Err(anyhow!("Phone must have 10 or 11 digits"));
-    }
-
-    Ok(())
+/// Número do documento na chave de acesso (posições 26 a 34)
+pub fn access_key_number(key: &str) -> Option<u64> {
+    key.trim().get(25..34)?.parse().ok()
 }
 
-pub fn validate_email(email: &str) -> Result<()> {
-    if !email.contains('@') || !email.contains('.') {
-        return Err(anyhow!("Invalid email format"));
-    }
-
-    let parts: Vec<&str> = email.split('@').collect();
-    if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
-        return Err(anyhow!("Invalid email format"));
+/// Número do documento confere com o da chave (ignorando zeros à esquerda)
+pub(crate) fn number_matches_key(numero: &str, key: &str) -> bool {
+    match (digits(numero).parse::<u64>(), access_key_number(key)) {
+        (Ok(numero), Some(da_chave)) => numero == da_chave,
+        _ => true,
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_company_validation() {
-        let mut company = Company {
-            nome: "".to_string(),
-            cnpj: "12345678000199".to_string(),
-            ..Default::default()
-        };
-
-        // Empty name should fail
-        assert!(company.validate().is_err());
-
-        company.nome = "Test Company".to_string();
-        assert!(company.validate().is_ok());
-
-        // Invalid CNPJ
-        company.cnpj = "123".to_string();
-        assert!(company.validate().is_err());
-    }
-
-    #[test]
-    fn test_freight_order_validation() {
-        let mut order = FreightOrder {
-            numero_pedido: "12345".to_string(),
-            valor_frete: -100.0,
-            ..Default::default()
-        };
-
-        // Negative value should fail
-        assert!(order.validate().is_err());
-
-        order.valor_frete = 100.0;
-        assert!(order.validate().is_ok());
-
-        // Invalid status
-        order.status = "invalid_status".to_string();
-        assert!(order.validate().is_err());
-    }
+    // Chave de NF-e modelo 55, número 4512
+    const CHAVE_NFE: &str = "35250411222333000181550010000045121000045129";
 
     #[test]
-    fn test_timesheet_validation() {
-        let mut timesheet = Timesheet {
-            horas_trabalhadas: 25.0, // More than 24h
-            ..Default::default()
-        };
-
-        assert!(timesheet.validate().is_err());
+    fn test_access_key_rules() {
+        let dv = access_key_check_digit(CHAVE_NFE).unwrap();
+        assert_eq!(CHAVE_NFE[43..].parse::<u32>().unwrap(), dv);
+        assert_eq!(access_key_number(CHAVE_NFE), Some(4512));
 
-        timesheet.horas_trabalhadas = 8.0;
-        assert!(timesheet.validate().is_ok());
-    }
+        let mut violations = Violations::new();
+        assert!(violations.access_key("chave", CHAVE_NFE, Some(MODELO_NFE)));
+        assert!(!violations.access_key("chave", CHAVE_NFE, Some(MODELO_CTE)));
+        assert!(!violations.access_key("chave", &CHAVE_NFE[..43], None));
+        let mut wrong_dv = CHAVE_NFE[..43].to_string();
+        wrong_dv.push(char::from_digit((dv + 1) % 10, 10).unwrap());
+        assert!(!violations.access_key("chave", &wrong_dv, None));
 
-    #[test]
-    fn test_cpf_validation() {
-        assert!(validate_cpf("12345678901").is_ok());
-        assert!(validate_cpf("123").is_err());
-        assert!(validate_cpf("11111111111").is_err()); // All same digit
+        let codes: Vec<&str> = violations.iter().map(|v| v.code.as_str()).collect();
+        assert_eq!(codes, ["mismatch", "length", "check_digit"]);
     }
 
     #[test]
-    fn test_email_validation() {
-        assert!(validate_email("test@example.com").is_ok());
-        assert!(validate_email("invalid").is_err());
-        assert!(validate_email("@example.com").is_err());
+    fn test_contact_rules() {
+        assert!(is_email("fretes@avila.com.br"));
+        assert!(!is_email("invalid"));
+        assert!(!is_email("@example"));
+        assert!(is_phone("(16) 3333-4444"));
+        assert!(!is_phone("3333-4444"));
+
+        let mut violations = Violations::new();
+        violations.email("email", Some("fretes"));
+        violations.phone("telefone", Some(""));
+        violations.tax_id("documento", Some("529.982.247-26"));
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations.iter().nth(1).unwrap().code, "invalid_cpf");
     }
 }